@@ -0,0 +1,40 @@
+//! Rough timings of the `simd::bytes` searches against naive loops.
+//!
+//! Run it with `cargo run --release --example byte_search_bench`.
+
+use lokacore::simd::bytes::*;
+use std::time::{Duration, Instant};
+
+const ROUNDS: u32 = 50;
+
+fn time<T>(name: &str, mut f: impl FnMut() -> T) -> Duration {
+  let start = Instant::now();
+  for _ in 0..ROUNDS {
+    std::hint::black_box(f());
+  }
+  let elapsed = start.elapsed() / ROUNDS;
+  println!("{:>20}: {:?}", name, elapsed);
+  elapsed
+}
+
+fn main() {
+  // 16 MiB of text-ish bytes with the thing we're looking for at the very end.
+  let mut haystack: Vec<u8> = (0..16 << 20).map(|i| b'a' + (i % 23) as u8).collect();
+  haystack.extend_from_slice(b"needle!");
+  let h = std::hint::black_box(&haystack[..]);
+
+  time("memchr", || memchr(b'!', h));
+  time("naive memchr", || h.iter().position(|&b| b == b'!'));
+  time("memrchr", || memrchr(b'z', h));
+  time("naive memrchr", || h.iter().rposition(|&b| b == b'z'));
+  time("memchr3", || memchr3(b'!', b'?', b'.', h));
+  time("naive memchr3", || {
+    h.iter().position(|&b| b == b'!' || b == b'?' || b == b'.')
+  });
+  time("count_byte", || count_byte(b'c', h));
+  time("naive count_byte", || {
+    h.iter().filter(|&&b| b == b'c').count()
+  });
+  time("find", || find(h, b"needle"));
+  time("naive find", || h.windows(6).position(|w| w == b"needle"));
+}
@@ -0,0 +1,227 @@
+//! The AES block cipher (FIPS-197), with 128, 192, and 256 bit keys.
+//!
+//! This is just the raw block cipher: one 16 byte block in, one 16 byte block
+//! out. It's built on the [AES-NI](m128i::aes_encrypt_round) instructions, so
+//! it runs in constant time, but picking a mode of operation (CTR, GCM, etc)
+//! and everything else that goes into using a cipher safely is up to you.
+//!
+//! ```rust
+//! use lokacore::aes::Aes128;
+//!
+//! let key = [
+//!   0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6,
+//!   0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+//! ];
+//! let aes = Aes128::new(&key);
+//! let mut block = *b"sixteen byte msg";
+//! aes.encrypt_block(&mut block);
+//! assert_ne!(&block, b"sixteen byte msg");
+//! aes.decrypt_block(&mut block);
+//! assert_eq!(&block, b"sixteen byte msg");
+//! ```
+//!
+//! The `_blocks` methods work on 4 blocks at a time, which keeps the AES unit
+//! busy instead of waiting on each round to finish before starting the next,
+//! so they're several times faster per block than calling `encrypt_block` in a
+//! loop.
+
+use crate::aes_keygen_assist;
+#[cfg(target_arch = "x86")]
+use crate::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use crate::arch::x86_64::*;
+use core::convert::TryInto;
+
+/// The `Rcon` values, one per key expansion step that uses one.
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1B, 0x36];
+
+/// `SubWord` of the FIPS-197 key expansion, with byte 0 of the word in the low
+/// bits.
+#[inline(always)]
+fn sub_word(x: u32) -> u32 {
+  aes_keygen_assist!(m128i::set_i32(0, 0, x as i32, 0), 0).extract_lowest_i32() as u32
+}
+
+/// The encryption and decryption round keys for `N - 1` rounds.
+#[derive(Clone)]
+struct RoundKeys<const N: usize> {
+  encrypt: [m128i; N],
+  decrypt: [m128i; N],
+}
+
+impl<const N: usize> RoundKeys<N> {
+  /// Runs the FIPS-197 key expansion over a key of 4, 6, or 8 words.
+  fn new(key: &[u8]) -> Self {
+    let nk = key.len() / 4;
+    let mut w = [0_u32; 60];
+    for (word, bytes) in w.iter_mut().zip(key.chunks_exact(4)) {
+      *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    for i in nk..4 * N {
+      let mut temp = w[i - 1];
+      if i % nk == 0 {
+        // `RotWord` and `SubWord` commute, and with byte 0 in the low bits
+        // `RotWord` is a rotate right by one byte.
+        temp = sub_word(temp).rotate_right(8) ^ u32::from(RCON[i / nk - 1]);
+      } else if nk > 6 && i % nk == 4 {
+        temp = sub_word(temp);
+      }
+      w[i] = w[i - nk] ^ temp;
+    }
+    let mut encrypt = [m128i::zeroed(); N];
+    for (k, words) in encrypt.iter_mut().zip(w.chunks_exact(4)) {
+      *k = m128i::setr_i32(
+        words[0] as i32,
+        words[1] as i32,
+        words[2] as i32,
+        words[3] as i32,
+      );
+    }
+    // The equivalent inverse cipher uses the keys in reverse order, with
+    // `InvMixColumns` applied to all but the first and last.
+    let mut decrypt = [m128i::zeroed(); N];
+    decrypt[0] = encrypt[N - 1];
+    for i in 1..N - 1 {
+      decrypt[i] = encrypt[N - 1 - i].aes_inverse_mix_columns();
+    }
+    decrypt[N - 1] = encrypt[0];
+    Self { encrypt, decrypt }
+  }
+
+  #[inline(always)]
+  fn encrypt(&self, block: m128i) -> m128i {
+    let mut state = block ^ self.encrypt[0];
+    for &k in &self.encrypt[1..N - 1] {
+      state = state.aes_encrypt_round(k);
+    }
+    state.aes_encrypt_last_round(self.encrypt[N - 1])
+  }
+
+  #[inline(always)]
+  fn decrypt(&self, block: m128i) -> m128i {
+    let mut state = block ^ self.decrypt[0];
+    for &k in &self.decrypt[1..N - 1] {
+      state = state.aes_decrypt_round(k);
+    }
+    state.aes_decrypt_last_round(self.decrypt[N - 1])
+  }
+
+  #[inline(always)]
+  fn encrypt4(&self, blocks: [m128i; 4]) -> [m128i; 4] {
+    let mut s = blocks;
+    s.iter_mut().for_each(|s| *s ^= self.encrypt[0]);
+    for &k in &self.encrypt[1..N - 1] {
+      s.iter_mut().for_each(|s| *s = s.aes_encrypt_round(k));
+    }
+    s.iter_mut()
+      .for_each(|s| *s = s.aes_encrypt_last_round(self.encrypt[N - 1]));
+    s
+  }
+
+  #[inline(always)]
+  fn decrypt4(&self, blocks: [m128i; 4]) -> [m128i; 4] {
+    let mut s = blocks;
+    s.iter_mut().for_each(|s| *s ^= self.decrypt[0]);
+    for &k in &self.decrypt[1..N - 1] {
+      s.iter_mut().for_each(|s| *s = s.aes_decrypt_round(k));
+    }
+    s.iter_mut()
+      .for_each(|s| *s = s.aes_decrypt_last_round(self.decrypt[N - 1]));
+    s
+  }
+
+  fn encrypt_blocks(&self, blocks: &mut [[u8; 16]], decrypt: bool) {
+    let mut quads = blocks.chunks_exact_mut(4);
+    for quad in &mut quads {
+      let input = [
+        m128i::load_unaligned(&quad[0]),
+        m128i::load_unaligned(&quad[1]),
+        m128i::load_unaligned(&quad[2]),
+        m128i::load_unaligned(&quad[3]),
+      ];
+      let output = if decrypt {
+        self.decrypt4(input)
+      } else {
+        self.encrypt4(input)
+      };
+      for (out, block) in output.iter().zip(quad.iter_mut()) {
+        out.storeu(block);
+      }
+    }
+    for block in quads.into_remainder() {
+      let input = m128i::load_unaligned(block);
+      let output = if decrypt {
+        self.decrypt(input)
+      } else {
+        self.encrypt(input)
+      };
+      output.storeu(block);
+    }
+  }
+}
+
+macro_rules! aes_cipher {
+  ($(#[$attr:meta])* $name:ident, $key_bytes:expr, $round_keys:expr) => {
+    $(#[$attr])*
+    #[derive(Clone)]
+    pub struct $name {
+      keys: RoundKeys<$round_keys>,
+    }
+    impl $name {
+      /// Expands the key into the round keys.
+      #[inline]
+      pub fn new(key: &[u8; $key_bytes]) -> Self {
+        Self { keys: RoundKeys::new(key) }
+      }
+
+      /// Encrypts one block in place.
+      #[inline]
+      pub fn encrypt_block(&self, block: &mut [u8; 16]) {
+        self.keys.encrypt(m128i::load_unaligned(block)).storeu(block);
+      }
+
+      /// Decrypts one block in place.
+      #[inline]
+      pub fn decrypt_block(&self, block: &mut [u8; 16]) {
+        self.keys.decrypt(m128i::load_unaligned(block)).storeu(block);
+      }
+
+      /// Encrypts every block in place, each one on its own (ECB).
+      #[inline]
+      pub fn encrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        self.keys.encrypt_blocks(blocks, false)
+      }
+
+      /// Decrypts every block in place, each one on its own (ECB).
+      #[inline]
+      pub fn decrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        self.keys.encrypt_blocks(blocks, true)
+      }
+    }
+    impl core::fmt::Debug for $name {
+      /// Doesn't print the round keys.
+      fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(concat!(stringify!($name), " { .. }"))
+      }
+    }
+  };
+}
+
+aes_cipher!(
+  /// AES with a 128 bit key (10 rounds).
+  Aes128,
+  16,
+  11
+);
+aes_cipher!(
+  /// AES with a 192 bit key (12 rounds).
+  Aes192,
+  24,
+  13
+);
+aes_cipher!(
+  /// AES with a 256 bit key (14 rounds).
+  Aes256,
+  32,
+  15
+);
@@ -0,0 +1,96 @@
+use super::*;
+
+/// # AES Operations
+///
+/// These are the individual rounds of the AES block cipher, with the state
+/// and round keys held in the usual byte order (byte 0 of the block in the
+/// lowest lane). See the [`aes`](crate::aes) module for the full cipher.
+impl m128i {
+  /// One full round of AES encryption: `ShiftRows`, `SubBytes`, `MixColumns`,
+  /// and then XOR with the round key.
+  #[inline(always)]
+  pub fn aes_encrypt_round(self, round_key: m128i) -> m128i {
+    m128i(unsafe { _mm_aesenc_si128(self.0, round_key.0) })
+  }
+
+  /// The last round of AES encryption, which skips `MixColumns`.
+  #[inline(always)]
+  pub fn aes_encrypt_last_round(self, round_key: m128i) -> m128i {
+    m128i(unsafe { _mm_aesenclast_si128(self.0, round_key.0) })
+  }
+
+  /// One full round of AES decryption: `InvShiftRows`, `InvSubBytes`,
+  /// `InvMixColumns`, and then XOR with the round key.
+  ///
+  /// The round key must have gone through
+  /// [`aes_inverse_mix_columns`](m128i::aes_inverse_mix_columns) first (the
+  /// "equivalent inverse cipher" of FIPS-197).
+  #[inline(always)]
+  pub fn aes_decrypt_round(self, round_key: m128i) -> m128i {
+    m128i(unsafe { _mm_aesdec_si128(self.0, round_key.0) })
+  }
+
+  /// The last round of AES decryption, which skips `InvMixColumns`.
+  #[inline(always)]
+  pub fn aes_decrypt_last_round(self, round_key: m128i) -> m128i {
+    m128i(unsafe { _mm_aesdeclast_si128(self.0, round_key.0) })
+  }
+
+  /// AES `InvMixColumns`, used to turn encryption round keys into decryption
+  /// round keys.
+  #[inline(always)]
+  pub fn aes_inverse_mix_columns(self) -> m128i {
+    m128i(unsafe { _mm_aesimc_si128(self.0) })
+  }
+}
+
+/// Helps with AES key expansion.
+///
+/// With `a` as four `u32` lanes `[x0, x1, x2, x3]` (lane 0 first), the output
+/// is
+///
+/// ```txt
+/// [SubWord(x1), RotWord(SubWord(x1)) ^ rcon, SubWord(x3), RotWord(SubWord(x3)) ^ rcon]
+/// ```
+///
+/// * `aes_keygen_assist!(a, rcon)`
+/// * The round constant must be a constant in `0..256`, anything else is a
+///   compile error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{aes_keygen_assist, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{aes_keygen_assist, arch::x86_64::m128i};
+///
+/// // The S-box sends 0 to 0x63
+/// let a = m128i::zeroed();
+/// let expected = m128i::set_i32(0x6363_6362, 0x6363_6363, 0x6363_6362, 0x6363_6363);
+/// assert_eq!(aes_keygen_assist!(a, 1).to_i128(), expected.to_i128());
+/// ```
+#[macro_export]
+macro_rules! aes_keygen_assist {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 256,
+      "aes_keygen_assist!: round constant must be in 0..256"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "aes"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_aeskeygenassist_si128(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "aes"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe {
+        core::arch::x86_64::_mm_aeskeygenassist_si128(a.0, IMM)
+      })
+    }
+    #[cfg(not(target_feature = "aes"))]
+    {
+      compile_error!("the aes_keygen_assist macro requires 'aes' to be enabled.");
+    }
+  }};
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+/// # F16C Operations
+impl m128 {
+  /// lanewise round the `f32` values into `f16` bit patterns, to nearest with
+  /// ties to even.
+  ///
+  /// The four results are in the low four `u16` lanes, the rest are zero.
+  #[inline(always)]
+  pub fn round_f16(self) -> m128i {
+    m128i(unsafe { _mm_cvtps_ph(self.0, _MM_FROUND_TO_NEAREST_INT) })
+  }
+}
+
+/// # F16C Operations
+impl m128i {
+  /// Converts the `f16` bit patterns in the low four `u16` lanes into `f32`
+  /// values. This is always exact.
+  #[inline(always)]
+  pub fn f16_to_f32(self) -> m128 {
+    m128(unsafe { _mm_cvtph_ps(self.0) })
+  }
+}
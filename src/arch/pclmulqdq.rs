@@ -0,0 +1,55 @@
+/// Carry-less multiply of one 64-bit half of `a` with one 64-bit half of `b`,
+/// giving a 128-bit product.
+///
+/// A carry-less multiply is a long multiplication that uses XOR instead of
+/// addition, which is multiplication of polynomials over GF(2). That's what
+/// CRCs and GHASH are built from.
+///
+/// * `clmul!(a, b, imm)`
+/// * Bit 0 of `imm` picks the half of `a` (0 for the low `i64` lane, 1 for the
+///   high lane), and bit 4 picks the half of `b`. So `0x00` is low times low,
+///   `0x01` is high `a` times low `b`, `0x10` is low `a` times high `b`, and
+///   `0x11` is high times high.
+/// * The selector must be a constant, and any other bits being set is a
+///   compile error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{clmul, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{clmul, arch::x86_64::m128i};
+///
+/// // (x + 1) * (x + 1) = x^2 + 1 when you don't carry
+/// let a = m128i::set_i64(0, 0b11);
+/// let b = m128i::set_i64(0b11, 0);
+/// assert_eq!(clmul!(a, b, 0x10).to_i128(), 0b101);
+/// assert_eq!(clmul!(a, b, 0x00).to_i128(), 0);
+/// ```
+#[macro_export]
+macro_rules! clmul {
+  ($a:expr, $b:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM & !0x11 == 0,
+      "clmul!: the selector can only use bits 0 and 4"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "pclmulqdq"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      let b: $crate::arch::x86::m128i = $b;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_clmulepi64_si128(a.0, b.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "pclmulqdq"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      let b: $crate::arch::x86_64::m128i = $b;
+      $crate::arch::x86_64::m128i(unsafe {
+        core::arch::x86_64::_mm_clmulepi64_si128(a.0, b.0, IMM)
+      })
+    }
+    #[cfg(not(target_feature = "pclmulqdq"))]
+    {
+      compile_error!("the clmul macro requires 'pclmulqdq' to be enabled.");
+    }
+  }};
+}
@@ -0,0 +1,93 @@
+use super::*;
+
+/// # SHA Operations
+///
+/// These are the pieces of the SHA-1 and SHA-256 compression functions. The
+/// message words and state are in the big-endian word order of the spec, with
+/// the _first_ word in the _highest_ lane for SHA-1, and the first word in the
+/// lowest lane for SHA-256. See [`sha`](crate::sha) for the full hashers.
+impl m128i {
+  /// The first half of the SHA-1 message schedule: the XOR of message words
+  /// `t-16` and `t-14` for 4 new words, with `self` as the older 4 words.
+  #[inline(always)]
+  pub fn sha1_msg1(self, next: m128i) -> m128i {
+    m128i(unsafe { _mm_sha1msg1_epu32(self.0, next.0) })
+  }
+
+  /// The last half of the SHA-1 message schedule: XORs in words `t-3` (from
+  /// `previous`) and rotates left by 1.
+  #[inline(always)]
+  pub fn sha1_msg2(self, previous: m128i) -> m128i {
+    m128i(unsafe { _mm_sha1msg2_epu32(self.0, previous.0) })
+  }
+
+  /// Computes the SHA-1 state variable `E` after four rounds from the `ABCD`
+  /// state before them (`self`), and adds it to the highest lane of the
+  /// message words.
+  #[inline(always)]
+  pub fn sha1_next_e(self, message: m128i) -> m128i {
+    m128i(unsafe { _mm_sha1nexte_epu32(self.0, message.0) })
+  }
+
+  /// The first half of the SHA-256 message schedule: adds `σ0` of words `t-15`
+  /// to words `t-16` for 4 new words, with `self` as the older 4 words.
+  #[inline(always)]
+  pub fn sha256_msg1(self, next: m128i) -> m128i {
+    m128i(unsafe { _mm_sha256msg1_epu32(self.0, next.0) })
+  }
+
+  /// The last half of the SHA-256 message schedule: adds `σ1` of words `t-2`
+  /// (from `previous`).
+  #[inline(always)]
+  pub fn sha256_msg2(self, previous: m128i) -> m128i {
+    m128i(unsafe { _mm_sha256msg2_epu32(self.0, previous.0) })
+  }
+
+  /// Two rounds of SHA-256.
+  ///
+  /// `self` is the `[H, G, D, C]` state (lane 0 first) and `abef` is the
+  /// `[F, E, B, A]` state. The low two lanes of `wk` are the message words
+  /// plus round constants. The output is the new `[F, E, B, A]`, and the old
+  /// `abef` becomes the new `[H, G, D, C]`.
+  #[inline(always)]
+  pub fn sha256_rounds2(self, abef: m128i, wk: m128i) -> m128i {
+    m128i(unsafe { _mm_sha256rnds2_epu32(self.0, abef.0, wk.0) })
+  }
+}
+
+/// Four rounds of SHA-1.
+///
+/// `abcd` is the state with `A` in the highest lane, and `e` holds the message
+/// words with `E` added to the highest lane (see
+/// [`sha1_next_e`](m128i::sha1_next_e)). The round function and constant are
+/// picked by `func`, which is `round / 20` for the rounds being done.
+///
+/// * `sha1_rounds4!(abcd, e, func)`
+/// * The function must be a constant in `0..4`, anything else is a compile
+///   error.
+#[macro_export]
+macro_rules! sha1_rounds4 {
+  ($abcd:expr, $e:expr, $func:expr) => {{
+    const IMM: i32 = $func;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 4,
+      "sha1_rounds4!: the round function must be in 0..4"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sha"))]
+    {
+      let (abcd, e): ($crate::arch::x86::m128i, $crate::arch::x86::m128i) = ($abcd, $e);
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_sha1rnds4_epu32(abcd.0, e.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sha"))]
+    {
+      let (abcd, e): ($crate::arch::x86_64::m128i, $crate::arch::x86_64::m128i) = ($abcd, $e);
+      $crate::arch::x86_64::m128i(unsafe {
+        core::arch::x86_64::_mm_sha1rnds4_epu32(abcd.0, e.0, IMM)
+      })
+    }
+    #[cfg(not(target_feature = "sha"))]
+    {
+      compile_error!("the sha1_rounds4 macro requires 'sha' to be enabled.");
+    }
+  }};
+}
@@ -214,6 +214,19 @@ impl m128 {
     Self(unsafe { _mm_loadu_ps(p) })
   }
 
+  /// Loads the first four `f32` values of the slice, without alignment
+  /// requirement.
+  ///
+  /// If the slice has fewer than four elements you get `None`.
+  #[inline(always)]
+  pub fn load_from_slice(slice: &[f32]) -> Option<Self> {
+    if slice.len() >= 4 {
+      Some(Self(unsafe { _mm_loadu_ps(slice.as_ptr()) }))
+    } else {
+      None
+    }
+  }
+
   /// Loads the `f32` referenced into all lanes.
   #[allow(clippy::trivially_copy_pass_by_ref)]
   #[inline(always)]
@@ -248,7 +261,6 @@ impl m128 {
   #[inline(always)]
   pub fn store_unaligned(self, addr: &mut [f32; 4]) {
     let p = addr as *mut [f32; 4] as *mut f32;
-    debug_assert!(p as usize % 16 == 0);
     unsafe { _mm_storeu_ps(p, self.0) };
   }
 
@@ -756,11 +768,13 @@ macro_rules! shuffle128 {
     const IMM8: i32 = (I0 << 6 | I1 << 4 | I2 << 2 | I3) as i32;
     #[cfg(all(target_arch = "x86", target_feature = "sse"))]
     {
-      m128(unsafe { core::arch::x86::_mm_shuffle_ps($a.0, $b.0, IMM8) })
+      let (a, b): (m128, m128) = ($a, $b);
+      m128(unsafe { core::arch::x86::_mm_shuffle_ps(a.0, b.0, IMM8) })
     }
     #[cfg(all(target_arch = "x86_64", target_feature = "sse"))]
     {
-      m128(unsafe { core::arch::x86_64::_mm_shuffle_ps($a.0, $b.0, IMM8) })
+      let (a, b): (m128, m128) = ($a, $b);
+      m128(unsafe { core::arch::x86_64::_mm_shuffle_ps(a.0, b.0, IMM8) })
     }
     #[cfg(not(target_feature = "sse"))]
     {
@@ -1,3 +1,237 @@
+//! SSE2 wrappers.
+//!
+//! Every stable SSE2 intrinsic in `core::arch` and where to find it:
+//!
+//! | Intrinsic             | Wrapper                                                   |
+//! |-----------------------|-----------------------------------------------------------|
+//! | `_mm_add_epi16`       | `m128i::add_i16`                                          |
+//! | `_mm_add_epi32`       | `m128i::add_i32`                                          |
+//! | `_mm_add_epi64`       | `m128i::add_i64`                                          |
+//! | `_mm_add_epi8`        | `m128i::add_i8`                                           |
+//! | `_mm_add_pd`          | `Add for m128d`                                           |
+//! | `_mm_add_sd`          | `m128d::add_low`                                          |
+//! | `_mm_adds_epi16`      | `m128i::saturating_add_i16`                               |
+//! | `_mm_adds_epi8`       | `m128i::saturating_add_i8`                                |
+//! | `_mm_adds_epu16`      | `m128i::saturating_add_u16`                               |
+//! | `_mm_adds_epu8`       | `m128i::saturating_add_u8`                                |
+//! | `_mm_and_pd`          | `BitAnd for m128d`                                        |
+//! | `_mm_and_si128`       | `BitAnd for m128i`                                        |
+//! | `_mm_andnot_pd`       | `m128d::andnot`                                           |
+//! | `_mm_andnot_si128`    | `m128i::andnot`                                           |
+//! | `_mm_avg_epu16`       | `m128i::average_u16`                                      |
+//! | `_mm_avg_epu8`        | `m128i::average_u8`                                       |
+//! | `_mm_bslli_si128`     | `byte_shift_left!` (same as `_mm_slli_si128`)             |
+//! | `_mm_bsrli_si128`     | `byte_shift_right!` (same as `_mm_srli_si128`)            |
+//! | `_mm_castpd_ps`       | `m128d::cast_m128`                                        |
+//! | `_mm_castpd_si128`    | `m128d::cast_m128i`                                       |
+//! | `_mm_castps_pd`       | `m128::transmute_m128d`                                   |
+//! | `_mm_castps_si128`    | `m128::transmute_m128i`                                   |
+//! | `_mm_castsi128_pd`    | `m128i::cast_m128d`                                       |
+//! | `_mm_castsi128_ps`    | `m128i::transmute_m128`                                   |
+//! | `_mm_clflush`         | `cache_line_flush`                                        |
+//! | `_mm_cmpeq_epi16`     | `m128i::cmp_eq_i16`                                       |
+//! | `_mm_cmpeq_epi32`     | `m128i::cmp_eq_i32`                                       |
+//! | `_mm_cmpeq_epi8`      | `m128i::cmp_eq_i8`                                        |
+//! | `_mm_cmpeq_pd`        | `m128d::cmp_eq`                                           |
+//! | `_mm_cmpeq_sd`        | `m128d::cmp_eq_low`                                       |
+//! | `_mm_cmpge_pd`        | `m128d::cmp_ge`                                           |
+//! | `_mm_cmpge_sd`        | `m128d::cmp_ge_low`                                       |
+//! | `_mm_cmpgt_epi16`     | `m128i::cmp_gt_i16`                                       |
+//! | `_mm_cmpgt_epi32`     | `m128i::cmp_gt_i32`                                       |
+//! | `_mm_cmpgt_epi8`      | `m128i::cmp_gt_i8`                                        |
+//! | `_mm_cmpgt_pd`        | `m128d::cmp_gt`                                           |
+//! | `_mm_cmpgt_sd`        | `m128d::cmp_gt_low`                                       |
+//! | `_mm_cmple_pd`        | `m128d::cmp_le`                                           |
+//! | `_mm_cmple_sd`        | `m128d::cmp_le_low`                                       |
+//! | `_mm_cmplt_epi16`     | `m128i::cmp_lt_i16`                                       |
+//! | `_mm_cmplt_epi32`     | `m128i::cmp_lt_i32`                                       |
+//! | `_mm_cmplt_epi8`      | `m128i::cmp_lt_i8`                                        |
+//! | `_mm_cmplt_pd`        | `m128d::cmp_lt`                                           |
+//! | `_mm_cmplt_sd`        | `m128d::cmp_lt_low`                                       |
+//! | `_mm_cmpneq_pd`       | `m128d::cmp_neq`                                          |
+//! | `_mm_cmpneq_sd`       | `m128d::cmp_neq_low`                                      |
+//! | `_mm_cmpnge_pd`       | `m128d::cmp_nge`                                          |
+//! | `_mm_cmpnge_sd`       | `m128d::cmp_nge_low`                                      |
+//! | `_mm_cmpngt_pd`       | `m128d::cmp_ngt`                                          |
+//! | `_mm_cmpngt_sd`       | `m128d::cmp_ngt_low`                                      |
+//! | `_mm_cmpnle_pd`       | `m128d::cmp_nle`                                          |
+//! | `_mm_cmpnle_sd`       | `m128d::cmp_nle_low`                                      |
+//! | `_mm_cmpnlt_pd`       | `m128d::cmp_nlt`                                          |
+//! | `_mm_cmpnlt_sd`       | `m128d::cmp_nlt_low`                                      |
+//! | `_mm_cmpord_pd`       | `m128d::cmp_nonnan`                                       |
+//! | `_mm_cmpord_sd`       | `m128d::cmp_nonnan_low`                                   |
+//! | `_mm_cmpunord_pd`     | `m128d::cmp_nan`                                          |
+//! | `_mm_cmpunord_sd`     | `m128d::cmp_nan_low`                                      |
+//! | `_mm_comieq_sd`       | `m128d::comi_eq_low`                                      |
+//! | `_mm_comige_sd`       | `m128d::comi_ge_low`                                      |
+//! | `_mm_comigt_sd`       | `m128d::comi_gt_low`                                      |
+//! | `_mm_comile_sd`       | `m128d::comi_le_low`                                      |
+//! | `_mm_comilt_sd`       | `m128d::comi_lt_low`                                      |
+//! | `_mm_comineq_sd`      | `m128d::comi_neq_low`                                     |
+//! | `_mm_cvtepi32_pd`     | `m128i::round_lower_i32_f64`                              |
+//! | `_mm_cvtepi32_ps`     | `m128i::round_i32`                                        |
+//! | `_mm_cvtpd_epi32`     | `m128d::round_i32`                                        |
+//! | `_mm_cvtpd_ps`        | `m128d::round_low_f64`                                    |
+//! | `_mm_cvtps_epi32`     | `m128::round_i32`                                         |
+//! | `_mm_cvtps_pd`        | `m128::round_lower_f64`                                   |
+//! | `_mm_cvtsd_f64`       | `m128d::extract_low_f64`                                  |
+//! | `_mm_cvtsd_si32`      | `m128d::extract_low_i32`                                  |
+//! | `_mm_cvtsd_si64`      | `m128d::extract_i64`                                      |
+//! | `_mm_cvtsd_si64x`     | `m128d::extract_i64` (same as `_mm_cvtsd_si64`)           |
+//! | `_mm_cvtsd_ss`        | `m128::round_replace_low_f64`                             |
+//! | `_mm_cvtsi128_si32`   | `m128i::extract_lowest_i32`                               |
+//! | `_mm_cvtsi128_si64`   | `m128i::extract_lowest_i64`                               |
+//! | `_mm_cvtsi128_si64x`  | `m128i::extract_lowest_i64` (same as `_mm_cvtsi128_si64`) |
+//! | `_mm_cvtsi32_sd`      | `m128d::round_replace_low_i32`                            |
+//! | `_mm_cvtsi32_si128`   | `m128i::set_low_i32`                                      |
+//! | `_mm_cvtsi64_sd`      | `m128d::round_replace_low_i64`                            |
+//! | `_mm_cvtsi64_si128`   | `m128i::set_low_i64`                                      |
+//! | `_mm_cvtsi64x_sd`     | `m128d::round_replace_low_i64` (same as `_mm_cvtsi64_sd`) |
+//! | `_mm_cvtsi64x_si128`  | `m128i::set_low_i64` (same as `_mm_cvtsi64_si128`)        |
+//! | `_mm_cvtss_sd`        | `m128d::round_replace_low_f64`                            |
+//! | `_mm_cvttpd_epi32`    | `m128d::truncate_i32`                                     |
+//! | `_mm_cvttps_epi32`    | `m128::truncate_i32`                                      |
+//! | `_mm_cvttsd_si32`     | `m128d::truncate_i32_low`                                 |
+//! | `_mm_cvttsd_si64`     | `m128d::truncate_i64_low`                                 |
+//! | `_mm_cvttsd_si64x`    | `m128d::truncate_i64_low` (same as `_mm_cvttsd_si64`)     |
+//! | `_mm_div_pd`          | `Div for m128d`                                           |
+//! | `_mm_div_sd`          | `m128d::div_low`                                          |
+//! | `_mm_extract_epi16`   | `m128i::extract_i16`                                      |
+//! | `_mm_insert_epi16`    | `m128i::insert_i16`                                       |
+//! | `_mm_lfence`          | `load_fence`                                              |
+//! | `_mm_load1_pd`        | `m128d::load_all`                                         |
+//! | `_mm_load_pd`         | `m128d::load`                                             |
+//! | `_mm_load_pd1`        | `m128d::load_all` (same as `_mm_load1_pd`)                |
+//! | `_mm_load_sd`         | `m128d::load_low`                                         |
+//! | `_mm_load_si128`      | `m128i::load`                                             |
+//! | `_mm_loadh_pd`        | `m128d::load_high`                                        |
+//! | `_mm_loadl_epi64`     | `m128i::load_low_i64`                                     |
+//! | `_mm_loadl_pd`        | `m128d::load_replace_low`                                 |
+//! | `_mm_loadr_pd`        | `m128d::load_reverse`                                     |
+//! | `_mm_loadu_pd`        | `m128d::load_unaligned`                                   |
+//! | `_mm_loadu_si128`     | `m128i::load_unaligned`                                   |
+//! | `_mm_loadu_si16`      | `m128i::load_unaligned_low_i16`                           |
+//! | `_mm_loadu_si32`      | `m128i::load_unaligned_low_i32`                           |
+//! | `_mm_loadu_si64`      | `m128i::load_unaligned_low_i64`                           |
+//! | `_mm_madd_epi16`      | `m128i::mul_hadd_i16`                                     |
+//! | `_mm_maskmoveu_si128` | `m128i::masked_store`                                     |
+//! | `_mm_max_epi16`       | `m128i::max_i16`                                          |
+//! | `_mm_max_epu8`        | `m128i::max_u8`                                           |
+//! | `_mm_max_pd`          | `m128d::max`                                              |
+//! | `_mm_max_sd`          | `m128d::max_low`                                          |
+//! | `_mm_mfence`          | `memory_fence`                                            |
+//! | `_mm_min_epi16`       | `m128i::min_i16`                                          |
+//! | `_mm_min_epu8`        | `m128i::min_u8`                                           |
+//! | `_mm_min_pd`          | `m128d::min`                                              |
+//! | `_mm_min_sd`          | `m128d::min_low`                                          |
+//! | `_mm_move_epi64`      | `m128i::move_low_i64`                                     |
+//! | `_mm_move_sd`         | `m128d::move_low`                                         |
+//! | `_mm_movemask_epi8`   | `m128i::movemask_i8`                                      |
+//! | `_mm_movemask_pd`     | `m128d::move_mask`                                        |
+//! | `_mm_mul_epu32`       | `m128i::mul_low32_u64`                                    |
+//! | `_mm_mul_pd`          | `Mul for m128d`                                           |
+//! | `_mm_mul_sd`          | `m128d::mul_low`                                          |
+//! | `_mm_mulhi_epi16`     | `m128i::mul_high_i16`                                     |
+//! | `_mm_mulhi_epu16`     | `m128i::mul_high_u16`                                     |
+//! | `_mm_mullo_epi16`     | `m128i::mul_low_i16`                                      |
+//! | `_mm_or_pd`           | `BitOr for m128d`                                         |
+//! | `_mm_or_si128`        | `BitOr for m128i`                                         |
+//! | `_mm_packs_epi16`     | `m128i::saturating_pack_i16_i8`                           |
+//! | `_mm_packs_epi32`     | `m128i::saturating_pack_i32_i16`                          |
+//! | `_mm_packus_epi16`    | `m128i::saturating_pack_i16_u8`                           |
+//! | `_mm_pause`           | `pause`                                                   |
+//! | `_mm_sad_epu8`        | `m128i::sad_u8`                                           |
+//! | `_mm_set1_epi16`      | `m128i::splat_i16`                                        |
+//! | `_mm_set1_epi32`      | `m128i::splat_i32`                                        |
+//! | `_mm_set1_epi64x`     | `m128i::splat_i64`                                        |
+//! | `_mm_set1_epi8`       | `m128i::splat_i8`                                         |
+//! | `_mm_set1_pd`         | `m128d::splat`                                            |
+//! | `_mm_set_epi16`       | `m128i::set_i16`                                          |
+//! | `_mm_set_epi32`       | `m128i::set_i32`                                          |
+//! | `_mm_set_epi64x`      | `m128i::set_i64`                                          |
+//! | `_mm_set_epi8`        | `m128i::set_i8`                                           |
+//! | `_mm_set_pd`          | `m128d::set`                                              |
+//! | `_mm_set_pd1`         | `m128d::splat` (same as `_mm_set1_pd`)                    |
+//! | `_mm_set_sd`          | `m128d::set_low`                                          |
+//! | `_mm_setr_epi16`      | `m128i::setr_i16`                                         |
+//! | `_mm_setr_epi32`      | `m128i::setr_i32`                                         |
+//! | `_mm_setr_epi8`       | `m128i::setr_i8`                                          |
+//! | `_mm_setr_pd`         | `m128d::setr`                                             |
+//! | `_mm_setzero_pd`      | `m128d::zeroed`                                           |
+//! | `_mm_setzero_si128`   | `m128i::zeroed`                                           |
+//! | `_mm_shuffle_epi32`   | `shuffle_i32!`                                            |
+//! | `_mm_shuffle_pd`      | `shuffle128d!`                                            |
+//! | `_mm_shufflehi_epi16` | `shuffle_high_i16!`                                       |
+//! | `_mm_shufflelo_epi16` | `shuffle_low_i16!`                                        |
+//! | `_mm_sll_epi16`       | `m128i::shl_i16`                                          |
+//! | `_mm_sll_epi32`       | `m128i::shl_i32`                                          |
+//! | `_mm_sll_epi64`       | `m128i::shl_i64`                                          |
+//! | `_mm_slli_epi16`      | `shl_i16!`                                                |
+//! | `_mm_slli_epi32`      | `shl_i32!`                                                |
+//! | `_mm_slli_epi64`      | `shl_i64!`                                                |
+//! | `_mm_slli_si128`      | `byte_shift_left!`                                        |
+//! | `_mm_sqrt_pd`         | `m128d::sqrt`                                             |
+//! | `_mm_sqrt_sd`         | `m128d::sqrt_low`                                         |
+//! | `_mm_sra_epi16`       | `m128i::shr_arith_i16`                                    |
+//! | `_mm_sra_epi32`       | `m128i::shr_arith_i32`                                    |
+//! | `_mm_srai_epi16`      | `shr_arith_i16!`                                          |
+//! | `_mm_srai_epi32`      | `shr_arith_i32!`                                          |
+//! | `_mm_srl_epi16`       | `m128i::shr_logical_u16`                                  |
+//! | `_mm_srl_epi32`       | `m128i::shr_logical_u32`                                  |
+//! | `_mm_srl_epi64`       | `m128i::shr_logical_u64`                                  |
+//! | `_mm_srli_epi16`      | `shr_logical_u16!`                                        |
+//! | `_mm_srli_epi32`      | `shr_logical_u32!`                                        |
+//! | `_mm_srli_epi64`      | `shr_logical_u64!`                                        |
+//! | `_mm_srli_si128`      | `byte_shift_right!`                                       |
+//! | `_mm_store1_pd`       | `m128d::store_lower_all`                                  |
+//! | `_mm_store_pd`        | `m128d::store`                                            |
+//! | `_mm_store_pd1`       | `m128d::store_lower_all` (same as `_mm_store1_pd`)        |
+//! | `_mm_store_sd`        | `m128d::store_low` (same as `_mm_storel_pd`)              |
+//! | `_mm_store_si128`     | `m128i::store`                                            |
+//! | `_mm_storeh_pd`       | `m128d::store_high`                                       |
+//! | `_mm_storel_epi64`    | `m128i::store_low_i64`                                    |
+//! | `_mm_storel_pd`       | `m128d::store_low`                                        |
+//! | `_mm_storer_pd`       | `m128d::storer`                                           |
+//! | `_mm_storeu_pd`       | `m128d::storeu`                                           |
+//! | `_mm_storeu_si128`    | `m128i::storeu`                                           |
+//! | `_mm_storeu_si16`     | `m128i::store_unaligned_low_i16`                          |
+//! | `_mm_storeu_si32`     | `m128i::store_unaligned_low_i32`                          |
+//! | `_mm_storeu_si64`     | `m128i::store_unaligned_low_i64`                          |
+//! | `_mm_stream_pd`       | `m128d::stream`                                           |
+//! | `_mm_stream_si128`    | `m128i::stream`                                           |
+//! | `_mm_stream_si32`     | `stream_i32`                                              |
+//! | `_mm_stream_si64`     | `stream_i64`                                              |
+//! | `_mm_sub_epi16`       | `m128i::sub_i16`                                          |
+//! | `_mm_sub_epi32`       | `m128i::sub_i32`                                          |
+//! | `_mm_sub_epi64`       | `m128i::sub_i64`                                          |
+//! | `_mm_sub_epi8`        | `m128i::sub_i8`                                           |
+//! | `_mm_sub_pd`          | `Sub for m128d`                                           |
+//! | `_mm_sub_sd`          | `m128d::sub_low`                                          |
+//! | `_mm_subs_epi16`      | `m128i::saturating_sub_i16`                               |
+//! | `_mm_subs_epi8`       | `m128i::saturating_sub_i8`                                |
+//! | `_mm_subs_epu16`      | `m128i::saturating_sub_u16`                               |
+//! | `_mm_subs_epu8`       | `m128i::saturating_sub_u8`                                |
+//! | `_mm_ucomieq_sd`      | `m128d::ucmp_int_eq_low`                                  |
+//! | `_mm_ucomige_sd`      | `m128d::ucmp_int_ge_low`                                  |
+//! | `_mm_ucomigt_sd`      | `m128d::ucmp_int_gt_low`                                  |
+//! | `_mm_ucomile_sd`      | `m128d::ucmp_int_le_low`                                  |
+//! | `_mm_ucomilt_sd`      | `m128d::ucmp_int_lt_low`                                  |
+//! | `_mm_ucomineq_sd`     | `m128d::ucmp_int_neq_low`                                 |
+//! | `_mm_undefined_pd`    | not wrapped, use `m128d::zeroed`                          |
+//! | `_mm_undefined_si128` | not wrapped, use `m128i::zeroed`                          |
+//! | `_mm_unpackhi_epi16`  | `m128i::unpack_high_i16`                                  |
+//! | `_mm_unpackhi_epi32`  | `m128i::unpack_high_i32`                                  |
+//! | `_mm_unpackhi_epi64`  | `m128i::unpack_high_i64`                                  |
+//! | `_mm_unpackhi_epi8`   | `m128i::unpack_high_i8`                                   |
+//! | `_mm_unpackhi_pd`     | `m128d::unpack_high`                                      |
+//! | `_mm_unpacklo_epi16`  | `m128i::unpack_low_i16`                                   |
+//! | `_mm_unpacklo_epi32`  | `m128i::unpack_low_i32`                                   |
+//! | `_mm_unpacklo_epi64`  | `m128i::unpack_low_i64`                                   |
+//! | `_mm_unpacklo_epi8`   | `m128i::unpack_low_i8`                                    |
+//! | `_mm_unpacklo_pd`     | `m128d::unpack_low`                                       |
+//! | `_mm_xor_pd`          | `BitXor for m128d`                                        |
+//! | `_mm_xor_si128`       | `BitXor for m128i`                                        |
+
 #![allow(clippy::use_self)]
 use super::*;
 use core::ops::*;
@@ -40,6 +274,121 @@ impl m128 {
   pub fn round_lower_f64(self) -> m128d {
     m128d(unsafe { _mm_cvtps_pd(self.0) })
   }
+
+  /// lanewise absolute value (clears the sign bit).
+  #[inline(always)]
+  pub fn abs(self) -> m128 {
+    m128::splat(-0.0).andnot(self)
+  }
+
+  /// lanewise magnitude of `self` with the sign bit of `sign`.
+  #[inline(always)]
+  pub fn copysign(self, sign: m128) -> m128 {
+    let sign_bit = m128::splat(-0.0);
+    sign_bit.andnot(self) | (sign & sign_bit)
+  }
+
+  /// lanewise `1.0` for a positive sign bit, `-1.0` for a negative sign bit,
+  /// and NaN for NaN (same as [`f32::signum`]).
+  #[inline(always)]
+  pub fn signum(self) -> m128 {
+    m128::splat(1.0).copysign(self) | self.cmp_nan(self)
+  }
+
+  /// lanewise round toward zero (same as `f32::trunc`).
+  ///
+  /// Unlike [`truncate_i32`](m128::truncate_i32) the lanes stay as `f32`, so
+  /// values outside of the `i32` range, infinities, and NaN pass through
+  /// unchanged. Does not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn trunc(self) -> m128 {
+    #[cfg(target_feature = "sse4.1")]
+    {
+      m128(unsafe { _mm_round_ps(self.0, _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC) })
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+      // Every `f32` with a magnitude of 2^23 or more is already an integer, and
+      // everything smaller fits in an `i32`, so going through `i32` and back is
+      // exact for the lanes we actually need to change.
+      let small = self.abs().cmp_lt(m128::splat(8_388_608.0));
+      let t = self.truncate_i32().round_i32().copysign(self);
+      (t & small) | small.andnot(self)
+    }
+  }
+
+  /// lanewise round toward negative infinity (same as `f32::floor`).
+  ///
+  /// Does not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn floor(self) -> m128 {
+    #[cfg(target_feature = "sse4.1")]
+    {
+      m128(unsafe { _mm_round_ps(self.0, _MM_FROUND_TO_NEG_INF | _MM_FROUND_NO_EXC) })
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+      let t = self.trunc();
+      let fix = t.cmp_gt(self) & m128::splat(1.0);
+      (t - fix).copysign(self)
+    }
+  }
+
+  /// lanewise round toward positive infinity (same as `f32::ceil`).
+  ///
+  /// Does not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn ceil(self) -> m128 {
+    #[cfg(target_feature = "sse4.1")]
+    {
+      m128(unsafe { _mm_round_ps(self.0, _MM_FROUND_TO_POS_INF | _MM_FROUND_NO_EXC) })
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+      let t = self.trunc();
+      let fix = t.cmp_lt(self) & m128::splat(1.0);
+      (t + fix).copysign(self)
+    }
+  }
+
+  /// lanewise round to nearest, with ties away from zero (same as
+  /// `f32::round`).
+  ///
+  /// Does not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn round(self) -> m128 {
+    let t = self.trunc();
+    let away = (self - t).abs().cmp_ge(m128::splat(0.5));
+    (t + (away & m128::splat(1.0).copysign(self))).copysign(self)
+  }
+
+  /// lanewise round to nearest, with ties to even (same as
+  /// `f32::round_ties_even`).
+  ///
+  /// Does not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn round_even(self) -> m128 {
+    #[cfg(target_feature = "sse4.1")]
+    {
+      m128(unsafe { _mm_round_ps(self.0, _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC) })
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+      let half = m128::splat(0.5);
+      let t = self.trunc();
+      let diff = (self - t).abs();
+      let t_half = t * half;
+      let odd = t_half.trunc().cmp_neq(t_half);
+      let away = diff.cmp_gt(half) | (diff.cmp_eq(half) & odd);
+      (t + (away & m128::splat(1.0).copysign(self))).copysign(self)
+    }
+  }
+
+  /// lanewise fractional part, `self - self.trunc()` (same as `f32::fract`).
+  #[inline(always)]
+  pub fn fract(self) -> m128 {
+    self - self.trunc()
+  }
 }
 
 /// A 128-bit SIMD value. Holds integral data of an undefined layout.
@@ -388,14 +737,14 @@ impl m128i {
   /// the low lanes, and `other` makes the high lanes.
   #[inline(always)]
   pub fn saturating_pack_i16_i8(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_mullo_epi16(self.0, other.0) })
+    m128i(unsafe { _mm_packs_epi16(self.0, other.0) })
   }
 
   /// lanewise i16x8 pack each lane into a saturated `u8` value, `self` makes up
   /// the low lanes, and `other` makes the high lanes.
   #[inline(always)]
   pub fn saturating_pack_i16_u8(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_packs_epi16(self.0, other.0) })
+    m128i(unsafe { _mm_packus_epi16(self.0, other.0) })
   }
 
   /// Sets the value as all `i16` lanes
@@ -418,37 +767,34 @@ impl m128i {
     m128i(unsafe { _mm_setr_epi16(e7, e6, e5, e4, e3, e2, e1, e0) })
   }
 
-  // TODO: _mm_shufflehi_epi16 (macro)
-
-  // TODO: _mm_shufflelo_epi16 (macro)
-
-  /// lanewise i16x8 shift left (0s shifted in). `other` provides the amount to
-  /// shift each lane.
+  /// lanewise i16x8 shift left (0s shifted in) by `count` bits.
+  ///
+  /// A `count` of 16 or more makes every lane 0. See
+  /// [`shl_i16!`](crate::shl_i16) for an immediate shift amount.
   #[inline(always)]
-  pub fn shift_left_i16(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_sll_epi16(self.0, other.0) })
+  pub fn shl_i16(self, count: u32) -> m128i {
+    m128i(unsafe { _mm_sll_epi16(self.0, _mm_cvtsi32_si128(count as i32)) })
   }
 
-  // TODO: _mm_slli_epi16 (macro)
-
-  /// lanewise i16x8 shift right (sign bit shifted in). `other` provides the
-  /// amount to shift each lane.
+  /// lanewise i16x8 arithmetic shift right (sign bit shifted in) by `count` bits.
+  ///
+  /// A `count` of 16 or more fills every lane with its sign bit. See
+  /// [`shr_arith_i16!`](crate::shr_arith_i16) for an immediate shift amount.
   #[inline(always)]
-  pub fn shift_right_sign_i16(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_sra_epi16(self.0, other.0) })
+  pub fn shr_arith_i16(self, count: u32) -> m128i {
+    m128i(unsafe { _mm_sra_epi16(self.0, _mm_cvtsi32_si128(count as i32)) })
   }
 
-  // TODO: _mm_srai_epi16 (macro)
-
-  /// lanewise i16x8 shift right (0s shifted in). `other` provides the
-  /// amount to shift each lane.
+  /// lanewise u16x8 logical shift right (0s shifted in) by `count` bits.
+  ///
+  /// A `count` of 16 or more makes every lane 0. See
+  /// [`shr_logical_u16!`](crate::shr_logical_u16) for an immediate shift
+  /// amount.
   #[inline(always)]
-  pub fn shift_right_zero_i16(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_srl_epi16(self.0, other.0) })
+  pub fn shr_logical_u16(self, count: u32) -> m128i {
+    m128i(unsafe { _mm_srl_epi16(self.0, _mm_cvtsi32_si128(count as i32)) })
   }
 
-  // TODO: _mm_srli_epi16 (macro)
-
   /// lanewise i16x8 subtraction
   #[inline(always)]
   pub fn sub_i16(self, other: m128i) -> m128i {
@@ -517,6 +863,30 @@ impl m128i {
     m128i(unsafe { _mm_mul_epu32(self.0, other.0) })
   }
 
+  /// lanewise i32x4 multiply and keep the low half of the `i64` intermediate.
+  ///
+  /// This is basically a `wrapping_mul_i32`. SSE2 only has the
+  /// [`mul_low32_u64`](m128i::mul_low32_u64) form, so without `sse4.1` this is
+  /// emulated with two of those (the low 32 bits of a product are the same for
+  /// signed and unsigned inputs).
+  #[inline(always)]
+  pub fn mul_low_i32(self, other: m128i) -> m128i {
+    #[cfg(target_feature = "sse4.1")]
+    {
+      m128i(unsafe { _mm_mullo_epi32(self.0, other.0) })
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+      let even = self.mul_low32_u64(other);
+      let odd = self
+        .shr_logical_u64(32)
+        .mul_low32_u64(other.shr_logical_u64(32));
+      let even = m128i(unsafe { _mm_shuffle_epi32(even.0, 0b00_00_10_00) });
+      let odd = m128i(unsafe { _mm_shuffle_epi32(odd.0, 0b00_00_10_00) });
+      even.unpack_low_i32(odd)
+    }
+  }
+
   /// lanewise i32x4 addition
   #[inline(always)]
   pub fn add_i32(self, other: m128i) -> m128i {
@@ -560,34 +930,39 @@ impl m128i {
     m128i(unsafe { _mm_set_epi32(e3, e2, e1, e0) })
   }
 
-  // TODO: _mm_shuffle_epi32 (macro)
-
-  /// lanewise i32x4 shift left (0s shifted in). `other` provides the amount to
-  /// shift each lane.
+  /// Sets the `i32` values in a register reversed, low to high.
   #[inline(always)]
-  pub fn shift_left_i32(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_sll_epi32(self.0, other.0) })
+  pub fn setr_i32(e0: i32, e1: i32, e2: i32, e3: i32) -> Self {
+    m128i(unsafe { _mm_setr_epi32(e0, e1, e2, e3) })
   }
 
-  // TODO: _mm_slli_epi32 (macro)
-
-  /// lanewise i32x4 shift right (sign bit shifted in). `other` provides the
-  /// amount to shift each lane.
+  /// lanewise i32x4 shift left (0s shifted in) by `count` bits.
+  ///
+  /// A `count` of 32 or more makes every lane 0. See
+  /// [`shl_i32!`](crate::shl_i32) for an immediate shift amount.
   #[inline(always)]
-  pub fn shift_right_sign_i32(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_sra_epi32(self.0, other.0) })
+  pub fn shl_i32(self, count: u32) -> m128i {
+    m128i(unsafe { _mm_sll_epi32(self.0, _mm_cvtsi32_si128(count as i32)) })
   }
 
-  // TODO: _mm_srai_epi32 (macro)
-
-  /// lanewise i32x4 shift right (0s shifted in). `other` provides the
-  /// amount to shift each lane.
+  /// lanewise i32x4 arithmetic shift right (sign bit shifted in) by `count` bits.
+  ///
+  /// A `count` of 32 or more fills every lane with its sign bit. See
+  /// [`shr_arith_i32!`](crate::shr_arith_i32) for an immediate shift amount.
   #[inline(always)]
-  pub fn shift_right_zero_i32(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_srl_epi32(self.0, other.0) })
+  pub fn shr_arith_i32(self, count: u32) -> m128i {
+    m128i(unsafe { _mm_sra_epi32(self.0, _mm_cvtsi32_si128(count as i32)) })
   }
 
-  // TODO: _mm_srli_epi32 (macro)
+  /// lanewise u32x4 logical shift right (0s shifted in) by `count` bits.
+  ///
+  /// A `count` of 32 or more makes every lane 0. See
+  /// [`shr_logical_u32!`](crate::shr_logical_u32) for an immediate shift
+  /// amount.
+  #[inline(always)]
+  pub fn shr_logical_u32(self, count: u32) -> m128i {
+    m128i(unsafe { _mm_srl_epi32(self.0, _mm_cvtsi32_si128(count as i32)) })
+  }
 
   /// lanewise i32x4 subtraction
   #[inline(always)]
@@ -645,23 +1020,38 @@ impl m128i {
     m128i(unsafe { _mm_move_epi64(self.0) })
   }
 
-  /// lanewise i64x2 shift left (0s shifted in). `other` provides the amount to
-  /// shift each lane.
+  /// lanewise i64x2 shift left (0s shifted in) by `count` bits.
+  ///
+  /// A `count` of 64 or more makes every lane 0. See
+  /// [`shl_i64!`](crate::shl_i64) for an immediate shift amount.
   #[inline(always)]
-  pub fn shift_left_i64(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_sll_epi64(self.0, other.0) })
+  pub fn shl_i64(self, count: u32) -> m128i {
+    m128i(unsafe { _mm_sll_epi64(self.0, _mm_cvtsi32_si128(count as i32)) })
   }
 
-  // TODO: _mm_slli_epi64 (macro)
-
-  /// lanewise i64x2 shift right (0s shifted in). `other` provides the
-  /// amount to shift each lane.
+  /// lanewise u64x2 logical shift right (0s shifted in) by `count` bits.
+  ///
+  /// A `count` of 64 or more makes every lane 0. See
+  /// [`shr_logical_u64!`](crate::shr_logical_u64) for an immediate shift
+  /// amount.
   #[inline(always)]
-  pub fn shift_right_zero_i64(self, other: m128i) -> m128i {
-    m128i(unsafe { _mm_srl_epi64(self.0, other.0) })
+  pub fn shr_logical_u64(self, count: u32) -> m128i {
+    m128i(unsafe { _mm_srl_epi64(self.0, _mm_cvtsi32_si128(count as i32)) })
   }
 
-  // TODO: _mm_srli_epi64 (macro)
+  /// lanewise i64x2 arithmetic shift right (sign bit shifted in) by `count`
+  /// bits.
+  ///
+  /// SSE2 doesn't have this instruction, so it's emulated with a logical shift
+  /// and a sign fix up. A `count` of 64 or more fills every lane with its sign
+  /// bit.
+  #[inline(always)]
+  pub fn shr_arith_i64(self, count: u32) -> m128i {
+    let count = count.min(63);
+    let sign = m128i::splat_i64(i64::MIN).shr_logical_u64(count);
+    let shifted = self.shr_logical_u64(count);
+    (shifted ^ sign).sub_i64(sign)
+  }
 
   /// Store the low `i64` lane to the address provided.
   #[inline(always)]
@@ -669,6 +1059,45 @@ impl m128i {
     unsafe { _mm_storel_epi64(&mut addr.0, self.0) }
   }
 
+  // `_mm_loadu_si16`, `_mm_loadu_si32`, and the unaligned stores are newer than
+  // our minimum Rust version, so these are built from older intrinsics.
+
+  /// Loads 2 bytes into the low `i16` lane, other lanes 0.
+  #[inline(always)]
+  pub fn load_unaligned_low_i16(addr: &[u8; 2]) -> m128i {
+    m128i::set_low_i32(i32::from(u16::from_le_bytes(*addr)))
+  }
+
+  /// Loads 4 bytes into the low `i32` lane, other lanes 0.
+  #[inline(always)]
+  pub fn load_unaligned_low_i32(addr: &[u8; 4]) -> m128i {
+    m128i::set_low_i32(i32::from_le_bytes(*addr))
+  }
+
+  /// Loads 8 bytes into the low `i64` lane, other lane 0.
+  #[inline(always)]
+  pub fn load_unaligned_low_i64(addr: &[u8; 8]) -> m128i {
+    m128i(unsafe { _mm_loadu_si64(addr.as_ptr()) })
+  }
+
+  /// Stores the low `i16` lane as 2 bytes.
+  #[inline(always)]
+  pub fn store_unaligned_low_i16(self, addr: &mut [u8; 2]) {
+    *addr = (self.extract_lowest_i32() as u16).to_le_bytes();
+  }
+
+  /// Stores the low `i32` lane as 4 bytes.
+  #[inline(always)]
+  pub fn store_unaligned_low_i32(self, addr: &mut [u8; 4]) {
+    *addr = self.extract_lowest_i32().to_le_bytes();
+  }
+
+  /// Stores the low `i64` lane as 8 bytes.
+  #[inline(always)]
+  pub fn store_unaligned_low_i64(self, addr: &mut [u8; 8]) {
+    unsafe { _mm_storel_epi64(addr.as_mut_ptr().cast(), self.0) }
+  }
+
   /// lanewise i64x2 subtraction
   #[inline(always)]
   pub fn sub_i64(self, other: m128i) -> m128i {
@@ -726,10 +1155,6 @@ impl m128i {
     m128i(unsafe { _mm_andnot_si128(self.0, other.0) })
   }
 
-  // TODO: _mm_bslli_si128 (immediate)
-
-  // TODO: _mm_bsrli_si128 (immediate)
-
   /// Sets the `i32` as the lowest lane, other lanes 0
   #[inline(always)]
   pub fn set_low_i32(val: i32) -> Self {
@@ -751,11 +1176,28 @@ impl m128i {
     m128i(unsafe { _mm_load_si128(p) })
   }
 
-  /// Loads the integer data from the address given without alignment requirement.
+  /// Loads the integer data from the bytes given without alignment requirement.
   #[inline(always)]
-  pub fn load_unaligned(addr: *const i128) -> Self {
+  pub fn load_unaligned(addr: &[u8; 16]) -> Self {
+    let p = addr as *const [u8; 16] as *const __m128i;
     #[allow(clippy::cast_ptr_alignment)]
-    m128i(unsafe { _mm_loadu_si128(addr as *const _) })
+    m128i(unsafe { _mm_loadu_si128(p) })
+  }
+
+  /// Loads the first 16 bytes of the slice, without alignment requirement.
+  ///
+  /// If the slice isn't at least 16 bytes long you get `None`.
+  #[inline(always)]
+  pub fn load_from_slice<T: Pod>(slice: &[T]) -> Option<Self> {
+    let bytes: &[u8] = try_cast_slice(slice).ok()?;
+    if bytes.len() >= 16 {
+      #[allow(clippy::cast_ptr_alignment)]
+      Some(m128i(unsafe {
+        _mm_loadu_si128(bytes.as_ptr() as *const __m128i)
+      }))
+    } else {
+      None
+    }
   }
 
   /// Creates a zeroed value.
@@ -764,10 +1206,6 @@ impl m128i {
     m128i(unsafe { _mm_setzero_si128() })
   }
 
-  // TODO: _mm_slli_si128 (immediate)
-
-  // TODO: _mm_srli_si128 (immediate)
-
   /// Store the data as a single `u128`, and you can re-interpret that however
   /// you like.
   #[inline(always)]
@@ -777,13 +1215,42 @@ impl m128i {
     unsafe { _mm_store_si128(p, self.0) };
   }
 
-  /// Store the data as a single `i128`, and you can re-interpret that however
-  /// you like.
+  /// Store the data as bytes, without alignment requirement.
   #[inline(always)]
-  pub fn storeu(self, addr: &mut Align16<i128>) {
+  pub fn storeu(self, addr: &mut [u8; 16]) {
+    let p = addr as *mut [u8; 16] as *mut __m128i;
+    #[allow(clippy::cast_ptr_alignment)]
+    unsafe {
+      _mm_storeu_si128(p, self.0)
+    };
+  }
+
+  /// Store the data with a non-temporal hint (it's written without going
+  /// through the cache).
+  ///
+  /// Non-temporal stores aren't ordered with other memory operations, so this
+  /// also performs a [`store_fence`] afterward. That keeps the wrapper sound,
+  /// but if you're streaming a lot of data the fence is most of the cost.
+  #[inline(always)]
+  pub fn stream(self, addr: &mut Align16<i128>) {
     let p = addr as *mut Align16<i128> as *mut __m128i;
-    debug_assert!(p as usize % 16 == 0);
-    unsafe { _mm_storeu_si128(p, self.0) };
+    unsafe {
+      _mm_stream_si128(p, self.0);
+      _mm_sfence();
+    }
+  }
+
+  /// Stores each `i8` lane of `self` where the matching lane of `mask` has its
+  /// high bit set, other bytes of `addr` are left alone.
+  ///
+  /// This store has a non-temporal hint, so as with [`stream`](m128i::stream)
+  /// it also performs a [`store_fence`] afterward.
+  #[inline(always)]
+  pub fn masked_store(self, mask: m128i, addr: &mut [i8; 16]) {
+    unsafe {
+      _mm_maskmoveu_si128(self.0, mask.0, addr.as_mut_ptr());
+      _mm_sfence();
+    }
   }
 
   /// As [store](m128i::store), but returns a new `i128` for you.
@@ -819,6 +1286,129 @@ impl m128i {
   }
 }
 
+/// Declares a lane-typed wrapper around [`m128i`].
+///
+/// The wrapper picks a lane interpretation for the bits so that operators
+/// which depend on the lane type (such as `>>`) can be provided.
+macro_rules! m128i_lane_wrapper {
+  (
+    $(#[$attr:meta])*
+    $name:ident, $lane:ty, $lanes:expr, $shl:ident, $shr:ident
+  ) => {
+    $(#[$attr])*
+    #[derive(Clone, Copy)]
+    #[allow(bad_style)]
+    #[repr(transparent)]
+    pub struct $name(pub m128i);
+
+    unsafe impl Zeroable for $name {}
+    unsafe impl Pod for $name {}
+
+    impl core::fmt::Debug for $name {
+      /// Formats in set/store order: high index lane to low index lane.
+      fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let a: [$lane; $lanes] = cast(self.0.to_i128());
+        write!(f, "{}(", stringify!($name))?;
+        for (i, lane) in a.iter().rev().enumerate() {
+          if i > 0 {
+            write!(f, ", ")?;
+          }
+          write!(f, "{}", lane)?;
+        }
+        write!(f, ")")
+      }
+    }
+
+    impl Shl<u32> for $name {
+      type Output = Self;
+      /// lanewise shift left
+      #[inline(always)]
+      fn shl(self, count: u32) -> Self {
+        $name(self.0.$shl(count))
+      }
+    }
+
+    impl Shr<u32> for $name {
+      type Output = Self;
+      /// lanewise shift right
+      #[inline(always)]
+      fn shr(self, count: u32) -> Self {
+        $name(self.0.$shr(count))
+      }
+    }
+
+    impl ShlAssign<u32> for $name {
+      /// lanewise shift left then assignment
+      #[inline(always)]
+      fn shl_assign(&mut self, count: u32) {
+        self.0 = self.0.$shl(count);
+      }
+    }
+
+    impl ShrAssign<u32> for $name {
+      /// lanewise shift right then assignment
+      #[inline(always)]
+      fn shr_assign(&mut self, count: u32) {
+        self.0 = self.0.$shr(count);
+      }
+    }
+  };
+}
+
+m128i_lane_wrapper!(
+  /// An [`m128i`] used as `i16x8`.
+  ///
+  /// `<<` and `>>` take a `u32` shift count, and `>>` is an arithmetic shift. Counts
+  /// of the lane bit width or more don't panic, they give the same result as
+  /// shifting one bit at a time that many times.
+  i16x8, i16, 8, shl_i16, shr_arith_i16
+);
+
+m128i_lane_wrapper!(
+  /// An [`m128i`] used as `u16x8`.
+  ///
+  /// `<<` and `>>` take a `u32` shift count, and `>>` is a logical shift. Counts
+  /// of the lane bit width or more don't panic, they give the same result as
+  /// shifting one bit at a time that many times.
+  u16x8, u16, 8, shl_i16, shr_logical_u16
+);
+
+m128i_lane_wrapper!(
+  /// An [`m128i`] used as `i32x4`.
+  ///
+  /// `<<` and `>>` take a `u32` shift count, and `>>` is an arithmetic shift. Counts
+  /// of the lane bit width or more don't panic, they give the same result as
+  /// shifting one bit at a time that many times.
+  i32x4, i32, 4, shl_i32, shr_arith_i32
+);
+
+m128i_lane_wrapper!(
+  /// An [`m128i`] used as `u32x4`.
+  ///
+  /// `<<` and `>>` take a `u32` shift count, and `>>` is a logical shift. Counts
+  /// of the lane bit width or more don't panic, they give the same result as
+  /// shifting one bit at a time that many times.
+  u32x4, u32, 4, shl_i32, shr_logical_u32
+);
+
+m128i_lane_wrapper!(
+  /// An [`m128i`] used as `i64x2`.
+  ///
+  /// `<<` and `>>` take a `u32` shift count, and `>>` is an arithmetic shift. Counts
+  /// of the lane bit width or more don't panic, they give the same result as
+  /// shifting one bit at a time that many times.
+  i64x2, i64, 2, shl_i64, shr_arith_i64
+);
+
+m128i_lane_wrapper!(
+  /// An [`m128i`] used as `u64x2`.
+  ///
+  /// `<<` and `>>` take a `u32` shift count, and `>>` is a logical shift. Counts
+  /// of the lane bit width or more don't panic, they give the same result as
+  /// shifting one bit at a time that many times.
+  u64x2, u64, 2, shl_i64, shr_logical_u64
+);
+
 /// A 128-bit SIMD value. Always used as `f64x2`.
 ///
 /// * The convention for SIMD data is that, similar to a `u128` or `i128`, the
@@ -1108,7 +1698,6 @@ impl m128d {
   /// Loads the `f64` values, high index to low index.
   #[inline(always)]
   pub fn load(arr: &Align16<[f64; 2]>) -> m128d {
-    // TODO: TEST THAT INDEX 0 GOES INTO LANE 0.
     let p = arr as *const Align16<[f64; 2]> as *const f64;
     debug_assert!(p as usize % 16 == 0);
     m128d(unsafe { _mm_load_pd(p) })
@@ -1131,7 +1720,6 @@ impl m128d {
   /// Loads the `f64` values, low index to high index.
   #[inline(always)]
   pub fn load_reverse(arr: &Align16<[f64; 2]>) -> m128d {
-    // TODO: TEST THAT INDEX 0 GOES INTO LANE 0.
     let p = arr as *const Align16<[f64; 2]> as *const f64;
     debug_assert!(p as usize % 16 == 0);
     m128d(unsafe { _mm_loadr_pd(p) })
@@ -1140,12 +1728,23 @@ impl m128d {
   /// Loads the `f64` values, high index to low index.
   #[inline(always)]
   pub fn load_unaligned(arr: &[f64; 2]) -> m128d {
-    // TODO: TEST THAT INDEX 0 GOES INTO LANE 0.
     let p = arr as *const [f64; 2] as *const f64;
-    debug_assert!(p as usize % 16 == 0);
     m128d(unsafe { _mm_loadu_pd(p) })
   }
 
+  /// Loads the first two `f64` values of the slice, without alignment
+  /// requirement.
+  ///
+  /// If the slice has fewer than two elements you get `None`.
+  #[inline(always)]
+  pub fn load_from_slice(slice: &[f64]) -> Option<Self> {
+    if slice.len() >= 2 {
+      Some(m128d(unsafe { _mm_loadu_pd(slice.as_ptr()) }))
+    } else {
+      None
+    }
+  }
+
   /// lanewise f64x2 maximum
   #[inline(always)]
   pub fn max(self, other: m128d) -> m128d {
@@ -1188,8 +1787,6 @@ impl m128d {
     m128d(unsafe { _mm_setzero_pd() })
   }
 
-  // TODO: _mm_shuffle_pd (macro? only two bits of input)
-
   /// lanewise f64x2 square root.
   #[inline(always)]
   pub fn sqrt(self) -> m128d {
@@ -1199,7 +1796,6 @@ impl m128d {
   /// Stores the lower `f64` into both slots of the array
   #[inline(always)]
   pub fn store_lower_all(self, arr: &mut Align16<[f64; 2]>) {
-    // TODO: TEST THAT INDEX 0 GOES INTO LANE 0.
     let p = arr as *mut Align16<[f64; 2]> as *mut f64;
     debug_assert!(p as usize % 16 == 0);
     unsafe { _mm_store1_pd(p, self.0) };
@@ -1251,6 +1847,20 @@ impl m128d {
     unsafe { _mm_storeu_pd(p, self.0) };
   }
 
+  /// Store the lanes with a non-temporal hint (it's written without going
+  /// through the cache). Lowest lane to lowest index, and so on.
+  ///
+  /// Non-temporal stores aren't ordered with other memory operations, so this
+  /// also performs a [`store_fence`] afterward.
+  #[inline(always)]
+  pub fn stream(self, addr: &mut Align16<[f64; 2]>) {
+    let p = addr as *mut Align16<[f64; 2]> as *mut f64;
+    unsafe {
+      _mm_stream_pd(p, self.0);
+      _mm_sfence();
+    }
+  }
+
   /// Unpack and interleave the high lanes of `self` and `other`.
   ///
   /// ```txt
@@ -1283,6 +1893,24 @@ impl m128d {
     m128d(unsafe { _mm_add_sd(self.0, other.0) })
   }
 
+  /// Divide the lower lanes, upper lane copies `self`.
+  #[inline(always)]
+  pub fn div_low(self, other: m128d) -> m128d {
+    m128d(unsafe { _mm_div_sd(self.0, other.0) })
+  }
+
+  /// Multiply the lower lanes, upper lane copies `self`.
+  #[inline(always)]
+  pub fn mul_low(self, other: m128d) -> m128d {
+    m128d(unsafe { _mm_mul_sd(self.0, other.0) })
+  }
+
+  /// Subtract the lower lanes, upper lane copies `self`.
+  #[inline(always)]
+  pub fn sub_low(self, other: m128d) -> m128d {
+    m128d(unsafe { _mm_sub_sd(self.0, other.0) })
+  }
+
   /// Low lane is `self==other`, high lane copies `self`.
   #[inline(always)]
   pub fn cmp_eq_low(self, other: m128d) -> m128d {
@@ -1498,6 +2126,716 @@ impl m128d {
   pub fn ucmp_int_neq_low(self, other: m128d) -> i32 {
     unsafe { _mm_ucomineq_sd(self.0, other.0) }
   }
+
+  /// lanewise absolute value (clears the sign bit).
+  #[inline(always)]
+  pub fn abs(self) -> m128d {
+    m128d::splat(-0.0).andnot(self)
+  }
+
+  /// lanewise magnitude of `self` with the sign bit of `sign`.
+  #[inline(always)]
+  pub fn copysign(self, sign: m128d) -> m128d {
+    let sign_bit = m128d::splat(-0.0);
+    sign_bit.andnot(self) | (sign & sign_bit)
+  }
+
+  /// lanewise `1.0` for a positive sign bit, `-1.0` for a negative sign bit,
+  /// and NaN for NaN (same as [`f64::signum`]).
+  #[inline(always)]
+  pub fn signum(self) -> m128d {
+    m128d::splat(1.0).copysign(self) | self.cmp_nan(self)
+  }
+
+  /// lanewise round toward zero (same as `f64::trunc`).
+  ///
+  /// Values of any magnitude, infinities, and NaN are handled correctly. Does
+  /// not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn trunc(self) -> m128d {
+    #[cfg(target_feature = "sse4.1")]
+    {
+      m128d(unsafe { _mm_round_pd(self.0, _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC) })
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+      // SSE2 can't convert `f64` lanes to `i64` lanes, so we clear the
+      // fractional mantissa bits directly. The number of fractional bits is
+      // `1075 - biased_exponent`, clamped into `0..=52`, and anything below
+      // `1.0` keeps only the sign bit. SSE2 shift counts apply to the whole
+      // register, so each lane's mask is built with its own shift.
+      let bits = self.cast_m128i();
+      let exponent = bits.shr_logical_u64(52) & m128i::splat_i64(0x7FF);
+      let count = m128i::splat_i64(1075).sub_i32(exponent);
+      let count = count.cmp_lt_i32(m128i::zeroed()).andnot(count);
+      let below_one = count.cmp_gt_i32(m128i::splat_i64(52));
+      let count = below_one.andnot(count) | (below_one & m128i::splat_i64(64));
+      let ones = m128i::splat_i32(-1);
+      let high_count = count.unpack_high_i64(count);
+      let low_mask = m128i(unsafe { _mm_sll_epi64(ones.0, count.0) });
+      let high_mask = m128i(unsafe { _mm_sll_epi64(ones.0, high_count.0) });
+      let mask = low_mask.unpack_low_i64(high_mask) | m128i::splat_i64(i64::MIN);
+      (bits & mask).cast_m128d()
+    }
+  }
+
+  /// lanewise round toward negative infinity (same as `f64::floor`).
+  ///
+  /// Does not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn floor(self) -> m128d {
+    #[cfg(target_feature = "sse4.1")]
+    {
+      m128d(unsafe { _mm_round_pd(self.0, _MM_FROUND_TO_NEG_INF | _MM_FROUND_NO_EXC) })
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+      let t = self.trunc();
+      let fix = t.cmp_gt(self) & m128d::splat(1.0);
+      (t - fix).copysign(self)
+    }
+  }
+
+  /// lanewise round toward positive infinity (same as `f64::ceil`).
+  ///
+  /// Does not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn ceil(self) -> m128d {
+    #[cfg(target_feature = "sse4.1")]
+    {
+      m128d(unsafe { _mm_round_pd(self.0, _MM_FROUND_TO_POS_INF | _MM_FROUND_NO_EXC) })
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+      let t = self.trunc();
+      let fix = t.cmp_lt(self) & m128d::splat(1.0);
+      (t + fix).copysign(self)
+    }
+  }
+
+  /// lanewise round to nearest, with ties away from zero (same as
+  /// `f64::round`).
+  ///
+  /// Does not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn round(self) -> m128d {
+    let t = self.trunc();
+    let away = (self - t).abs().cmp_ge(m128d::splat(0.5));
+    (t + (away & m128d::splat(1.0).copysign(self))).copysign(self)
+  }
+
+  /// lanewise round to nearest, with ties to even (same as
+  /// `f64::round_ties_even`).
+  ///
+  /// Does not depend on the current rounding mode.
+  #[inline(always)]
+  pub fn round_even(self) -> m128d {
+    #[cfg(target_feature = "sse4.1")]
+    {
+      m128d(unsafe { _mm_round_pd(self.0, _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC) })
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+      let half = m128d::splat(0.5);
+      let t = self.trunc();
+      let diff = (self - t).abs();
+      let t_half = t * half;
+      let odd = t_half.trunc().cmp_neq(t_half);
+      let away = diff.cmp_gt(half) | (diff.cmp_eq(half) & odd);
+      (t + (away & m128d::splat(1.0).copysign(self))).copysign(self)
+    }
+  }
+
+  /// lanewise fractional part, `self - self.trunc()` (same as `f64::fract`).
+  #[inline(always)]
+  pub fn fract(self) -> m128d {
+    self - self.trunc()
+  }
+}
+
+/// lanewise i16x8 shift left by an immediate amount (0s shifted in).
+///
+/// * `shl_i16!(a, imm)`
+/// * The shift amount must be a constant in `0..16`, anything else is a
+///   compile error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{shl_i16, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{shl_i16, arch::x86_64::m128i};
+///
+/// let a = m128i::splat_i16(0b1011);
+/// let expected = m128i::splat_i16(0b1011_000);
+/// assert_eq!(shl_i16!(a, 3).to_i128(), expected.to_i128());
+/// ```
+#[macro_export]
+macro_rules! shl_i16 {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 16,
+      "shl_i16!: shift amount must be in 0..16"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_slli_epi16(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_slli_epi16(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shl_i16 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// lanewise i16x8 shift right by an immediate amount (sign bit shifted in).
+///
+/// * `shr_arith_i16!(a, imm)`
+/// * The shift amount must be a constant in `0..16`, anything else is a
+///   compile error.
+#[macro_export]
+macro_rules! shr_arith_i16 {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 16,
+      "shr_arith_i16!: shift amount must be in 0..16"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_srai_epi16(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_srai_epi16(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shr_arith_i16 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// lanewise i16x8 shift right by an immediate amount (0s shifted in).
+///
+/// * `shr_logical_u16!(a, imm)`
+/// * The shift amount must be a constant in `0..16`, anything else is a
+///   compile error.
+#[macro_export]
+macro_rules! shr_logical_u16 {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 16,
+      "shr_logical_u16!: shift amount must be in 0..16"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_srli_epi16(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_srli_epi16(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shr_logical_u16 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// lanewise i32x4 shift left by an immediate amount (0s shifted in).
+///
+/// * `shl_i32!(a, imm)`
+/// * The shift amount must be a constant in `0..32`, anything else is a
+///   compile error.
+#[macro_export]
+macro_rules! shl_i32 {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 32,
+      "shl_i32!: shift amount must be in 0..32"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_slli_epi32(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_slli_epi32(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shl_i32 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// lanewise i32x4 shift right by an immediate amount (sign bit shifted in).
+///
+/// * `shr_arith_i32!(a, imm)`
+/// * The shift amount must be a constant in `0..32`, anything else is a
+///   compile error.
+#[macro_export]
+macro_rules! shr_arith_i32 {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 32,
+      "shr_arith_i32!: shift amount must be in 0..32"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_srai_epi32(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_srai_epi32(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shr_arith_i32 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// lanewise i32x4 shift right by an immediate amount (0s shifted in).
+///
+/// * `shr_logical_u32!(a, imm)`
+/// * The shift amount must be a constant in `0..32`, anything else is a
+///   compile error.
+#[macro_export]
+macro_rules! shr_logical_u32 {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 32,
+      "shr_logical_u32!: shift amount must be in 0..32"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_srli_epi32(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_srli_epi32(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shr_logical_u32 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// lanewise i64x2 shift left by an immediate amount (0s shifted in).
+///
+/// * `shl_i64!(a, imm)`
+/// * The shift amount must be a constant in `0..64`, anything else is a
+///   compile error.
+#[macro_export]
+macro_rules! shl_i64 {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 64,
+      "shl_i64!: shift amount must be in 0..64"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_slli_epi64(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_slli_epi64(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shl_i64 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// lanewise i64x2 shift right by an immediate amount (0s shifted in).
+///
+/// * `shr_logical_u64!(a, imm)`
+/// * The shift amount must be a constant in `0..64`, anything else is a
+///   compile error.
+#[macro_export]
+macro_rules! shr_logical_u64 {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 64,
+      "shr_logical_u64!: shift amount must be in 0..64"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_srli_epi64(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_srli_epi64(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shr_logical_u64 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// Shifts the entire register left by an immediate number of _bytes_ (0s
+/// shifted in).
+///
+/// Bytes move toward the higher lanes, same as `<<` on a `u128`.
+///
+/// * `byte_shift_left!(a, imm)`
+/// * The shift amount must be a constant in `0..16`, anything else is a
+///   compile error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{byte_shift_left, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{byte_shift_left, arch::x86_64::m128i};
+///
+/// let a = m128i::set_i32(4, 3, 2, 1);
+/// let expected = m128i::set_i32(3, 2, 1, 0);
+/// assert_eq!(byte_shift_left!(a, 4).to_i128(), expected.to_i128());
+/// ```
+#[macro_export]
+macro_rules! byte_shift_left {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 16,
+      "byte_shift_left!: shift amount must be in 0..16"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_slli_si128(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_slli_si128(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the byte_shift_left macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// Shifts the entire register right by an immediate number of _bytes_ (0s
+/// shifted in).
+///
+/// Bytes move toward the lower lanes, same as `>>` on a `u128`.
+///
+/// * `byte_shift_right!(a, imm)`
+/// * The shift amount must be a constant in `0..16`, anything else is a
+///   compile error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{byte_shift_right, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{byte_shift_right, arch::x86_64::m128i};
+///
+/// let a = m128i::set_i32(4, 3, 2, 1);
+/// let expected = m128i::set_i32(0, 4, 3, 2);
+/// assert_eq!(byte_shift_right!(a, 4).to_i128(), expected.to_i128());
+/// ```
+///
+/// Out of range shift amounts don't compile:
+///
+/// ```compile_fail
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{byte_shift_right, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{byte_shift_right, arch::x86_64::m128i};
+///
+/// let a = m128i::set_i32(4, 3, 2, 1);
+/// let _ = byte_shift_right!(a, 16);
+/// ```
+#[macro_export]
+macro_rules! byte_shift_right {
+  ($a:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 16,
+      "byte_shift_right!: shift amount must be in 0..16"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_srli_si128(a.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_srli_si128(a.0, IMM) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the byte_shift_right macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// Shuffles the `i32` lanes of `a` into an output according to the indexes
+/// given.
+///
+/// * `shuffle_i32!(a, i3, i2, i1, i0)`
+/// * The index literals are ordered the same as the
+///   [set](m128i::set_i32) methods: highest lane to
+///   lowest lane. Each index must be in `0..4`, anything else is a compile
+///   error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{shuffle_i32, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{shuffle_i32, arch::x86_64::m128i};
+///
+/// let a = m128i::set_i32(9, 8, 7, 6);
+/// let output = shuffle_i32!(a, 0, 1, 3, 2);
+/// let expected = m128i::set_i32(6, 7, 9, 8);
+/// assert_eq!(output.to_i128(), expected.to_i128());
+/// ```
+#[macro_export]
+macro_rules! shuffle_i32 {
+  ($a:expr, $i3:literal, $i2:literal, $i1:literal, $i0:literal) => {{
+    const I3: i32 = $i3;
+    const I2: i32 = $i2;
+    const I1: i32 = $i1;
+    const I0: i32 = $i0;
+    const _: () = assert!(
+      (I3 | I2 | I1 | I0) & !0b11 == 0,
+      "shuffle_i32!: indexes must be in 0..4"
+    );
+    const IMM8: i32 = I3 << 6 | I2 << 4 | I1 << 2 | I0;
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_shuffle_epi32(a.0, IMM8) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_shuffle_epi32(a.0, IMM8) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shuffle_i32 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// Shuffles the high four `i16` lanes of `a` according to the indexes given,
+/// the low four lanes are copied unchanged.
+///
+/// Index `0` is lane 4, index `3` is lane 7.
+///
+/// * `shuffle_high_i16!(a, i3, i2, i1, i0)`
+/// * The index literals are ordered the same as the
+///   [set](m128i::set_i32) methods: highest lane to
+///   lowest lane. Each index must be in `0..4`, anything else is a compile
+///   error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{shuffle_high_i16, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{shuffle_high_i16, arch::x86_64::m128i};
+///
+/// let a = m128i::set_i16(7, 6, 5, 4, 3, 2, 1, 0);
+/// let output = shuffle_high_i16!(a, 0, 0, 3, 2);
+/// let expected = m128i::set_i16(4, 4, 7, 6, 3, 2, 1, 0);
+/// assert_eq!(output.to_i128(), expected.to_i128());
+/// ```
+#[macro_export]
+macro_rules! shuffle_high_i16 {
+  ($a:expr, $i3:literal, $i2:literal, $i1:literal, $i0:literal) => {{
+    const I3: i32 = $i3;
+    const I2: i32 = $i2;
+    const I1: i32 = $i1;
+    const I0: i32 = $i0;
+    const _: () = assert!(
+      (I3 | I2 | I1 | I0) & !0b11 == 0,
+      "shuffle_high_i16!: indexes must be in 0..4"
+    );
+    const IMM8: i32 = I3 << 6 | I2 << 4 | I1 << 2 | I0;
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_shufflehi_epi16(a.0, IMM8) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_shufflehi_epi16(a.0, IMM8) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shuffle_high_i16 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// Shuffles the low four `i16` lanes of `a` according to the indexes given,
+/// the high four lanes are copied unchanged.
+///
+/// * `shuffle_low_i16!(a, i3, i2, i1, i0)`
+/// * The index literals are ordered the same as the
+///   [set](m128i::set_i32) methods: highest lane to
+///   lowest lane. Each index must be in `0..4`, anything else is a compile
+///   error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{shuffle_low_i16, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{shuffle_low_i16, arch::x86_64::m128i};
+///
+/// let a = m128i::set_i16(7, 6, 5, 4, 3, 2, 1, 0);
+/// let output = shuffle_low_i16!(a, 0, 0, 3, 2);
+/// let expected = m128i::set_i16(7, 6, 5, 4, 0, 0, 3, 2);
+/// assert_eq!(output.to_i128(), expected.to_i128());
+/// ```
+#[macro_export]
+macro_rules! shuffle_low_i16 {
+  ($a:expr, $i3:literal, $i2:literal, $i1:literal, $i0:literal) => {{
+    const I3: i32 = $i3;
+    const I2: i32 = $i2;
+    const I1: i32 = $i1;
+    const I0: i32 = $i0;
+    const _: () = assert!(
+      (I3 | I2 | I1 | I0) & !0b11 == 0,
+      "shuffle_low_i16!: indexes must be in 0..4"
+    );
+    const IMM8: i32 = I3 << 6 | I2 << 4 | I1 << 2 | I0;
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128i = $a;
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_shufflelo_epi16(a.0, IMM8) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128i = $a;
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_shufflelo_epi16(a.0, IMM8) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shuffle_low_i16 macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// Shuffles `a` and `b` into an output according to the indexes given.
+///
+/// * `shuffle128d!(a, b, i1, i0)`
+/// * The low lane comes from `a`, using index `i0`.
+/// * The high lane comes from `b`, using index `i1`.
+/// * Each index must be `0` or `1`, anything else is a compile error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{shuffle128d, arch::x86::m128d};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{shuffle128d, arch::x86_64::m128d};
+///
+/// let a = m128d::set(2.0, 1.0);
+/// let b = m128d::set(4.0, 3.0);
+/// let output = shuffle128d!(a, b, 0, 1);
+/// assert_eq!(output.to_array(), [2.0, 3.0]);
+/// ```
+#[macro_export]
+macro_rules! shuffle128d {
+  ($a:expr, $b:expr, $i1:literal, $i0:literal) => {{
+    const I1: i32 = $i1;
+    const I0: i32 = $i0;
+    const _: () = assert!(
+      (I1 | I0) & !0b1 == 0,
+      "shuffle128d!: indexes must be 0 or 1"
+    );
+    const IMM8: i32 = I1 << 1 | I0;
+    #[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86::m128d = $a;
+      let b: $crate::arch::x86::m128d = $b;
+      $crate::arch::x86::m128d(unsafe { core::arch::x86::_mm_shuffle_pd(a.0, b.0, IMM8) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    {
+      let a: $crate::arch::x86_64::m128d = $a;
+      let b: $crate::arch::x86_64::m128d = $b;
+      $crate::arch::x86_64::m128d(unsafe { core::arch::x86_64::_mm_shuffle_pd(a.0, b.0, IMM8) })
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      compile_error!("the shuffle128d macro requires 'sse2' to be enabled.");
+    }
+  }};
+}
+
+/// Stores the `i32` with a non-temporal hint (it's written without going
+/// through the cache).
+///
+/// Non-temporal stores aren't ordered with other memory operations, so this
+/// also performs a [`store_fence`] afterward.
+#[inline(always)]
+pub fn stream_i32(addr: &mut i32, val: i32) {
+  unsafe {
+    _mm_stream_si32(addr, val);
+    _mm_sfence();
+  }
+}
+
+/// Stores the `i64` with a non-temporal hint (it's written without going
+/// through the cache).
+///
+/// Non-temporal stores aren't ordered with other memory operations, so this
+/// also performs a [`store_fence`] afterward.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+pub fn stream_i64(addr: &mut i64, val: i64) {
+  unsafe {
+    _mm_stream_si64(addr, val);
+    _mm_sfence();
+  }
+}
+
+/// Hints to the CPU that this is a spin-wait loop.
+///
+/// This can improve performance and power use while spinning, and it's a
+/// no-op on CPUs that don't know about it.
+#[inline(always)]
+#[allow(unused_unsafe)]
+pub fn pause() {
+  // newer compilers mark `_mm_pause` as safe, older ones don't.
+  unsafe { _mm_pause() }
 }
 
 /// Serializes all load-from-memory and store-to-memory operations.
@@ -1512,9 +2850,17 @@ pub fn load_fence() {
   unsafe { _mm_lfence() }
 }
 
-/// Invalidate and flush the cache line that contains the pointer from all
-/// levels of the cache hierarchy.
+/// Invalidate and flush the cache line that contains the start of the
+/// referenced value from all levels of the cache hierarchy.
+///
+/// A zero-sized value doesn't live in any cache line, so this does nothing.
 #[inline(always)]
-pub fn cache_line_flush(p: *const impl Sized) {
-  unsafe { _mm_clflush(p as *mut u8) }
+pub fn cache_line_flush(r: &impl Sized) {
+  // Unlike a prefetch, a flush of an unmapped address will fault. A reference
+  // to a value with a non-zero size always points at mapped memory, but one to
+  // a zero-sized value can dangle, so those are skipped.
+  if core::mem::size_of_val(r) == 0 {
+    return;
+  }
+  unsafe { _mm_clflush(r as *const _ as *const u8) }
 }
@@ -129,8 +129,9 @@ impl m128i {
   /// This can perform faster than [`m128i::load_unaligned`] if the data would
   /// cross a cache line boundary.
   #[inline(always)]
-  pub fn load_quick_unaligned(addr: *const i128) -> Self {
+  pub fn load_quick_unaligned(addr: &[u8; 16]) -> Self {
+    let p = addr as *const [u8; 16] as *const __m128i;
     #[allow(clippy::cast_ptr_alignment)]
-    Self(unsafe { _mm_lddqu_si128(addr as *const _) })
+    Self(unsafe { _mm_lddqu_si128(p) })
   }
 }
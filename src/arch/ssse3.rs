@@ -0,0 +1,179 @@
+use super::*;
+
+/// # SSSE3 Operations
+impl m128i {
+  /// lanewise i8x16 absolute value. `i8::MIN` stays as `i8::MIN`.
+  #[inline(always)]
+  pub fn abs_i8(self) -> m128i {
+    m128i(unsafe { _mm_abs_epi8(self.0) })
+  }
+
+  /// lanewise i16x8 absolute value. `i16::MIN` stays as `i16::MIN`.
+  #[inline(always)]
+  pub fn abs_i16(self) -> m128i {
+    m128i(unsafe { _mm_abs_epi16(self.0) })
+  }
+
+  /// lanewise i32x4 absolute value. `i32::MIN` stays as `i32::MIN`.
+  #[inline(always)]
+  pub fn abs_i32(self) -> m128i {
+    m128i(unsafe { _mm_abs_epi32(self.0) })
+  }
+
+  /// Horizontal add `i16` pairs of both `self` and `rhs`, then pack together.
+  ///
+  /// ```txt
+  /// out[0]= self[0] + self[1]
+  /// out[1]= self[2] + self[3]
+  /// ...
+  /// out[3]= self[6] + self[7]
+  /// out[4]= rhs[0] + rhs[1]
+  /// ...
+  /// out[7]= rhs[6] + rhs[7]
+  /// ```
+  #[inline(always)]
+  pub fn horizontal_add_i16(self, rhs: Self) -> Self {
+    Self(unsafe { _mm_hadd_epi16(self.0, rhs.0) })
+  }
+
+  /// As [`horizontal_add_i16`](m128i::horizontal_add_i16), but saturating.
+  #[inline(always)]
+  pub fn horizontal_saturating_add_i16(self, rhs: Self) -> Self {
+    Self(unsafe { _mm_hadds_epi16(self.0, rhs.0) })
+  }
+
+  /// Horizontal add `i32` pairs of both `self` and `rhs`, then pack together.
+  ///
+  /// ```txt
+  /// out[0]= self[0] + self[1]
+  /// out[1]= self[2] + self[3]
+  /// out[2]= rhs[0] + rhs[1]
+  /// out[3]= rhs[2] + rhs[3]
+  /// ```
+  #[inline(always)]
+  pub fn horizontal_add_i32(self, rhs: Self) -> Self {
+    Self(unsafe { _mm_hadd_epi32(self.0, rhs.0) })
+  }
+
+  /// Horizontal subtract `i16` pairs of both `self` and `rhs`, then pack
+  /// together.
+  ///
+  /// ```txt
+  /// out[0]= self[0] - self[1]
+  /// ...
+  /// out[7]= rhs[6] - rhs[7]
+  /// ```
+  #[inline(always)]
+  pub fn horizontal_sub_i16(self, rhs: Self) -> Self {
+    Self(unsafe { _mm_hsub_epi16(self.0, rhs.0) })
+  }
+
+  /// As [`horizontal_sub_i16`](m128i::horizontal_sub_i16), but saturating.
+  #[inline(always)]
+  pub fn horizontal_saturating_sub_i16(self, rhs: Self) -> Self {
+    Self(unsafe { _mm_hsubs_epi16(self.0, rhs.0) })
+  }
+
+  /// Horizontal subtract `i32` pairs of both `self` and `rhs`, then pack
+  /// together.
+  ///
+  /// ```txt
+  /// out[0]= self[0] - self[1]
+  /// out[1]= self[2] - self[3]
+  /// out[2]= rhs[0] - rhs[1]
+  /// out[3]= rhs[2] - rhs[3]
+  /// ```
+  #[inline(always)]
+  pub fn horizontal_sub_i32(self, rhs: Self) -> Self {
+    Self(unsafe { _mm_hsub_epi32(self.0, rhs.0) })
+  }
+
+  /// lanewise multiply the `u8` lanes of `self` with the `i8` lanes of
+  /// `other`, then saturating add pairs of the `i16` intermediates to form an
+  /// i16x8 output.
+  #[inline(always)]
+  pub fn mul_hadd_saturating_u8_i8(self, other: m128i) -> m128i {
+    m128i(unsafe { _mm_maddubs_epi16(self.0, other.0) })
+  }
+
+  /// lanewise i16x8 multiply into `i32`, then keep bits 15 through 30 after
+  /// rounding (a fixed point Q15 multiply).
+  #[inline(always)]
+  pub fn mul_high_round_i16(self, other: m128i) -> m128i {
+    m128i(unsafe { _mm_mulhrs_epi16(self.0, other.0) })
+  }
+
+  /// Shuffles the `i8` lanes of `self` according to the `i8` lanes of
+  /// `indexes`.
+  ///
+  /// For each output lane, if the index has its high bit set the output is 0,
+  /// otherwise the low four bits of the index select a lane of `self`.
+  #[inline(always)]
+  pub fn shuffle_i8(self, indexes: m128i) -> m128i {
+    m128i(unsafe { _mm_shuffle_epi8(self.0, indexes.0) })
+  }
+
+  /// lanewise i8x16 `self` negated where `other` is negative, 0 where `other`
+  /// is 0, and unchanged where `other` is positive.
+  #[inline(always)]
+  pub fn sign_i8(self, other: m128i) -> m128i {
+    m128i(unsafe { _mm_sign_epi8(self.0, other.0) })
+  }
+
+  /// lanewise i16x8 `self` negated where `other` is negative, 0 where `other`
+  /// is 0, and unchanged where `other` is positive.
+  #[inline(always)]
+  pub fn sign_i16(self, other: m128i) -> m128i {
+    m128i(unsafe { _mm_sign_epi16(self.0, other.0) })
+  }
+
+  /// lanewise i32x4 `self` negated where `other` is negative, 0 where `other`
+  /// is 0, and unchanged where `other` is positive.
+  #[inline(always)]
+  pub fn sign_i32(self, other: m128i) -> m128i {
+    m128i(unsafe { _mm_sign_epi32(self.0, other.0) })
+  }
+}
+
+/// Joins `a` (high) and `b` (low) into a 32 byte value, shifts that right by
+/// some number of bytes, and gives the low 16 bytes.
+///
+/// * `byte_align_right!(a, b, imm)`
+/// * The shift amount must be a constant in `0..32`, anything else is a
+///   compile error.
+///
+/// ```rust
+/// #[cfg(target_arch = "x86")]
+/// use lokacore::{byte_align_right, arch::x86::m128i};
+/// #[cfg(target_arch = "x86_64")]
+/// use lokacore::{byte_align_right, arch::x86_64::m128i};
+///
+/// let a = m128i::set_i32(8, 7, 6, 5);
+/// let b = m128i::set_i32(4, 3, 2, 1);
+/// let expected = m128i::set_i32(5, 4, 3, 2);
+/// assert_eq!(byte_align_right!(a, b, 4).to_i128(), expected.to_i128());
+/// ```
+#[macro_export]
+macro_rules! byte_align_right {
+  ($a:expr, $b:expr, $imm:expr) => {{
+    const IMM: i32 = $imm;
+    const _: () = assert!(
+      IMM >= 0 && IMM < 32,
+      "byte_align_right!: shift amount must be in 0..32"
+    );
+    #[cfg(all(target_arch = "x86", target_feature = "ssse3"))]
+    {
+      let (a, b): ($crate::arch::x86::m128i, $crate::arch::x86::m128i) = ($a, $b);
+      $crate::arch::x86::m128i(unsafe { core::arch::x86::_mm_alignr_epi8(a.0, b.0, IMM) })
+    }
+    #[cfg(all(target_arch = "x86_64", target_feature = "ssse3"))]
+    {
+      let (a, b): ($crate::arch::x86_64::m128i, $crate::arch::x86_64::m128i) = ($a, $b);
+      $crate::arch::x86_64::m128i(unsafe { core::arch::x86_64::_mm_alignr_epi8(a.0, b.0, IMM) })
+    }
+    #[cfg(not(target_feature = "ssse3"))]
+    {
+      compile_error!("the byte_align_right macro requires 'ssse3' to be enabled.");
+    }
+  }};
+}
@@ -40,6 +40,24 @@ mod sse3;
 #[cfg(target_feature = "sse3")]
 pub use sse3::*;
 
+// The modules below only add methods and macros to the types above, so there's
+// nothing to re-export.
+#[cfg(target_feature = "ssse3")]
+#[path = "ssse3.rs"]
+mod ssse3;
+#[cfg(target_feature = "aes")]
+#[path = "aes.rs"]
+mod aes;
+#[cfg(target_feature = "pclmulqdq")]
+#[path = "pclmulqdq.rs"]
+mod pclmulqdq;
+#[cfg(target_feature = "sha")]
+#[path = "sha.rs"]
+mod sha;
+#[cfg(target_feature = "f16c")]
+#[path = "f16c.rs"]
+mod f16c;
+
 /// As [`_rdtsc`](core::arch::x86_64::_rdtsc).
 #[inline]
 pub fn rdtsc() -> u64 {
@@ -0,0 +1,590 @@
+//! Integer bit manipulation, using BMI1, BMI2, LZCNT, and POPCNT when they're
+//! enabled.
+//!
+//! Every function here works on every target. When the matching target
+//! feature is enabled at compile time the function is a single instruction,
+//! otherwise it's a portable version that gives the same results.
+//!
+//! On top of the single operations there's Morton encoding (also called Z-order
+//! encoding), which interleaves the bits of 2 or 3 coordinates into one
+//! integer so that points near each other in space tend to be near each other
+//! in memory.
+//!
+//! ```rust
+//! use lokacore::bits::*;
+//!
+//! assert_eq!(deposit_bits_u32(0b101, 0b1101_0000), 0b1001_0000);
+//! assert_eq!(extract_bits_u32(0b1001_0000, 0b1101_0000), 0b101);
+//! assert_eq!(morton_encode_2d_u32(0b11, 0b00), 0b0101);
+//! assert_eq!(morton_decode_2d_u32(0b0101), (0b11, 0b00));
+//! ```
+
+#[cfg(all(
+  target_arch = "x86",
+  any(
+    target_feature = "bmi1",
+    target_feature = "bmi2",
+    target_feature = "lzcnt",
+    target_feature = "popcnt"
+  )
+))]
+use core::arch::x86::*;
+#[cfg(all(
+  target_arch = "x86_64",
+  any(
+    target_feature = "bmi1",
+    target_feature = "bmi2",
+    target_feature = "lzcnt",
+    target_feature = "popcnt"
+  )
+))]
+use core::arch::x86_64::*;
+
+/// Deposits the low bits of `a` into the positions of the set bits of `mask`,
+/// from lowest to highest. Other bits of the output are 0.
+///
+/// As `_pdep_u32`.
+#[inline]
+pub fn deposit_bits_u32(a: u32, mask: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi2"
+  ))]
+  {
+    unsafe { _pdep_u32(a, mask) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi2"
+  )))]
+  {
+    deposit_bits_u64(u64::from(a), u64::from(mask)) as u32
+  }
+}
+
+/// Deposits the low bits of `a` into the positions of the set bits of `mask`,
+/// from lowest to highest. Other bits of the output are 0.
+///
+/// As `_pdep_u64`.
+#[inline]
+pub fn deposit_bits_u64(a: u64, mask: u64) -> u64 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+  {
+    unsafe { _pdep_u64(a, mask) }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+  {
+    let (mut out, mut mask, mut bit) = (0, mask, 1);
+    while mask != 0 {
+      if a & bit != 0 {
+        out |= mask & mask.wrapping_neg();
+      }
+      mask &= mask - 1;
+      bit <<= 1;
+    }
+    out
+  }
+}
+
+/// Gathers the bits of `a` at the positions of the set bits of `mask` into
+/// the low bits of the output, from lowest to highest. Other bits of the
+/// output are 0.
+///
+/// As `_pext_u32`.
+#[inline]
+pub fn extract_bits_u32(a: u32, mask: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi2"
+  ))]
+  {
+    unsafe { _pext_u32(a, mask) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi2"
+  )))]
+  {
+    extract_bits_u64(u64::from(a), u64::from(mask)) as u32
+  }
+}
+
+/// Gathers the bits of `a` at the positions of the set bits of `mask` into
+/// the low bits of the output, from lowest to highest. Other bits of the
+/// output are 0.
+///
+/// As `_pext_u64`.
+#[inline]
+pub fn extract_bits_u64(a: u64, mask: u64) -> u64 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+  {
+    unsafe { _pext_u64(a, mask) }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+  {
+    let (mut out, mut mask, mut bit) = (0, mask, 1);
+    while mask != 0 {
+      if a & mask & mask.wrapping_neg() != 0 {
+        out |= bit;
+      }
+      mask &= mask - 1;
+      bit <<= 1;
+    }
+    out
+  }
+}
+
+/// Clears the bits of `a` from bit `index` upward.
+///
+/// Only the low 8 bits of `index` are used, and an index of 32 or more leaves
+/// `a` unchanged.
+///
+/// As `_bzhi_u32`.
+#[inline]
+pub fn zero_high_bits_u32(a: u32, index: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi2"
+  ))]
+  {
+    unsafe { _bzhi_u32(a, index) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi2"
+  )))]
+  {
+    let keep = 1_u32
+      .checked_shl(index & 0xFF)
+      .map_or(u32::MAX, |bit| bit - 1);
+    a & keep
+  }
+}
+
+/// Clears the bits of `a` from bit `index` upward.
+///
+/// Only the low 8 bits of `index` are used, and an index of 64 or more leaves
+/// `a` unchanged.
+///
+/// As `_bzhi_u64`.
+#[inline]
+pub fn zero_high_bits_u64(a: u64, index: u32) -> u64 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+  {
+    unsafe { _bzhi_u64(a, index) }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+  {
+    let keep = 1_u64
+      .checked_shl(index & 0xFF)
+      .map_or(u64::MAX, |bit| bit - 1);
+    a & keep
+  }
+}
+
+/// The `len` bits of `a` starting at bit `start`, moved down to bit 0.
+///
+/// Only the low 8 bits of `start` and `len` are used. Bits past the top of `a`
+/// read as 0.
+///
+/// As `_bextr_u32`.
+#[inline]
+pub fn bit_field_extract_u32(a: u32, start: u32, len: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  ))]
+  {
+    unsafe { _bextr_u32(a, start, len) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  )))]
+  {
+    let shifted = a.checked_shr(start & 0xFF).unwrap_or(0);
+    let keep = 1_u32
+      .checked_shl(len & 0xFF)
+      .map_or(u32::MAX, |bit| bit - 1);
+    shifted & keep
+  }
+}
+
+/// The `len` bits of `a` starting at bit `start`, moved down to bit 0.
+///
+/// Only the low 8 bits of `start` and `len` are used. Bits past the top of `a`
+/// read as 0.
+///
+/// As `_bextr_u64`.
+#[inline]
+pub fn bit_field_extract_u64(a: u64, start: u32, len: u32) -> u64 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi1"))]
+  {
+    unsafe { _bextr_u64(a, start, len) }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi1")))]
+  {
+    let shifted = a.checked_shr(start & 0xFF).unwrap_or(0);
+    let keep = 1_u64
+      .checked_shl(len & 0xFF)
+      .map_or(u64::MAX, |bit| bit - 1);
+    shifted & keep
+  }
+}
+
+/// Just the lowest set bit of `a` (0 if `a` is 0).
+///
+/// As `_blsi_u32`.
+#[inline]
+pub fn lowest_set_bit_u32(a: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  ))]
+  {
+    unsafe { _blsi_u32(a) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  )))]
+  {
+    a & a.wrapping_neg()
+  }
+}
+
+/// Just the lowest set bit of `a` (0 if `a` is 0).
+///
+/// As `_blsi_u64`.
+#[inline]
+pub fn lowest_set_bit_u64(a: u64) -> u64 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi1"))]
+  {
+    unsafe { _blsi_u64(a) }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi1")))]
+  {
+    a & a.wrapping_neg()
+  }
+}
+
+/// `a` with its lowest set bit cleared.
+///
+/// As `_blsr_u32`.
+#[inline]
+pub fn clear_lowest_set_bit_u32(a: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  ))]
+  {
+    unsafe { _blsr_u32(a) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  )))]
+  {
+    a & a.wrapping_sub(1)
+  }
+}
+
+/// `a` with its lowest set bit cleared.
+///
+/// As `_blsr_u64`.
+#[inline]
+pub fn clear_lowest_set_bit_u64(a: u64) -> u64 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi1"))]
+  {
+    unsafe { _blsr_u64(a) }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi1")))]
+  {
+    a & a.wrapping_sub(1)
+  }
+}
+
+/// A mask of the lowest set bit of `a` and every bit below it (all bits if
+/// `a` is 0).
+///
+/// As `_blsmsk_u32`.
+#[inline]
+pub fn mask_through_lowest_set_bit_u32(a: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  ))]
+  {
+    unsafe { _blsmsk_u32(a) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  )))]
+  {
+    a ^ a.wrapping_sub(1)
+  }
+}
+
+/// A mask of the lowest set bit of `a` and every bit below it (all bits if
+/// `a` is 0).
+///
+/// As `_blsmsk_u64`.
+#[inline]
+pub fn mask_through_lowest_set_bit_u64(a: u64) -> u64 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi1"))]
+  {
+    unsafe { _blsmsk_u64(a) }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi1")))]
+  {
+    a ^ a.wrapping_sub(1)
+  }
+}
+
+/// `!a & b`
+///
+/// As `_andn_u32`.
+#[inline]
+pub fn and_not_u32(a: u32, b: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  ))]
+  {
+    unsafe { _andn_u32(a, b) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  )))]
+  {
+    !a & b
+  }
+}
+
+/// `!a & b`
+///
+/// As `_andn_u64`.
+#[inline]
+pub fn and_not_u64(a: u64, b: u64) -> u64 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi1"))]
+  {
+    unsafe { _andn_u64(a, b) }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi1")))]
+  {
+    !a & b
+  }
+}
+
+/// The full 128-bit product of `a` and `b`, as `(low, high)` halves.
+///
+/// As `_mulx_u64`.
+#[inline]
+pub fn mul_wide_u64(a: u64, b: u64) -> (u64, u64) {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+  {
+    let mut high = 0;
+    let low = unsafe { _mulx_u64(a, b, &mut high) };
+    (low, high)
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+  {
+    let product = u128::from(a) * u128::from(b);
+    (product as u64, (product >> 64) as u64)
+  }
+}
+
+/// The number of leading zero bits (32 if `a` is 0).
+///
+/// As `_lzcnt_u32`.
+#[inline]
+pub fn leading_zeros_u32(a: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "lzcnt"
+  ))]
+  {
+    unsafe { _lzcnt_u32(a) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "lzcnt"
+  )))]
+  {
+    a.leading_zeros()
+  }
+}
+
+/// The number of leading zero bits (64 if `a` is 0).
+///
+/// As `_lzcnt_u64`.
+#[inline]
+pub fn leading_zeros_u64(a: u64) -> u32 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "lzcnt"))]
+  {
+    unsafe { _lzcnt_u64(a) as u32 }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "lzcnt")))]
+  {
+    a.leading_zeros()
+  }
+}
+
+/// The number of trailing zero bits (32 if `a` is 0).
+///
+/// As `_tzcnt_u32`.
+#[inline]
+pub fn trailing_zeros_u32(a: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  ))]
+  {
+    unsafe { _tzcnt_u32(a) }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "bmi1"
+  )))]
+  {
+    a.trailing_zeros()
+  }
+}
+
+/// The number of trailing zero bits (64 if `a` is 0).
+///
+/// As `_tzcnt_u64`.
+#[inline]
+pub fn trailing_zeros_u64(a: u64) -> u32 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "bmi1"))]
+  {
+    unsafe { _tzcnt_u64(a) as u32 }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi1")))]
+  {
+    a.trailing_zeros()
+  }
+}
+
+/// The number of set bits.
+///
+/// As `_popcnt32`.
+#[inline]
+pub fn count_ones_u32(a: u32) -> u32 {
+  #[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "popcnt"
+  ))]
+  {
+    unsafe { _popcnt32(a as i32) as u32 }
+  }
+  #[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "popcnt"
+  )))]
+  {
+    a.count_ones()
+  }
+}
+
+/// The number of set bits.
+///
+/// As `_popcnt64`.
+#[inline]
+pub fn count_ones_u64(a: u64) -> u32 {
+  #[cfg(all(target_arch = "x86_64", target_feature = "popcnt"))]
+  {
+    unsafe { _popcnt64(a as i64) as u32 }
+  }
+  #[cfg(not(all(target_arch = "x86_64", target_feature = "popcnt")))]
+  {
+    a.count_ones()
+  }
+}
+
+const MORTON_2D_X_32: u32 = 0x5555_5555;
+const MORTON_2D_Y_32: u32 = 0xAAAA_AAAA;
+const MORTON_2D_X_64: u64 = 0x5555_5555_5555_5555;
+const MORTON_2D_Y_64: u64 = 0xAAAA_AAAA_AAAA_AAAA;
+const MORTON_3D_X_32: u32 = 0x0924_9249;
+const MORTON_3D_Y_32: u32 = 0x1249_2492;
+const MORTON_3D_Z_32: u32 = 0x2492_4924;
+const MORTON_3D_X_64: u64 = 0x1249_2492_4924_9249;
+const MORTON_3D_Y_64: u64 = 0x2492_4924_9249_2492;
+const MORTON_3D_Z_64: u64 = 0x4924_9249_2492_4924;
+
+/// Interleaves the bits of two 16-bit coordinates, with `x` in the even bits
+/// and `y` in the odd bits.
+#[inline]
+pub fn morton_encode_2d_u32(x: u16, y: u16) -> u32 {
+  deposit_bits_u32(u32::from(x), MORTON_2D_X_32) | deposit_bits_u32(u32::from(y), MORTON_2D_Y_32)
+}
+
+/// Splits a 2D Morton code back into `(x, y)`.
+#[inline]
+pub fn morton_decode_2d_u32(code: u32) -> (u16, u16) {
+  (
+    extract_bits_u32(code, MORTON_2D_X_32) as u16,
+    extract_bits_u32(code, MORTON_2D_Y_32) as u16,
+  )
+}
+
+/// Interleaves the bits of two 32-bit coordinates, with `x` in the even bits
+/// and `y` in the odd bits.
+#[inline]
+pub fn morton_encode_2d_u64(x: u32, y: u32) -> u64 {
+  deposit_bits_u64(u64::from(x), MORTON_2D_X_64) | deposit_bits_u64(u64::from(y), MORTON_2D_Y_64)
+}
+
+/// Splits a 2D Morton code back into `(x, y)`.
+#[inline]
+pub fn morton_decode_2d_u64(code: u64) -> (u32, u32) {
+  (
+    extract_bits_u64(code, MORTON_2D_X_64) as u32,
+    extract_bits_u64(code, MORTON_2D_Y_64) as u32,
+  )
+}
+
+/// Interleaves the low 10 bits of three coordinates, with `x` in bits 0, 3,
+/// 6, and so on, `y` one bit above that, and `z` one bit above `y`.
+///
+/// Bits above the low 10 are ignored.
+#[inline]
+pub fn morton_encode_3d_u32(x: u32, y: u32, z: u32) -> u32 {
+  deposit_bits_u32(x, MORTON_3D_X_32)
+    | deposit_bits_u32(y, MORTON_3D_Y_32)
+    | deposit_bits_u32(z, MORTON_3D_Z_32)
+}
+
+/// Splits a 3D Morton code back into `(x, y, z)`.
+#[inline]
+pub fn morton_decode_3d_u32(code: u32) -> (u32, u32, u32) {
+  (
+    extract_bits_u32(code, MORTON_3D_X_32),
+    extract_bits_u32(code, MORTON_3D_Y_32),
+    extract_bits_u32(code, MORTON_3D_Z_32),
+  )
+}
+
+/// Interleaves the low 21 bits of three coordinates, with `x` in bits 0, 3,
+/// 6, and so on, `y` one bit above that, and `z` one bit above `y`.
+///
+/// Bits above the low 21 are ignored.
+#[inline]
+pub fn morton_encode_3d_u64(x: u32, y: u32, z: u32) -> u64 {
+  deposit_bits_u64(u64::from(x), MORTON_3D_X_64)
+    | deposit_bits_u64(u64::from(y), MORTON_3D_Y_64)
+    | deposit_bits_u64(u64::from(z), MORTON_3D_Z_64)
+}
+
+/// Splits a 3D Morton code back into `(x, y, z)`.
+#[inline]
+pub fn morton_decode_3d_u64(code: u64) -> (u32, u32, u32) {
+  (
+    extract_bits_u64(code, MORTON_3D_X_64) as u32,
+    extract_bits_u64(code, MORTON_3D_Y_64) as u32,
+    extract_bits_u64(code, MORTON_3D_Z_64) as u32,
+  )
+}
@@ -0,0 +1,357 @@
+//! Branchless integer operations, as `const fn` and through a trait.
+//!
+//! Each integer type has a module of `const fn` operations, so you can use
+//! them in a `const` context without having to say the type like the
+//! [`branchless_min!`](crate::branchless_min) family of macros needs:
+//!
+//! ```rust
+//! use lokacore::branchless;
+//!
+//! const SMALLER: u8 = branchless::u8::min(7, 3);
+//! assert_eq!(SMALLER, 3);
+//! assert_eq!(branchless::i16::clamp(-900, -128, 127), -128);
+//! assert_eq!(branchless::u32::align_up(13, 8), 16);
+//! assert_eq!(branchless::i32::saturating_to_u8(300), 255);
+//! ```
+//!
+//! For generic code the same operations are on the [`Branchless`] trait,
+//! which every integer type implements:
+//!
+//! ```rust
+//! use lokacore::branchless::Branchless;
+//!
+//! fn midpoint<T: Branchless>(a: T, b: T) -> T {
+//!   T::average_floor(a, b)
+//! }
+//! assert_eq!(midpoint(u8::MAX, u8::MAX - 2), u8::MAX - 1);
+//! assert_eq!(midpoint(-3_i64, 0), -2);
+//! ```
+//!
+//! None of these use a branch or a lookup table as written, but that's not a
+//! timing promise: once the optimizer can see where a value came from it's
+//! free to turn the masking back into a branch. Use [`ct`](crate::ct) when the
+//! time taken mustn't depend on the inputs. Nothing here panics: results that
+//! don't fit the type wrap, same as the `wrapping_` methods, unless the docs
+//! say otherwise.
+
+/// Integer operations that don't branch.
+///
+/// The functions take no `self` so that they never get mixed up with the
+/// inherent and [`Ord`] methods of the same names. Call them as `T::min(a, b)`
+/// in generic code. For the details of each one see the `const fn` of the
+/// same name in the module for a type, such as [`u8::min`].
+pub trait Branchless: Copy {
+  /// The absolute value (unchanged for unsigned types).
+  fn abs(x: Self) -> Self;
+  /// The smaller value.
+  fn min(a: Self, b: Self) -> Self;
+  /// The larger value.
+  fn max(a: Self, b: Self) -> Self;
+  /// `x` limited to the range `low..=high`.
+  fn clamp(x: Self, low: Self, high: Self) -> Self;
+  /// `a` if `cond` is true, otherwise `b`.
+  fn select(cond: bool, a: Self, b: Self) -> Self;
+  /// -1, 0, or 1 depending on the sign.
+  fn sign(x: Self) -> Self;
+  /// If the value is a power of two.
+  fn is_power_of_two(x: Self) -> bool;
+  /// The smallest power of two that's at least `x`.
+  fn next_power_of_two(x: Self) -> Self;
+  /// `x` rounded up to a multiple of `align`.
+  fn align_up(x: Self, align: Self) -> Self;
+  /// `x` rounded down to a multiple of `align`.
+  fn align_down(x: Self, align: Self) -> Self;
+  /// The average, rounded down.
+  fn average_floor(a: Self, b: Self) -> Self;
+  /// The average, rounded up.
+  fn average_ceil(a: Self, b: Self) -> Self;
+  /// The bits in reverse order.
+  fn bit_reverse(x: Self) -> Self;
+  /// The high and low 4 bits of each byte swapped.
+  fn swap_nibbles(x: Self) -> Self;
+}
+
+macro_rules! saturating_to_all {
+  ($t:ident, $kind:ident) => {
+    saturating_to!(
+      $t,
+      $kind,
+      u8 saturating_to_u8,
+      u16 saturating_to_u16,
+      u32 saturating_to_u32,
+      u64 saturating_to_u64,
+      u128 saturating_to_u128,
+      usize saturating_to_usize,
+      i8 saturating_to_i8,
+      i16 saturating_to_i16,
+      i32 saturating_to_i32,
+      i64 saturating_to_i64,
+      i128 saturating_to_i128,
+      isize saturating_to_isize
+    );
+  };
+}
+
+macro_rules! saturating_to {
+  ($t:ident, signed, $($dst:ident $name:ident),*) => {
+    $(
+      #[doc = concat!(
+        "Converts to `", stringify!($dst), "`, giving the closest value when `x` is out of range."
+      )]
+      #[inline]
+      pub const fn $name(x: $t) -> $dst {
+        let below = (x as i128) < ($dst::MIN as i128);
+        let above = (x > 0) & ((x as u128) > ($dst::MAX as u128));
+        super::$dst::select(below, $dst::MIN, super::$dst::select(above, $dst::MAX, x as $dst))
+      }
+    )*
+  };
+  ($t:ident, unsigned, $($dst:ident $name:ident),*) => {
+    $(
+      #[doc = concat!(
+        "Converts to `", stringify!($dst), "`, giving the closest value when `x` is out of range."
+      )]
+      #[inline]
+      pub const fn $name(x: $t) -> $dst {
+        let above = (x as u128) > ($dst::MAX as u128);
+        super::$dst::select(above, $dst::MAX, x as $dst)
+      }
+    )*
+  };
+}
+
+/// The operations that are written the same for signed and unsigned types.
+macro_rules! common_ops {
+  ($t:ident) => {
+    /// `a` if `cond` is true, otherwise `b`.
+    #[inline]
+    pub const fn select(cond: bool, a: $t, b: $t) -> $t {
+      b ^ ((a ^ b) & (cond as $t).wrapping_neg())
+    }
+
+    /// The smaller value.
+    #[inline]
+    pub const fn min(a: $t, b: $t) -> $t {
+      select(a < b, a, b)
+    }
+
+    /// The larger value.
+    #[inline]
+    pub const fn max(a: $t, b: $t) -> $t {
+      select(a < b, b, a)
+    }
+
+    /// `x` limited to the range `low..=high`.
+    ///
+    /// Unlike [`Ord::clamp`] this doesn't panic when `low > high`, you just
+    /// get `low`.
+    #[inline]
+    pub const fn clamp(x: $t, low: $t, high: $t) -> $t {
+      max(low, min(x, high))
+    }
+
+    /// `x` rounded up to a multiple of `align`, which must be a power of two.
+    ///
+    /// Wraps if the result doesn't fit.
+    #[inline]
+    pub const fn align_up(x: $t, align: $t) -> $t {
+      let low_bits = align.wrapping_sub(1);
+      x.wrapping_add(low_bits) & !low_bits
+    }
+
+    /// `x` rounded down (toward negative infinity) to a multiple of `align`,
+    /// which must be a power of two.
+    #[inline]
+    pub const fn align_down(x: $t, align: $t) -> $t {
+      x & !align.wrapping_sub(1)
+    }
+
+    /// The average of the two values, rounded down, without overflowing.
+    #[inline]
+    pub const fn average_floor(a: $t, b: $t) -> $t {
+      (a & b) + ((a ^ b) >> 1)
+    }
+
+    /// The average of the two values, rounded up, without overflowing.
+    #[inline]
+    pub const fn average_ceil(a: $t, b: $t) -> $t {
+      (a | b) - ((a ^ b) >> 1)
+    }
+
+    /// The bits in reverse order.
+    #[inline]
+    pub const fn bit_reverse(x: $t) -> $t {
+      x.reverse_bits()
+    }
+  };
+}
+
+macro_rules! impl_trait {
+  ($t:ident) => {
+    impl Branchless for core::primitive::$t {
+      #[inline]
+      fn abs(x: Self) -> Self {
+        self::$t::abs(x)
+      }
+      #[inline]
+      fn min(a: Self, b: Self) -> Self {
+        self::$t::min(a, b)
+      }
+      #[inline]
+      fn max(a: Self, b: Self) -> Self {
+        self::$t::max(a, b)
+      }
+      #[inline]
+      fn clamp(x: Self, low: Self, high: Self) -> Self {
+        self::$t::clamp(x, low, high)
+      }
+      #[inline]
+      fn select(cond: bool, a: Self, b: Self) -> Self {
+        self::$t::select(cond, a, b)
+      }
+      #[inline]
+      fn sign(x: Self) -> Self {
+        self::$t::sign(x)
+      }
+      #[inline]
+      fn is_power_of_two(x: Self) -> bool {
+        self::$t::is_power_of_two(x)
+      }
+      #[inline]
+      fn next_power_of_two(x: Self) -> Self {
+        self::$t::next_power_of_two(x)
+      }
+      #[inline]
+      fn align_up(x: Self, align: Self) -> Self {
+        self::$t::align_up(x, align)
+      }
+      #[inline]
+      fn align_down(x: Self, align: Self) -> Self {
+        self::$t::align_down(x, align)
+      }
+      #[inline]
+      fn average_floor(a: Self, b: Self) -> Self {
+        self::$t::average_floor(a, b)
+      }
+      #[inline]
+      fn average_ceil(a: Self, b: Self) -> Self {
+        self::$t::average_ceil(a, b)
+      }
+      #[inline]
+      fn bit_reverse(x: Self) -> Self {
+        self::$t::bit_reverse(x)
+      }
+      #[inline]
+      fn swap_nibbles(x: Self) -> Self {
+        self::$t::swap_nibbles(x)
+      }
+    }
+  };
+}
+
+macro_rules! unsigned_module {
+  ($t:ident) => {
+    #[doc = concat!("Branchless `const fn` operations on `", stringify!($t), "`.")]
+    pub mod $t {
+      common_ops!($t);
+      saturating_to_all!($t, unsigned);
+
+      /// Unsigned values are already their own absolute value.
+      #[inline]
+      pub const fn abs(x: $t) -> $t {
+        x
+      }
+
+      /// 1 if `x` is non-zero, otherwise 0.
+      #[inline]
+      pub const fn sign(x: $t) -> $t {
+        (x != 0) as $t
+      }
+
+      /// If `x` has exactly one bit set.
+      #[inline]
+      pub const fn is_power_of_two(x: $t) -> bool {
+        (x != 0) & (x & x.wrapping_sub(1) == 0)
+      }
+
+      /// The smallest power of two that's at least `x`.
+      ///
+      /// This is 1 for 0, and 0 if the power of two doesn't fit in the type.
+      #[inline]
+      pub const fn next_power_of_two(x: $t) -> $t {
+        let shift = $t::BITS - x.wrapping_sub(1).leading_zeros();
+        let power = (1 as $t).wrapping_shl(shift);
+        select(x == 0, 1, select(shift == $t::BITS, 0, power))
+      }
+
+      /// The high and low 4 bits of each byte swapped.
+      #[inline]
+      pub const fn swap_nibbles(x: $t) -> $t {
+        let low_nibbles = $t::MAX / 0x11;
+        ((x & low_nibbles) << 4) | ((x >> 4) & low_nibbles)
+      }
+    }
+    impl_trait!($t);
+  };
+}
+
+macro_rules! signed_module {
+  ($t:ident, $u:ident) => {
+    #[doc = concat!("Branchless `const fn` operations on `", stringify!($t), "`.")]
+    pub mod $t {
+      common_ops!($t);
+      saturating_to_all!($t, signed);
+
+      /// The absolute value.
+      ///
+      #[doc = concat!("`", stringify!($t), "::MIN` stays as it is, same as `wrapping_abs`.")]
+      #[inline]
+      pub const fn abs(x: $t) -> $t {
+        let mask = x >> ($t::BITS - 1);
+        (x ^ mask).wrapping_sub(mask)
+      }
+
+      /// -1 if `x` is negative, 0 if it's zero, and 1 if it's positive.
+      #[inline]
+      pub const fn sign(x: $t) -> $t {
+        (x > 0) as $t - (x < 0) as $t
+      }
+
+      /// If `x` is positive and has exactly one bit set.
+      #[inline]
+      pub const fn is_power_of_two(x: $t) -> bool {
+        (x > 0) & (x & x.wrapping_sub(1) == 0)
+      }
+
+      /// The smallest power of two that's at least `x`.
+      ///
+      /// This is 1 for anything less than 1, and 0 if the power of two
+      /// doesn't fit in the type.
+      #[inline]
+      pub const fn next_power_of_two(x: $t) -> $t {
+        let power = super::$u::next_power_of_two(x as $u);
+        select(x <= 1, 1, select(power > $t::MAX as $u, 0, power as $t))
+      }
+
+      /// The high and low 4 bits of each byte swapped.
+      #[inline]
+      pub const fn swap_nibbles(x: $t) -> $t {
+        super::$u::swap_nibbles(x as $u) as $t
+      }
+    }
+    impl_trait!($t);
+  };
+}
+
+unsigned_module!(u8);
+unsigned_module!(u16);
+unsigned_module!(u32);
+unsigned_module!(u64);
+unsigned_module!(u128);
+unsigned_module!(usize);
+signed_module!(i8, u8);
+signed_module!(i16, u16);
+signed_module!(i32, u32);
+signed_module!(i64, u64);
+signed_module!(i128, u128);
+signed_module!(isize, usize);
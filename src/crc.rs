@@ -0,0 +1,225 @@
+//! CRC-32 and CRC-64 checksums.
+//!
+//! * [`crc32`] is the CRC-32 of zlib, gzip, PNG, and Ethernet (the IEEE
+//!   polynomial, bit reflected).
+//! * [`crc64`] is CRC-64/XZ (the ECMA-182 polynomial, bit reflected), as used
+//!   by xz.
+//!
+//! The `_update` versions continue a checksum from where an earlier call left
+//! off, so you can feed in the data a piece at a time:
+//!
+//! ```rust
+//! use lokacore::crc::*;
+//!
+//! assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+//! assert_eq!(crc32_update(crc32(b"1234"), b"56789"), 0xCBF4_3926);
+//! assert_eq!(crc64(b"123456789"), 0x995D_C9BB_DF19_39FA);
+//! ```
+//!
+//! With PCLMULQDQ, long inputs are folded down 64 bytes at a time with
+//! carry-less multiplies (see the `clmul!` macro), which is many times
+//! faster than a table. The last 16 bytes of the fold and anything left over
+//! go through the table.
+
+#[cfg(all(target_feature = "sse2", target_feature = "pclmulqdq"))]
+use crate::clmul;
+
+#[cfg(all(
+  target_arch = "x86",
+  target_feature = "sse2",
+  target_feature = "pclmulqdq"
+))]
+use crate::arch::x86::*;
+#[cfg(all(
+  target_arch = "x86_64",
+  target_feature = "sse2",
+  target_feature = "pclmulqdq"
+))]
+use crate::arch::x86_64::*;
+
+const fn make_table_32(poly: u32) -> [u32; 256] {
+  let mut table = [0; 256];
+  let mut i = 0;
+  while i < 256 {
+    let mut crc = i as u32;
+    let mut bit = 0;
+    while bit < 8 {
+      crc = if crc & 1 != 0 {
+        (crc >> 1) ^ poly
+      } else {
+        crc >> 1
+      };
+      bit += 1;
+    }
+    table[i] = crc;
+    i += 1;
+  }
+  table
+}
+
+const fn make_table_64(poly: u64) -> [u64; 256] {
+  let mut table = [0; 256];
+  let mut i = 0;
+  while i < 256 {
+    let mut crc = i as u64;
+    let mut bit = 0;
+    while bit < 8 {
+      crc = if crc & 1 != 0 {
+        (crc >> 1) ^ poly
+      } else {
+        crc >> 1
+      };
+      bit += 1;
+    }
+    table[i] = crc;
+    i += 1;
+  }
+  table
+}
+
+static CRC32_TABLE: [u32; 256] = make_table_32(0xEDB8_8320);
+static CRC64_TABLE: [u64; 256] = make_table_64(0xC96C_5795_D787_0F42);
+
+/// The constants to fold 16 bytes forward by 64 bytes and by 16 bytes.
+///
+/// To move the low and high halves of a block forward by `d` bytes they get
+/// multiplied by `x^(8d+63) mod P` and `x^(8d-1) mod P` (bit reflected, like
+/// the rest of the CRC).
+#[cfg(all(target_feature = "sse2", target_feature = "pclmulqdq"))]
+struct FoldKeys {
+  by64: [u64; 2],
+  by16: [u64; 2],
+}
+
+#[cfg(all(target_feature = "sse2", target_feature = "pclmulqdq"))]
+const CRC32_FOLD: FoldKeys = FoldKeys {
+  by64: [0x8F35_2D95, 0x1D95_13D7],
+  by16: [0xAE68_9191, 0xCCAA_009E],
+};
+
+#[cfg(all(target_feature = "sse2", target_feature = "pclmulqdq"))]
+const CRC64_FOLD: FoldKeys = FoldKeys {
+  by64: [0x6AE3_EFBB_9DD4_41F3, 0x081F_6054_A784_2DF4],
+  by16: [0xE05D_D497_CA39_3AE4, 0xDABE_95AF_C787_5F40],
+};
+
+/// Moves the block forward by however many bytes `keys` is for.
+#[cfg(all(target_feature = "sse2", target_feature = "pclmulqdq"))]
+#[inline(always)]
+fn fold16(x: m128i, keys: m128i) -> m128i {
+  clmul!(x, keys, 0x00) ^ clmul!(x, keys, 0x11)
+}
+
+/// Folds all the whole 16 byte blocks of `data` (at least 64 bytes) into one
+/// block with the same CRC, after XORing `init` into the start.
+///
+/// Returns that block and the leftover bytes.
+#[cfg(all(target_feature = "sse2", target_feature = "pclmulqdq"))]
+fn fold<'a>(data: &'a [u8], init: u64, keys: &FoldKeys) -> ([u8; 16], &'a [u8]) {
+  let n = data.len();
+  let load = |i: usize| m128i::load_from_slice(&data[i..]).unwrap();
+  let by64 = m128i::set_i64(keys.by64[1] as i64, keys.by64[0] as i64);
+  let by16 = m128i::set_i64(keys.by16[1] as i64, keys.by16[0] as i64);
+  let mut lanes = [
+    load(0) ^ m128i::set_i64(0, init as i64),
+    load(16),
+    load(32),
+    load(48),
+  ];
+  let mut i = 64;
+  while i + 64 <= n {
+    for (j, lane) in lanes.iter_mut().enumerate() {
+      *lane = fold16(*lane, by64) ^ load(i + 16 * j);
+    }
+    i += 64;
+  }
+  let mut x = lanes[0];
+  for &lane in &lanes[1..] {
+    x = fold16(x, by16) ^ lane;
+  }
+  while i + 16 <= n {
+    x = fold16(x, by16) ^ load(i);
+    i += 16;
+  }
+  let mut block = [0; 16];
+  x.storeu(&mut block);
+  (block, &data[i..])
+}
+
+#[inline(always)]
+fn table_32(mut state: u32, data: &[u8]) -> u32 {
+  for &b in data {
+    state = CRC32_TABLE[usize::from(state as u8 ^ b)] ^ (state >> 8);
+  }
+  state
+}
+
+#[inline(always)]
+fn table_64(mut state: u64, data: &[u8]) -> u64 {
+  for &b in data {
+    state = CRC64_TABLE[usize::from(state as u8 ^ b)] ^ (state >> 8);
+  }
+  state
+}
+
+/// Continues a CRC-32 with more data.
+///
+/// Start from `0` (or use [`crc32`]) for a fresh checksum.
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+  #[cfg_attr(
+    not(all(target_feature = "sse2", target_feature = "pclmulqdq")),
+    allow(unused_mut)
+  )]
+  let mut state = !crc;
+  #[cfg_attr(
+    not(all(target_feature = "sse2", target_feature = "pclmulqdq")),
+    allow(unused_mut)
+  )]
+  let mut data = data;
+  #[cfg(all(target_feature = "sse2", target_feature = "pclmulqdq"))]
+  {
+    if data.len() >= 64 {
+      let (block, rest) = fold(data, u64::from(state), &CRC32_FOLD);
+      state = table_32(0, &block);
+      data = rest;
+    }
+  }
+  !table_32(state, data)
+}
+
+/// The CRC-32 (IEEE) of the data.
+#[inline]
+pub fn crc32(data: &[u8]) -> u32 {
+  crc32_update(0, data)
+}
+
+/// Continues a CRC-64/XZ with more data.
+///
+/// Start from `0` (or use [`crc64`]) for a fresh checksum.
+pub fn crc64_update(crc: u64, data: &[u8]) -> u64 {
+  #[cfg_attr(
+    not(all(target_feature = "sse2", target_feature = "pclmulqdq")),
+    allow(unused_mut)
+  )]
+  let mut state = !crc;
+  #[cfg_attr(
+    not(all(target_feature = "sse2", target_feature = "pclmulqdq")),
+    allow(unused_mut)
+  )]
+  let mut data = data;
+  #[cfg(all(target_feature = "sse2", target_feature = "pclmulqdq"))]
+  {
+    if data.len() >= 64 {
+      let (block, rest) = fold(data, state, &CRC64_FOLD);
+      state = table_64(0, &block);
+      data = rest;
+    }
+  }
+  !table_64(state, data)
+}
+
+/// The CRC-64/XZ of the data.
+#[inline]
+pub fn crc64(data: &[u8]) -> u64 {
+  crc64_update(0, data)
+}
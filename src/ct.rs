@@ -0,0 +1,234 @@
+//! Constant-time comparisons and selection, for cryptographic code.
+//!
+//! The [`branchless`](crate::branchless) operations don't branch as written,
+//! but once the optimizer can see where a condition came from it's free to
+//! turn the masking back into a branch. Here every condition is a [`Choice`],
+//! and a `Choice` always goes through an [`optimization_barrier`] before it's
+//! used, so the compiler can't know what value it holds.
+//!
+//! ```rust
+//! use lokacore::ct::*;
+//!
+//! let tag = [7_u8; 16];
+//! let expected = [7_u8; 16];
+//! let ok = ct_eq_slices(&tag, &expected);
+//! assert!(bool::from(ok));
+//!
+//! let smaller = ct_select(ct_lt(3_u32, 9), 3, 9);
+//! assert_eq!(smaller, 3);
+//! ```
+//!
+//! This is the usual best effort: it keeps secrets out of branches and
+//! memory addresses in the code that gets generated, but Rust itself makes no
+//! timing promises. Turning a `Choice` into a `bool` is the point where you're
+//! saying the result is fine to branch on.
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+/// Returns the value, without letting the optimizer know what it is.
+///
+/// The value is copied through a volatile read, which the compiler has to
+/// assume could give back anything.
+#[inline(always)]
+pub fn optimization_barrier<T: Copy>(x: T) -> T {
+  // Safety: the reference is valid for reads, and `T: Copy` makes it fine to
+  // duplicate the value.
+  unsafe { core::ptr::read_volatile(&x) }
+}
+
+/// A `bool` for constant-time code: `1` for true, `0` for false.
+///
+/// The logical operations are `&`, `|`, `^` and `!`. Use `bool::from` once
+/// it's safe for the answer to be known.
+#[derive(Debug, Clone, Copy)]
+#[repr(transparent)]
+pub struct Choice(u8);
+
+impl Choice {
+  /// The true choice.
+  pub const TRUE: Self = Choice(1);
+  /// The false choice.
+  pub const FALSE: Self = Choice(0);
+
+  /// Makes a choice from the low bit of `bit`, ignoring the others.
+  #[inline]
+  pub fn from_bit(bit: u8) -> Self {
+    Choice(optimization_barrier(bit & 1))
+  }
+
+  /// The choice as `1` or `0`.
+  #[inline]
+  pub fn unwrap_u8(self) -> u8 {
+    self.0
+  }
+}
+
+impl From<bool> for Choice {
+  #[inline]
+  fn from(b: bool) -> Self {
+    Choice::from_bit(b as u8)
+  }
+}
+
+impl From<Choice> for bool {
+  #[inline]
+  fn from(c: Choice) -> Self {
+    c.0 != 0
+  }
+}
+
+impl Not for Choice {
+  type Output = Self;
+  #[inline]
+  fn not(self) -> Self {
+    Choice(self.0 ^ 1)
+  }
+}
+
+macro_rules! choice_bit_op {
+  ($trait:ident, $f:ident, $assign_trait:ident, $assign_f:ident, $op:tt) => {
+    impl $trait for Choice {
+      type Output = Self;
+      #[inline]
+      fn $f(self, other: Self) -> Self {
+        Choice(self.0 $op other.0)
+      }
+    }
+    impl $assign_trait for Choice {
+      #[inline]
+      fn $assign_f(&mut self, other: Self) {
+        self.0 = self.0 $op other.0;
+      }
+    }
+  };
+}
+choice_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+choice_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+choice_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+/// Integers that can be compared and selected in constant time.
+///
+/// Use the free functions ([`ct_eq`], [`ct_lt`], [`ct_select`], [`ct_swap`])
+/// rather than calling these directly.
+pub trait ConstantTime: Copy {
+  /// If `a == b`.
+  fn ct_eq(a: Self, b: Self) -> Choice;
+  /// If `a < b`.
+  fn ct_lt(a: Self, b: Self) -> Choice;
+  /// `a` if `choice` is true, otherwise `b`.
+  fn ct_select(choice: Choice, a: Self, b: Self) -> Self;
+  /// Swaps `a` and `b` if `choice` is true.
+  fn ct_swap(choice: Choice, a: &mut Self, b: &mut Self);
+}
+
+macro_rules! impl_constant_time {
+  ($t:ty, $u:ty, $flip:expr) => {
+    impl ConstantTime for $t {
+      #[inline]
+      fn ct_eq(a: Self, b: Self) -> Choice {
+        let x = (a ^ b) as $u;
+        let non_zero = (x | x.wrapping_neg()) >> (<$u>::BITS - 1);
+        Choice::from_bit(non_zero as u8 ^ 1)
+      }
+      #[inline]
+      fn ct_lt(a: Self, b: Self) -> Choice {
+        // flipping the sign bit orders signed values like unsigned ones
+        let (a, b) = (a as $u ^ $flip, b as $u ^ $flip);
+        let borrow = (!a & b) | (!(a ^ b) & a.wrapping_sub(b));
+        Choice::from_bit((borrow >> (<$u>::BITS - 1)) as u8)
+      }
+      #[inline]
+      fn ct_select(choice: Choice, a: Self, b: Self) -> Self {
+        let mask = (optimization_barrier(choice.0) as Self).wrapping_neg();
+        b ^ (mask & (a ^ b))
+      }
+      #[inline]
+      fn ct_swap(choice: Choice, a: &mut Self, b: &mut Self) {
+        let mask = (optimization_barrier(choice.0) as Self).wrapping_neg();
+        let t = mask & (*a ^ *b);
+        *a ^= t;
+        *b ^= t;
+      }
+    }
+  };
+}
+impl_constant_time!(u8, u8, 0);
+impl_constant_time!(u16, u16, 0);
+impl_constant_time!(u32, u32, 0);
+impl_constant_time!(u64, u64, 0);
+impl_constant_time!(u128, u128, 0);
+impl_constant_time!(usize, usize, 0);
+impl_constant_time!(i8, u8, 1 << 7);
+impl_constant_time!(i16, u16, 1 << 15);
+impl_constant_time!(i32, u32, 1 << 31);
+impl_constant_time!(i64, u64, 1 << 63);
+impl_constant_time!(i128, u128, 1 << 127);
+impl_constant_time!(isize, usize, 1 << (usize::BITS - 1));
+
+/// If `a == b`, in constant time.
+#[inline]
+pub fn ct_eq<T: ConstantTime>(a: T, b: T) -> Choice {
+  T::ct_eq(a, b)
+}
+
+/// If `a < b`, in constant time.
+#[inline]
+pub fn ct_lt<T: ConstantTime>(a: T, b: T) -> Choice {
+  T::ct_lt(a, b)
+}
+
+/// `a` if `choice` is true, otherwise `b`, in constant time.
+#[inline]
+pub fn ct_select<T: ConstantTime>(choice: Choice, a: T, b: T) -> T {
+  T::ct_select(choice, a, b)
+}
+
+/// Swaps `a` and `b` if `choice` is true, in constant time.
+#[inline]
+pub fn ct_swap<T: ConstantTime>(choice: Choice, a: &mut T, b: &mut T) {
+  T::ct_swap(choice, a, b)
+}
+
+/// If the two slices hold the same bytes, in constant time.
+///
+/// This is what you want for checking a MAC or a password hash. Every byte of
+/// both slices is looked at no matter where (or if) they differ, so the time
+/// taken only depends on the length. The lengths aren't secret: slices of
+/// different lengths give false right away.
+///
+/// With SSE2, 16 byte blocks are compared with `m128i::cmp_eq_i8` and the
+/// results are all combined before a single `movemask_i8` at the end. The
+/// running result goes through [`optimization_barrier`] after every block and
+/// every byte, so the compiler can't tell when the answer is already known.
+pub fn ct_eq_slices(a: &[u8], b: &[u8]) -> Choice {
+  if a.len() != b.len() {
+    return Choice::FALSE;
+  }
+  let mut diff = 0_u32;
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let (mut a, mut b) = (a, b);
+  #[cfg(target_feature = "sse2")]
+  {
+    let load = |chunk: &[u8]| m128i::load_from_slice(chunk).unwrap();
+    let a_blocks = a.chunks_exact(16);
+    let b_blocks = b.chunks_exact(16);
+    let (a_rest, b_rest) = (a_blocks.remainder(), b_blocks.remainder());
+    let mut same = m128i::zeroed().cmp_eq_i8(m128i::zeroed());
+    for (x, y) in a_blocks.zip(b_blocks) {
+      // keeps the compiler from adding an early exit once `same` is all zero
+      same = optimization_barrier(same & load(x).cmp_eq_i8(load(y)));
+    }
+    diff |= (same.movemask_i8() ^ 0xFFFF) as u32;
+    a = a_rest;
+    b = b_rest;
+  }
+  for (x, y) in a.iter().zip(b) {
+    diff = optimization_barrier(diff | u32::from(x ^ y));
+  }
+  ct_eq(diff, 0)
+}
@@ -0,0 +1,520 @@
+//! Fixed point numbers, for DSP code where floats are slow or unwanted.
+//!
+//! * [`Q15`] is an `i16` counting in steps of `2^-15`, so it covers
+//!   `-1.0..1.0`. It's the usual format for audio samples and filter
+//!   coefficients.
+//! * [`Q16_16`] is an `i32` counting in steps of `2^-16`, so it covers
+//!   `-32768.0..32768.0`.
+//! * [`q15x8`] is eight `Q15` values, worked on all at once with SSE2 (and
+//!   SSSE3 for the multiply) when it's available.
+//!
+//! The operators _saturate_: a result past either end of the range gives the
+//! closest value that fits, instead of wrapping or panicking. Dividing by zero
+//! saturates toward the sign of the dividend (and `0 / 0` is `0`).
+//!
+//! ```rust
+//! use lokacore::fixed::*;
+//!
+//! let half = Q15::from_f32(0.5);
+//! let quarter = half * half;
+//! assert_eq!(quarter.to_f32(), 0.25);
+//! assert_eq!(Q15::MAX + half, Q15::MAX);
+//!
+//! let x = Q16_16::from_int(3) / Q16_16::from_int(4);
+//! assert_eq!(x.to_f32(), 0.75);
+//! assert_eq!(Q16_16::from_f32(2.5).to_int(Rounding::Floor), 2);
+//! ```
+//!
+//! Multiplying and dividing have to round away the low bits of the exact
+//! result. The operators round to nearest, and the `_round` methods let you
+//! pick a [`Rounding`].
+
+use crate::{Pod, Zeroable};
+use core::ops::*;
+
+#[cfg(target_feature = "sse2")]
+use crate::cast;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+/// How to round away the bits of a result that don't fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rounding {
+  /// To the nearest value, with ties going up (toward positive infinity).
+  ///
+  /// This is what `m128i::mul_high_round_i16` does.
+  Nearest,
+  /// Down, toward negative infinity. This is just an arithmetic shift.
+  Floor,
+  /// Up, toward positive infinity.
+  Ceil,
+  /// Toward zero, like integer division.
+  TowardZero,
+}
+
+/// Shifts `x` right by `shift` bits, rounding the bits that fall off.
+#[inline]
+fn shr_round(x: i64, shift: u32, rounding: Rounding) -> i64 {
+  let unit = 1_i64 << shift;
+  match rounding {
+    Rounding::Nearest => (x + (unit >> 1)) >> shift,
+    Rounding::Floor => x >> shift,
+    Rounding::Ceil => (x + unit - 1) >> shift,
+    Rounding::TowardZero if x < 0 => (x + unit - 1) >> shift,
+    Rounding::TowardZero => x >> shift,
+  }
+}
+
+/// `n / d`, rounded. `d` can't be zero.
+#[inline]
+fn div_round(n: i64, d: i64, rounding: Rounding) -> i64 {
+  let (n, d) = if d < 0 { (-n, -d) } else { (n, d) };
+  let floor = n.div_euclid(d);
+  let rem = n.rem_euclid(d);
+  match rounding {
+    Rounding::Nearest => floor + (2 * rem >= d) as i64,
+    Rounding::Floor => floor,
+    Rounding::Ceil => floor + (rem != 0) as i64,
+    Rounding::TowardZero if n < 0 => floor + (rem != 0) as i64,
+    Rounding::TowardZero => floor,
+  }
+}
+
+/// Rounds to the nearest integer, with ties going to even (same as the SSE
+/// conversions in the default rounding mode).
+#[inline]
+fn round_ties_even(x: f32) -> f32 {
+  // from 2^23 up there's no fraction, and below it adding 2^23 leaves exactly
+  // the integer bits, which the add rounds for us.
+  const TWO_23: f32 = 8_388_608.0;
+  if (0.0..TWO_23).contains(&x) {
+    (x + TWO_23) - TWO_23
+  } else if (-TWO_23..0.0).contains(&x) {
+    (x - TWO_23) + TWO_23
+  } else {
+    x
+  }
+}
+
+macro_rules! fixed_common {
+  ($name:ident, $t:ty, $frac:expr) => {
+    unsafe impl Zeroable for $name {}
+    unsafe impl Pod for $name {}
+
+    impl $name {
+      /// How many of the bits are after the point.
+      pub const FRAC_BITS: u32 = $frac;
+      /// Zero.
+      pub const ZERO: Self = Self(0);
+      /// One half.
+      pub const HALF: Self = Self(1 << ($frac - 1));
+      /// The smallest step between two values.
+      pub const EPSILON: Self = Self(1);
+      /// The lowest value.
+      pub const MIN: Self = Self(<$t>::MIN);
+      /// The highest value.
+      pub const MAX: Self = Self(<$t>::MAX);
+
+      /// Makes a value from its raw bits.
+      #[inline]
+      pub const fn from_bits(bits: $t) -> Self {
+        Self(bits)
+      }
+
+      /// The raw bits of the value.
+      #[inline]
+      pub const fn to_bits(self) -> $t {
+        self.0
+      }
+
+      /// Converts from `f32`, rounding to nearest (ties to even).
+      ///
+      /// Values out of range saturate, and NaN gives zero.
+      #[inline]
+      pub fn from_f32(f: f32) -> Self {
+        let scaled = round_ties_even(f * (1_u32 << $frac) as f32);
+        Self::saturate(scaled as i64)
+      }
+
+      /// Converts to `f32`.
+      #[inline]
+      pub fn to_f32(self) -> f32 {
+        self.0 as f32 * (1.0 / (1_u32 << $frac) as f32)
+      }
+
+      #[inline]
+      fn saturate(x: i64) -> Self {
+        Self(x.clamp(<$t>::MIN as i64, <$t>::MAX as i64) as $t)
+      }
+
+      /// Adds, saturating at the ends of the range.
+      #[inline]
+      pub fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+      }
+
+      /// Subtracts, saturating at the ends of the range.
+      #[inline]
+      pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+      }
+
+      /// Negates, with `MIN` going to `MAX`.
+      #[inline]
+      pub fn saturating_neg(self) -> Self {
+        Self(self.0.saturating_neg())
+      }
+
+      /// Adds, wrapping around at the ends of the range.
+      #[inline]
+      pub fn wrapping_add(self, other: Self) -> Self {
+        Self(self.0.wrapping_add(other.0))
+      }
+
+      /// Subtracts, wrapping around at the ends of the range.
+      #[inline]
+      pub fn wrapping_sub(self, other: Self) -> Self {
+        Self(self.0.wrapping_sub(other.0))
+      }
+
+      /// Multiplies with the given rounding, saturating.
+      #[inline]
+      pub fn mul_round(self, other: Self, rounding: Rounding) -> Self {
+        let product = self.0 as i64 * other.0 as i64;
+        Self::saturate(shr_round(product, $frac, rounding))
+      }
+
+      /// Divides with the given rounding, saturating.
+      ///
+      /// Dividing by zero gives `MAX` or `MIN` depending on the sign of
+      /// `self`, or zero if `self` is zero.
+      #[inline]
+      pub fn div_round(self, other: Self, rounding: Rounding) -> Self {
+        if other.0 == 0 {
+          Self::saturate((self.0 as i64).signum() * i64::MAX)
+        } else {
+          Self::saturate(div_round(
+            (self.0 as i64) << $frac,
+            other.0 as i64,
+            rounding,
+          ))
+        }
+      }
+    }
+
+    impl Add for $name {
+      type Output = Self;
+      #[inline]
+      fn add(self, other: Self) -> Self {
+        self.saturating_add(other)
+      }
+    }
+    impl Sub for $name {
+      type Output = Self;
+      #[inline]
+      fn sub(self, other: Self) -> Self {
+        self.saturating_sub(other)
+      }
+    }
+    impl Mul for $name {
+      type Output = Self;
+      #[inline]
+      fn mul(self, other: Self) -> Self {
+        self.mul_round(other, Rounding::Nearest)
+      }
+    }
+    impl Div for $name {
+      type Output = Self;
+      #[inline]
+      fn div(self, other: Self) -> Self {
+        self.div_round(other, Rounding::Nearest)
+      }
+    }
+    impl Neg for $name {
+      type Output = Self;
+      #[inline]
+      fn neg(self) -> Self {
+        self.saturating_neg()
+      }
+    }
+    impl AddAssign for $name {
+      #[inline]
+      fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+      }
+    }
+    impl SubAssign for $name {
+      #[inline]
+      fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+      }
+    }
+    impl MulAssign for $name {
+      #[inline]
+      fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+      }
+    }
+    impl DivAssign for $name {
+      #[inline]
+      fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+      }
+    }
+  };
+}
+
+/// A Q15 fixed point value: `self.0 / 32768`, which covers `-1.0..1.0`.
+///
+/// One itself is just out of range, so [`Q15::MAX`] is the closest there is.
+/// Notably `MIN * MIN` is `MAX`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Q15(pub i16);
+fixed_common!(Q15, i16, 15);
+
+/// A Q16.16 fixed point value: `self.0 / 65536`, which covers
+/// `-32768.0..32768.0`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Q16_16(pub i32);
+fixed_common!(Q16_16, i32, 16);
+
+impl Q16_16 {
+  /// One.
+  pub const ONE: Self = Self(1 << 16);
+
+  /// Makes a whole number value. Every `i16` fits exactly.
+  #[inline]
+  pub const fn from_int(i: i16) -> Self {
+    Self((i as i32) << 16)
+  }
+
+  /// Rounds to a whole number.
+  ///
+  /// This is an `i32` because rounding `MAX` up gives 32768.
+  #[inline]
+  pub fn to_int(self, rounding: Rounding) -> i32 {
+    shr_round(i64::from(self.0), 16, rounding) as i32
+  }
+
+  /// Converts to [`Q15`] with the given rounding, saturating.
+  #[inline]
+  pub fn saturating_to_q15(self, rounding: Rounding) -> Q15 {
+    Q15::saturate(shr_round(i64::from(self.0), 1, rounding))
+  }
+}
+
+impl From<Q15> for Q16_16 {
+  /// Every `Q15` fits exactly.
+  #[inline]
+  fn from(q: Q15) -> Self {
+    Self(i32::from(q.0) << 1)
+  }
+}
+
+/// Eight [`Q15`] values.
+///
+/// This is `m128i` sized and aligned. The operators saturate and round the
+/// same as the ones on `Q15`, lane by lane:
+///
+/// * `+`, `-`, and negation use the SSE2 saturating `i16` ops.
+/// * `*` uses `mul_high_round_i16` with SSSE3, or an SSE2 widening multiply
+///   without it.
+/// * `/` is done one lane at a time, there's no SIMD integer division.
+///
+/// Without SSE2 every operation is done one lane at a time.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C, align(16))]
+pub struct q15x8(pub [Q15; 8]);
+
+unsafe impl Zeroable for q15x8 {}
+unsafe impl Pod for q15x8 {}
+
+impl q15x8 {
+  /// All lanes set to `q`.
+  #[inline]
+  pub fn splat(q: Q15) -> Self {
+    Self([q; 8])
+  }
+
+  /// Converts eight `f32` values, as [`Q15::from_f32`].
+  ///
+  /// With SSE2 this uses `m128::round_i32`, so it needs the default rounding
+  /// mode (to nearest) to match `Q15::from_f32`.
+  #[inline]
+  pub fn from_f32s(f: &[f32; 8]) -> Self {
+    #[cfg(target_feature = "sse2")]
+    {
+      let half = |i: usize| {
+        let mut x = m128::load_from_slice(&f[i..]).unwrap() * m128::splat(32768.0);
+        // NaN to zero, then clamp so that `round_i32` can't overflow.
+        x = x & x.cmp_eq(x);
+        x = x.max(m128::splat(-32768.0)).min(m128::splat(32767.0));
+        x.round_i32()
+      };
+      cast(half(0).saturating_pack_i32_i16(half(4)))
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      let mut out = Self::default();
+      for (q, &f) in out.0.iter_mut().zip(f.iter()) {
+        *q = Q15::from_f32(f);
+      }
+      out
+    }
+  }
+
+  /// Converts to eight `f32` values, as [`Q15::to_f32`].
+  #[inline]
+  pub fn to_f32s(self) -> [f32; 8] {
+    #[cfg(target_feature = "sse2")]
+    {
+      let v: m128i = cast(self);
+      let scale = m128::splat(1.0 / 32768.0);
+      let low = v.unpack_low_i16(v).shr_arith_i32(16).round_i32() * scale;
+      let high = v.unpack_high_i16(v).shr_arith_i32(16).round_i32() * scale;
+      let [a, b, c, d] = low.to_array();
+      let [e, f, g, h] = high.to_array();
+      [a, b, c, d, e, f, g, h]
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      let mut out = [0.0; 8];
+      for (f, q) in out.iter_mut().zip(self.0.iter()) {
+        *f = q.to_f32();
+      }
+      out
+    }
+  }
+
+  /// Multiplies lane by lane with the given rounding, saturating.
+  ///
+  /// Only [`Rounding::Nearest`] (the same as `*`) is done with SIMD, the others
+  /// go one lane at a time.
+  #[inline]
+  pub fn mul_round(self, other: Self, rounding: Rounding) -> Self {
+    if rounding == Rounding::Nearest {
+      self * other
+    } else {
+      self.zip_map(other, |a, b| a.mul_round(b, rounding))
+    }
+  }
+
+  /// Divides lane by lane with the given rounding, saturating.
+  #[inline]
+  pub fn div_round(self, other: Self, rounding: Rounding) -> Self {
+    self.zip_map(other, |a, b| a.div_round(b, rounding))
+  }
+
+  #[inline]
+  fn zip_map(self, other: Self, f: impl Fn(Q15, Q15) -> Q15) -> Self {
+    let mut out = self;
+    for (a, &b) in out.0.iter_mut().zip(other.0.iter()) {
+      *a = f(*a, b);
+    }
+    out
+  }
+}
+
+impl Add for q15x8 {
+  type Output = Self;
+  #[inline]
+  fn add(self, other: Self) -> Self {
+    #[cfg(target_feature = "sse2")]
+    {
+      cast(cast::<_, m128i>(self).saturating_add_i16(cast(other)))
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      self.zip_map(other, Q15::saturating_add)
+    }
+  }
+}
+
+impl Sub for q15x8 {
+  type Output = Self;
+  #[inline]
+  fn sub(self, other: Self) -> Self {
+    #[cfg(target_feature = "sse2")]
+    {
+      cast(cast::<_, m128i>(self).saturating_sub_i16(cast(other)))
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      self.zip_map(other, Q15::saturating_sub)
+    }
+  }
+}
+
+impl Mul for q15x8 {
+  type Output = Self;
+  #[inline]
+  #[allow(clippy::suspicious_arithmetic_impl)]
+  fn mul(self, other: Self) -> Self {
+    #[cfg(all(target_feature = "sse2", target_feature = "ssse3"))]
+    {
+      let product = cast::<_, m128i>(self).mul_high_round_i16(cast(other));
+      // only `MIN * MIN` comes out as `MIN`, and it should be `MAX`
+      cast(product ^ product.cmp_eq_i16(m128i::splat_i16(i16::MIN)))
+    }
+    #[cfg(all(target_feature = "sse2", not(target_feature = "ssse3")))]
+    {
+      let (a, b): (m128i, m128i) = (cast(self), cast(other));
+      let (low, high) = (a.mul_low_i16(b), a.mul_high_i16(b));
+      let round = |product: m128i| product.add_i32(m128i::splat_i32(1 << 14)).shr_arith_i32(15);
+      let products_low = round(low.unpack_low_i16(high));
+      let products_high = round(low.unpack_high_i16(high));
+      cast(products_low.saturating_pack_i32_i16(products_high))
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      self.zip_map(other, Q15::mul)
+    }
+  }
+}
+
+impl Div for q15x8 {
+  type Output = Self;
+  #[inline]
+  fn div(self, other: Self) -> Self {
+    self.zip_map(other, Q15::div)
+  }
+}
+
+impl Neg for q15x8 {
+  type Output = Self;
+  #[inline]
+  fn neg(self) -> Self {
+    Self::default() - self
+  }
+}
+
+impl AddAssign for q15x8 {
+  #[inline]
+  fn add_assign(&mut self, other: Self) {
+    *self = *self + other;
+  }
+}
+impl SubAssign for q15x8 {
+  #[inline]
+  fn sub_assign(&mut self, other: Self) {
+    *self = *self - other;
+  }
+}
+impl MulAssign for q15x8 {
+  #[inline]
+  fn mul_assign(&mut self, other: Self) {
+    *self = *self * other;
+  }
+}
+impl DivAssign for q15x8 {
+  #[inline]
+  fn div_assign(&mut self, other: Self) {
+    *self = *self / other;
+  }
+}
@@ -0,0 +1,127 @@
+//! GHASH, the universal hash that GCM mode uses to authenticate data.
+//!
+//! GHASH multiplies each 16 byte block into an accumulator in GF(2^128), with
+//! the field defined by `x^128 + x^7 + x^2 + x + 1` and the bit order from the
+//! GCM spec (NIST SP 800-38D). With PCLMULQDQ each multiply is four
+//! [`clmul!`](crate::clmul) calls and a reduction.
+//!
+//! For AES-GCM the hash key is a block of zeros encrypted with the AES key.
+//! Feed in the additional data, then the ciphertext, then one block with the
+//! bit lengths of each as big-endian `u64` values:
+//!
+//! ```rust
+//! use lokacore::ghash::Ghash;
+//!
+//! # let hash_key = [7; 16];
+//! # let (aad, ciphertext) = (b"header".as_ref(), b"some secret".as_ref());
+//! let mut ghash = Ghash::new(&hash_key);
+//! ghash.update(aad);
+//! ghash.update(ciphertext);
+//! let mut lengths = [0; 16];
+//! lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+//! lengths[8..].copy_from_slice(&(ciphertext.len() as u64 * 8).to_be_bytes());
+//! ghash.update_block(&lengths);
+//! let tag_before_encryption: [u8; 16] = ghash.finish();
+//! ```
+
+#[cfg(target_arch = "x86")]
+use crate::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use crate::arch::x86_64::*;
+use crate::{byte_shift_left, byte_shift_right, clmul};
+use core::convert::TryInto;
+
+/// Loads a block with the bytes reversed, so that the first byte of the block
+/// is the highest byte of the register.
+#[inline(always)]
+fn load_reversed(block: &[u8; 16]) -> m128i {
+  let mut bytes = *block;
+  bytes.reverse();
+  m128i::load_unaligned(&bytes)
+}
+
+/// Multiplies two byte reversed field elements.
+///
+/// This is the method from Intel's "Carry-Less Multiplication and Its Usage
+/// for Computing the GCM Mode" white paper: a 256-bit product, shifted left
+/// one bit to account for the reflected bit order, then reduced.
+#[inline(always)]
+fn gf_mul(a: m128i, b: m128i) -> m128i {
+  let mut low = clmul!(a, b, 0x00);
+  let mut high = clmul!(a, b, 0x11);
+  let middle = clmul!(a, b, 0x10) ^ clmul!(a, b, 0x01);
+  low ^= byte_shift_left!(middle, 8);
+  high ^= byte_shift_right!(middle, 8);
+
+  // shift the 256-bit `high:low` left by one
+  let low_carry = low.shr_logical_u32(31);
+  let high_carry = high.shr_logical_u32(31);
+  low = low.shl_i32(1) | byte_shift_left!(low_carry, 4);
+  high = high.shl_i32(1) | byte_shift_left!(high_carry, 4) | byte_shift_right!(low_carry, 12);
+
+  // reduce modulo x^128 + x^7 + x^2 + x + 1
+  let a = low.shl_i32(31) ^ low.shl_i32(30) ^ low.shl_i32(25);
+  low ^= byte_shift_left!(a, 12);
+  let b = low.shr_logical_u32(1)
+    ^ low.shr_logical_u32(2)
+    ^ low.shr_logical_u32(7)
+    ^ byte_shift_right!(a, 4);
+  high ^ low ^ b
+}
+
+/// A GHASH computation in progress.
+#[derive(Clone)]
+pub struct Ghash {
+  key: m128i,
+  acc: m128i,
+}
+
+impl Ghash {
+  /// A new hash with the given hash key (`H` in the GCM spec).
+  #[inline]
+  pub fn new(key: &[u8; 16]) -> Self {
+    Self {
+      key: load_reversed(key),
+      acc: m128i::zeroed(),
+    }
+  }
+
+  /// Hashes one block.
+  #[inline]
+  pub fn update_block(&mut self, block: &[u8; 16]) {
+    self.acc = gf_mul(self.acc ^ load_reversed(block), self.key);
+  }
+
+  /// Hashes the data, with zeros padding it out to a whole number of blocks.
+  ///
+  /// GCM pads the additional data and the ciphertext separately, so one call
+  /// for each does the right thing.
+  pub fn update(&mut self, data: &[u8]) {
+    let mut blocks = data.chunks_exact(16);
+    for block in &mut blocks {
+      self.update_block(block.try_into().unwrap());
+    }
+    let rest = blocks.remainder();
+    if !rest.is_empty() {
+      let mut last = [0; 16];
+      last[..rest.len()].copy_from_slice(rest);
+      self.update_block(&last);
+    }
+  }
+
+  /// The hash of everything so far.
+  #[inline]
+  pub fn finish(&self) -> [u8; 16] {
+    let mut out = [0; 16];
+    self.acc.storeu(&mut out);
+    out.reverse();
+    out
+  }
+}
+
+impl core::fmt::Debug for Ghash {
+  /// Doesn't print the hash key.
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.write_str("Ghash { .. }")
+  }
+}
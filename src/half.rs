@@ -0,0 +1,431 @@
+//! 16-bit floats: [`f16`](struct@f16) (IEEE binary16) and [`bf16`] (bfloat16).
+//!
+//! These are for storing and moving floats around in half the space, such as
+//! in GPU vertex and texture data. To do math, convert to `f32` and back.
+//!
+//! * `f16` has 5 exponent bits and 10 mantissa bits. It goes up to 65504 and
+//!   keeps about 3 decimal digits. This is the "half" of GPUs.
+//! * `bf16` is just the high half of an `f32`, with 8 exponent bits and 7
+//!   mantissa bits. It has the full `f32` range but only about 2 decimal
+//!   digits.
+//!
+//! Converting from `f32` rounds to nearest with ties to even, and anything too
+//! big becomes infinity. `f16` subnormals are kept (not flushed to zero). NaN
+//! stays NaN, keeping as much of its payload as fits, and always comes out
+//! quiet. Converting to `f32` is exact, other than NaN being made quiet.
+//!
+//! ```rust
+//! use lokacore::half::*;
+//!
+//! let x = f16::from_f32(1.0 / 3.0);
+//! assert_eq!(x.to_bits(), 0x3555);
+//! assert_eq!(x.to_f32(), 0.333_251_95);
+//! assert!(f16::from_f32(70000.0).is_infinite());
+//!
+//! let mut halves = [f16::ZERO; 4];
+//! assert_eq!(f32_to_f16(&[0.5, -2.0, 1e-7, f32::NAN], &mut halves), 4);
+//! assert_eq!(halves[3].to_bits(), 0x7E00);
+//! assert_eq!(bf16::from_f32(3.0).to_bits(), 0x4040);
+//! ```
+//!
+//! The slice functions ([`f16_to_f32`], [`f32_to_f16`], [`bf16_to_f32`], and
+//! [`f32_to_bf16`]) work on as many values as fit in the shorter slice and
+//! return that count. With SSE2 they do 8 values at a time with integer
+//! tricks, and with F16C the `f16` ones use the hardware conversions instead.
+//! Every path gives the same bits as the scalar conversions.
+//!
+//! The SSE2 paths rely on the default `MXCSR` settings: with flush-to-zero
+//! or denormals-are-zero turned on, `f16` subnormals can come out as zero.
+
+#[cfg(target_feature = "sse2")]
+use core::convert::TryInto;
+
+#[cfg(target_feature = "sse2")]
+use crate::cast;
+use crate::{Pod, Zeroable};
+use core::{cmp::Ordering, fmt};
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+fn f32_to_f16_bits(f: f32) -> u16 {
+  let x = f.to_bits();
+  let sign = ((x >> 16) & 0x8000) as u16;
+  let exp = ((x >> 23) & 0xFF) as i32;
+  let man = x & 0x7F_FFFF;
+  if exp == 0xFF {
+    let nan = if man != 0 {
+      0x200 | (man >> 13) as u16
+    } else {
+      0
+    };
+    return sign | 0x7C00 | nan;
+  }
+  let half_exp = exp - 127 + 15;
+  if half_exp >= 0x1F {
+    return sign | 0x7C00;
+  }
+  // the kept bits, the dropped bits, and what the dropped bits are at halfway
+  let (kept, dropped, halfway) = if half_exp > 0 {
+    (
+      ((half_exp as u32) << 10) | (man >> 13),
+      man & 0x1FFF,
+      0x1000,
+    )
+  } else if exp == 0 || half_exp < -10 {
+    // less than half of the smallest subnormal
+    return sign;
+  } else {
+    let m = man | 0x80_0000;
+    let shift = (14 - half_exp) as u32;
+    (m >> shift, m & ((1 << shift) - 1), 1 << (shift - 1))
+  };
+  // rounding up can carry into the exponent, which is what we want: the
+  // largest subnormal goes to the smallest normal, and the largest finite
+  // value goes to infinity.
+  let round_up = dropped > halfway || (dropped == halfway && kept & 1 == 1);
+  sign | (kept + round_up as u32) as u16
+}
+
+fn f16_bits_to_f32(h: u16) -> f32 {
+  let sign = u32::from(h & 0x8000) << 16;
+  let exp = u32::from(h >> 10) & 0x1F;
+  let man = u32::from(h & 0x3FF);
+  let bits = match exp {
+    0x1F if man != 0 => 0x7FC0_0000 | (man << 13),
+    0x1F => 0x7F80_0000,
+    0 => (man as f32 * (1.0 / 16_777_216.0)).to_bits(),
+    _ => ((exp + 112) << 23) | (man << 13),
+  };
+  f32::from_bits(sign | bits)
+}
+
+fn f32_to_bf16_bits(f: f32) -> u16 {
+  let x = f.to_bits();
+  if f.is_nan() {
+    return ((x >> 16) | 0x40) as u16;
+  }
+  let round = 0x7FFF + ((x >> 16) & 1);
+  ((x + round) >> 16) as u16
+}
+
+fn bf16_bits_to_f32(b: u16) -> f32 {
+  let x = u32::from(b) << 16;
+  if b & 0x7F80 == 0x7F80 && b & 0x7F != 0 {
+    f32::from_bits(x | 0x40_0000)
+  } else {
+    f32::from_bits(x)
+  }
+}
+
+macro_rules! half_common {
+  ($name:ident, $to_f32:ident, $from_f32:ident, $exp_mask:expr) => {
+    unsafe impl Zeroable for $name {}
+    unsafe impl Pod for $name {}
+
+    impl $name {
+      /// Makes a value from its raw bits.
+      #[inline]
+      pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+      }
+
+      /// The raw bits of the value.
+      #[inline]
+      pub const fn to_bits(self) -> u16 {
+        self.0
+      }
+
+      /// Converts from `f32`, rounding to nearest with ties to even.
+      #[inline]
+      pub fn from_f32(f: f32) -> Self {
+        Self($from_f32(f))
+      }
+
+      /// Converts to `f32`, which is exact (NaN is made quiet).
+      #[inline]
+      pub fn to_f32(self) -> f32 {
+        $to_f32(self.0)
+      }
+
+      /// If the value is NaN.
+      #[inline]
+      pub const fn is_nan(self) -> bool {
+        (self.0 & 0x7FFF) > $exp_mask
+      }
+
+      /// If the value is positive or negative infinity.
+      #[inline]
+      pub const fn is_infinite(self) -> bool {
+        (self.0 & 0x7FFF) == $exp_mask
+      }
+
+      /// If the value is neither infinite nor NaN.
+      #[inline]
+      pub const fn is_finite(self) -> bool {
+        (self.0 & $exp_mask) != $exp_mask
+      }
+
+      /// If the sign bit is set (including on `-0.0` and NaN).
+      #[inline]
+      pub const fn is_sign_negative(self) -> bool {
+        self.0 & 0x8000 != 0
+      }
+    }
+
+    impl From<$name> for f32 {
+      #[inline]
+      fn from(h: $name) -> f32 {
+        h.to_f32()
+      }
+    }
+
+    /// Compares as floats: NaN isn't equal to anything, and `-0.0 == 0.0`.
+    impl PartialEq for $name {
+      #[inline]
+      fn eq(&self, other: &Self) -> bool {
+        self.to_f32() == other.to_f32()
+      }
+    }
+
+    /// Compares as floats.
+    impl PartialOrd for $name {
+      #[inline]
+      fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.to_f32().partial_cmp(&other.to_f32())
+      }
+    }
+
+    impl fmt::Debug for $name {
+      fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_f32(), f)
+      }
+    }
+
+    impl fmt::Display for $name {
+      fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_f32(), f)
+      }
+    }
+  };
+}
+
+/// An IEEE 754 binary16 float.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Default)]
+#[repr(transparent)]
+pub struct f16(pub u16);
+half_common!(f16, f16_bits_to_f32, f32_to_f16_bits, 0x7C00);
+
+impl f16 {
+  /// `0.0`
+  pub const ZERO: Self = Self(0x0000);
+  /// `-0.0`
+  pub const NEG_ZERO: Self = Self(0x8000);
+  /// `1.0`
+  pub const ONE: Self = Self(0x3C00);
+  /// The largest finite value, `65504.0`.
+  pub const MAX: Self = Self(0x7BFF);
+  /// The lowest finite value, `-65504.0`.
+  pub const MIN: Self = Self(0xFBFF);
+  /// The smallest positive normal value, `2^-14`.
+  pub const MIN_POSITIVE: Self = Self(0x0400);
+  /// The gap between `1.0` and the next value up, `2^-10`.
+  pub const EPSILON: Self = Self(0x1400);
+  /// Positive infinity.
+  pub const INFINITY: Self = Self(0x7C00);
+  /// Negative infinity.
+  pub const NEG_INFINITY: Self = Self(0xFC00);
+  /// A quiet NaN.
+  pub const NAN: Self = Self(0x7E00);
+}
+
+/// A bfloat16 float: the high 16 bits of an `f32`.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Default)]
+#[repr(transparent)]
+pub struct bf16(pub u16);
+half_common!(bf16, bf16_bits_to_f32, f32_to_bf16_bits, 0x7F80);
+
+impl bf16 {
+  /// `0.0`
+  pub const ZERO: Self = Self(0x0000);
+  /// `-0.0`
+  pub const NEG_ZERO: Self = Self(0x8000);
+  /// `1.0`
+  pub const ONE: Self = Self(0x3F80);
+  /// The largest finite value, about `3.39e38`.
+  pub const MAX: Self = Self(0x7F7F);
+  /// The lowest finite value, about `-3.39e38`.
+  pub const MIN: Self = Self(0xFF7F);
+  /// The smallest positive normal value, same as [`f32::MIN_POSITIVE`].
+  pub const MIN_POSITIVE: Self = Self(0x0080);
+  /// The gap between `1.0` and the next value up, `2^-7`.
+  pub const EPSILON: Self = Self(0x3C00);
+  /// Positive infinity.
+  pub const INFINITY: Self = Self(0x7F80);
+  /// Negative infinity.
+  pub const NEG_INFINITY: Self = Self(0xFF80);
+  /// A quiet NaN.
+  pub const NAN: Self = Self(0x7FC0);
+}
+
+/// lanewise `f32` to `f16` bits, sign extended into `i32` lanes so that a
+/// saturating pack keeps them as they are.
+#[cfg(all(target_feature = "sse2", not(target_feature = "f16c")))]
+#[inline(always)]
+fn f16_from_m128(f: m128) -> m128i {
+  let x = f.transmute_m128i();
+  let sign = x & m128i::splat_i32(i32::MIN);
+  let abs = x ^ sign;
+  let is_nan = f.cmp_nan(f).transmute_m128i();
+  // anything at least 65536 (as an integer, this includes NaN) is special
+  let is_regular = m128i::splat_i32((127 + 16) << 23).cmp_gt_i32(abs);
+  let is_subnormal = m128i::splat_i32((127 - 14) << 23).cmp_gt_i32(abs);
+  let payload = abs.shr_logical_u32(13) | m128i::splat_i32(0x200);
+  let special = (is_nan & payload & m128i::splat_i32(0x3FF)) | m128i::splat_i32(0x7C00);
+  // adding this lines the mantissa up with the subnormal steps, and the add
+  // itself does the rounding
+  let magic = m128i::splat_i32(((127 - 15) + (23 - 10) + 1) << 23);
+  let subnormal = (abs.transmute_m128() + magic.transmute_m128())
+    .transmute_m128i()
+    .sub_i32(magic);
+  // rebias the exponent and round, adding one more when the lowest kept bit
+  // is odd so that ties go to even
+  let odd = abs.shl_i32(31 - 13).shr_arith_i32(31);
+  let normal = abs
+    .add_i32(m128i::splat_i32(0xFFF - ((127 - 15) << 23)))
+    .sub_i32(odd)
+    .shr_logical_u32(13);
+  let finite = (is_subnormal & subnormal) | is_subnormal.andnot(normal);
+  let joined = (is_regular & finite) | is_regular.andnot(special);
+  joined | sign.shr_arith_i32(16)
+}
+
+/// lanewise `f16` bits (zero extended in `i32` lanes) to `f32`.
+#[cfg(all(target_feature = "sse2", not(target_feature = "f16c")))]
+#[inline(always)]
+fn f16_to_m128(h: m128i) -> m128 {
+  let exp_man = h & m128i::splat_i32(0x7FFF);
+  let sign = (h ^ exp_man).shl_i32(16);
+  // scaling by 2^112 fixes the exponent bias, and makes subnormals normal
+  let scale = m128::splat(f32::from_bits((254 - 15) << 23));
+  let scaled = exp_man.shl_i32(13).transmute_m128() * scale;
+  let is_inf_nan = exp_man.cmp_gt_i32(m128i::splat_i32(0x7BFF));
+  let is_nan = exp_man.cmp_gt_i32(m128i::splat_i32(0x7C00));
+  let special =
+    (is_inf_nan & m128i::splat_i32(0x7F80_0000)) | (is_nan & m128i::splat_i32(0x40_0000));
+  (scaled.transmute_m128i() | special | sign).transmute_m128()
+}
+
+/// Converts `f16` values into `f32` values.
+pub fn f16_to_f32(src: &[f16], dst: &mut [f32]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    while i + 8 <= n {
+      let v = m128i::load_from_slice(&src[i..]).unwrap();
+      #[cfg(target_feature = "f16c")]
+      let (low, high) = (v.f16_to_f32(), v.unpack_high_i64(v).f16_to_f32());
+      #[cfg(not(target_feature = "f16c"))]
+      let (low, high) = {
+        let zero = m128i::zeroed();
+        (
+          f16_to_m128(v.unpack_low_i16(zero)),
+          f16_to_m128(v.unpack_high_i16(zero)),
+        )
+      };
+      low.store_unaligned((&mut dst[i..i + 4]).try_into().unwrap());
+      high.store_unaligned((&mut dst[i + 4..i + 8]).try_into().unwrap());
+      i += 8;
+    }
+  }
+  for i in i..n {
+    dst[i] = src[i].to_f32();
+  }
+  n
+}
+
+/// Converts `f32` values into `f16` values.
+pub fn f32_to_f16(src: &[f32], dst: &mut [f16]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    while i + 8 <= n {
+      let low = m128::load_from_slice(&src[i..]).unwrap();
+      let high = m128::load_from_slice(&src[i + 4..]).unwrap();
+      #[cfg(target_feature = "f16c")]
+      let v = low.round_f16().unpack_low_i64(high.round_f16());
+      #[cfg(not(target_feature = "f16c"))]
+      let v = f16_from_m128(low).saturating_pack_i32_i16(f16_from_m128(high));
+      dst[i..i + 8].copy_from_slice(&cast::<m128i, [f16; 8]>(v));
+      i += 8;
+    }
+  }
+  for i in i..n {
+    dst[i] = f16::from_f32(src[i]);
+  }
+  n
+}
+
+/// Converts `bf16` values into `f32` values.
+pub fn bf16_to_f32(src: &[bf16], dst: &mut [f32]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let zero = m128i::zeroed();
+    let quiet = |x: m128i| {
+      let f = x.transmute_m128();
+      (x | (f.cmp_nan(f).transmute_m128i() & m128i::splat_i32(0x40_0000))).transmute_m128()
+    };
+    while i + 8 <= n {
+      let v = m128i::load_from_slice(&src[i..]).unwrap();
+      let low = quiet(zero.unpack_low_i16(v));
+      let high = quiet(zero.unpack_high_i16(v));
+      low.store_unaligned((&mut dst[i..i + 4]).try_into().unwrap());
+      high.store_unaligned((&mut dst[i + 4..i + 8]).try_into().unwrap());
+      i += 8;
+    }
+  }
+  for i in i..n {
+    dst[i] = src[i].to_f32();
+  }
+  n
+}
+
+/// Converts `f32` values into `bf16` values.
+pub fn f32_to_bf16(src: &[f32], dst: &mut [bf16]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    while i + 8 <= n {
+      // the high halves, sign extended so that a saturating pack keeps them
+      let quad = |k: usize| {
+        let f = m128::load_from_slice(&src[i + k * 4..]).unwrap();
+        let x = f.transmute_m128i();
+        let odd = x.shr_logical_u32(16) & m128i::splat_i32(1);
+        let rounded = x.add_i32(m128i::splat_i32(0x7FFF)).add_i32(odd);
+        let is_nan = f.cmp_nan(f).transmute_m128i();
+        let nan = x | m128i::splat_i32(0x40_0000);
+        ((is_nan & nan) | is_nan.andnot(rounded)).shr_arith_i32(16)
+      };
+      let v = quad(0).saturating_pack_i32_i16(quad(1));
+      dst[i..i + 8].copy_from_slice(&cast::<m128i, [bf16; 8]>(v));
+      i += 8;
+    }
+  }
+  for i in i..n {
+    dst[i] = bf16::from_f32(src[i]);
+  }
+  n
+}
@@ -11,6 +11,27 @@ use core::{
 };
 
 pub mod arch;
+pub mod bits;
+pub mod branchless;
+pub mod crc;
+pub mod ct;
+pub mod fixed;
+pub mod half;
+pub mod sha;
+pub mod simd;
+
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse"))]
+pub mod math;
+
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes"))]
+pub mod aes;
+
+#[cfg(all(
+  any(target_arch = "x86", target_arch = "x86_64"),
+  target_feature = "sse2",
+  target_feature = "pclmulqdq"
+))]
+pub mod ghash;
 
 mod marker;
 pub use marker::*;
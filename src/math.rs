@@ -0,0 +1,1053 @@
+//! Vector, matrix, and quaternion types for 3D graphics, built on [`m128`].
+//!
+//! * [`Vec4`] and [`Vec3A`] keep `x`, `y`, `z` (and `w`) in lanes 0 through 3.
+//!   A `Vec3A` is the same size as a `Vec4`, the `w` lane is just ignored.
+//! * [`Mat4`] is column major: four `m128` columns, the same layout that you'd
+//!   upload to a GPU.
+//! * [`Quat`] is `x`, `y`, `z`, `w`, with `w` as the real part.
+//!
+//! All of them are [`Pod`] and have an alignment of 16.
+//!
+//! The projection and view constructors with an `_rh` or `_lh` suffix are for
+//! a right or left handed view space and a depth range of `0.0..=1.0` (Vulkan,
+//! Direct3D, Metal). The ones ending in `_rh_gl` use the `-1.0..=1.0` depth
+//! range of OpenGL.
+
+#[cfg(target_arch = "x86")]
+use crate::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use crate::arch::x86_64::*;
+use crate::{cast, shuffle128, Pod, Zeroable};
+use core::{
+  f32::consts::{FRAC_2_PI, FRAC_PI_2, PI},
+  ops::*,
+};
+
+/// As [`shuffle128!`], but the lanes are listed in `x y z w` order (low lane
+/// to high lane), which is a lot easier to follow for this sort of code.
+macro_rules! shuf {
+  ($a:ident, $b:ident, $x:literal, $y:literal, $z:literal, $w:literal) => {
+    shuffle128!($a, $b, $w, $z, $y, $x)
+  };
+}
+
+/// Every lane gets the sum of all four lanes.
+#[inline(always)]
+fn sum_lanes(v: m128) -> m128 {
+  let t = v + shuf!(v, v, 1, 0, 3, 2);
+  t + shuf!(t, t, 2, 3, 0, 1)
+}
+
+/// The `x`, `y`, and `z` lanes each get the sum of those three lanes.
+#[inline(always)]
+fn sum_lanes3(v: m128) -> m128 {
+  v + shuf!(v, v, 1, 2, 0, 3) + shuf!(v, v, 2, 0, 1, 3)
+}
+
+/// Sine and cosine of the angle.
+///
+/// Within a few ULP of the true value for angles of reasonable size (a few
+/// thousand radians or less).
+fn sin_cos(x: f32) -> (f32, f32) {
+  // `core` has no trig functions, so this is the Cephes approach. Find the
+  // closest multiple of pi/2, subtract it off in three parts so that we don't
+  // lose precision, and then use a polynomial for each.
+  let quadrant = (x * FRAC_2_PI + if x < 0.0 { -0.5 } else { 0.5 }) as i32;
+  let q = quadrant as f32;
+  let r = ((x - q * 1.5703125) - q * 4.837_513e-4) - q * 7.549_79e-8;
+  let z = r * r;
+  let sin = r + r * z * (-1.666_665_5e-1 + z * (8.332_161e-3 + z * -1.951_529_6e-4));
+  let cos = 1.0 - 0.5 * z + z * z * (4.166_664_6e-2 + z * (-1.388_731_6e-3 + z * 2.443_315_7e-5));
+  match quadrant & 3 {
+    0 => (sin, cos),
+    1 => (cos, -sin),
+    2 => (-sin, -cos),
+    _ => (-cos, sin),
+  }
+}
+
+/// Arc cosine, for inputs in `-1.0..=1.0`.
+fn acos(x: f32) -> f32 {
+  // this polynomial is only good for `-0.5..=0.5`, so bigger inputs use the
+  // half angle identity to get into that range.
+  let asin_small = |y: f32| {
+    let z = y * y;
+    y + y
+      * z
+      * ((((4.216_32e-2 * z + 2.418_131e-2) * z + 4.547_002_6e-2) * z + 7.495_300_3e-2) * z
+        + 1.666_675_2e-1)
+  };
+  let sqrt = |f: f32| m128::set_low(f).sqrt().extract_low();
+  if x > 0.5 {
+    2.0 * asin_small(sqrt(0.5 - 0.5 * x))
+  } else if x < -0.5 {
+    PI - 2.0 * asin_small(sqrt(0.5 + 0.5 * x))
+  } else {
+    FRAC_PI_2 - asin_small(x)
+  }
+}
+
+macro_rules! impl_vector_ops {
+  ($t:ident) => {
+    impl Add for $t {
+      type Output = Self;
+      #[inline(always)]
+      fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+      }
+    }
+    impl Sub for $t {
+      type Output = Self;
+      #[inline(always)]
+      fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+      }
+    }
+    impl Mul for $t {
+      type Output = Self;
+      #[inline(always)]
+      fn mul(self, rhs: Self) -> Self {
+        Self(self.0 * rhs.0)
+      }
+    }
+    impl Div for $t {
+      type Output = Self;
+      #[inline(always)]
+      fn div(self, rhs: Self) -> Self {
+        Self(self.0 / rhs.0)
+      }
+    }
+    impl Mul<f32> for $t {
+      type Output = Self;
+      #[inline(always)]
+      fn mul(self, rhs: f32) -> Self {
+        Self(self.0 * m128::splat(rhs))
+      }
+    }
+    impl Div<f32> for $t {
+      type Output = Self;
+      #[inline(always)]
+      fn div(self, rhs: f32) -> Self {
+        Self(self.0 / m128::splat(rhs))
+      }
+    }
+    impl Neg for $t {
+      type Output = Self;
+      #[inline(always)]
+      fn neg(self) -> Self {
+        Self(-self.0)
+      }
+    }
+    impl AddAssign for $t {
+      #[inline(always)]
+      fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+      }
+    }
+    impl SubAssign for $t {
+      #[inline(always)]
+      fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+      }
+    }
+    impl MulAssign<f32> for $t {
+      #[inline(always)]
+      fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs
+      }
+    }
+  };
+}
+
+/// A vector of four `f32` values.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Vec4(pub m128);
+
+unsafe impl Zeroable for Vec4 {}
+unsafe impl Pod for Vec4 {}
+
+impl_vector_ops!(Vec4);
+
+impl core::fmt::Debug for Vec4 {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let [x, y, z, w] = self.to_array();
+    write!(f, "Vec4({:?}, {:?}, {:?}, {:?})", x, y, z, w)
+  }
+}
+
+impl PartialEq for Vec4 {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.cmp_eq(other.0).move_mask() == 0b1111
+  }
+}
+
+impl Default for Vec4 {
+  fn default() -> Self {
+    Self::zeroed()
+  }
+}
+
+impl Vec4 {
+  /// Makes a vector from its four parts.
+  #[inline(always)]
+  pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+    Self(m128::set_reverse(x, y, z, w))
+  }
+
+  /// Every part is the same value.
+  #[inline(always)]
+  pub fn splat(f: f32) -> Self {
+    Self(m128::splat(f))
+  }
+
+  /// All zeroes.
+  #[inline(always)]
+  pub fn zeroed() -> Self {
+    Self(m128::zeroed())
+  }
+
+  /// Makes a vector from `[x, y, z, w]`.
+  #[inline(always)]
+  pub fn from_array(a: [f32; 4]) -> Self {
+    Self(m128::load_unaligned(&a))
+  }
+
+  /// Gives `[x, y, z, w]`.
+  #[inline(always)]
+  pub fn to_array(self) -> [f32; 4] {
+    self.0.to_array()
+  }
+
+  /// The `x` part.
+  #[inline(always)]
+  pub fn x(self) -> f32 {
+    self.0.extract_low()
+  }
+
+  /// The `y` part.
+  #[inline(always)]
+  pub fn y(self) -> f32 {
+    self.to_array()[1]
+  }
+
+  /// The `z` part.
+  #[inline(always)]
+  pub fn z(self) -> f32 {
+    self.to_array()[2]
+  }
+
+  /// The `w` part.
+  #[inline(always)]
+  pub fn w(self) -> f32 {
+    self.to_array()[3]
+  }
+
+  /// Drops the `w` part.
+  #[inline(always)]
+  pub fn truncate(self) -> Vec3A {
+    Vec3A(self.0)
+  }
+
+  /// Dot product.
+  #[inline(always)]
+  pub fn dot(self, other: Self) -> f32 {
+    sum_lanes(self.0 * other.0).extract_low()
+  }
+
+  /// The squared length, which skips a square root compared to
+  /// [`length`](Vec4::length).
+  #[inline(always)]
+  pub fn length_squared(self) -> f32 {
+    self.dot(self)
+  }
+
+  /// The length.
+  #[inline(always)]
+  pub fn length(self) -> f32 {
+    sum_lanes(self.0 * self.0).sqrt().extract_low()
+  }
+
+  /// This vector scaled to have a length of 1.
+  ///
+  /// A zero length vector gives NaN in every part.
+  #[inline(always)]
+  pub fn normalize(self) -> Self {
+    Self(self.0 / sum_lanes(self.0 * self.0).sqrt())
+  }
+
+  /// Linear interpolation, giving `self` when `t` is 0 and `other` when `t`
+  /// is 1.
+  #[inline(always)]
+  pub fn lerp(self, other: Self, t: f32) -> Self {
+    self + (other - self) * t
+  }
+
+  /// Partwise minimum.
+  #[inline(always)]
+  pub fn min(self, other: Self) -> Self {
+    Self(self.0.min(other.0))
+  }
+
+  /// Partwise maximum.
+  #[inline(always)]
+  pub fn max(self, other: Self) -> Self {
+    Self(self.0.max(other.0))
+  }
+}
+
+/// A vector of three `f32` values, padded out to 16 bytes.
+///
+/// The fourth lane is ignored by everything except the partwise operators,
+/// where it's just along for the ride.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Vec3A(pub m128);
+
+unsafe impl Zeroable for Vec3A {}
+unsafe impl Pod for Vec3A {}
+
+impl_vector_ops!(Vec3A);
+
+impl core::fmt::Debug for Vec3A {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let [x, y, z] = self.to_array();
+    write!(f, "Vec3A({:?}, {:?}, {:?})", x, y, z)
+  }
+}
+
+impl PartialEq for Vec3A {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.cmp_eq(other.0).move_mask() & 0b111 == 0b111
+  }
+}
+
+impl Default for Vec3A {
+  fn default() -> Self {
+    Self::zeroed()
+  }
+}
+
+impl Vec3A {
+  /// Makes a vector from its three parts.
+  #[inline(always)]
+  pub fn new(x: f32, y: f32, z: f32) -> Self {
+    Self(m128::set_reverse(x, y, z, 0.0))
+  }
+
+  /// Every part is the same value.
+  #[inline(always)]
+  pub fn splat(f: f32) -> Self {
+    Self(m128::splat(f))
+  }
+
+  /// All zeroes.
+  #[inline(always)]
+  pub fn zeroed() -> Self {
+    Self(m128::zeroed())
+  }
+
+  /// Makes a vector from `[x, y, z]`.
+  #[inline(always)]
+  pub fn from_array([x, y, z]: [f32; 3]) -> Self {
+    Self::new(x, y, z)
+  }
+
+  /// Gives `[x, y, z]`.
+  #[inline(always)]
+  pub fn to_array(self) -> [f32; 3] {
+    let [x, y, z, _] = self.0.to_array();
+    [x, y, z]
+  }
+
+  /// The `x` part.
+  #[inline(always)]
+  pub fn x(self) -> f32 {
+    self.0.extract_low()
+  }
+
+  /// The `y` part.
+  #[inline(always)]
+  pub fn y(self) -> f32 {
+    self.0.to_array()[1]
+  }
+
+  /// The `z` part.
+  #[inline(always)]
+  pub fn z(self) -> f32 {
+    self.0.to_array()[2]
+  }
+
+  /// Adds a `w` part.
+  #[inline(always)]
+  pub fn extend(self, w: f32) -> Vec4 {
+    let [x, y, z] = self.to_array();
+    Vec4::new(x, y, z, w)
+  }
+
+  /// Dot product.
+  #[inline(always)]
+  pub fn dot(self, other: Self) -> f32 {
+    sum_lanes3(self.0 * other.0).extract_low()
+  }
+
+  /// Cross product.
+  #[inline(always)]
+  pub fn cross(self, other: Self) -> Self {
+    // `a * b.yzx - a.yzx * b` is the cross product, but in `zxy` order, so one
+    // more shuffle puts it right.
+    let (a, b) = (self.0, other.0);
+    let a_yzx = shuf!(a, a, 1, 2, 0, 3);
+    let b_yzx = shuf!(b, b, 1, 2, 0, 3);
+    let c = a * b_yzx - a_yzx * b;
+    Self(shuf!(c, c, 1, 2, 0, 3))
+  }
+
+  /// The squared length, which skips a square root compared to
+  /// [`length`](Vec3A::length).
+  #[inline(always)]
+  pub fn length_squared(self) -> f32 {
+    self.dot(self)
+  }
+
+  /// The length.
+  #[inline(always)]
+  pub fn length(self) -> f32 {
+    sum_lanes3(self.0 * self.0).sqrt().extract_low()
+  }
+
+  /// This vector scaled to have a length of 1.
+  ///
+  /// A zero length vector gives NaN in every part.
+  #[inline(always)]
+  pub fn normalize(self) -> Self {
+    Self(self.0 / sum_lanes3(self.0 * self.0).sqrt())
+  }
+
+  /// Linear interpolation, giving `self` when `t` is 0 and `other` when `t`
+  /// is 1.
+  #[inline(always)]
+  pub fn lerp(self, other: Self, t: f32) -> Self {
+    self + (other - self) * t
+  }
+
+  /// Partwise minimum.
+  #[inline(always)]
+  pub fn min(self, other: Self) -> Self {
+    Self(self.0.min(other.0))
+  }
+
+  /// Partwise maximum.
+  #[inline(always)]
+  pub fn max(self, other: Self) -> Self {
+    Self(self.0.max(other.0))
+  }
+}
+
+/// A quaternion, for rotations.
+///
+/// The rotation functions expect a unit quaternion (length 1).
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Quat(pub m128);
+
+unsafe impl Zeroable for Quat {}
+unsafe impl Pod for Quat {}
+
+impl core::fmt::Debug for Quat {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let [x, y, z, w] = self.to_array();
+    write!(f, "Quat({:?}, {:?}, {:?}, {:?})", x, y, z, w)
+  }
+}
+
+impl PartialEq for Quat {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.cmp_eq(other.0).move_mask() == 0b1111
+  }
+}
+
+impl Default for Quat {
+  fn default() -> Self {
+    Self::identity()
+  }
+}
+
+impl Mul for Quat {
+  type Output = Self;
+  /// Combines the rotations, `rhs` first and then `self`.
+  #[inline(always)]
+  fn mul(self, rhs: Self) -> Self {
+    let (a, b) = (self.0, rhs.0);
+    let a_x = shuf!(a, a, 0, 0, 0, 0);
+    let a_y = shuf!(a, a, 1, 1, 1, 1);
+    let a_z = shuf!(a, a, 2, 2, 2, 2);
+    let a_w = shuf!(a, a, 3, 3, 3, 3);
+    let b_wzyx = shuf!(b, b, 3, 2, 1, 0);
+    let b_zwxy = shuf!(b, b, 2, 3, 0, 1);
+    let b_yxwz = shuf!(b, b, 1, 0, 3, 2);
+    Self(
+      a_w * b
+        + a_x * b_wzyx * m128::set_reverse(1.0, -1.0, 1.0, -1.0)
+        + a_y * b_zwxy * m128::set_reverse(1.0, 1.0, -1.0, -1.0)
+        + a_z * b_yxwz * m128::set_reverse(-1.0, 1.0, 1.0, -1.0),
+    )
+  }
+}
+
+impl Mul<Vec3A> for Quat {
+  type Output = Vec3A;
+  /// Rotates the vector.
+  #[inline(always)]
+  fn mul(self, v: Vec3A) -> Vec3A {
+    let q = Vec3A(self.0);
+    let t = q.cross(v) * 2.0;
+    v + t * self.w() + q.cross(t)
+  }
+}
+
+impl Quat {
+  /// Makes a quaternion from its four parts.
+  #[inline(always)]
+  pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+    Self(m128::set_reverse(x, y, z, w))
+  }
+
+  /// No rotation at all.
+  #[inline(always)]
+  pub fn identity() -> Self {
+    Self::new(0.0, 0.0, 0.0, 1.0)
+  }
+
+  /// A rotation of `angle` radians around `axis`, which should be normalized.
+  #[inline]
+  pub fn from_axis_angle(axis: Vec3A, angle: f32) -> Self {
+    let (sin, cos) = sin_cos(angle * 0.5);
+    let [x, y, z] = (axis * sin).to_array();
+    Self::new(x, y, z, cos)
+  }
+
+  /// Makes a quaternion from `[x, y, z, w]`.
+  #[inline(always)]
+  pub fn from_array(a: [f32; 4]) -> Self {
+    Self(m128::load_unaligned(&a))
+  }
+
+  /// Gives `[x, y, z, w]`.
+  #[inline(always)]
+  pub fn to_array(self) -> [f32; 4] {
+    self.0.to_array()
+  }
+
+  /// The `x` part.
+  #[inline(always)]
+  pub fn x(self) -> f32 {
+    self.0.extract_low()
+  }
+
+  /// The `y` part.
+  #[inline(always)]
+  pub fn y(self) -> f32 {
+    self.to_array()[1]
+  }
+
+  /// The `z` part.
+  #[inline(always)]
+  pub fn z(self) -> f32 {
+    self.to_array()[2]
+  }
+
+  /// The `w` part.
+  #[inline(always)]
+  pub fn w(self) -> f32 {
+    self.to_array()[3]
+  }
+
+  /// Dot product, as if this were a [`Vec4`].
+  #[inline(always)]
+  pub fn dot(self, other: Self) -> f32 {
+    sum_lanes(self.0 * other.0).extract_low()
+  }
+
+  /// The length, as if this were a [`Vec4`].
+  #[inline(always)]
+  pub fn length(self) -> f32 {
+    sum_lanes(self.0 * self.0).sqrt().extract_low()
+  }
+
+  /// This quaternion scaled to have a length of 1.
+  #[inline(always)]
+  pub fn normalize(self) -> Self {
+    Self(self.0 / sum_lanes(self.0 * self.0).sqrt())
+  }
+
+  /// Flips the `x`, `y`, and `z` parts. For a unit quaternion that's the
+  /// opposite rotation.
+  #[inline(always)]
+  pub fn conjugate(self) -> Self {
+    Self(self.0 * m128::set_reverse(-1.0, -1.0, -1.0, 1.0))
+  }
+
+  /// The multiplicative inverse, which works even if this isn't a unit
+  /// quaternion.
+  #[inline(always)]
+  pub fn inverse(self) -> Self {
+    Self(self.conjugate().0 / sum_lanes(self.0 * self.0))
+  }
+
+  /// Spherical linear interpolation, giving `self` when `t` is 0 and `end`
+  /// when `t` is 1.
+  ///
+  /// This always goes the short way around. Both inputs should be unit
+  /// quaternions.
+  pub fn slerp(self, end: Self, t: f32) -> Self {
+    let mut cos = self.dot(end);
+    let mut end = end;
+    if cos < 0.0 {
+      cos = -cos;
+      end = Self(-end.0);
+    }
+    if cos > 0.9995 {
+      // when the two are this close then `sin(theta)` is nearly zero, and a
+      // plain lerp is more accurate.
+      return Self(self.0 + (end.0 - self.0) * m128::splat(t)).normalize();
+    }
+    let theta = acos(cos);
+    let (sin_theta, _) = sin_cos(theta);
+    let (sin_start, _) = sin_cos((1.0 - t) * theta);
+    let (sin_end, _) = sin_cos(t * theta);
+    Self((self.0 * m128::splat(sin_start) + end.0 * m128::splat(sin_end)) / m128::splat(sin_theta))
+  }
+}
+
+/// A 4x4 matrix of `f32`, stored as four columns.
+///
+/// Vectors are columns, so `a * b * v` applies `b` to `v` first, then `a`.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct Mat4 {
+  /// The columns, left to right.
+  pub cols: [m128; 4],
+}
+
+unsafe impl Zeroable for Mat4 {}
+unsafe impl Pod for Mat4 {}
+
+impl core::fmt::Debug for Mat4 {
+  /// Formats as the list of columns.
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("Mat4")
+      .field(
+        "cols",
+        &[self.col(0), self.col(1), self.col(2), self.col(3)],
+      )
+      .finish()
+  }
+}
+
+impl PartialEq for Mat4 {
+  fn eq(&self, other: &Self) -> bool {
+    (0..4).all(|i| self.col(i) == other.col(i))
+  }
+}
+
+impl Default for Mat4 {
+  fn default() -> Self {
+    Self::identity()
+  }
+}
+
+impl Mul for Mat4 {
+  type Output = Self;
+  #[inline(always)]
+  fn mul(self, rhs: Self) -> Self {
+    let [c0, c1, c2, c3] = rhs.cols;
+    Self {
+      cols: [
+        self.mul_m128(c0),
+        self.mul_m128(c1),
+        self.mul_m128(c2),
+        self.mul_m128(c3),
+      ],
+    }
+  }
+}
+
+impl Mul<Vec4> for Mat4 {
+  type Output = Vec4;
+  #[inline(always)]
+  fn mul(self, v: Vec4) -> Vec4 {
+    Vec4(self.mul_m128(v.0))
+  }
+}
+
+/// A 2x2 matrix as `(m00, m01, m10, m11)`, times another.
+#[inline(always)]
+fn mat2_mul(a: m128, b: m128) -> m128 {
+  a * shuf!(b, b, 0, 3, 0, 3) + shuf!(a, a, 1, 0, 3, 2) * shuf!(b, b, 2, 1, 2, 1)
+}
+
+/// The adjugate of `a` times `b`, as 2x2 matrices.
+#[inline(always)]
+fn mat2_adj_mul(a: m128, b: m128) -> m128 {
+  shuf!(a, a, 3, 3, 0, 0) * b - shuf!(a, a, 1, 1, 2, 2) * shuf!(b, b, 2, 3, 0, 1)
+}
+
+/// `a` times the adjugate of `b`, as 2x2 matrices.
+#[inline(always)]
+fn mat2_mul_adj(a: m128, b: m128) -> m128 {
+  a * shuf!(b, b, 3, 0, 3, 0) - shuf!(a, a, 1, 0, 3, 2) * shuf!(b, b, 2, 1, 2, 1)
+}
+
+/// The parts of a 4x4 inverse done with 2x2 blocks.
+struct Blocks {
+  /// The upper left, upper right, lower left, and lower right 2x2 blocks.
+  abcd: [m128; 4],
+  /// The determinant of each block.
+  dets: m128,
+  /// `A# * B`, with `#` being the adjugate.
+  a_b: m128,
+  /// `D# * C`
+  d_c: m128,
+  /// The determinant of the whole matrix, in every lane.
+  det: m128,
+}
+
+impl Mat4 {
+  /// The identity matrix.
+  #[inline(always)]
+  pub fn identity() -> Self {
+    Self::from_cols_array(&[
+      1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+    ])
+  }
+
+  /// All zeroes.
+  #[inline(always)]
+  pub fn zeroed() -> Self {
+    Self {
+      cols: [m128::zeroed(); 4],
+    }
+  }
+
+  /// Makes a matrix from its columns.
+  #[inline(always)]
+  pub fn from_cols(x: Vec4, y: Vec4, z: Vec4, w: Vec4) -> Self {
+    Self {
+      cols: [x.0, y.0, z.0, w.0],
+    }
+  }
+
+  /// Makes a matrix from 16 values in column major order.
+  #[inline(always)]
+  pub fn from_cols_array(a: &[f32; 16]) -> Self {
+    cast(*a)
+  }
+
+  /// Gives the 16 values in column major order.
+  #[inline(always)]
+  pub fn to_cols_array(&self) -> [f32; 16] {
+    cast(*self)
+  }
+
+  /// The column at the index, which must be less than 4.
+  #[inline(always)]
+  pub fn col(&self, index: usize) -> Vec4 {
+    Vec4(self.cols[index])
+  }
+
+  /// A translation.
+  #[inline(always)]
+  pub fn from_translation(v: Vec3A) -> Self {
+    let mut out = Self::identity();
+    out.cols[3] = v.extend(1.0).0;
+    out
+  }
+
+  /// A scaling along each axis.
+  #[inline(always)]
+  pub fn from_scale(v: Vec3A) -> Self {
+    let [x, y, z] = v.to_array();
+    Self::from_cols(
+      Vec4::new(x, 0.0, 0.0, 0.0),
+      Vec4::new(0.0, y, 0.0, 0.0),
+      Vec4::new(0.0, 0.0, z, 0.0),
+      Vec4::new(0.0, 0.0, 0.0, 1.0),
+    )
+  }
+
+  /// The rotation of a unit quaternion.
+  pub fn from_quat(q: Quat) -> Self {
+    let [x, y, z, w] = q.to_array();
+    let (x2, y2, z2) = (x + x, y + y, z + z);
+    let (xx, xy, xz) = (x * x2, x * y2, x * z2);
+    let (yy, yz, zz) = (y * y2, y * z2, z * z2);
+    let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+    Self::from_cols(
+      Vec4::new(1.0 - (yy + zz), xy + wz, xz - wy, 0.0),
+      Vec4::new(xy - wz, 1.0 - (xx + zz), yz + wx, 0.0),
+      Vec4::new(xz + wy, yz - wx, 1.0 - (xx + yy), 0.0),
+      Vec4::new(0.0, 0.0, 0.0, 1.0),
+    )
+  }
+
+  /// The matrix times a column vector.
+  #[inline(always)]
+  fn mul_m128(&self, v: m128) -> m128 {
+    let [c0, c1, c2, c3] = self.cols;
+    c0 * shuf!(v, v, 0, 0, 0, 0)
+      + c1 * shuf!(v, v, 1, 1, 1, 1)
+      + c2 * shuf!(v, v, 2, 2, 2, 2)
+      + c3 * shuf!(v, v, 3, 3, 3, 3)
+  }
+
+  /// Transforms a point, as if it had a `w` of 1.
+  ///
+  /// There's no divide by `w` afterwards, so this is for affine transforms.
+  #[inline(always)]
+  pub fn transform_point3(&self, p: Vec3A) -> Vec3A {
+    Vec3A(self.mul_m128(p.extend(1.0).0))
+  }
+
+  /// Transforms a direction, as if it had a `w` of 0, so translation doesn't
+  /// affect it.
+  #[inline(always)]
+  pub fn transform_vector3(&self, v: Vec3A) -> Vec3A {
+    Vec3A(self.mul_m128(v.extend(0.0).0))
+  }
+
+  /// Swaps the rows and columns.
+  #[inline(always)]
+  pub fn transpose(&self) -> Self {
+    let [mut c0, mut c1, mut c2, mut c3] = self.cols;
+    transpose4(&mut c0, &mut c1, &mut c2, &mut c3);
+    Self {
+      cols: [c0, c1, c2, c3],
+    }
+  }
+
+  // This is the 2x2 block method, as explained by Eric Zhang in "Fast 4x4
+  // Matrix Inverse with SSE SIMD, Explained". It's written for row major data,
+  // but the inverse of the transpose is the transpose of the inverse, so it
+  // works just the same on columns.
+  #[inline(always)]
+  fn blocks(&self) -> Blocks {
+    let [c0, c1, c2, c3] = self.cols;
+    let a = shuf!(c0, c1, 0, 1, 0, 1);
+    let b = shuf!(c0, c1, 2, 3, 2, 3);
+    let c = shuf!(c2, c3, 0, 1, 0, 1);
+    let d = shuf!(c2, c3, 2, 3, 2, 3);
+    let dets = shuf!(c0, c2, 0, 2, 0, 2) * shuf!(c1, c3, 1, 3, 1, 3)
+      - shuf!(c0, c2, 1, 3, 1, 3) * shuf!(c1, c3, 0, 2, 0, 2);
+    let a_b = mat2_adj_mul(a, b);
+    let d_c = mat2_adj_mul(d, c);
+    let det_a = shuf!(dets, dets, 0, 0, 0, 0);
+    let det_b = shuf!(dets, dets, 1, 1, 1, 1);
+    let det_c = shuf!(dets, dets, 2, 2, 2, 2);
+    let det_d = shuf!(dets, dets, 3, 3, 3, 3);
+    // |M| = |A||D| + |B||C| - tr((A#B)(D#C))
+    let trace = sum_lanes(a_b * shuf!(d_c, d_c, 0, 2, 1, 3));
+    let det = det_a * det_d + det_b * det_c - trace;
+    Blocks {
+      abcd: [a, b, c, d],
+      dets,
+      a_b,
+      d_c,
+      det,
+    }
+  }
+
+  /// The determinant.
+  #[inline]
+  pub fn determinant(&self) -> f32 {
+    self.blocks().det.extract_low()
+  }
+
+  /// The inverse.
+  ///
+  /// If the matrix can't be inverted (the determinant is zero) the output
+  /// will be full of infinities and NaN.
+  pub fn inverse(&self) -> Self {
+    let Blocks {
+      abcd: [a, b, c, d],
+      dets,
+      a_b,
+      d_c,
+      det,
+    } = self.blocks();
+    let det_a = shuf!(dets, dets, 0, 0, 0, 0);
+    let det_b = shuf!(dets, dets, 1, 1, 1, 1);
+    let det_c = shuf!(dets, dets, 2, 2, 2, 2);
+    let det_d = shuf!(dets, dets, 3, 3, 3, 3);
+    // the adjugates of the blocks of the inverse, times 1/|M|
+    let scale = m128::set_reverse(1.0, -1.0, -1.0, 1.0) / det;
+    let x = (det_d * a - mat2_mul(b, d_c)) * scale;
+    let y = (det_b * c - mat2_mul_adj(d, a_b)) * scale;
+    let z = (det_c * b - mat2_mul_adj(a, d_c)) * scale;
+    let w = (det_a * d - mat2_mul(c, a_b)) * scale;
+    // undo the adjugates and put the blocks back together at the same time
+    Self {
+      cols: [
+        shuf!(x, y, 3, 1, 3, 1),
+        shuf!(x, y, 2, 0, 2, 0),
+        shuf!(z, w, 3, 1, 3, 1),
+        shuf!(z, w, 2, 0, 2, 0),
+      ],
+    }
+  }
+
+  /// The rotation and translation parts of a view matrix, given the rows of
+  /// the rotation.
+  fn view(right: Vec3A, up: Vec3A, back: Vec3A, eye: Vec3A) -> Self {
+    let (mut c0, mut c1, mut c2, mut c3) = (right.0, up.0, back.0, m128::zeroed());
+    transpose4(&mut c0, &mut c1, &mut c2, &mut c3);
+    let w = Vec4::new(-right.dot(eye), -up.dot(eye), -back.dot(eye), 1.0);
+    Self {
+      cols: [c0, c1, c2, w.0],
+    }
+  }
+
+  /// A right handed view matrix from `eye` looking at `center`.
+  ///
+  /// In view space the camera looks down negative `z`, with `+y` up.
+  pub fn look_at_rh(eye: Vec3A, center: Vec3A, up: Vec3A) -> Self {
+    let forward = (center - eye).normalize();
+    let right = forward.cross(up).normalize();
+    let up = right.cross(forward);
+    Self::view(right, up, -forward, eye)
+  }
+
+  /// A left handed view matrix from `eye` looking at `center`.
+  ///
+  /// In view space the camera looks down positive `z`, with `+y` up.
+  pub fn look_at_lh(eye: Vec3A, center: Vec3A, up: Vec3A) -> Self {
+    let forward = (center - eye).normalize();
+    let right = up.cross(forward).normalize();
+    let up = forward.cross(right);
+    Self::view(right, up, forward, eye)
+  }
+
+  /// A right handed perspective projection, with a depth range of
+  /// `0.0..=1.0`.
+  ///
+  /// The field of view is vertical and in radians, `aspect` is width over
+  /// height.
+  pub fn perspective_rh(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+    let (sin, cos) = sin_cos(0.5 * fov_y);
+    let h = cos / sin;
+    let r = far / (near - far);
+    Self::from_cols(
+      Vec4::new(h / aspect, 0.0, 0.0, 0.0),
+      Vec4::new(0.0, h, 0.0, 0.0),
+      Vec4::new(0.0, 0.0, r, -1.0),
+      Vec4::new(0.0, 0.0, r * near, 0.0),
+    )
+  }
+
+  /// A left handed perspective projection, with a depth range of `0.0..=1.0`.
+  ///
+  /// The field of view is vertical and in radians, `aspect` is width over
+  /// height.
+  pub fn perspective_lh(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+    let (sin, cos) = sin_cos(0.5 * fov_y);
+    let h = cos / sin;
+    let r = far / (far - near);
+    Self::from_cols(
+      Vec4::new(h / aspect, 0.0, 0.0, 0.0),
+      Vec4::new(0.0, h, 0.0, 0.0),
+      Vec4::new(0.0, 0.0, r, 1.0),
+      Vec4::new(0.0, 0.0, -r * near, 0.0),
+    )
+  }
+
+  /// A right handed perspective projection, with a depth range of
+  /// `-1.0..=1.0`.
+  ///
+  /// The field of view is vertical and in radians, `aspect` is width over
+  /// height.
+  pub fn perspective_rh_gl(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+    let (sin, cos) = sin_cos(0.5 * fov_y);
+    let h = cos / sin;
+    let r = 1.0 / (near - far);
+    Self::from_cols(
+      Vec4::new(h / aspect, 0.0, 0.0, 0.0),
+      Vec4::new(0.0, h, 0.0, 0.0),
+      Vec4::new(0.0, 0.0, (near + far) * r, -1.0),
+      Vec4::new(0.0, 0.0, 2.0 * near * far * r, 0.0),
+    )
+  }
+
+  /// A right handed orthographic projection, with a depth range of
+  /// `0.0..=1.0`.
+  pub fn orthographic_rh(
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    near: f32,
+    far: f32,
+  ) -> Self {
+    let (w, h, r) = (
+      1.0 / (right - left),
+      1.0 / (top - bottom),
+      1.0 / (near - far),
+    );
+    Self::from_cols(
+      Vec4::new(2.0 * w, 0.0, 0.0, 0.0),
+      Vec4::new(0.0, 2.0 * h, 0.0, 0.0),
+      Vec4::new(0.0, 0.0, r, 0.0),
+      Vec4::new(-(left + right) * w, -(top + bottom) * h, r * near, 1.0),
+    )
+  }
+
+  /// A left handed orthographic projection, with a depth range of
+  /// `0.0..=1.0`.
+  pub fn orthographic_lh(
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    near: f32,
+    far: f32,
+  ) -> Self {
+    let (w, h, r) = (
+      1.0 / (right - left),
+      1.0 / (top - bottom),
+      1.0 / (far - near),
+    );
+    Self::from_cols(
+      Vec4::new(2.0 * w, 0.0, 0.0, 0.0),
+      Vec4::new(0.0, 2.0 * h, 0.0, 0.0),
+      Vec4::new(0.0, 0.0, r, 0.0),
+      Vec4::new(-(left + right) * w, -(top + bottom) * h, -r * near, 1.0),
+    )
+  }
+
+  /// A right handed orthographic projection, with a depth range of
+  /// `-1.0..=1.0`.
+  pub fn orthographic_rh_gl(
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    near: f32,
+    far: f32,
+  ) -> Self {
+    let (w, h, r) = (
+      1.0 / (right - left),
+      1.0 / (top - bottom),
+      1.0 / (near - far),
+    );
+    Self::from_cols(
+      Vec4::new(2.0 * w, 0.0, 0.0, 0.0),
+      Vec4::new(0.0, 2.0 * h, 0.0, 0.0),
+      Vec4::new(0.0, 0.0, 2.0 * r, 0.0),
+      Vec4::new(
+        -(left + right) * w,
+        -(top + bottom) * h,
+        (far + near) * r,
+        1.0,
+      ),
+    )
+  }
+}
@@ -0,0 +1,445 @@
+//! The SHA-256 and SHA-1 hash functions (FIPS 180-4).
+//!
+//! Both hashers take their input in pieces with `update` and give the digest
+//! with `finalize`, or you can use `digest` for data that's all in one slice.
+//!
+//! ```rust
+//! use lokacore::sha::*;
+//!
+//! let mut hasher = Sha256::new();
+//! hasher.update(b"ab");
+//! hasher.update(b"c");
+//! assert_eq!(hasher.finalize(), Sha256::digest(b"abc"));
+//! assert_eq!(Sha256::digest(b"abc")[..4], [0xBA, 0x78, 0x16, 0xBF]);
+//! ```
+//!
+//! With the SHA extensions (and SSSE3, which every CPU with them has) the
+//! compression functions use the SHA instructions, otherwise they're plain
+//! scalar code.
+//!
+//! SHA-1 is broken for collision resistance. It's here for old formats that
+//! need it, and for content addressing where nobody is trying to forge a
+//! collision, but prefer SHA-256 for anything new.
+
+#[cfg(all(target_feature = "sha", target_feature = "ssse3"))]
+use crate::{byte_align_right, cast, sha1_rounds4, shuffle_i32};
+
+#[cfg(all(target_arch = "x86", target_feature = "sha", target_feature = "ssse3"))]
+use crate::arch::x86::*;
+#[cfg(all(
+  target_arch = "x86_64",
+  target_feature = "sha",
+  target_feature = "ssse3"
+))]
+use crate::arch::x86_64::*;
+
+/// Gathers input into 64 byte blocks for a compression function.
+#[derive(Debug, Clone, Copy)]
+struct BlockBuffer {
+  block: [u8; 64],
+  len: usize,
+  total: u64,
+}
+
+impl BlockBuffer {
+  const fn new() -> Self {
+    Self {
+      block: [0; 64],
+      len: 0,
+      total: 0,
+    }
+  }
+
+  /// Adds the data, passing whole blocks (as many as possible in each call)
+  /// to `compress`.
+  fn update(&mut self, data: &[u8], mut compress: impl FnMut(&[u8])) {
+    let mut data = data;
+    self.total = self.total.wrapping_add(data.len() as u64);
+    if self.len > 0 {
+      let take = (64 - self.len).min(data.len());
+      self.block[self.len..self.len + take].copy_from_slice(&data[..take]);
+      self.len += take;
+      data = &data[take..];
+      if self.len < 64 {
+        return;
+      }
+      compress(&self.block);
+      self.len = 0;
+    }
+    let whole = data.len() - data.len() % 64;
+    if whole > 0 {
+      compress(&data[..whole]);
+    }
+    let rest = &data[whole..];
+    self.block[..rest.len()].copy_from_slice(rest);
+    self.len = rest.len();
+  }
+
+  /// Adds the padding and the big-endian bit length.
+  fn finish(&mut self, mut compress: impl FnMut(&[u8])) {
+    let bits = self.total.wrapping_mul(8);
+    self.block[self.len] = 0x80;
+    for b in &mut self.block[self.len + 1..] {
+      *b = 0;
+    }
+    if self.len >= 56 {
+      compress(&self.block);
+      self.block = [0; 64];
+    }
+    self.block[56..].copy_from_slice(&bits.to_be_bytes());
+    compress(&self.block);
+  }
+}
+
+/// Reads the 16 big-endian words of a block.
+#[cfg(not(all(target_feature = "sha", target_feature = "ssse3")))]
+#[inline(always)]
+fn block_words(block: &[u8]) -> [u32; 16] {
+  let mut w = [0; 16];
+  for (w, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+    *w = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+  }
+  w
+}
+
+const SHA256_K: [u32; 64] = [
+  0x428A_2F98,
+  0x7137_4491,
+  0xB5C0_FBCF,
+  0xE9B5_DBA5,
+  0x3956_C25B,
+  0x59F1_11F1,
+  0x923F_82A4,
+  0xAB1C_5ED5,
+  0xD807_AA98,
+  0x1283_5B01,
+  0x2431_85BE,
+  0x550C_7DC3,
+  0x72BE_5D74,
+  0x80DE_B1FE,
+  0x9BDC_06A7,
+  0xC19B_F174,
+  0xE49B_69C1,
+  0xEFBE_4786,
+  0x0FC1_9DC6,
+  0x240C_A1CC,
+  0x2DE9_2C6F,
+  0x4A74_84AA,
+  0x5CB0_A9DC,
+  0x76F9_88DA,
+  0x983E_5152,
+  0xA831_C66D,
+  0xB003_27C8,
+  0xBF59_7FC7,
+  0xC6E0_0BF3,
+  0xD5A7_9147,
+  0x06CA_6351,
+  0x1429_2967,
+  0x27B7_0A85,
+  0x2E1B_2138,
+  0x4D2C_6DFC,
+  0x5338_0D13,
+  0x650A_7354,
+  0x766A_0ABB,
+  0x81C2_C92E,
+  0x9272_2C85,
+  0xA2BF_E8A1,
+  0xA81A_664B,
+  0xC24B_8B70,
+  0xC76C_51A3,
+  0xD192_E819,
+  0xD699_0624,
+  0xF40E_3585,
+  0x106A_A070,
+  0x19A4_C116,
+  0x1E37_6C08,
+  0x2748_774C,
+  0x34B0_BCB5,
+  0x391C_0CB3,
+  0x4ED8_AA4A,
+  0x5B9C_CA4F,
+  0x682E_6FF3,
+  0x748F_82EE,
+  0x78A5_636F,
+  0x84C8_7814,
+  0x8CC7_0208,
+  0x90BE_FFFA,
+  0xA450_6CEB,
+  0xBEF9_A3F7,
+  0xC671_78F2,
+];
+
+const SHA256_INIT: [u32; 8] = [
+  0x6A09_E667,
+  0xBB67_AE85,
+  0x3C6E_F372,
+  0xA54F_F53A,
+  0x510E_527F,
+  0x9B05_688C,
+  0x1F83_D9AB,
+  0x5BE0_CD19,
+];
+
+const SHA1_INIT: [u32; 5] = [
+  0x6745_2301,
+  0xEFCD_AB89,
+  0x98BA_DCFE,
+  0x1032_5476,
+  0xC3D2_E1F0,
+];
+
+/// The SHA-256 compression function over whole blocks.
+#[cfg(not(all(target_feature = "sha", target_feature = "ssse3")))]
+fn compress_256(state: &mut [u32; 8], blocks: &[u8]) {
+  for block in blocks.chunks_exact(64) {
+    let mut w = [0_u32; 64];
+    w[..16].copy_from_slice(&block_words(block));
+    for t in 16..64 {
+      let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+      let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+      w[t] = w[t - 16]
+        .wrapping_add(s0)
+        .wrapping_add(w[t - 7])
+        .wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for t in 0..64 {
+      let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+      let ch = (e & f) ^ (!e & g);
+      let t1 = h
+        .wrapping_add(s1)
+        .wrapping_add(ch)
+        .wrapping_add(SHA256_K[t])
+        .wrapping_add(w[t]);
+      let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+      let maj = (a & b) ^ (a & c) ^ (b & c);
+      let t2 = s0.wrapping_add(maj);
+      h = g;
+      g = f;
+      f = e;
+      e = d.wrapping_add(t1);
+      d = c;
+      c = b;
+      b = a;
+      a = t1.wrapping_add(t2);
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+      *s = s.wrapping_add(*v);
+    }
+  }
+}
+
+/// The SHA-256 compression function over whole blocks.
+#[cfg(all(target_feature = "sha", target_feature = "ssse3"))]
+fn compress_256(state: &mut [u32; 8], blocks: &[u8]) {
+  let [a, b, c, d, e, f, g, h] = *state;
+  let mut abef = m128i::set_i32(a as i32, b as i32, e as i32, f as i32);
+  let mut cdgh = m128i::set_i32(c as i32, d as i32, g as i32, h as i32);
+  let swap = m128i::setr_i8(3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12);
+  for block in blocks.chunks_exact(64) {
+    let (abef_start, cdgh_start) = (abef, cdgh);
+    let mut w = [m128i::zeroed(); 4];
+    for (j, w) in w.iter_mut().enumerate() {
+      *w = m128i::load_from_slice(&block[16 * j..])
+        .unwrap()
+        .shuffle_i8(swap);
+    }
+    for i in 0..16 {
+      if i >= 4 {
+        let older = w[i % 4].sha256_msg1(w[(i + 1) % 4]);
+        let t7 = byte_align_right!(w[(i + 3) % 4], w[(i + 2) % 4], 4);
+        w[i % 4] = older.add_i32(t7).sha256_msg2(w[(i + 3) % 4]);
+      }
+      let wk = w[i % 4].add_i32(m128i::load_from_slice(&SHA256_K[4 * i..]).unwrap());
+      let next = cdgh.sha256_rounds2(abef, wk);
+      cdgh = abef;
+      abef = next;
+      let next = cdgh.sha256_rounds2(abef, shuffle_i32!(wk, 1, 0, 3, 2));
+      cdgh = abef;
+      abef = next;
+    }
+    abef = abef.add_i32(abef_start);
+    cdgh = cdgh.add_i32(cdgh_start);
+  }
+  let [f, e, b, a]: [u32; 4] = cast(abef);
+  let [h, g, d, c]: [u32; 4] = cast(cdgh);
+  *state = [a, b, c, d, e, f, g, h];
+}
+
+/// The SHA-1 compression function over whole blocks.
+#[cfg(not(all(target_feature = "sha", target_feature = "ssse3")))]
+fn compress_1(state: &mut [u32; 5], blocks: &[u8]) {
+  for block in blocks.chunks_exact(64) {
+    let mut w = [0_u32; 80];
+    w[..16].copy_from_slice(&block_words(block));
+    for t in 16..80 {
+      w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (t, &w) in w.iter().enumerate() {
+      let (f, k) = match t / 20 {
+        0 => ((b & c) | (!b & d), 0x5A82_7999),
+        1 => (b ^ c ^ d, 0x6ED9_EBA1),
+        2 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+        _ => (b ^ c ^ d, 0xCA62_C1D6_u32),
+      };
+      let temp = a
+        .rotate_left(5)
+        .wrapping_add(f)
+        .wrapping_add(e)
+        .wrapping_add(k)
+        .wrapping_add(w);
+      e = d;
+      d = c;
+      c = b.rotate_left(30);
+      b = a;
+      a = temp;
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e].iter()) {
+      *s = s.wrapping_add(*v);
+    }
+  }
+}
+
+/// The SHA-1 compression function over whole blocks.
+#[cfg(all(target_feature = "sha", target_feature = "ssse3"))]
+fn compress_1(state: &mut [u32; 5], blocks: &[u8]) {
+  let [a, b, c, d, e] = *state;
+  let mut abcd = m128i::set_i32(a as i32, b as i32, c as i32, d as i32);
+  let mut e0 = m128i::set_i32(e as i32, 0, 0, 0);
+  let reverse = m128i::setr_i8(15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0);
+  for block in blocks.chunks_exact(64) {
+    let (abcd_start, e_start) = (abcd, e0);
+    let mut w = [m128i::zeroed(); 4];
+    for (j, w) in w.iter_mut().enumerate() {
+      *w = m128i::load_from_slice(&block[16 * j..])
+        .unwrap()
+        .shuffle_i8(reverse);
+    }
+    let mut e = e_start.add_i32(w[0]);
+    let mut before = abcd;
+    for i in 0..20 {
+      if i >= 4 {
+        let older = w[i % 4].sha1_msg1(w[(i + 1) % 4]) ^ w[(i + 2) % 4];
+        w[i % 4] = older.sha1_msg2(w[(i + 3) % 4]);
+      }
+      if i > 0 {
+        e = before.sha1_next_e(w[i % 4]);
+      }
+      before = abcd;
+      abcd = match i / 5 {
+        0 => sha1_rounds4!(abcd, e, 0),
+        1 => sha1_rounds4!(abcd, e, 1),
+        2 => sha1_rounds4!(abcd, e, 2),
+        _ => sha1_rounds4!(abcd, e, 3),
+      };
+    }
+    e0 = before.sha1_next_e(e_start);
+    abcd = abcd.add_i32(abcd_start);
+  }
+  let [d, c, b, a]: [u32; 4] = cast(abcd);
+  let [_, _, _, e]: [u32; 4] = cast(e0);
+  *state = [a, b, c, d, e];
+}
+
+/// A SHA-256 hash in progress.
+#[derive(Debug, Clone, Copy)]
+pub struct Sha256 {
+  state: [u32; 8],
+  buffer: BlockBuffer,
+}
+
+impl Default for Sha256 {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Sha256 {
+  /// A hasher with no data in it yet.
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      state: SHA256_INIT,
+      buffer: BlockBuffer::new(),
+    }
+  }
+
+  /// Adds more data to the hash.
+  pub fn update(&mut self, data: &[u8]) {
+    let state = &mut self.state;
+    self
+      .buffer
+      .update(data, |blocks| compress_256(state, blocks));
+  }
+
+  /// The hash of all the data.
+  pub fn finalize(mut self) -> [u8; 32] {
+    let state = &mut self.state;
+    self.buffer.finish(|blocks| compress_256(state, blocks));
+    let mut out = [0; 32];
+    for (bytes, s) in out.chunks_exact_mut(4).zip(self.state.iter()) {
+      bytes.copy_from_slice(&s.to_be_bytes());
+    }
+    out
+  }
+
+  /// The hash of a slice.
+  #[inline]
+  pub fn digest(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Self::new();
+    hasher.update(data);
+    hasher.finalize()
+  }
+}
+
+/// A SHA-1 hash in progress.
+#[derive(Debug, Clone, Copy)]
+pub struct Sha1 {
+  state: [u32; 5],
+  buffer: BlockBuffer,
+}
+
+impl Default for Sha1 {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Sha1 {
+  /// A hasher with no data in it yet.
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      state: SHA1_INIT,
+      buffer: BlockBuffer::new(),
+    }
+  }
+
+  /// Adds more data to the hash.
+  pub fn update(&mut self, data: &[u8]) {
+    let state = &mut self.state;
+    self.buffer.update(data, |blocks| compress_1(state, blocks));
+  }
+
+  /// The hash of all the data.
+  pub fn finalize(mut self) -> [u8; 20] {
+    let state = &mut self.state;
+    self.buffer.finish(|blocks| compress_1(state, blocks));
+    let mut out = [0; 20];
+    for (bytes, s) in out.chunks_exact_mut(4).zip(self.state.iter()) {
+      bytes.copy_from_slice(&s.to_be_bytes());
+    }
+    out
+  }
+
+  /// The hash of a slice.
+  #[inline]
+  pub fn digest(data: &[u8]) -> [u8; 20] {
+    let mut hasher = Self::new();
+    hasher.update(data);
+    hasher.finalize()
+  }
+}
@@ -0,0 +1,42 @@
+//! Higher level SIMD routines built on top of the [`arch`](crate::arch)
+//! wrappers.
+//!
+//! Unlike `arch`, everything in here is available on every target. The best
+//! code path is picked at compile time based on the enabled target features,
+//! and there's always a plain scalar path for when no SIMD is available.
+
+#[cfg(target_feature = "sse")]
+use crate::{try_cast_slice, Pod};
+#[cfg(target_feature = "sse")]
+use core::mem::{align_of, size_of};
+
+pub mod audio;
+pub mod bytes;
+pub mod codec;
+pub mod pixel;
+pub mod rng;
+pub mod scan;
+pub mod slice;
+pub mod soa;
+pub mod sort;
+pub mod srgb;
+pub mod stats;
+pub mod utf8;
+
+/// Splits the slice into the elements before the first address aligned for
+/// `A`, the aligned middle re-interpreted as `A` values, and the leftovers.
+#[cfg(target_feature = "sse")]
+fn split_aligned<T: Pod, A: Pod>(slice: &[T]) -> (&[T], &[A], &[T]) {
+  let lanes = size_of::<A>() / size_of::<T>();
+  // if the alignment can't be reached at all `align_offset` gives `usize::MAX`,
+  // and then everything just goes in the head.
+  let head_len = slice
+    .as_ptr()
+    .align_offset(align_of::<A>())
+    .min(slice.len());
+  let (head, rest) = slice.split_at(head_len);
+  let (body, tail) = rest.split_at(rest.len() - rest.len() % lanes);
+  // `body` is aligned by construction unless it's empty, and an empty slice is
+  // fine to replace with any other empty slice.
+  (head, try_cast_slice(body).unwrap_or(&[]), tail)
+}
@@ -0,0 +1,320 @@
+//! Audio sample format conversions, channel interleaving, and gain.
+//!
+//! Samples are mixed as `f32` in `-1.0..=1.0`. Integer formats are scaled by
+//! a power of two so that the conversions into `f32` are exact:
+//!
+//! * `i16` samples are divided by 32768.
+//! * 24-bit samples are packed as 3 little-endian bytes each (as in WAV
+//!   files), and are divided by 8388608.
+//!
+//! Going back to `i16` multiplies by 32768 and rounds to nearest (ties to
+//! even), so it's the exact inverse for values that came from `i16`. Anything
+//! past the ends (including `1.0` itself) saturates, and NaN becomes 0.
+//!
+//! Functions with a source and a destination work on as many samples (or
+//! frames, for the interleaving functions) as fit in the shorter slice and
+//! return that count.
+//!
+//! ```rust
+//! use lokacore::simd::audio::*;
+//!
+//! let mut f = [0.0; 3];
+//! assert_eq!(i16_to_f32(&[-32768, 16384, 1], &mut f), 3);
+//! assert_eq!(f, [-1.0, 0.5, 1.0 / 32768.0]);
+//!
+//! apply_gain(&mut f, 4.0);
+//! assert_eq!(f, [-1.0, 1.0, 4.0 / 32768.0]);
+//!
+//! let mut pcm = [0_i16; 3];
+//! f32_to_i16(&f, &mut pcm);
+//! assert_eq!(pcm, [-32768, 32767, 4]);
+//!
+//! let mut stereo = [0.0; 4];
+//! interleave_stereo(&[1.0, 2.0], &[-1.0, -2.0], &mut stereo);
+//! assert_eq!(stereo, [1.0, -1.0, 2.0, -2.0]);
+//! ```
+
+use crate::{
+  simd::soa::{deinterleave3_f32, deinterleave4_f32, interleave3_f32, interleave4_f32},
+  try_cast_slice, try_cast_slice_mut,
+};
+#[cfg(target_feature = "sse")]
+use core::convert::TryInto;
+
+#[cfg(target_feature = "sse2")]
+use crate::cast;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse"))]
+use crate::{arch::x86::*, shuffle128};
+#[cfg(all(target_arch = "x86_64", target_feature = "sse"))]
+use crate::{arch::x86_64::*, shuffle128};
+
+/// Rounds a sample that's already been scaled to the `i16` range, to nearest
+/// with ties to even, saturating.
+#[inline]
+fn round_to_i16(x: f32) -> i16 {
+  if x.is_nan() {
+    return 0;
+  }
+  // with `|x| <= 2^22`, adding `1.5 * 2^23` leaves no fraction bits, so the
+  // add does the rounding
+  const MAGIC: f32 = 12_582_912.0;
+  ((x.clamp(-32768.0, 32767.0) + MAGIC) - MAGIC) as i16
+}
+
+/// Converts `i16` samples into `f32` samples.
+pub fn i16_to_f32(src: &[i16], dst: &mut [f32]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let scale = m128::splat(1.0 / 32768.0);
+    while i + 8 <= n {
+      let v = m128i::load_from_slice(&src[i..]).unwrap();
+      // each `i16` goes in the high half of an `i32`, then shifts back down
+      let low = v.unpack_low_i16(v).shr_arith_i32(16).round_i32() * scale;
+      let high = v.unpack_high_i16(v).shr_arith_i32(16).round_i32() * scale;
+      low.store_unaligned((&mut dst[i..i + 4]).try_into().unwrap());
+      high.store_unaligned((&mut dst[i + 4..i + 8]).try_into().unwrap());
+      i += 8;
+    }
+  }
+  for i in i..n {
+    dst[i] = f32::from(src[i]) * (1.0 / 32768.0);
+  }
+  n
+}
+
+/// Converts `f32` samples into `i16` samples.
+pub fn f32_to_i16(src: &[f32], dst: &mut [i16]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let (scale, limit) = (m128::splat(32768.0), m128::splat(65536.0));
+    while i + 8 <= n {
+      // `round_i32` gives `i32::MIN` for anything out of range, which the pack
+      // saturates correctly for negative values. Only the positive side (and
+      // NaN) need fixing up first.
+      let quad = |k: usize| {
+        let x = m128::load_from_slice(&src[i + k * 4..]).unwrap() * scale;
+        (x & x.cmp_eq(x)).min(limit).round_i32()
+      };
+      let v = quad(0).saturating_pack_i32_i16(quad(1));
+      dst[i..i + 8].copy_from_slice(&cast::<m128i, [i16; 8]>(v));
+      i += 8;
+    }
+  }
+  for i in i..n {
+    dst[i] = round_to_i16(src[i] * 32768.0);
+  }
+  n
+}
+
+/// Converts packed 24-bit samples into `i32` values, sign extended.
+///
+/// `src` is 3 bytes per sample, and any partial sample at the end is skipped.
+pub fn i24_to_i32(src: &[u8], dst: &mut [i32]) -> usize {
+  let n = (src.len() / 3).min(dst.len());
+  #[cfg_attr(not(target_feature = "ssse3"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "ssse3")]
+  {
+    // the load takes 16 bytes even though only 12 are used.
+    while i + 4 <= n && i * 3 + 16 <= src.len() {
+      let v = i24_quad(&src[i * 3..]).shr_arith_i32(8);
+      dst[i..i + 4].copy_from_slice(&cast::<m128i, [i32; 4]>(v));
+      i += 4;
+    }
+  }
+  for (k, d) in (i..n).zip(&mut dst[i..n]) {
+    *d = i24_at(src, k);
+  }
+  n
+}
+
+/// Converts packed 24-bit samples into `f32` samples.
+///
+/// `src` is 3 bytes per sample, and any partial sample at the end is skipped.
+pub fn i24_to_f32(src: &[u8], dst: &mut [f32]) -> usize {
+  let n = (src.len() / 3).min(dst.len());
+  #[cfg_attr(not(target_feature = "ssse3"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "ssse3")]
+  {
+    // the samples are still shifted up by 8 bits, so scale by 2^-31
+    let scale = m128::splat(1.0 / 2_147_483_648.0);
+    while i + 4 <= n && i * 3 + 16 <= src.len() {
+      let v = i24_quad(&src[i * 3..]).round_i32() * scale;
+      v.store_unaligned((&mut dst[i..i + 4]).try_into().unwrap());
+      i += 4;
+    }
+  }
+  for (k, d) in (i..n).zip(&mut dst[i..n]) {
+    *d = i24_at(src, k) as f32 * (1.0 / 8_388_608.0);
+  }
+  n
+}
+
+/// The 24-bit sample at index `i`.
+#[inline]
+fn i24_at(src: &[u8], i: usize) -> i32 {
+  let b = &src[i * 3..i * 3 + 3];
+  i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8
+}
+
+/// Four 24-bit samples from the start of `src` (which must be at least 16
+/// bytes), each in the high 24 bits of an `i32` lane.
+#[cfg(target_feature = "ssse3")]
+#[inline(always)]
+fn i24_quad(src: &[u8]) -> m128i {
+  let spread = m128i::setr_i8(-1, 0, 1, 2, -1, 3, 4, 5, -1, 6, 7, 8, -1, 9, 10, 11);
+  m128i::load_from_slice(src).unwrap().shuffle_i8(spread)
+}
+
+/// Multiplies every sample by `gain`, then clamps to `-1.0..=1.0`.
+///
+/// NaN becomes 0.
+pub fn apply_gain(samples: &mut [f32], gain: f32) {
+  #[cfg_attr(not(target_feature = "sse"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse")]
+  {
+    let (g, low, high) = (m128::splat(gain), m128::splat(-1.0), m128::splat(1.0));
+    while i + 4 <= samples.len() {
+      let out: &mut [f32; 4] = (&mut samples[i..i + 4]).try_into().unwrap();
+      let x = m128::load_unaligned(out) * g;
+      (x & x.cmp_eq(x)).max(low).min(high).store_unaligned(out);
+      i += 4;
+    }
+  }
+  for s in &mut samples[i..] {
+    let x = *s * gain;
+    *s = if x.is_nan() { 0.0 } else { x.clamp(-1.0, 1.0) };
+  }
+}
+
+/// Interleaves a left and right channel into `[l, r, l, r, ...]` frames.
+///
+/// Returns the number of frames.
+pub fn interleave_stereo(left: &[f32], right: &[f32], dst: &mut [f32]) -> usize {
+  let n = left.len().min(right.len()).min(dst.len() / 2);
+  #[cfg_attr(not(target_feature = "sse"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse")]
+  {
+    while i + 4 <= n {
+      let l = m128::load_from_slice(&left[i..]).unwrap();
+      let r = m128::load_from_slice(&right[i..]).unwrap();
+      l.unpack_low(r)
+        .store_unaligned((&mut dst[i * 2..i * 2 + 4]).try_into().unwrap());
+      l.unpack_high(r)
+        .store_unaligned((&mut dst[i * 2 + 4..i * 2 + 8]).try_into().unwrap());
+      i += 4;
+    }
+  }
+  for i in i..n {
+    dst[i * 2] = left[i];
+    dst[i * 2 + 1] = right[i];
+  }
+  n
+}
+
+/// Splits `[l, r, l, r, ...]` frames into a left and right channel.
+///
+/// Returns the number of frames.
+pub fn deinterleave_stereo(src: &[f32], left: &mut [f32], right: &mut [f32]) -> usize {
+  let n = (src.len() / 2).min(left.len()).min(right.len());
+  #[cfg_attr(not(target_feature = "sse"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse")]
+  {
+    while i + 4 <= n {
+      let a = m128::load_from_slice(&src[i * 2..]).unwrap();
+      let b = m128::load_from_slice(&src[i * 2 + 4..]).unwrap();
+      shuffle128!(a, b, 2, 0, 2, 0).store_unaligned((&mut left[i..i + 4]).try_into().unwrap());
+      shuffle128!(a, b, 3, 1, 3, 1).store_unaligned((&mut right[i..i + 4]).try_into().unwrap());
+      i += 4;
+    }
+  }
+  for i in i..n {
+    left[i] = src[i * 2];
+    right[i] = src[i * 2 + 1];
+  }
+  n
+}
+
+/// Interleaves any number of channels into frames of one sample from each
+/// channel, in order.
+///
+/// Two channels go through [`interleave_stereo`], and three or four go through
+/// [`interleave3_f32`] or [`interleave4_f32`]. Returns the number of frames,
+/// which is 0 if there are no channels.
+pub fn interleave(channels: &[&[f32]], dst: &mut [f32]) -> usize {
+  match *channels {
+    [left, right] => return interleave_stereo(left, right, dst),
+    [xs, ys, zs] => {
+      let frames = dst.len() / 3;
+      let dst = &mut dst[..frames * 3];
+      return interleave3_f32(xs, ys, zs, try_cast_slice_mut(dst).unwrap());
+    }
+    [xs, ys, zs, ws] => {
+      let frames = dst.len() / 4;
+      let dst = &mut dst[..frames * 4];
+      return interleave4_f32(xs, ys, zs, ws, try_cast_slice_mut(dst).unwrap());
+    }
+    _ => (),
+  }
+  let count = channels.len();
+  if count == 0 {
+    return 0;
+  }
+  let n = channels
+    .iter()
+    .map(|c| c.len())
+    .fold(dst.len() / count, usize::min);
+  for (frame, out) in dst.chunks_exact_mut(count).take(n).enumerate() {
+    for (d, channel) in out.iter_mut().zip(channels) {
+      *d = channel[frame];
+    }
+  }
+  n
+}
+
+/// Splits frames of one sample from each channel into separate channels.
+///
+/// Two channels go through [`deinterleave_stereo`], and three or four go
+/// through [`deinterleave3_f32`] or [`deinterleave4_f32`]. Returns the number
+/// of frames, which is 0 if there are no channels.
+pub fn deinterleave(src: &[f32], channels: &mut [&mut [f32]]) -> usize {
+  match channels {
+    [left, right] => return deinterleave_stereo(src, left, right),
+    [xs, ys, zs] => {
+      let frames = src.len() / 3;
+      let src = &src[..frames * 3];
+      return deinterleave3_f32(try_cast_slice(src).unwrap(), xs, ys, zs);
+    }
+    [xs, ys, zs, ws] => {
+      let frames = src.len() / 4;
+      let src = &src[..frames * 4];
+      return deinterleave4_f32(try_cast_slice(src).unwrap(), xs, ys, zs, ws);
+    }
+    _ => (),
+  }
+  let count = channels.len();
+  if count == 0 {
+    return 0;
+  }
+  let n = channels
+    .iter()
+    .map(|c| c.len())
+    .fold(src.len() / count, usize::min);
+  for (frame, input) in src.chunks_exact(count).take(n).enumerate() {
+    for (&s, channel) in input.iter().zip(channels.iter_mut()) {
+      channel[frame] = s;
+    }
+  }
+  n
+}
@@ -0,0 +1,326 @@
+//! Searching through byte slices.
+//!
+//! The SIMD paths compare 16 bytes at a time with `m128i::cmp_eq_i8` and then
+//! use `m128i::movemask_i8` to turn the matches into bit positions. The
+//! single-byte searches only ever load whole aligned blocks, with the unaligned
+//! head and tail of the haystack checked one byte at a time.
+
+#[cfg(target_feature = "sse2")]
+use super::split_aligned;
+#[cfg(target_feature = "sse2")]
+use crate::cast;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+/// Index of the first byte that passes `scalar`, where `simd` gives `-1` in
+/// exactly those lanes that `scalar` would pass.
+#[cfg_attr(not(target_feature = "sse2"), allow(unused_variables))]
+#[inline(always)]
+fn forward(
+  haystack: &[u8],
+  scalar: impl Fn(u8) -> bool,
+  #[cfg(target_feature = "sse2")] simd: impl Fn(m128i) -> m128i,
+) -> Option<usize> {
+  #[cfg(target_feature = "sse2")]
+  {
+    let (head, body, tail) = split_aligned::<u8, m128i>(haystack);
+    if let Some(i) = head.iter().position(|&b| scalar(b)) {
+      return Some(i);
+    }
+    let mut offset = head.len();
+    let mut quads = body.chunks_exact(4);
+    for quad in &mut quads {
+      let masks = [simd(quad[0]), simd(quad[1]), simd(quad[2]), simd(quad[3])];
+      if (masks[0] | masks[1] | masks[2] | masks[3]).movemask_i8() != 0 {
+        for (i, mask) in masks.iter().enumerate() {
+          let bits = mask.movemask_i8();
+          if bits != 0 {
+            return Some(offset + i * 16 + bits.trailing_zeros() as usize);
+          }
+        }
+      }
+      offset += 64;
+    }
+    for &v in quads.remainder() {
+      let bits = simd(v).movemask_i8();
+      if bits != 0 {
+        return Some(offset + bits.trailing_zeros() as usize);
+      }
+      offset += 16;
+    }
+    tail.iter().position(|&b| scalar(b)).map(|i| offset + i)
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    haystack.iter().position(|&b| scalar(b))
+  }
+}
+
+/// As [`forward`], but gives the index of the last byte.
+#[cfg_attr(not(target_feature = "sse2"), allow(unused_variables))]
+#[inline(always)]
+fn reverse(
+  haystack: &[u8],
+  scalar: impl Fn(u8) -> bool,
+  #[cfg(target_feature = "sse2")] simd: impl Fn(m128i) -> m128i,
+) -> Option<usize> {
+  #[cfg(target_feature = "sse2")]
+  {
+    let (head, body, tail) = split_aligned::<u8, m128i>(haystack);
+    let mut end = haystack.len() - tail.len();
+    if let Some(i) = tail.iter().rposition(|&b| scalar(b)) {
+      return Some(end + i);
+    }
+    for &v in body.iter().rev() {
+      end -= 16;
+      let bits = simd(v).movemask_i8();
+      if bits != 0 {
+        return Some(end + 31 - bits.leading_zeros() as usize);
+      }
+    }
+    head.iter().rposition(|&b| scalar(b))
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    haystack.iter().rposition(|&b| scalar(b))
+  }
+}
+
+/// Index of the first `needle` byte in the haystack.
+pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+  #[cfg(target_feature = "sse2")]
+  let n = m128i::splat_i8(needle as i8);
+  forward(
+    haystack,
+    |b| b == needle,
+    #[cfg(target_feature = "sse2")]
+    |v| v.cmp_eq_i8(n),
+  )
+}
+
+/// Index of the first byte in the haystack that's either of the needles.
+pub fn memchr2(needle1: u8, needle2: u8, haystack: &[u8]) -> Option<usize> {
+  #[cfg(target_feature = "sse2")]
+  let (n1, n2) = (
+    m128i::splat_i8(needle1 as i8),
+    m128i::splat_i8(needle2 as i8),
+  );
+  forward(
+    haystack,
+    |b| b == needle1 || b == needle2,
+    #[cfg(target_feature = "sse2")]
+    |v| v.cmp_eq_i8(n1) | v.cmp_eq_i8(n2),
+  )
+}
+
+/// Index of the first byte in the haystack that's any of the needles.
+pub fn memchr3(needle1: u8, needle2: u8, needle3: u8, haystack: &[u8]) -> Option<usize> {
+  #[cfg(target_feature = "sse2")]
+  let (n1, n2, n3) = (
+    m128i::splat_i8(needle1 as i8),
+    m128i::splat_i8(needle2 as i8),
+    m128i::splat_i8(needle3 as i8),
+  );
+  forward(
+    haystack,
+    |b| b == needle1 || b == needle2 || b == needle3,
+    #[cfg(target_feature = "sse2")]
+    |v| v.cmp_eq_i8(n1) | v.cmp_eq_i8(n2) | v.cmp_eq_i8(n3),
+  )
+}
+
+/// Index of the last `needle` byte in the haystack.
+pub fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+  #[cfg(target_feature = "sse2")]
+  let n = m128i::splat_i8(needle as i8);
+  reverse(
+    haystack,
+    |b| b == needle,
+    #[cfg(target_feature = "sse2")]
+    |v| v.cmp_eq_i8(n),
+  )
+}
+
+/// Counts how many times `needle` appears in the haystack.
+pub fn count_byte(needle: u8, haystack: &[u8]) -> usize {
+  let scalar = |s: &[u8]| s.iter().filter(|&&b| b == needle).count();
+  #[cfg(target_feature = "sse2")]
+  {
+    let (head, body, tail) = split_aligned::<u8, m128i>(haystack);
+    let n = m128i::splat_i8(needle as i8);
+    let zero = m128i::zeroed();
+    let mut total = scalar(head) + scalar(tail);
+    // a match is -1, so subtracting it adds one to that `u8` lane. After 255
+    // blocks a lane could wrap, so we total up before then.
+    for block in body.chunks(255) {
+      let mut counts = m128i::zeroed();
+      for &v in block {
+        counts = counts.sub_i8(v.cmp_eq_i8(n));
+      }
+      let [low, high]: [u64; 2] = cast(counts.sad_u8(zero));
+      total += (low + high) as usize;
+    }
+    total
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    scalar(haystack)
+  }
+}
+
+/// Index of the first place that `needle` appears in the haystack.
+///
+/// An empty needle is found at index 0.
+///
+/// The SIMD path checks 16 possible starting points at once by comparing the
+/// first byte of the needle at each point and the last byte of the needle at
+/// the matching end point. Only where both match does it compare the rest.
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+  let (&first, rest) = match needle.split_first() {
+    Some(split) => split,
+    None => return Some(0),
+  };
+  if rest.is_empty() {
+    return memchr(first, haystack);
+  }
+  if needle.len() > haystack.len() {
+    return None;
+  }
+  let last_offset = needle.len() - 1;
+  // every start index strictly less than this leaves room for the needle.
+  let starts = haystack.len() - last_offset;
+  let is_match = |p: usize| haystack[p..p + needle.len()] == *needle;
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut p = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let first_v = m128i::splat_i8(first as i8);
+    let last_v = m128i::splat_i8(needle[last_offset] as i8);
+    while p + 16 <= starts {
+      // `p + last_offset + 16 <= haystack.len()` here, so both loads always
+      // have 16 bytes to work with.
+      let a = m128i::load_from_slice(&haystack[p..]).unwrap();
+      let b = m128i::load_from_slice(&haystack[p + last_offset..]).unwrap();
+      let mut bits = (a.cmp_eq_i8(first_v) & b.cmp_eq_i8(last_v)).movemask_i8();
+      while bits != 0 {
+        let q = p + bits.trailing_zeros() as usize;
+        if is_match(q) {
+          return Some(q);
+        }
+        bits &= bits - 1;
+      }
+      p += 16;
+    }
+  }
+  (p..starts).find(|&q| is_match(q))
+}
+
+/// An iterator over the positions found by [`memchr_iter`], [`memchr2_iter`],
+/// and [`memchr3_iter`].
+#[derive(Debug, Clone)]
+pub struct Memchr<'h> {
+  needles: [u8; 3],
+  needle_count: usize,
+  haystack: &'h [u8],
+  position: usize,
+}
+
+impl<'h> Iterator for Memchr<'h> {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    let rest = self.haystack.get(self.position..)?;
+    let [n1, n2, n3] = self.needles;
+    let found = match self.needle_count {
+      1 => memchr(n1, rest),
+      2 => memchr2(n1, n2, rest),
+      _ => memchr3(n1, n2, n3, rest),
+    };
+    match found {
+      Some(i) => {
+        let out = self.position + i;
+        self.position = out + 1;
+        Some(out)
+      }
+      None => {
+        self.position = self.haystack.len() + 1;
+        None
+      }
+    }
+  }
+}
+
+/// Iterates over the index of every `needle` byte in the haystack.
+pub fn memchr_iter(needle: u8, haystack: &[u8]) -> Memchr<'_> {
+  Memchr {
+    needles: [needle; 3],
+    needle_count: 1,
+    haystack,
+    position: 0,
+  }
+}
+
+/// Iterates over the index of every byte in the haystack that's either of the
+/// needles.
+pub fn memchr2_iter(needle1: u8, needle2: u8, haystack: &[u8]) -> Memchr<'_> {
+  Memchr {
+    needles: [needle1, needle2, needle2],
+    needle_count: 2,
+    haystack,
+    position: 0,
+  }
+}
+
+/// Iterates over the index of every byte in the haystack that's any of the
+/// needles.
+pub fn memchr3_iter(needle1: u8, needle2: u8, needle3: u8, haystack: &[u8]) -> Memchr<'_> {
+  Memchr {
+    needles: [needle1, needle2, needle3],
+    needle_count: 3,
+    haystack,
+    position: 0,
+  }
+}
+
+/// An iterator over the positions found by [`find_iter`].
+#[derive(Debug, Clone)]
+pub struct FindIter<'h, 'n> {
+  haystack: &'h [u8],
+  needle: &'n [u8],
+  position: usize,
+}
+
+impl<'h, 'n> Iterator for FindIter<'h, 'n> {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    let rest = self.haystack.get(self.position..)?;
+    match find(rest, self.needle) {
+      Some(i) => {
+        let out = self.position + i;
+        // an empty needle would otherwise match at the same spot forever.
+        self.position = out + self.needle.len().max(1);
+        Some(out)
+      }
+      None => {
+        self.position = self.haystack.len() + 1;
+        None
+      }
+    }
+  }
+}
+
+/// Iterates over the index of every non-overlapping place that `needle`
+/// appears in the haystack, same as [`str::match_indices`] does.
+///
+/// An empty needle matches at every index, including the very end.
+pub fn find_iter<'h, 'n>(haystack: &'h [u8], needle: &'n [u8]) -> FindIter<'h, 'n> {
+  FindIter {
+    haystack,
+    needle,
+    position: 0,
+  }
+}
@@ -0,0 +1,415 @@
+//! Hex and base64 encoding and decoding into caller provided buffers.
+//!
+//! Hex works 16 input bytes at a time with SSE2. Base64 works 12 input bytes
+//! (16 characters) at a time, which needs SSSE3 to move the bytes around. In
+//! both cases the characters are mapped to and from their values with compares
+//! and adds instead of lookup tables. Leftovers at the end are done one at a
+//! time.
+//!
+//! When the SIMD decode sees any bad byte in a block it leaves that block to
+//! the scalar code, which is what finds the exact position for the error.
+
+#[cfg(target_feature = "sse2")]
+use core::convert::TryInto;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+/// An error from one of the encode or decode functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecError {
+  /// The output buffer is too small, it needs to be at least `needed` long.
+  OutputTooSmall {
+    /// The smallest output buffer length that would have worked.
+    needed: usize,
+  },
+  /// The input is a length that no encoding can be, such as an odd number of
+  /// hex digits.
+  InvalidLength {
+    /// The length of the input.
+    len: usize,
+  },
+  /// The byte at `index` of the input isn't allowed there.
+  ///
+  /// For base64 this includes padding in the wrong place, and a final
+  /// character with extra bits set that the decoded output can't hold.
+  InvalidByte {
+    /// Where in the input the bad byte is.
+    index: usize,
+    /// The bad byte itself.
+    byte: u8,
+  },
+}
+
+impl core::fmt::Display for CodecError {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    match self {
+      CodecError::OutputTooSmall { needed } => {
+        write!(f, "output buffer too small, {} bytes needed", needed)
+      }
+      CodecError::InvalidLength { len } => write!(f, "invalid input length {}", len),
+      CodecError::InvalidByte { index, byte } => {
+        write!(f, "invalid byte 0x{:02X} at index {}", byte, index)
+      }
+    }
+  }
+}
+
+/// Splits off the first `needed` bytes of the output buffer.
+fn output(dst: &mut [u8], needed: usize) -> Result<&mut [u8], CodecError> {
+  dst
+    .get_mut(..needed)
+    .ok_or(CodecError::OutputTooSmall { needed })
+}
+
+/// Turns the encoder output into a `&str`.
+fn ascii_str(bytes: &[u8]) -> &str {
+  debug_assert!(bytes.is_ascii());
+  // the encoders only ever write ASCII.
+  unsafe { core::str::from_utf8_unchecked(bytes) }
+}
+
+/// How long the hex encoding of `len` bytes is.
+pub const fn hex_encoded_len(len: usize) -> usize {
+  len * 2
+}
+
+/// Writes the lowercase hex digits for `src` to the start of `dst`, giving the
+/// written part back as a `&str`.
+///
+/// `dst` must be at least [`hex_encoded_len`] bytes.
+pub fn hex_encode<'d>(src: &[u8], dst: &'d mut [u8]) -> Result<&'d str, CodecError> {
+  hex_encode_with(src, dst, b'a')
+}
+
+/// As [`hex_encode`], but with uppercase digits.
+pub fn hex_encode_upper<'d>(src: &[u8], dst: &'d mut [u8]) -> Result<&'d str, CodecError> {
+  hex_encode_with(src, dst, b'A')
+}
+
+fn hex_encode_with<'d>(src: &[u8], dst: &'d mut [u8], ten: u8) -> Result<&'d str, CodecError> {
+  let dst = output(dst, hex_encoded_len(src.len()))?;
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let nibble = m128i::splat_i8(0x0F);
+    let nine = m128i::splat_i8(9);
+    let zero_char = m128i::splat_i8(b'0' as i8);
+    let letter_skip = m128i::splat_i8((ten - b'0' - 10) as i8);
+    let digits = |n: m128i| n.add_i8(zero_char).add_i8(n.cmp_gt_i8(nine) & letter_skip);
+    while i + 16 <= src.len() {
+      let v = m128i::load_from_slice(&src[i..]).unwrap();
+      let high = v.shr_logical_u16(4) & nibble;
+      let low = v & nibble;
+      let out = &mut dst[i * 2..i * 2 + 32];
+      digits(high.unpack_low_i8(low)).storeu((&mut out[..16]).try_into().unwrap());
+      digits(high.unpack_high_i8(low)).storeu((&mut out[16..]).try_into().unwrap());
+      i += 16;
+    }
+  }
+  let digit = |n: u8| if n < 10 { b'0' + n } else { ten + (n - 10) };
+  for (&b, out) in src[i..].iter().zip(dst[i * 2..].chunks_exact_mut(2)) {
+    out[0] = digit(b >> 4);
+    out[1] = digit(b & 0xF);
+  }
+  Ok(ascii_str(dst))
+}
+
+/// Decodes hex digits (either case) from `src` into the start of `dst`, giving
+/// the number of bytes written.
+///
+/// `dst` must be at least half as long as `src`.
+pub fn hex_decode(src: &[u8], dst: &mut [u8]) -> Result<usize, CodecError> {
+  if src.len() % 2 != 0 {
+    return Err(CodecError::InvalidLength { len: src.len() });
+  }
+  let dst = output(dst, src.len() / 2)?;
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let zero = m128i::zeroed();
+    let zero_char = m128i::splat_i8(b'0' as i8);
+    let a_char = m128i::splat_i8(b'a' as i8);
+    let case_bit = m128i::splat_i8(0x20);
+    let (nine, five, ten) = (m128i::splat_i8(9), m128i::splat_i8(5), m128i::splat_i8(10));
+    // gives the digit values, and a mask of the lanes that aren't hex digits.
+    let values = |c: m128i| {
+      let digit = c.sub_i8(zero_char);
+      let is_digit = digit.min_u8(nine).cmp_eq_i8(digit);
+      let letter = (c | case_bit).sub_i8(a_char);
+      let is_letter = letter.min_u8(five).cmp_eq_i8(letter);
+      let value = (digit & is_digit) | (letter.add_i8(ten) & is_letter);
+      (value, (is_digit | is_letter).cmp_eq_i8(zero))
+    };
+    // each `u16` lane holds a high nibble in its low byte and a low nibble in
+    // its high byte, which becomes one output byte.
+    let low_byte = m128i::splat_i16(0xF0);
+    let join = |v: m128i| (v.shl_i16(4) & low_byte) | v.shr_logical_u16(8);
+    while i + 32 <= src.len() {
+      let (a, a_bad) = values(m128i::load_from_slice(&src[i..]).unwrap());
+      let (b, b_bad) = values(m128i::load_from_slice(&src[i + 16..]).unwrap());
+      if (a_bad | b_bad).movemask_i8() != 0 {
+        break;
+      }
+      let out = join(a).saturating_pack_i16_u8(join(b));
+      out.storeu((&mut dst[i / 2..i / 2 + 16]).try_into().unwrap());
+      i += 32;
+    }
+  }
+  let value = |index: usize| {
+    let byte = src[index];
+    match byte {
+      b'0'..=b'9' => Ok(byte - b'0'),
+      b'a'..=b'f' => Ok(byte - b'a' + 10),
+      b'A'..=b'F' => Ok(byte - b'A' + 10),
+      _ => Err(CodecError::InvalidByte { index, byte }),
+    }
+  };
+  while i < src.len() {
+    dst[i / 2] = (value(i)? << 4) | value(i + 1)?;
+    i += 2;
+  }
+  Ok(dst.len())
+}
+
+/// The alphabets for base64, as given in RFC 4648.
+///
+/// Encoding can be with or without `=` padding. Decoding accepts either, but
+/// if there is padding it has to be the right amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64 {
+  /// Uses `+` and `/` for 62 and 63.
+  Standard,
+  /// Uses `-` and `_` for 62 and 63, so that the output can go in URLs and
+  /// file names.
+  UrlSafe,
+}
+
+impl Base64 {
+  /// How long the base64 encoding of `len` bytes is.
+  pub const fn encoded_len(len: usize, padded: bool) -> usize {
+    if padded {
+      len.div_ceil(3) * 4
+    } else {
+      len / 3 * 4 + [0, 2, 3][len % 3]
+    }
+  }
+
+  /// An output buffer this long is always enough to decode `encoded_len`
+  /// characters of base64.
+  pub const fn decoded_len_max(encoded_len: usize) -> usize {
+    encoded_len.div_ceil(4) * 3
+  }
+
+  /// The characters for 62 and 63.
+  fn extra_chars(self) -> (u8, u8) {
+    match self {
+      Base64::Standard => (b'+', b'/'),
+      Base64::UrlSafe => (b'-', b'_'),
+    }
+  }
+
+  fn char_for(self, sextet: u8) -> u8 {
+    let (c62, c63) = self.extra_chars();
+    match sextet {
+      0..=25 => b'A' + sextet,
+      26..=51 => b'a' + (sextet - 26),
+      52..=61 => b'0' + (sextet - 52),
+      62 => c62,
+      _ => c63,
+    }
+  }
+
+  fn sextet_for(self, c: u8) -> Option<u8> {
+    let (c62, c63) = self.extra_chars();
+    match c {
+      b'A'..=b'Z' => Some(c - b'A'),
+      b'a'..=b'z' => Some(c - b'a' + 26),
+      b'0'..=b'9' => Some(c - b'0' + 52),
+      _ if c == c62 => Some(62),
+      _ if c == c63 => Some(63),
+      _ => None,
+    }
+  }
+
+  /// Writes the padded base64 for `src` to the start of `dst`, giving the
+  /// written part back as a `&str`.
+  ///
+  /// `dst` must be at least [`encoded_len`](Base64::encoded_len) bytes.
+  pub fn encode<'d>(self, src: &[u8], dst: &'d mut [u8]) -> Result<&'d str, CodecError> {
+    self.encode_with(src, dst, true)
+  }
+
+  /// As [`encode`](Base64::encode), but without any `=` padding.
+  pub fn encode_unpadded<'d>(self, src: &[u8], dst: &'d mut [u8]) -> Result<&'d str, CodecError> {
+    self.encode_with(src, dst, false)
+  }
+
+  fn encode_with<'d>(
+    self,
+    src: &[u8],
+    dst: &'d mut [u8],
+    padded: bool,
+  ) -> Result<&'d str, CodecError> {
+    let dst = output(dst, Base64::encoded_len(src.len(), padded))?;
+    #[cfg_attr(not(target_feature = "ssse3"), allow(unused_mut))]
+    let mut i = 0;
+    #[cfg(target_feature = "ssse3")]
+    {
+      let (c62, c63) = self.extra_chars();
+      // each `u32` lane gets the bytes of one 3 byte group as `[b1, b0, b2, b1]`
+      let spread = m128i::setr_i8(1, 0, 2, 1, 4, 3, 5, 4, 7, 6, 8, 7, 10, 9, 11, 10);
+      let (mask_a, mul_a) = (m128i::splat_i32(0x0FC0_FC00), m128i::splat_i32(0x0400_0040));
+      let (mask_b, mul_b) = (m128i::splat_i32(0x003F_03F0), m128i::splat_i32(0x0100_0010));
+      let (s25, s51, s61, s62) = (
+        m128i::splat_i8(25),
+        m128i::splat_i8(51),
+        m128i::splat_i8(61),
+        m128i::splat_i8(62),
+      );
+      // start from 'A' and then add the gap to each following range of
+      // characters for every range boundary that the sextet is past.
+      let base = m128i::splat_i8(b'A' as i8);
+      let to_lower = m128i::splat_i8((b'a' - 26 - b'A') as i8);
+      let to_digit = m128i::splat_i8((b'0' as i8 - 52) - (b'a' as i8 - 26));
+      let to_62 = m128i::splat_i8((c62 as i8).wrapping_sub(62 + b'0' as i8 - 52));
+      let to_63 = m128i::splat_i8((c63 - c62 - 1) as i8);
+      // the load takes 16 bytes even though only 12 are used.
+      while i + 16 <= src.len() {
+        let v = m128i::load_from_slice(&src[i..])
+          .unwrap()
+          .shuffle_i8(spread);
+        let sextets = (v & mask_a).mul_high_u16(mul_a) | (v & mask_b).mul_low_i16(mul_b);
+        let chars = sextets
+          .add_i8(base)
+          .add_i8(sextets.cmp_gt_i8(s25) & to_lower)
+          .add_i8(sextets.cmp_gt_i8(s51) & to_digit)
+          .add_i8(sextets.cmp_gt_i8(s61) & to_62)
+          .add_i8(sextets.cmp_gt_i8(s62) & to_63);
+        let o = i / 3 * 4;
+        chars.storeu((&mut dst[o..o + 16]).try_into().unwrap());
+        i += 12;
+      }
+    }
+    let mut o = i / 3 * 4;
+    for group in src[i..].chunks(3) {
+      let mut bytes = [0_u8; 3];
+      bytes[..group.len()].copy_from_slice(group);
+      let n = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+      let used = group.len() + 1;
+      for k in 0..4 {
+        if k < used {
+          dst[o] = self.char_for((n >> (18 - 6 * k)) as u8 & 0x3F);
+        } else if padded {
+          dst[o] = b'=';
+        } else {
+          break;
+        }
+        o += 1;
+      }
+    }
+    Ok(ascii_str(dst))
+  }
+
+  /// Decodes base64, with or without padding, from `src` into the start of
+  /// `dst`, giving the number of bytes written.
+  ///
+  /// `dst` must be long enough for the exact decoded length, which is at
+  /// most [`decoded_len_max`](Base64::decoded_len_max).
+  pub fn decode(self, src: &[u8], dst: &mut [u8]) -> Result<usize, CodecError> {
+    let mut body = src;
+    let mut padding = 0;
+    while padding < 2 && body.last() == Some(&b'=') {
+      body = &body[..body.len() - 1];
+      padding += 1;
+    }
+    if (padding > 0 && src.len() % 4 != 0) || body.len() % 4 == 1 {
+      return Err(CodecError::InvalidLength { len: src.len() });
+    }
+    // any `=` still in the body is found as a bad character.
+    let leftover = body.len() % 4;
+    let dst = output(dst, body.len() / 4 * 3 + [0, 0, 1, 2][leftover])?;
+    #[cfg_attr(not(target_feature = "ssse3"), allow(unused_mut))]
+    let mut i = 0;
+    #[cfg(target_feature = "ssse3")]
+    {
+      let (c62, c63) = self.extra_chars();
+      let zero = m128i::zeroed();
+      let in_range = |c: m128i, low: u8, high: u8| {
+        c.max_u8(m128i::splat_i8(low as i8))
+          .min_u8(m128i::splat_i8(high as i8))
+          .cmp_eq_i8(c)
+      };
+      let add = |offset: i8| m128i::splat_i8(offset);
+      let (is_62, is_63) = (m128i::splat_i8(c62 as i8), m128i::splat_i8(c63 as i8));
+      let join_pairs = m128i::splat_i32(0x0140_0140);
+      let join_quads = m128i::splat_i32(0x0001_1000);
+      // each `u32` lane now holds 3 output bytes, backwards.
+      let gather = m128i::setr_i8(2, 1, 0, 6, 5, 4, 10, 9, 8, 14, 13, 12, -1, -1, -1, -1);
+      while i + 16 <= body.len() {
+        let c = m128i::load_from_slice(&body[i..]).unwrap();
+        let upper = in_range(c, b'A', b'Z');
+        let lower = in_range(c, b'a', b'z');
+        let digit = in_range(c, b'0', b'9');
+        let c62_mask = c.cmp_eq_i8(is_62);
+        let c63_mask = c.cmp_eq_i8(is_63);
+        if (upper | lower | digit | c62_mask | c63_mask)
+          .cmp_eq_i8(zero)
+          .movemask_i8()
+          != 0
+        {
+          break;
+        }
+        let offset = (upper & add(-(b'A' as i8)))
+          | (lower & add(26 - b'a' as i8))
+          | (digit & add(52 - b'0' as i8))
+          | (c62_mask & add(62_u8.wrapping_sub(c62) as i8))
+          | (c63_mask & add(63_u8.wrapping_sub(c63) as i8));
+        let sextets = c.add_i8(offset);
+        let bytes = sextets
+          .mul_hadd_saturating_u8_i8(join_pairs)
+          .mul_hadd_i16(join_quads)
+          .shuffle_i8(gather);
+        let mut out = [0_u8; 16];
+        bytes.storeu(&mut out);
+        let o = i / 4 * 3;
+        dst[o..o + 12].copy_from_slice(&out[..12]);
+        i += 16;
+      }
+    }
+    let sextet = |index: usize| {
+      let byte = body[index];
+      self
+        .sextet_for(byte)
+        .map(u32::from)
+        .ok_or(CodecError::InvalidByte { index, byte })
+    };
+    let mut o = i / 4 * 3;
+    while i < body.len() {
+      let used = (body.len() - i).min(4);
+      let mut n = 0;
+      for k in 0..used {
+        n |= sextet(i + k)? << (18 - 6 * k);
+      }
+      let out_len = used - 1;
+      if n << (8 * out_len) & 0xFF_FFFF != 0 {
+        // the last character has bits that don't fit in the output
+        let index = i + used - 1;
+        return Err(CodecError::InvalidByte {
+          index,
+          byte: body[index],
+        });
+      }
+      for k in 0..out_len {
+        dst[o + k] = (n >> (16 - 8 * k)) as u8;
+      }
+      o += out_len;
+      i += used;
+    }
+    Ok(dst.len())
+  }
+}
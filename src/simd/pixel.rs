@@ -0,0 +1,405 @@
+//! Pixel format conversions and blending for 8-bit color channels.
+//!
+//! Pixels are `[u8; 4]` values in the channel order given by each function's
+//! name, so `[r, g, b, a]` for RGBA8. Packed 16-bit formats are `u16` values
+//! with the first named channel in the highest bits:
+//!
+//! * RGB565 is `rrrrrggg_gggbbbbb`.
+//! * RGBA4444 is `rrrrgggg_bbbbaaaa`.
+//!
+//! Functions with a source and a destination work on as many pixels as fit in
+//! the shorter slice and return that count.
+//!
+//! Every division by 255 is rounded to nearest. With SSE2 this is done as
+//! `((x + 128) * 257) >> 16` on 16-bit lanes using `m128i::mul_high_u16`,
+//! which gives exactly the same result as `(x + 127) / 255` for every product
+//! of two `u8` values, so the SIMD and scalar paths always agree.
+
+#[cfg(target_feature = "sse2")]
+use crate::{try_cast_slice, try_cast_slice_mut};
+#[cfg(target_feature = "sse2")]
+use core::convert::TryInto;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+/// `x / 255`, rounded to nearest, for any `x` up to `255 * 255`.
+#[inline(always)]
+fn div255(x: u32) -> u32 {
+  ((x + 128) * 257) >> 16
+}
+
+/// u16x8 lanewise [`div255`].
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn div255_u16(x: m128i) -> m128i {
+  x.add_i16(m128i::splat_i16(128))
+    .mul_high_u16(m128i::splat_i16(257))
+}
+
+/// Splits 8 RGBA8 pixels into `r`, `g`, `b`, and `a` with one pixel per
+/// 16-bit lane.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn split_channels(p0: m128i, p1: m128i) -> [m128i; 4] {
+  let byte = m128i::splat_i32(0xFF);
+  let channel = |shift: u32| {
+    (p0.shr_logical_u32(shift) & byte).saturating_pack_i32_i16(p1.shr_logical_u32(shift) & byte)
+  };
+  [channel(0), channel(8), channel(16), channel(24)]
+}
+
+/// Joins 16-bit `r`, `g`, `b`, and `a` lanes back into 8 RGBA8 pixels.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn join_channels(r: m128i, g: m128i, b: m128i, a: m128i) -> [m128i; 2] {
+  let rg = r | g.shl_i16(8);
+  let ba = b | a.shl_i16(8);
+  [rg.unpack_low_i16(ba), rg.unpack_high_i16(ba)]
+}
+
+/// Multiplies each byte of `p` by the matching byte of `f`, divided by 255.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn scale_u8(p: m128i, f: m128i) -> m128i {
+  let zero = m128i::zeroed();
+  let low = div255_u16(p.unpack_low_i8(zero).mul_low_i16(f.unpack_low_i8(zero)));
+  let high = div255_u16(p.unpack_high_i8(zero).mul_low_i16(f.unpack_high_i8(zero)));
+  low.saturating_pack_i16_u8(high)
+}
+
+/// Swaps the red and blue channels of each pixel.
+///
+/// This turns RGBA8 into BGRA8, and BGRA8 back into RGBA8.
+pub fn swap_red_blue(pixels: &mut [[u8; 4]]) {
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let flat: &mut [u8] = try_cast_slice_mut(pixels).unwrap();
+    let green_alpha = m128i::splat_i32(0xFF00_FF00_u32 as i32);
+    while i + 4 <= flat.len() / 4 {
+      let out: &mut [u8; 16] = (&mut flat[i * 4..i * 4 + 16]).try_into().unwrap();
+      let p = m128i::load_unaligned(out);
+      let red_blue = green_alpha.andnot(p);
+      let swapped = red_blue.shl_i32(16) | red_blue.shr_logical_u32(16);
+      ((p & green_alpha) | swapped).storeu(out);
+      i += 4;
+    }
+  }
+  for p in &mut pixels[i..] {
+    p.swap(0, 2);
+  }
+}
+
+/// Expands RGB565 values into RGBA8 pixels with an alpha of 255.
+///
+/// Each channel's high bits are repeated into its new low bits, so 0 stays 0
+/// and the maximum value becomes 255.
+pub fn rgb565_to_rgba8(src: &[u16], dst: &mut [[u8; 4]]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let flat: &mut [u8] = try_cast_slice_mut(dst).unwrap();
+    let (five, six) = (m128i::splat_i16(0x1F), m128i::splat_i16(0x3F));
+    while i + 8 <= n {
+      let v = m128i::load_from_slice(&src[i..]).unwrap();
+      let r = v.shr_logical_u16(11);
+      let g = v.shr_logical_u16(5) & six;
+      let b = v & five;
+      let r = r.shl_i16(3) | r.shr_logical_u16(2);
+      let g = g.shl_i16(2) | g.shr_logical_u16(4);
+      let b = b.shl_i16(3) | b.shr_logical_u16(2);
+      let pixels = join_channels(r, g, b, m128i::splat_i16(0xFF));
+      for (v, chunk) in pixels
+        .iter()
+        .zip(flat[i * 4..i * 4 + 32].chunks_exact_mut(16))
+      {
+        v.storeu(chunk.try_into().unwrap());
+      }
+      i += 8;
+    }
+  }
+  for i in i..n {
+    let v = src[i];
+    let (r, g, b) = ((v >> 11) as u8, (v >> 5) as u8 & 0x3F, v as u8 & 0x1F);
+    dst[i] = [
+      (r << 3) | (r >> 2),
+      (g << 2) | (g >> 4),
+      (b << 3) | (b >> 2),
+      0xFF,
+    ];
+  }
+  n
+}
+
+/// Reduces RGBA8 pixels to RGB565 values, dropping the alpha channel.
+///
+/// Each channel is scaled to its new range and rounded to nearest.
+pub fn rgba8_to_rgb565(src: &[[u8; 4]], dst: &mut [u16]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let flat: &[u8] = try_cast_slice(src).unwrap();
+    let out: &mut [u8] = try_cast_slice_mut(dst).unwrap();
+    let (five, six) = (m128i::splat_i16(31), m128i::splat_i16(63));
+    while i + 8 <= n {
+      let p0 = m128i::load_from_slice(&flat[i * 4..]).unwrap();
+      let p1 = m128i::load_from_slice(&flat[i * 4 + 16..]).unwrap();
+      let [r, g, b, _] = split_channels(p0, p1);
+      let r = div255_u16(r.mul_low_i16(five));
+      let g = div255_u16(g.mul_low_i16(six));
+      let b = div255_u16(b.mul_low_i16(five));
+      let v = r.shl_i16(11) | g.shl_i16(5) | b;
+      v.storeu((&mut out[i * 2..i * 2 + 16]).try_into().unwrap());
+      i += 8;
+    }
+  }
+  for i in i..n {
+    let [r, g, b, _] = src[i];
+    let r = div255(u32::from(r) * 31) as u16;
+    let g = div255(u32::from(g) * 63) as u16;
+    let b = div255(u32::from(b) * 31) as u16;
+    dst[i] = (r << 11) | (g << 5) | b;
+  }
+  n
+}
+
+/// Expands RGBA4444 values into RGBA8 pixels.
+///
+/// Each 4-bit channel `c` becomes `c * 17`, so 0 stays 0 and 15 becomes 255.
+pub fn rgba4444_to_rgba8(src: &[u16], dst: &mut [[u8; 4]]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let flat: &mut [u8] = try_cast_slice_mut(dst).unwrap();
+    let nibble = m128i::splat_i16(0xF);
+    while i + 8 <= n {
+      let v = m128i::load_from_slice(&src[i..]).unwrap();
+      let expand = |shift: u32| {
+        let c = v.shr_logical_u16(shift) & nibble;
+        c.shl_i16(4) | c
+      };
+      let pixels = join_channels(expand(12), expand(8), expand(4), expand(0));
+      for (v, chunk) in pixels
+        .iter()
+        .zip(flat[i * 4..i * 4 + 32].chunks_exact_mut(16))
+      {
+        v.storeu(chunk.try_into().unwrap());
+      }
+      i += 8;
+    }
+  }
+  for i in i..n {
+    let v = src[i];
+    let expand = |shift: u16| ((v >> shift) & 0xF) as u8 * 17;
+    dst[i] = [expand(12), expand(8), expand(4), expand(0)];
+  }
+  n
+}
+
+/// Reduces RGBA8 pixels to RGBA4444 values.
+///
+/// Each channel is scaled to `0..=15` and rounded to nearest.
+pub fn rgba8_to_rgba4444(src: &[[u8; 4]], dst: &mut [u16]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let flat: &[u8] = try_cast_slice(src).unwrap();
+    let out: &mut [u8] = try_cast_slice_mut(dst).unwrap();
+    let fifteen = m128i::splat_i16(15);
+    while i + 8 <= n {
+      let p0 = m128i::load_from_slice(&flat[i * 4..]).unwrap();
+      let p1 = m128i::load_from_slice(&flat[i * 4 + 16..]).unwrap();
+      let [r, g, b, a] = split_channels(p0, p1);
+      let reduce = |c: m128i| div255_u16(c.mul_low_i16(fifteen));
+      let v = reduce(r).shl_i16(12) | reduce(g).shl_i16(8) | reduce(b).shl_i16(4) | reduce(a);
+      v.storeu((&mut out[i * 2..i * 2 + 16]).try_into().unwrap());
+      i += 8;
+    }
+  }
+  for i in i..n {
+    let reduce = |c: u8| div255(u32::from(c) * 15) as u16;
+    let [r, g, b, a] = src[i];
+    dst[i] = (reduce(r) << 12) | (reduce(g) << 8) | (reduce(b) << 4) | reduce(a);
+  }
+  n
+}
+
+/// Converts RGBA8 pixels from straight alpha to premultiplied alpha.
+///
+/// Each color channel `c` becomes `c * a / 255`, rounded to nearest. The alpha
+/// channel is unchanged.
+pub fn premultiply_alpha(pixels: &mut [[u8; 4]]) {
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let flat: &mut [u8] = try_cast_slice_mut(pixels).unwrap();
+    let opaque = m128i::splat_i32(0xFF00_0000_u32 as i32);
+    while i + 4 <= flat.len() / 4 {
+      let out: &mut [u8; 16] = (&mut flat[i * 4..i * 4 + 16]).try_into().unwrap();
+      let p = m128i::load_unaligned(out);
+      // scale the colors by `a` and the alpha by 255, which leaves it as is
+      let a = p.shr_logical_u32(24);
+      let factor = a | a.shl_i32(8) | a.shl_i32(16) | opaque;
+      scale_u8(p, factor).storeu(out);
+      i += 4;
+    }
+  }
+  for p in &mut pixels[i..] {
+    let a = u32::from(p[3]);
+    for c in &mut p[..3] {
+      *c = div255(u32::from(*c) * a) as u8;
+    }
+  }
+}
+
+/// Converts RGBA8 pixels from premultiplied alpha to straight alpha.
+///
+/// Each color channel `c` becomes `c * 255 / a`, rounded to nearest and
+/// clamped to 255. Pixels with an alpha of 0 become all 0. The alpha channel
+/// is unchanged.
+///
+/// Premultiplying loses precision at low alpha values, so this is not an exact
+/// inverse of [`premultiply_alpha`].
+pub fn unpremultiply_alpha(pixels: &mut [[u8; 4]]) {
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let flat: &mut [u8] = try_cast_slice_mut(pixels).unwrap();
+    let byte = m128i::splat_i32(0xFF);
+    let (max, half) = (m128::splat(255.0), m128::splat(0.5));
+    while i + 4 <= flat.len() / 4 {
+      let out: &mut [u8; 16] = (&mut flat[i * 4..i * 4 + 16]).try_into().unwrap();
+      let p = m128i::load_unaligned(out);
+      let a = p.shr_logical_u32(24);
+      let a_f = a.round_i32();
+      // `c * 255` is exact in `f32` and the division is correctly rounded, so
+      // adding one half and truncating gives the same result as the integer
+      // math in the scalar loop.
+      let channel = |shift: u32| {
+        let c = (p.shr_logical_u32(shift) & byte).round_i32();
+        ((c * max / a_f).min(max) + half).truncate_i32()
+      };
+      let rgb = channel(0) | channel(8).shl_i32(8) | channel(16).shl_i32(16);
+      let transparent = a.cmp_eq_i32(m128i::zeroed());
+      transparent.andnot(rgb | a.shl_i32(24)).storeu(out);
+      i += 4;
+    }
+  }
+  for p in &mut pixels[i..] {
+    let a = u32::from(p[3]);
+    for c in &mut p[..3] {
+      *c = (u32::from(*c) * 255 + a / 2)
+        .checked_div(a)
+        .map_or(0, |x| x.min(255) as u8);
+    }
+  }
+}
+
+/// Porter-Duff "source over destination" blending of premultiplied RGBA8
+/// pixels.
+///
+/// Each channel of `dst`, including alpha, becomes
+/// `src + dst * (255 - src_alpha) / 255`, with the division rounded to nearest
+/// and the sum saturating at 255.
+pub fn blend_over(src: &[[u8; 4]], dst: &mut [[u8; 4]]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let flat_src: &[u8] = try_cast_slice(src).unwrap();
+    let flat_dst: &mut [u8] = try_cast_slice_mut(dst).unwrap();
+    while i + 4 <= n {
+      let s = m128i::load_from_slice(&flat_src[i * 4..]).unwrap();
+      let out: &mut [u8; 16] = (&mut flat_dst[i * 4..i * 4 + 16]).try_into().unwrap();
+      let d = m128i::load_unaligned(out);
+      let inv_a = m128i::splat_i32(0xFF).sub_i32(s.shr_logical_u32(24));
+      let factor = inv_a | inv_a.shl_i32(8) | inv_a.shl_i32(16) | inv_a.shl_i32(24);
+      s.saturating_add_u8(scale_u8(d, factor)).storeu(out);
+      i += 4;
+    }
+  }
+  for i in i..n {
+    let inv_a = 255 - u32::from(src[i][3]);
+    for (d, s) in dst[i].iter_mut().zip(src[i].iter()) {
+      *d = s.saturating_add(div255(u32::from(*d) * inv_a) as u8);
+    }
+  }
+  n
+}
+
+/// Converts `u8` values into `f32` values in `0.0..=1.0` by dividing by 255.
+pub fn u8_to_unorm_f32(src: &[u8], dst: &mut [f32]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let (zero, max) = (m128i::zeroed(), m128::splat(255.0));
+    while i + 16 <= n {
+      let v = m128i::load_from_slice(&src[i..]).unwrap();
+      let (low, high) = (v.unpack_low_i8(zero), v.unpack_high_i8(zero));
+      let quads = [
+        low.unpack_low_i16(zero),
+        low.unpack_high_i16(zero),
+        high.unpack_low_i16(zero),
+        high.unpack_high_i16(zero),
+      ];
+      for (q, chunk) in quads.iter().zip(dst[i..i + 16].chunks_exact_mut(4)) {
+        (q.round_i32() / max).store_unaligned(chunk.try_into().unwrap());
+      }
+      i += 16;
+    }
+  }
+  for i in i..n {
+    dst[i] = f32::from(src[i]) / 255.0;
+  }
+  n
+}
+
+/// Converts `f32` values in `0.0..=1.0` into `u8` values by multiplying by
+/// 255.
+///
+/// Inputs are clamped to `0.0..=1.0` first and the result is rounded to
+/// nearest, with ties rounding up. NaN becomes 0.
+pub fn unorm_f32_to_u8(src: &[f32], dst: &mut [u8]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let (zero, one) = (m128::zeroed(), m128::splat(1.0));
+    let (max, half) = (m128::splat(255.0), m128::splat(0.5));
+    while i + 16 <= n {
+      // `max` returns its second argument when either one is NaN
+      let quad = |k: usize| {
+        let x = m128::load_from_slice(&src[i + k * 4..]).unwrap();
+        (x.max(zero).min(one) * max + half).truncate_i32()
+      };
+      let low = quad(0).saturating_pack_i32_i16(quad(1));
+      let high = quad(2).saturating_pack_i32_i16(quad(3));
+      low
+        .saturating_pack_i16_u8(high)
+        .storeu((&mut dst[i..i + 16]).try_into().unwrap());
+      i += 16;
+    }
+  }
+  for i in i..n {
+    dst[i] = (src[i].clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+  }
+  n
+}
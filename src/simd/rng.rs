@@ -0,0 +1,477 @@
+//! Pseudo-random number generators that run four streams at once.
+//!
+//! These are for simulations and other bulk noise, they are **not** suitable
+//! for cryptography.
+//!
+//! There are two generators, each in a single stream scalar form and a four
+//! lane form:
+//!
+//! * [`Xoshiro128PlusPlus`] and [`Xoshiro128PlusPlusX4`]
+//! * [`Pcg32`] (PCG-XSH-RR 64/32) and [`Pcg32X4`]
+//!
+//! Each lane of a four lane generator is exactly a scalar generator, so the
+//! output is the same on every target no matter which code path gets used.
+//! [`to_lanes`](Pcg32X4::to_lanes) and [`from_lanes`](Pcg32X4::from_lanes)
+//! move between the two forms at any point in a stream. With SSE2 the state
+//! lives in `m128i` values and there's `next_m128i`, `next_m128`, and
+//! `next_below_m128i` too.
+//!
+//! Every generator gives:
+//!
+//! * `u32` values over the full range.
+//! * `f32` values in `0.0..1.0`, from the top 24 bits of a `u32` so that every
+//!   value is equally likely and `1.0` is never hit.
+//! * `u32` values in `0..bound` without any bias, by multiplying and then
+//!   rejecting the few values that would make some results more likely (this
+//!   is Lemire's method). A rejection only redraws in the lanes that need it.
+//!
+//! ```rust
+//! use lokacore::simd::rng::*;
+//!
+//! let mut x4 = Pcg32X4::new(42);
+//! let mut lanes = x4.to_lanes();
+//! let v = x4.next_u32x4();
+//! for (lane, &x) in lanes.iter_mut().zip(&v) {
+//!   assert_eq!(lane.next_u32(), x);
+//! }
+//!
+//! let dice = x4.next_below_x4(6);
+//! assert!(dice.iter().all(|&d| d < 6));
+//! ```
+
+#[cfg(target_feature = "sse2")]
+use crate::cast;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::{arch::x86::*, shuffle_i32};
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::{arch::x86_64::*, shuffle_i32};
+
+/// The next output of a SplitMix64 generator, used to spread out seeds.
+#[inline]
+fn splitmix64(state: &mut u64) -> u64 {
+  *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+  let mut z = *state;
+  z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+  z ^ (z >> 31)
+}
+
+/// The top 24 bits of `x` as an `f32` in `0.0..1.0`.
+#[inline]
+fn unit_f32(x: u32) -> f32 {
+  (x >> 8) as f32 * (1.0 / 16_777_216.0)
+}
+
+macro_rules! scalar_common {
+  ($t:ty) => {
+    impl $t {
+      /// A uniform `f32` in `0.0..1.0`.
+      #[inline]
+      pub fn next_f32(&mut self) -> f32 {
+        unit_f32(self.next_u32())
+      }
+
+      /// A uniform `u32` in `0..bound`, without bias.
+      ///
+      /// A `bound` of 0 always gives 0.
+      #[inline]
+      pub fn next_below(&mut self, bound: u32) -> u32 {
+        let mut m = u64::from(self.next_u32()) * u64::from(bound);
+        if (m as u32) < bound {
+          let threshold = bound.wrapping_neg() % bound;
+          while (m as u32) < threshold {
+            m = u64::from(self.next_u32()) * u64::from(bound);
+          }
+        }
+        (m >> 32) as u32
+      }
+    }
+  };
+}
+
+/// The xoshiro128++ generator, one stream.
+///
+/// 128 bits of state and a period of `2^128 - 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xoshiro128PlusPlus {
+  s: [u32; 4],
+}
+
+impl Xoshiro128PlusPlus {
+  /// Makes a generator with the exact state given.
+  ///
+  /// The state must not be all zero, or the generator only ever gives 0.
+  #[inline]
+  pub const fn from_state(s: [u32; 4]) -> Self {
+    Self { s }
+  }
+
+  /// The current state.
+  #[inline]
+  pub const fn state(&self) -> [u32; 4] {
+    self.s
+  }
+
+  /// Makes a generator with the state filled in from `seed` by SplitMix64.
+  ///
+  /// Any seed is fine, including 0.
+  #[inline]
+  pub fn new(seed: u64) -> Self {
+    let mut sm = seed;
+    let (a, b) = (splitmix64(&mut sm), splitmix64(&mut sm));
+    Self::from_state([a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32])
+  }
+
+  /// A uniform `u32`.
+  #[inline]
+  pub fn next_u32(&mut self) -> u32 {
+    let s = &mut self.s;
+    let out = s[0].wrapping_add(s[3]).rotate_left(7).wrapping_add(s[0]);
+    let t = s[1] << 9;
+    s[2] ^= s[0];
+    s[3] ^= s[1];
+    s[1] ^= s[2];
+    s[0] ^= s[3];
+    s[2] ^= t;
+    s[3] = s[3].rotate_left(11);
+    out
+  }
+}
+scalar_common!(Xoshiro128PlusPlus);
+
+/// The PCG32 generator (PCG-XSH-RR with 64 bits of state), one stream.
+///
+/// Each stream number picks a different sequence, and each sequence has a
+/// period of `2^64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pcg32 {
+  state: u64,
+  inc: u64,
+}
+
+impl Pcg32 {
+  const MUL: u64 = 6_364_136_223_846_793_005;
+
+  /// Makes a generator from a seed and a stream number.
+  ///
+  /// This is the same setup as `pcg32_srandom_r` in the PCG reference code.
+  /// Only the low 63 bits of `stream` matter.
+  #[inline]
+  pub fn new(seed: u64, stream: u64) -> Self {
+    let mut pcg = Self {
+      state: 0,
+      inc: (stream << 1) | 1,
+    };
+    pcg.next_u32();
+    pcg.state = pcg.state.wrapping_add(seed);
+    pcg.next_u32();
+    pcg
+  }
+
+  /// A uniform `u32`.
+  #[inline]
+  pub fn next_u32(&mut self) -> u32 {
+    let old = self.state;
+    self.state = old.wrapping_mul(Self::MUL).wrapping_add(self.inc);
+    let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+    xorshifted.rotate_right((old >> 59) as u32)
+  }
+}
+scalar_common!(Pcg32);
+
+macro_rules! x4_common {
+  ($t:ty, $scalar:ty) => {
+    impl $t {
+      /// The next `u32` from each lane.
+      #[inline]
+      pub fn next_u32x4(&mut self) -> [u32; 4] {
+        #[cfg(target_feature = "sse2")]
+        {
+          cast(self.next_m128i())
+        }
+        #[cfg(not(target_feature = "sse2"))]
+        {
+          let l = &mut self.lanes;
+          [
+            l[0].next_u32(),
+            l[1].next_u32(),
+            l[2].next_u32(),
+            l[3].next_u32(),
+          ]
+        }
+      }
+
+      /// The next `f32` in `0.0..1.0` from each lane.
+      #[inline]
+      pub fn next_f32x4(&mut self) -> [f32; 4] {
+        #[cfg(target_feature = "sse2")]
+        {
+          cast(self.next_m128())
+        }
+        #[cfg(not(target_feature = "sse2"))]
+        {
+          let l = &mut self.lanes;
+          [
+            l[0].next_f32(),
+            l[1].next_f32(),
+            l[2].next_f32(),
+            l[3].next_f32(),
+          ]
+        }
+      }
+
+      /// The next `u32` in `0..bound` from each lane, without bias.
+      ///
+      /// A `bound` of 0 always gives 0.
+      #[inline]
+      pub fn next_below_x4(&mut self, bound: u32) -> [u32; 4] {
+        #[cfg(target_feature = "sse2")]
+        {
+          cast(self.next_below_m128i(bound))
+        }
+        #[cfg(not(target_feature = "sse2"))]
+        {
+          let l = &mut self.lanes;
+          [
+            l[0].next_below(bound),
+            l[1].next_below(bound),
+            l[2].next_below(bound),
+            l[3].next_below(bound),
+          ]
+        }
+      }
+
+      /// The next `f32` in `0.0..1.0` from each lane.
+      #[cfg(target_feature = "sse2")]
+      #[inline]
+      pub fn next_m128(&mut self) -> m128 {
+        let top = self.next_m128i().shr_logical_u32(8).round_i32();
+        top * m128::splat(1.0 / 16_777_216.0)
+      }
+
+      /// The next `u32` in `0..bound` from each lane, without bias.
+      ///
+      /// A `bound` of 0 always gives 0.
+      #[cfg(target_feature = "sse2")]
+      #[inline]
+      pub fn next_below_m128i(&mut self, bound: u32) -> m128i {
+        let x = self.next_m128i();
+        let b = m128i::splat_i32(bound as i32);
+        // the multiply only works on the even lanes, so the odd lanes go
+        // through a second one and the halves get put back together after.
+        let even = x.mul_low32_u64(b);
+        let odd = x.shr_logical_u64(32).mul_low32_u64(b);
+        let high_halves = m128i::set_i64(-1 << 32, -1 << 32);
+        let high = even.shr_logical_u64(32) | (odd & high_halves);
+        let low = high_halves.andnot(even) | odd.shl_i64(32);
+        // unsigned compare, by flipping the sign bits
+        let flip = m128i::splat_i32(i32::MIN);
+        if (low ^ flip).cmp_lt_i32(b ^ flip).movemask_i8() == 0 {
+          return high;
+        }
+        let threshold = bound.wrapping_neg() % bound;
+        let low: [u32; 4] = cast(low);
+        if low.iter().all(|&l| l >= threshold) {
+          return high;
+        }
+        // Rejections are rare, so those lanes just finish as scalars.
+        let mut out: [u32; 4] = cast(high);
+        let mut lanes = self.to_lanes();
+        for ((o, &l), lane) in out.iter_mut().zip(&low).zip(&mut lanes) {
+          if l < threshold {
+            *o = lane.next_below(bound);
+          }
+        }
+        *self = Self::from_lanes(lanes);
+        cast(out)
+      }
+    }
+
+    impl From<[$scalar; 4]> for $t {
+      #[inline]
+      fn from(lanes: [$scalar; 4]) -> Self {
+        Self::from_lanes(lanes)
+      }
+    }
+  };
+}
+
+/// Four xoshiro128++ streams, one per lane.
+///
+/// With SSE2 each of the four state words is an `m128i` holding that word for
+/// all four lanes.
+#[derive(Debug, Clone, Copy)]
+pub struct Xoshiro128PlusPlusX4 {
+  #[cfg(target_feature = "sse2")]
+  s: [m128i; 4],
+  #[cfg(not(target_feature = "sse2"))]
+  lanes: [Xoshiro128PlusPlus; 4],
+}
+
+impl Xoshiro128PlusPlusX4 {
+  /// Makes four streams where lane `k` is `Xoshiro128PlusPlus::new(seed + k)`
+  /// (with wrapping).
+  #[inline]
+  pub fn new(seed: u64) -> Self {
+    Self::from_lanes([
+      Xoshiro128PlusPlus::new(seed),
+      Xoshiro128PlusPlus::new(seed.wrapping_add(1)),
+      Xoshiro128PlusPlus::new(seed.wrapping_add(2)),
+      Xoshiro128PlusPlus::new(seed.wrapping_add(3)),
+    ])
+  }
+
+  /// Puts four scalar generators into the lanes.
+  #[inline]
+  pub fn from_lanes(lanes: [Xoshiro128PlusPlus; 4]) -> Self {
+    #[cfg(target_feature = "sse2")]
+    {
+      let word = |w: usize| cast([lanes[0].s[w], lanes[1].s[w], lanes[2].s[w], lanes[3].s[w]]);
+      Self {
+        s: [word(0), word(1), word(2), word(3)],
+      }
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      Self { lanes }
+    }
+  }
+
+  /// Takes the lanes apart into four scalar generators, which continue each
+  /// stream from where it is now.
+  #[inline]
+  pub fn to_lanes(&self) -> [Xoshiro128PlusPlus; 4] {
+    #[cfg(target_feature = "sse2")]
+    {
+      let w: [[u32; 4]; 4] = cast(self.s);
+      let lane = |k: usize| Xoshiro128PlusPlus::from_state([w[0][k], w[1][k], w[2][k], w[3][k]]);
+      [lane(0), lane(1), lane(2), lane(3)]
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      self.lanes
+    }
+  }
+
+  /// The next `u32` from each lane.
+  #[cfg(target_feature = "sse2")]
+  #[inline]
+  pub fn next_m128i(&mut self) -> m128i {
+    let rotl = |x: m128i, k: u32| x.shl_i32(k) | x.shr_logical_u32(32 - k);
+    let [s0, s1, s2, s3] = self.s;
+    let out = rotl(s0.add_i32(s3), 7).add_i32(s0);
+    let t = s1.shl_i32(9);
+    let s2 = s2 ^ s0;
+    let s3 = s3 ^ s1;
+    let s1 = s1 ^ s2;
+    let s0 = s0 ^ s3;
+    self.s = [s0, s1, s2 ^ t, rotl(s3, 11)];
+    out
+  }
+}
+x4_common!(Xoshiro128PlusPlusX4, Xoshiro128PlusPlus);
+
+/// Four PCG32 streams, one per lane.
+///
+/// With SSE2 the 64-bit states are kept in two `m128i` values (lanes 0 and 1,
+/// then lanes 2 and 3), and so are the increments.
+#[derive(Debug, Clone, Copy)]
+pub struct Pcg32X4 {
+  #[cfg(target_feature = "sse2")]
+  state: [m128i; 2],
+  #[cfg(target_feature = "sse2")]
+  inc: [m128i; 2],
+  #[cfg(not(target_feature = "sse2"))]
+  lanes: [Pcg32; 4],
+}
+
+impl Pcg32X4 {
+  /// Makes four streams where lane `k` is `Pcg32::new(seed, k)`.
+  #[inline]
+  pub fn new(seed: u64) -> Self {
+    Self::from_lanes([
+      Pcg32::new(seed, 0),
+      Pcg32::new(seed, 1),
+      Pcg32::new(seed, 2),
+      Pcg32::new(seed, 3),
+    ])
+  }
+
+  /// Puts four scalar generators into the lanes.
+  #[inline]
+  pub fn from_lanes(lanes: [Pcg32; 4]) -> Self {
+    #[cfg(target_feature = "sse2")]
+    {
+      let pair = |a: u64, b: u64| m128i::set_i64(b as i64, a as i64);
+      let [l0, l1, l2, l3] = lanes;
+      Self {
+        state: [pair(l0.state, l1.state), pair(l2.state, l3.state)],
+        inc: [pair(l0.inc, l1.inc), pair(l2.inc, l3.inc)],
+      }
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      Self { lanes }
+    }
+  }
+
+  /// Takes the lanes apart into four scalar generators, which continue each
+  /// stream from where it is now.
+  #[inline]
+  pub fn to_lanes(&self) -> [Pcg32; 4] {
+    #[cfg(target_feature = "sse2")]
+    {
+      let state: [u64; 4] = cast(self.state);
+      let inc: [u64; 4] = cast(self.inc);
+      let lane = |k: usize| Pcg32 {
+        state: state[k],
+        inc: inc[k],
+      };
+      [lane(0), lane(1), lane(2), lane(3)]
+    }
+    #[cfg(not(target_feature = "sse2"))]
+    {
+      self.lanes
+    }
+  }
+
+  /// The next `u32` from each lane.
+  #[cfg(target_feature = "sse2")]
+  #[inline]
+  pub fn next_m128i(&mut self) -> m128i {
+    let half = |old: m128i, inc: m128i| {
+      // SSE2 only multiplies 32-bit halves, so the 64-bit multiply is put
+      // together from the three partial products that reach the low 64 bits.
+      let mul_lo = m128i::set_i64(Pcg32::MUL as u32 as i64, Pcg32::MUL as u32 as i64);
+      let mul_hi = m128i::set_i64((Pcg32::MUL >> 32) as i64, (Pcg32::MUL >> 32) as i64);
+      let cross = old
+        .mul_low32_u64(mul_hi)
+        .add_i64(old.shr_logical_u64(32).mul_low32_u64(mul_lo));
+      let next = old
+        .mul_low32_u64(mul_lo)
+        .add_i64(cross.shl_i64(32))
+        .add_i64(inc);
+      // There's no per-lane shift, so the rotate is done as a multiply by `2^k`
+      // (with `k = -rot & 31`): the low half of the product is `x << k` and the
+      // high half is `x >> (32 - k)`. The `2^k` values come from building `f32`
+      // exponents and truncating those to integers, and `2^31` truncates to
+      // `i32::MIN`, which is the right bits anyway.
+      let xorshifted = (old.shr_logical_u64(18) ^ old).shr_logical_u64(27);
+      let k = m128i::zeroed().sub_i64(old.shr_logical_u64(59)) & m128i::set_i64(31, 31);
+      let pow2 = k
+        .add_i32(m128i::splat_i32(127))
+        .shl_i32(23)
+        .transmute_m128()
+        .truncate_i32();
+      let wide = xorshifted.mul_low32_u64(pow2);
+      (next, wide ^ wide.shr_logical_u64(32))
+    };
+    let (next_a, out_a) = half(self.state[0], self.inc[0]);
+    let (next_b, out_b) = half(self.state[1], self.inc[1]);
+    self.state = [next_a, next_b];
+    // the outputs are in the even lanes of each half
+    shuffle_i32!(out_a, 3, 1, 2, 0).unpack_low_i64(shuffle_i32!(out_b, 3, 1, 2, 0))
+  }
+}
+x4_common!(Pcg32X4, Pcg32);
@@ -0,0 +1,220 @@
+//! Prefix sums (also called scans) over slices.
+//!
+//! An inclusive prefix sum replaces each element with the sum of itself and
+//! every element before it. An exclusive prefix sum uses only the elements
+//! before it, so the first output is always 0:
+//!
+//! ```txt
+//! input:     [3, 1, 4, 1,  5]
+//! inclusive: [3, 4, 8, 9, 14]
+//! exclusive: [0, 3, 4, 8,  9]
+//! ```
+//!
+//! Exclusive sums are what you want for turning a histogram into the starting
+//! offset of each bucket, or the keep/drop flags of a compaction into output
+//! indexes.
+//!
+//! With SSE2 each group of 4 lanes is summed with two shift-and-add steps
+//! (shift the register up by one lane and add, then by two lanes and add), and
+//! then the running total of the groups before it is added to every lane.
+//!
+//! The `u8` to `u32` functions work on as many values as fit in the shorter
+//! slice and return that count.
+//!
+//! Integer sums wrap on overflow. The `f32` sums are added up in a different
+//! order than a simple loop would use, so the low bits of the results can be
+//! different from a sequential sum.
+
+#[cfg(target_feature = "sse2")]
+use crate::try_cast_slice_mut;
+#[cfg(target_feature = "sse2")]
+use crate::{byte_shift_left, shuffle128, shuffle_high_i16, shuffle_i32};
+#[cfg(target_feature = "sse2")]
+use core::convert::TryInto;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+/// Inclusive prefix sum of the 4 `i32` lanes.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn scan4_i32(x: m128i) -> m128i {
+  let x = x.add_i32(byte_shift_left!(x, 4));
+  x.add_i32(byte_shift_left!(x, 8))
+}
+
+/// Inclusive prefix sum of the 4 `f32` lanes.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn scan4_f32(x: m128) -> m128 {
+  let x = x + byte_shift_left!(x.transmute_m128i(), 4).transmute_m128();
+  x + byte_shift_left!(x.transmute_m128i(), 8).transmute_m128()
+}
+
+/// Inclusive prefix sum of the 8 `u16` lanes.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn scan8_u16(x: m128i) -> m128i {
+  let x = x.add_i16(byte_shift_left!(x, 2));
+  let x = x.add_i16(byte_shift_left!(x, 4));
+  x.add_i16(byte_shift_left!(x, 8))
+}
+
+fn scan_i32(data: &mut [i32], exclusive: bool) -> i32 {
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut total = 0_i32;
+  #[cfg(target_feature = "sse2")]
+  {
+    let n = data.len();
+    let bytes: &mut [u8] = try_cast_slice_mut(data).unwrap();
+    let mut carry = m128i::zeroed();
+    while i + 4 <= n {
+      let out: &mut [u8; 16] = (&mut bytes[i * 4..i * 4 + 16]).try_into().unwrap();
+      let sums = scan4_i32(m128i::load_unaligned(out));
+      let inclusive = sums.add_i32(carry);
+      if exclusive {
+        byte_shift_left!(sums, 4).add_i32(carry).storeu(out);
+      } else {
+        inclusive.storeu(out);
+      }
+      carry = shuffle_i32!(inclusive, 3, 3, 3, 3);
+      i += 4;
+    }
+    total = total.wrapping_add(carry.extract_lowest_i32());
+  }
+  for x in &mut data[i..] {
+    let next = total.wrapping_add(*x);
+    *x = if exclusive { total } else { next };
+    total = next;
+  }
+  total
+}
+
+/// Inclusive prefix sum of `i32` values, in place.
+///
+/// Returns the sum of all the values. The sums wrap on overflow.
+pub fn prefix_sum_i32(data: &mut [i32]) -> i32 {
+  scan_i32(data, false)
+}
+
+/// Exclusive prefix sum of `i32` values, in place.
+///
+/// Returns the sum of all the values, which is the exclusive sum that the
+/// next element would have gotten. The sums wrap on overflow.
+pub fn exclusive_prefix_sum_i32(data: &mut [i32]) -> i32 {
+  scan_i32(data, true)
+}
+
+fn scan_f32(data: &mut [f32], exclusive: bool) -> f32 {
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut total = 0.0_f32;
+  #[cfg(target_feature = "sse2")]
+  {
+    let n = data.len();
+    let mut carry = m128::zeroed();
+    while i + 4 <= n {
+      let out: &mut [f32; 4] = (&mut data[i..i + 4]).try_into().unwrap();
+      let sums = scan4_f32(m128::load_unaligned(out));
+      let inclusive = sums + carry;
+      if exclusive {
+        let before = byte_shift_left!(sums.transmute_m128i(), 4).transmute_m128();
+        (before + carry).store_unaligned(out);
+      } else {
+        inclusive.store_unaligned(out);
+      }
+      carry = shuffle128!(inclusive, inclusive, 3, 3, 3, 3);
+      i += 4;
+    }
+    total += carry.extract_low();
+  }
+  for x in &mut data[i..] {
+    let next = total + *x;
+    *x = if exclusive { total } else { next };
+    total = next;
+  }
+  total
+}
+
+/// Inclusive prefix sum of `f32` values, in place.
+///
+/// Returns the sum of all the values.
+pub fn prefix_sum_f32(data: &mut [f32]) -> f32 {
+  scan_f32(data, false)
+}
+
+/// Exclusive prefix sum of `f32` values, in place.
+///
+/// Returns the sum of all the values, which is the exclusive sum that the
+/// next element would have gotten.
+pub fn exclusive_prefix_sum_f32(data: &mut [f32]) -> f32 {
+  scan_f32(data, true)
+}
+
+fn scan_u8_to_u32(src: &[u8], dst: &mut [u32], exclusive: bool) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut total = 0_u32;
+  #[cfg(target_feature = "sse2")]
+  {
+    let out: &mut [u8] = try_cast_slice_mut(dst).unwrap();
+    let zero = m128i::zeroed();
+    let mut carry = m128i::zeroed();
+    while i + 16 <= n {
+      let v = m128i::load_from_slice(&src[i..]).unwrap();
+      let (low, high) = (v.unpack_low_i8(zero), v.unpack_high_i8(zero));
+      // 16 bytes sum to at most 4080, so `u16` lanes are enough until the
+      // running total gets added in.
+      let low_sums = scan8_u16(low);
+      let low_total = shuffle_i32!(shuffle_high_i16!(low_sums, 3, 3, 3, 3), 3, 3, 3, 3);
+      let high_sums = scan8_u16(high).add_i16(low_total);
+      let (low_out, high_out) = if exclusive {
+        (low_sums.sub_i16(low), high_sums.sub_i16(high))
+      } else {
+        (low_sums, high_sums)
+      };
+      let quads = [
+        low_out.unpack_low_i16(zero),
+        low_out.unpack_high_i16(zero),
+        high_out.unpack_low_i16(zero),
+        high_out.unpack_high_i16(zero),
+      ];
+      for (q, chunk) in quads
+        .iter()
+        .zip(out[i * 4..i * 4 + 64].chunks_exact_mut(16))
+      {
+        q.add_i32(carry).storeu(chunk.try_into().unwrap());
+      }
+      carry = carry.add_i32(shuffle_i32!(high_sums.unpack_high_i16(zero), 3, 3, 3, 3));
+      i += 16;
+    }
+    total = total.wrapping_add(carry.extract_lowest_i32() as u32);
+  }
+  for i in i..n {
+    let next = total.wrapping_add(u32::from(src[i]));
+    dst[i] = if exclusive { total } else { next };
+    total = next;
+  }
+  n
+}
+
+/// Inclusive prefix sum of `u8` values into `u32` outputs.
+///
+/// The sums wrap on overflow, which takes more than 16 million elements.
+pub fn prefix_sum_u8_to_u32(src: &[u8], dst: &mut [u32]) -> usize {
+  scan_u8_to_u32(src, dst, false)
+}
+
+/// Exclusive prefix sum of `u8` values into `u32` outputs.
+///
+/// The sums wrap on overflow, which takes more than 16 million elements.
+pub fn exclusive_prefix_sum_u8_to_u32(src: &[u8], dst: &mut [u32]) -> usize {
+  scan_u8_to_u32(src, dst, true)
+}
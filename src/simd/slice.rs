@@ -0,0 +1,327 @@
+//! Reductions over whole slices.
+//!
+//! Every function accepts any length and any alignment. The elements before
+//! the first 16-byte boundary and after the last full 16 bytes are handled one
+//! at a time, and the aligned middle goes through SIMD when it's available.
+//!
+//! The floating point sums add things up in a different order than a plain
+//! loop would, so expect them to differ from a scalar sum by a little rounding.
+
+#[cfg(target_feature = "sse")]
+use super::split_aligned;
+#[cfg(target_feature = "sse2")]
+use crate::cast;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse"))]
+use crate::arch::x86_64::*;
+
+#[cfg(target_feature = "sse")]
+#[inline(always)]
+fn reduce_add_m128(v: m128) -> f32 {
+  #[cfg(target_feature = "sse3")]
+  {
+    let h = v.horizontal_add(v);
+    h.horizontal_add(h).extract_low()
+  }
+  #[cfg(not(target_feature = "sse3"))]
+  {
+    let [a, b, c, d] = v.to_array();
+    (a + b) + (c + d)
+  }
+}
+
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn reduce_add_m128d(v: m128d) -> f64 {
+  #[cfg(target_feature = "sse3")]
+  {
+    v.horizontal_add(v).extract_low_f64()
+  }
+  #[cfg(not(target_feature = "sse3"))]
+  {
+    let [a, b] = v.to_array();
+    a + b
+  }
+}
+
+/// Sum of all the `f32` values.
+pub fn sum_f32(slice: &[f32]) -> f32 {
+  #[cfg(target_feature = "sse")]
+  {
+    let (head, body, tail) = split_aligned::<f32, m128>(slice);
+    let mut acc = m128::zeroed();
+    for &v in body {
+      acc += v;
+    }
+    head.iter().sum::<f32>() + reduce_add_m128(acc) + tail.iter().sum::<f32>()
+  }
+  #[cfg(not(target_feature = "sse"))]
+  {
+    slice.iter().sum()
+  }
+}
+
+/// Sum of all the `f64` values.
+pub fn sum_f64(slice: &[f64]) -> f64 {
+  #[cfg(target_feature = "sse2")]
+  {
+    let (head, body, tail) = split_aligned::<f64, m128d>(slice);
+    let mut acc = m128d::zeroed();
+    for &v in body {
+      acc += v;
+    }
+    head.iter().sum::<f64>() + reduce_add_m128d(acc) + tail.iter().sum::<f64>()
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    slice.iter().sum()
+  }
+}
+
+/// Mean of the `f32` values, or `None` if the slice is empty.
+pub fn mean_f32(slice: &[f32]) -> Option<f32> {
+  if slice.is_empty() {
+    None
+  } else {
+    Some(sum_f32(slice) / slice.len() as f32)
+  }
+}
+
+/// Mean of the `f64` values, or `None` if the slice is empty.
+pub fn mean_f64(slice: &[f64]) -> Option<f64> {
+  if slice.is_empty() {
+    None
+  } else {
+    Some(sum_f64(slice) / slice.len() as f64)
+  }
+}
+
+fn dot_f32_scalar(a: &[f32], b: &[f32]) -> f32 {
+  a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Dot product of the two slices.
+///
+/// Like [`Iterator::zip`], if one slice is longer its extra elements are
+/// ignored.
+pub fn dot_f32(a: &[f32], b: &[f32]) -> f32 {
+  let len = a.len().min(b.len());
+  let (a, b) = (&a[..len], &b[..len]);
+  #[cfg(target_feature = "sse")]
+  {
+    let a_chunks = a.chunks_exact(4);
+    let b_chunks = b.chunks_exact(4);
+    let (a_rem, b_rem) = (a_chunks.remainder(), b_chunks.remainder());
+    let mut acc = m128::zeroed();
+    for (x, y) in a_chunks.zip(b_chunks) {
+      acc += m128::load_from_slice(x).unwrap() * m128::load_from_slice(y).unwrap();
+    }
+    reduce_add_m128(acc) + dot_f32_scalar(a_rem, b_rem)
+  }
+  #[cfg(not(target_feature = "sse"))]
+  {
+    dot_f32_scalar(a, b)
+  }
+}
+
+#[cfg_attr(not(target_feature = "sse"), allow(unused_variables))]
+fn min_max_f32(
+  slice: &[f32],
+  identity: f32,
+  scalar: fn(f32, f32) -> f32,
+  #[cfg(target_feature = "sse")] simd: fn(m128, m128) -> m128,
+) -> Option<f32> {
+  if slice.is_empty() {
+    return None;
+  }
+  // `f32::min` and `f32::max` skip NaN, so starting the fold at NaN means that
+  // an all-NaN input stays NaN.
+  let fold = |s: &[f32], init: f32| s.iter().fold(init, |acc, &x| scalar(acc, x));
+  #[cfg(target_feature = "sse")]
+  {
+    let (head, body, tail) = split_aligned::<f32, m128>(slice);
+    let mut acc = m128::splat(identity);
+    let mut seen = m128::zeroed();
+    for &v in body {
+      // when either input is NaN the SSE min and max give back the second
+      // argument, so NaN values never get into `acc`.
+      acc = simd(v, acc);
+      seen |= v.cmp_eq(v);
+    }
+    let mut out = fold(head, f32::NAN);
+    if seen.move_mask() != 0 {
+      out = fold(&acc.to_array(), out);
+    }
+    Some(fold(tail, out))
+  }
+  #[cfg(not(target_feature = "sse"))]
+  {
+    Some(fold(slice, f32::NAN))
+  }
+}
+
+/// Minimum of the `f32` values, or `None` if the slice is empty.
+///
+/// NaN values are skipped the same as with [`f32::min`], so you only get NaN
+/// if every value is NaN.
+pub fn min_f32(slice: &[f32]) -> Option<f32> {
+  min_max_f32(
+    slice,
+    f32::INFINITY,
+    f32::min,
+    #[cfg(target_feature = "sse")]
+    m128::min,
+  )
+}
+
+/// Maximum of the `f32` values, or `None` if the slice is empty.
+///
+/// NaN values are skipped the same as with [`f32::max`], so you only get NaN
+/// if every value is NaN.
+pub fn max_f32(slice: &[f32]) -> Option<f32> {
+  min_max_f32(
+    slice,
+    f32::NEG_INFINITY,
+    f32::max,
+    #[cfg(target_feature = "sse")]
+    m128::max,
+  )
+}
+
+/// Offers `x` at index `i` as a new best, ties go to the lower index.
+fn consider(
+  best: Option<(f32, usize)>,
+  x: f32,
+  i: usize,
+  better: fn(f32, f32) -> bool,
+) -> Option<(f32, usize)> {
+  if x.is_nan() {
+    return best;
+  }
+  match best {
+    Some((b, j)) if !(better(x, b) || (x == b && i < j)) => best,
+    _ => Some((x, i)),
+  }
+}
+
+#[cfg_attr(not(target_feature = "sse2"), allow(unused_variables))]
+fn arg_best_f32(
+  slice: &[f32],
+  better: fn(f32, f32) -> bool,
+  #[cfg(target_feature = "sse2")] simd_better: fn(m128, m128) -> m128,
+) -> Option<usize> {
+  let scan = |best: Option<(f32, usize)>, s: &[f32], offset: usize| {
+    s.iter()
+      .enumerate()
+      .fold(best, |best, (i, &x)| consider(best, x, offset + i, better))
+  };
+  #[cfg(target_feature = "sse2")]
+  {
+    // lane indexes are tracked as `i32`.
+    if slice.len() <= i32::MAX as usize {
+      let (head, body, tail) = split_aligned::<f32, m128>(slice);
+      let mut best = scan(None, head, 0);
+      let mut acc = m128::splat(f32::NAN);
+      let mut acc_index = m128i::splat_i32(-1);
+      let mut index = m128i::setr_i32(0, 1, 2, 3).add_i32(m128i::splat_i32(head.len() as i32));
+      let four = m128i::splat_i32(4);
+      for &v in body {
+        // A lane takes `v` if it's strictly better, or if the lane is still
+        // empty (NaN) and `v` isn't NaN.
+        let take = simd_better(v, acc) | (acc.cmp_nan(acc) & v.cmp_eq(v));
+        acc = (take & v) | take.andnot(acc);
+        let take = take.transmute_m128i();
+        acc_index = (take & index) | take.andnot(acc_index);
+        index = index.add_i32(four);
+      }
+      let values = acc.to_array();
+      let indexes: [i32; 4] = cast(acc_index);
+      for (&x, &i) in values.iter().zip(indexes.iter()) {
+        best = consider(best, x, i as usize, better);
+      }
+      let best = scan(best, tail, slice.len() - tail.len());
+      return best.map(|(_, i)| i);
+    }
+  }
+  scan(None, slice, 0).map(|(_, i)| i)
+}
+
+/// Index of the minimum `f32` value.
+///
+/// NaN values are skipped and ties go to the lowest index. You get `None` if
+/// the slice is empty or all NaN.
+pub fn argmin_f32(slice: &[f32]) -> Option<usize> {
+  arg_best_f32(
+    slice,
+    |x, b| x < b,
+    #[cfg(target_feature = "sse2")]
+    m128::cmp_lt,
+  )
+}
+
+/// Index of the maximum `f32` value.
+///
+/// NaN values are skipped and ties go to the lowest index. You get `None` if
+/// the slice is empty or all NaN.
+pub fn argmax_f32(slice: &[f32]) -> Option<usize> {
+  arg_best_f32(
+    slice,
+    |x, b| x > b,
+    #[cfg(target_feature = "sse2")]
+    m128::cmp_gt,
+  )
+}
+
+/// Sum of all the `u8` values.
+///
+/// The SIMD path uses `m128i::sad_u8` against zero to add up 16 bytes at a
+/// time.
+pub fn sum_u8(slice: &[u8]) -> u64 {
+  let scalar = |s: &[u8]| s.iter().map(|&x| u64::from(x)).sum::<u64>();
+  #[cfg(target_feature = "sse2")]
+  {
+    let (head, body, tail) = split_aligned::<u8, m128i>(slice);
+    let zero = m128i::zeroed();
+    let mut acc = m128i::zeroed();
+    for &v in body {
+      acc = acc.add_i64(v.sad_u8(zero));
+    }
+    let [low, high]: [u64; 2] = cast(acc);
+    scalar(head) + low + high + scalar(tail)
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    scalar(slice)
+  }
+}
+
+/// Sum of all the `i16` values.
+///
+/// The SIMD path uses `m128i::mul_hadd_i16` with all-ones to add up eight
+/// values at a time.
+pub fn sum_i16(slice: &[i16]) -> i64 {
+  let scalar = |s: &[i16]| s.iter().map(|&x| i64::from(x)).sum::<i64>();
+  #[cfg(target_feature = "sse2")]
+  {
+    let (head, body, tail) = split_aligned::<i16, m128i>(slice);
+    let ones = m128i::splat_i16(1);
+    let mut total = scalar(head) + scalar(tail);
+    // each step adds at most 2^16 in magnitude to an `i32` lane, so we widen to
+    // `i64` well before that could overflow.
+    for block in body.chunks(1 << 14) {
+      let mut acc = m128i::zeroed();
+      for &v in block {
+        acc = acc.add_i32(v.mul_hadd_i16(ones));
+      }
+      let lanes: [i32; 4] = cast(acc);
+      total += lanes.iter().map(|&x| i64::from(x)).sum::<i64>();
+    }
+    total
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    scalar(slice)
+  }
+}
@@ -0,0 +1,325 @@
+//! Converting between "array of structs" and "struct of arrays" layouts.
+//!
+//! The `deinterleave` functions split a slice of records into one slice per
+//! field (a "channel"), and the `interleave` functions put them back together.
+//!
+//! Each function works on as many records as fit in the shortest of its
+//! slices, and returns that count. Any extra elements in the other slices are
+//! left alone.
+//!
+//! With SSE the `f32` functions move 4 records at a time using shuffles and a
+//! 4x4 transpose (`transpose4` in the `arch` module). The `u8` functions move
+//! 16 records at a time, which needs SSSE3 for the byte shuffle when splitting
+//! records apart (putting them together only needs SSE2).
+
+#[cfg(target_feature = "sse")]
+use crate::shuffle128;
+#[cfg(target_feature = "sse")]
+use crate::{try_cast_slice, try_cast_slice_mut};
+#[cfg(target_feature = "sse")]
+use core::convert::TryInto;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse"))]
+use crate::arch::x86_64::*;
+
+/// Splits `[x, y]` records into an `x` slice and a `y` slice.
+pub fn deinterleave2_f32(src: &[[f32; 2]], xs: &mut [f32], ys: &mut [f32]) -> usize {
+  let n = src.len().min(xs.len()).min(ys.len());
+  #[cfg_attr(not(target_feature = "sse"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse")]
+  {
+    let flat: &[f32] = try_cast_slice(src).unwrap();
+    while i + 4 <= n {
+      let v0 = m128::load_from_slice(&flat[i * 2..]).unwrap();
+      let v1 = m128::load_from_slice(&flat[i * 2 + 4..]).unwrap();
+      shuffle128!(v0, v1, 2, 0, 2, 0).store_unaligned((&mut xs[i..i + 4]).try_into().unwrap());
+      shuffle128!(v0, v1, 3, 1, 3, 1).store_unaligned((&mut ys[i..i + 4]).try_into().unwrap());
+      i += 4;
+    }
+  }
+  for i in i..n {
+    let [x, y] = src[i];
+    xs[i] = x;
+    ys[i] = y;
+  }
+  n
+}
+
+/// Joins an `x` slice and a `y` slice into `[x, y]` records.
+pub fn interleave2_f32(xs: &[f32], ys: &[f32], dst: &mut [[f32; 2]]) -> usize {
+  let n = dst.len().min(xs.len()).min(ys.len());
+  #[cfg_attr(not(target_feature = "sse"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse")]
+  {
+    let flat: &mut [f32] = try_cast_slice_mut(dst).unwrap();
+    while i + 4 <= n {
+      let x = m128::load_from_slice(&xs[i..]).unwrap();
+      let y = m128::load_from_slice(&ys[i..]).unwrap();
+      let out = &mut flat[i * 2..i * 2 + 8];
+      x.unpack_low(y)
+        .store_unaligned((&mut out[..4]).try_into().unwrap());
+      x.unpack_high(y)
+        .store_unaligned((&mut out[4..]).try_into().unwrap());
+      i += 4;
+    }
+  }
+  for i in i..n {
+    dst[i] = [xs[i], ys[i]];
+  }
+  n
+}
+
+/// Splits `[x, y, z]` records into `x`, `y`, and `z` slices.
+pub fn deinterleave3_f32(
+  src: &[[f32; 3]],
+  xs: &mut [f32],
+  ys: &mut [f32],
+  zs: &mut [f32],
+) -> usize {
+  let n = src.len().min(xs.len()).min(ys.len()).min(zs.len());
+  #[cfg_attr(not(target_feature = "sse"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse")]
+  {
+    let flat: &[f32] = try_cast_slice(src).unwrap();
+    while i + 4 <= n {
+      // in set order: [x1 z0 y0 x0], [y2 x2 z1 y1], [z3 y3 x3 z2]
+      let v0 = m128::load_from_slice(&flat[i * 3..]).unwrap();
+      let v1 = m128::load_from_slice(&flat[i * 3 + 4..]).unwrap();
+      let v2 = m128::load_from_slice(&flat[i * 3 + 8..]).unwrap();
+      // [x3 z2 y2 x2]
+      let t = shuffle128!(v1, v2, 1, 0, 3, 2);
+      let x = shuffle128!(v0, t, 3, 0, 3, 0);
+      // [z1 y1 z0 y0] and [z3 y3 y2 y2]
+      let t0 = shuffle128!(v0, v1, 1, 0, 2, 1);
+      let t1 = shuffle128!(v1, v2, 3, 2, 3, 3);
+      let y = shuffle128!(t0, t1, 2, 0, 2, 0);
+      // [z1 z1 z0 z0]
+      let t = shuffle128!(v0, v1, 1, 1, 2, 2);
+      let z = shuffle128!(t, v2, 3, 0, 2, 0);
+      x.store_unaligned((&mut xs[i..i + 4]).try_into().unwrap());
+      y.store_unaligned((&mut ys[i..i + 4]).try_into().unwrap());
+      z.store_unaligned((&mut zs[i..i + 4]).try_into().unwrap());
+      i += 4;
+    }
+  }
+  for i in i..n {
+    let [x, y, z] = src[i];
+    xs[i] = x;
+    ys[i] = y;
+    zs[i] = z;
+  }
+  n
+}
+
+/// Joins `x`, `y`, and `z` slices into `[x, y, z]` records.
+pub fn interleave3_f32(xs: &[f32], ys: &[f32], zs: &[f32], dst: &mut [[f32; 3]]) -> usize {
+  let n = dst.len().min(xs.len()).min(ys.len()).min(zs.len());
+  #[cfg_attr(not(target_feature = "sse"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse")]
+  {
+    let flat: &mut [f32] = try_cast_slice_mut(dst).unwrap();
+    while i + 4 <= n {
+      let x = m128::load_from_slice(&xs[i..]).unwrap();
+      let y = m128::load_from_slice(&ys[i..]).unwrap();
+      let z = m128::load_from_slice(&zs[i..]).unwrap();
+      // [x1 z0 y0 x0]
+      let xy = x.unpack_low(y);
+      let zx = shuffle128!(z, x, 1, 1, 0, 0);
+      let v0 = shuffle128!(xy, zx, 2, 0, 1, 0);
+      // [y2 x2 z1 y1]
+      let yz = shuffle128!(y, z, 1, 1, 1, 1);
+      let xy = x.unpack_high(y);
+      let v1 = shuffle128!(yz, xy, 1, 0, 2, 0);
+      // [z3 y3 x3 z2]
+      let zx = shuffle128!(z, x, 3, 3, 2, 2);
+      let yz = shuffle128!(y, z, 3, 3, 3, 3);
+      let v2 = shuffle128!(zx, yz, 2, 0, 2, 0);
+      let out = &mut flat[i * 3..i * 3 + 12];
+      v0.store_unaligned((&mut out[..4]).try_into().unwrap());
+      v1.store_unaligned((&mut out[4..8]).try_into().unwrap());
+      v2.store_unaligned((&mut out[8..]).try_into().unwrap());
+      i += 4;
+    }
+  }
+  for i in i..n {
+    dst[i] = [xs[i], ys[i], zs[i]];
+  }
+  n
+}
+
+/// Splits `[x, y, z, w]` records into `x`, `y`, `z`, and `w` slices.
+pub fn deinterleave4_f32(
+  src: &[[f32; 4]],
+  xs: &mut [f32],
+  ys: &mut [f32],
+  zs: &mut [f32],
+  ws: &mut [f32],
+) -> usize {
+  let n = src
+    .len()
+    .min(xs.len())
+    .min(ys.len())
+    .min(zs.len())
+    .min(ws.len());
+  #[cfg_attr(not(target_feature = "sse"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse")]
+  {
+    while i + 4 <= n {
+      let mut x = m128::load_unaligned(&src[i]);
+      let mut y = m128::load_unaligned(&src[i + 1]);
+      let mut z = m128::load_unaligned(&src[i + 2]);
+      let mut w = m128::load_unaligned(&src[i + 3]);
+      transpose4(&mut x, &mut y, &mut z, &mut w);
+      x.store_unaligned((&mut xs[i..i + 4]).try_into().unwrap());
+      y.store_unaligned((&mut ys[i..i + 4]).try_into().unwrap());
+      z.store_unaligned((&mut zs[i..i + 4]).try_into().unwrap());
+      w.store_unaligned((&mut ws[i..i + 4]).try_into().unwrap());
+      i += 4;
+    }
+  }
+  for i in i..n {
+    let [x, y, z, w] = src[i];
+    xs[i] = x;
+    ys[i] = y;
+    zs[i] = z;
+    ws[i] = w;
+  }
+  n
+}
+
+/// Joins `x`, `y`, `z`, and `w` slices into `[x, y, z, w]` records.
+pub fn interleave4_f32(
+  xs: &[f32],
+  ys: &[f32],
+  zs: &[f32],
+  ws: &[f32],
+  dst: &mut [[f32; 4]],
+) -> usize {
+  let n = dst
+    .len()
+    .min(xs.len())
+    .min(ys.len())
+    .min(zs.len())
+    .min(ws.len());
+  #[cfg_attr(not(target_feature = "sse"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse")]
+  {
+    while i + 4 <= n {
+      let mut r0 = m128::load_from_slice(&xs[i..]).unwrap();
+      let mut r1 = m128::load_from_slice(&ys[i..]).unwrap();
+      let mut r2 = m128::load_from_slice(&zs[i..]).unwrap();
+      let mut r3 = m128::load_from_slice(&ws[i..]).unwrap();
+      transpose4(&mut r0, &mut r1, &mut r2, &mut r3);
+      r0.store_unaligned(&mut dst[i]);
+      r1.store_unaligned(&mut dst[i + 1]);
+      r2.store_unaligned(&mut dst[i + 2]);
+      r3.store_unaligned(&mut dst[i + 3]);
+      i += 4;
+    }
+  }
+  for i in i..n {
+    dst[i] = [xs[i], ys[i], zs[i], ws[i]];
+  }
+  n
+}
+
+/// Splits `[r, g, b, a]` pixels into `r`, `g`, `b`, and `a` slices.
+pub fn deinterleave4_u8(
+  src: &[[u8; 4]],
+  rs: &mut [u8],
+  gs: &mut [u8],
+  bs: &mut [u8],
+  a_s: &mut [u8],
+) -> usize {
+  let n = src
+    .len()
+    .min(rs.len())
+    .min(gs.len())
+    .min(bs.len())
+    .min(a_s.len());
+  #[cfg_attr(not(target_feature = "ssse3"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "ssse3")]
+  {
+    let flat: &[u8] = try_cast_slice(src).unwrap();
+    // within each block, group the bytes of each channel into one `i32` lane
+    let gather = m128i::setr_i8(0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15);
+    while i + 16 <= n {
+      let block = |k: usize| {
+        m128i::load_from_slice(&flat[i * 4 + k * 16..])
+          .unwrap()
+          .shuffle_i8(gather)
+      };
+      let (t0, t1, t2, t3) = (block(0), block(1), block(2), block(3));
+      // then it's a 4x4 transpose of the `i32` lanes
+      let rg_low = t0.unpack_low_i32(t1);
+      let rg_high = t2.unpack_low_i32(t3);
+      let ba_low = t0.unpack_high_i32(t1);
+      let ba_high = t2.unpack_high_i32(t3);
+      let r = rg_low.unpack_low_i64(rg_high);
+      let g = rg_low.unpack_high_i64(rg_high);
+      let b = ba_low.unpack_low_i64(ba_high);
+      let a = ba_low.unpack_high_i64(ba_high);
+      r.storeu((&mut rs[i..i + 16]).try_into().unwrap());
+      g.storeu((&mut gs[i..i + 16]).try_into().unwrap());
+      b.storeu((&mut bs[i..i + 16]).try_into().unwrap());
+      a.storeu((&mut a_s[i..i + 16]).try_into().unwrap());
+      i += 16;
+    }
+  }
+  for i in i..n {
+    let [r, g, b, a] = src[i];
+    rs[i] = r;
+    gs[i] = g;
+    bs[i] = b;
+    a_s[i] = a;
+  }
+  n
+}
+
+/// Joins `r`, `g`, `b`, and `a` slices into `[r, g, b, a]` pixels.
+pub fn interleave4_u8(rs: &[u8], gs: &[u8], bs: &[u8], a_s: &[u8], dst: &mut [[u8; 4]]) -> usize {
+  let n = dst
+    .len()
+    .min(rs.len())
+    .min(gs.len())
+    .min(bs.len())
+    .min(a_s.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let flat: &mut [u8] = try_cast_slice_mut(dst).unwrap();
+    while i + 16 <= n {
+      let r = m128i::load_from_slice(&rs[i..]).unwrap();
+      let g = m128i::load_from_slice(&gs[i..]).unwrap();
+      let b = m128i::load_from_slice(&bs[i..]).unwrap();
+      let a = m128i::load_from_slice(&a_s[i..]).unwrap();
+      let (rg_low, rg_high) = (r.unpack_low_i8(g), r.unpack_high_i8(g));
+      let (ba_low, ba_high) = (b.unpack_low_i8(a), b.unpack_high_i8(a));
+      let out = &mut flat[i * 4..i * 4 + 64];
+      let pixels = [
+        rg_low.unpack_low_i16(ba_low),
+        rg_low.unpack_high_i16(ba_low),
+        rg_high.unpack_low_i16(ba_high),
+        rg_high.unpack_high_i16(ba_high),
+      ];
+      for (v, chunk) in pixels.iter().zip(out.chunks_exact_mut(16)) {
+        v.storeu(chunk.try_into().unwrap());
+      }
+      i += 16;
+    }
+  }
+  for i in i..n {
+    dst[i] = [rs[i], gs[i], bs[i], a_s[i]];
+  }
+  n
+}
@@ -0,0 +1,283 @@
+//! Sorting networks for small fixed size arrays, and a merge sort for slices
+//! that uses them.
+//!
+//! * [`sort4_i32`], [`sort8_i32`], and [`sort16_i32`] (and the `f32` versions)
+//!   sort a whole array with a fixed sequence of lanewise min/max steps, so
+//!   there are no data dependent branches. With SSE2 that's a bitonic network
+//!   on `m128i` registers.
+//! * [`sort_i32`] and [`sort_f32`] sort any slice by first sorting blocks of
+//!   16 with [`sort16_i32`], and then merging the blocks. There's no allocator
+//!   in `core`, so you pass in a scratch slice for the merges.
+//!
+//! The `f32` functions sort by [`f32::total_cmp`]:
+//!
+//! `-NaN < -inf < ... < -0.0 < 0.0 < ... < inf < NaN`
+//!
+//! A NaN with the sign bit set goes first and one without goes last, and
+//! `-0.0` goes before `0.0`. This is done by sorting the bits of each value as
+//! an integer key that has the same order, and every value comes out with the
+//! exact same bits that it went in with.
+
+use crate::try_cast_slice_mut;
+use core::convert::TryInto;
+
+#[cfg(target_feature = "sse2")]
+use crate::{cast, shuffle_i32};
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+/// Flips the bits of negative floats so that comparing them as `i32` gives
+/// the same order as [`f32::total_cmp`]. It's its own inverse.
+#[inline(always)]
+fn total_order_key(bits: i32) -> i32 {
+  bits ^ (((bits >> 31) as u32) >> 1) as i32
+}
+
+/// i32x4 lanewise [`total_order_key`].
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn total_order_keys(v: m128i) -> m128i {
+  v ^ v.shr_arith_i32(31).shr_logical_u32(1)
+}
+
+/// The lanewise `(min, max)` of two vectors.
+///
+/// SSE2 only has `min_i16` and `max_i16` for integer lanes, so for `i32` lanes
+/// this is a compare and a blend. The `f32` sorts go through here too, on their
+/// [`total_order_key`]s, rather than using `m128::min` and `m128::max`. Those
+/// aren't a total order: they treat `-0.0` and `0.0` as equal, and when either
+/// input is NaN they give back the second one for both the min and the max,
+/// which would duplicate one value and lose the other.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn min_max(a: m128i, b: m128i) -> (m128i, m128i) {
+  let swap = (a ^ b) & a.cmp_gt_i32(b);
+  (a ^ swap, b ^ swap)
+}
+
+/// Compares each lane with a lane of `partner`, and keeps the larger value in
+/// the lanes set in `take_max` and the smaller value in the rest.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn exchange(v: m128i, partner: m128i, take_max: m128i) -> m128i {
+  let (min, max) = min_max(v, partner);
+  (max & take_max) | take_max.andnot(min)
+}
+
+/// Sorts a bitonic vector.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn merge4(v: m128i) -> m128i {
+  let v = exchange(v, shuffle_i32!(v, 1, 0, 3, 2), m128i::set_i32(-1, -1, 0, 0));
+  exchange(v, shuffle_i32!(v, 2, 3, 0, 1), m128i::set_i32(-1, 0, -1, 0))
+}
+
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn sort4(v: m128i) -> m128i {
+  // make the low pair ascending and the high pair descending, which is bitonic
+  merge4(exchange(
+    v,
+    shuffle_i32!(v, 2, 3, 0, 1),
+    m128i::set_i32(0, -1, -1, 0),
+  ))
+}
+
+/// Merges two sorted vectors into one sorted run of 8.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn merge8(a: m128i, b: m128i) -> [m128i; 2] {
+  let (min, max) = min_max(a, shuffle_i32!(b, 0, 1, 2, 3));
+  [merge4(min), merge4(max)]
+}
+
+/// Sorts a bitonic run of 8.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn merge8_bitonic(a: m128i, b: m128i) -> [m128i; 2] {
+  let (min, max) = min_max(a, b);
+  [merge4(min), merge4(max)]
+}
+
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn sort8([a, b]: [m128i; 2]) -> [m128i; 2] {
+  merge8(sort4(a), sort4(b))
+}
+
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+fn sort16([a, b, c, d]: [m128i; 4]) -> [m128i; 4] {
+  let [a, b] = sort8([a, b]);
+  let [c, d] = sort8([c, d]);
+  // `(a, b)` against `(c, d)` reversed
+  let (low0, high0) = min_max(a, shuffle_i32!(d, 0, 1, 2, 3));
+  let (low1, high1) = min_max(b, shuffle_i32!(c, 0, 1, 2, 3));
+  let [a, b] = merge8_bitonic(low0, low1);
+  let [c, d] = merge8_bitonic(high0, high1);
+  [a, b, c, d]
+}
+
+/// Sorts 4 `i32` values.
+#[inline]
+pub fn sort4_i32(arr: &mut [i32; 4]) {
+  #[cfg(target_feature = "sse2")]
+  {
+    *arr = cast(sort4(cast(*arr)));
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    arr.sort_unstable();
+  }
+}
+
+/// Sorts 8 `i32` values.
+#[inline]
+pub fn sort8_i32(arr: &mut [i32; 8]) {
+  #[cfg(target_feature = "sse2")]
+  {
+    *arr = cast(sort8(cast(*arr)));
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    arr.sort_unstable();
+  }
+}
+
+/// Sorts 16 `i32` values.
+#[inline]
+pub fn sort16_i32(arr: &mut [i32; 16]) {
+  #[cfg(target_feature = "sse2")]
+  {
+    *arr = cast(sort16(cast(*arr)));
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    arr.sort_unstable();
+  }
+}
+
+/// Sorts 4 `f32` values by [`f32::total_cmp`].
+#[inline]
+pub fn sort4_f32(arr: &mut [f32; 4]) {
+  #[cfg(target_feature = "sse2")]
+  {
+    *arr = cast(total_order_keys(sort4(total_order_keys(cast(*arr)))));
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    arr.sort_unstable_by(f32::total_cmp);
+  }
+}
+
+/// Sorts 8 `f32` values by [`f32::total_cmp`].
+#[inline]
+pub fn sort8_f32(arr: &mut [f32; 8]) {
+  #[cfg(target_feature = "sse2")]
+  {
+    let [a, b]: [m128i; 2] = cast(*arr);
+    let [a, b] = sort8([total_order_keys(a), total_order_keys(b)]);
+    *arr = cast([total_order_keys(a), total_order_keys(b)]);
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    arr.sort_unstable_by(f32::total_cmp);
+  }
+}
+
+/// Sorts 16 `f32` values by [`f32::total_cmp`].
+#[inline]
+pub fn sort16_f32(arr: &mut [f32; 16]) {
+  #[cfg(target_feature = "sse2")]
+  {
+    let mut v: [m128i; 4] = cast(*arr);
+    v.iter_mut().for_each(|v| *v = total_order_keys(*v));
+    let mut v = sort16(v);
+    v.iter_mut().for_each(|v| *v = total_order_keys(*v));
+    *arr = cast(v);
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    arr.sort_unstable_by(f32::total_cmp);
+  }
+}
+
+/// Merges the sorted runs `left` and `right` into `out`.
+fn merge(left: &[i32], right: &[i32], out: &mut [i32]) {
+  let (mut l, mut r) = (0, 0);
+  for o in out.iter_mut() {
+    let take_left = r == right.len() || (l < left.len() && left[l] <= right[r]);
+    if take_left {
+      *o = left[l];
+      l += 1;
+    } else {
+      *o = right[r];
+      r += 1;
+    }
+  }
+}
+
+/// Sorts a slice of `i32` values.
+///
+/// `scratch` is used as working space during the merges, and is left holding
+/// unspecified values.
+///
+/// ## Panics
+/// * If `scratch` is shorter than `data`.
+pub fn sort_i32(data: &mut [i32], scratch: &mut [i32]) {
+  const BLOCK: usize = 16;
+  let n = data.len();
+  assert!(scratch.len() >= n, "sort_i32: scratch is shorter than data");
+  let scratch = &mut scratch[..n];
+
+  let mut blocks = data.chunks_exact_mut(BLOCK);
+  for block in &mut blocks {
+    sort16_i32(block.try_into().unwrap());
+  }
+  let tail = blocks.into_remainder();
+  if !tail.is_empty() {
+    // padding with the largest value keeps the real values at the front
+    let mut padded = [i32::MAX; BLOCK];
+    padded[..tail.len()].copy_from_slice(tail);
+    sort16_i32(&mut padded);
+    tail.copy_from_slice(&padded[..tail.len()]);
+  }
+
+  // merge runs back and forth between the two buffers
+  let (mut src, mut dst) = (data, scratch);
+  let mut in_scratch = false;
+  let mut width = BLOCK;
+  while width < n {
+    for (s, d) in src.chunks(width * 2).zip(dst.chunks_mut(width * 2)) {
+      let (left, right) = s.split_at(width.min(s.len()));
+      merge(left, right, d);
+    }
+    core::mem::swap(&mut src, &mut dst);
+    in_scratch = !in_scratch;
+    width *= 2;
+  }
+  if in_scratch {
+    dst.copy_from_slice(src);
+  }
+}
+
+/// Sorts a slice of `f32` values by [`f32::total_cmp`].
+///
+/// `scratch` is used as working space during the merges, and is left holding
+/// unspecified values.
+///
+/// ## Panics
+/// * If `scratch` is shorter than `data`.
+pub fn sort_f32(data: &mut [f32], scratch: &mut [f32]) {
+  assert!(
+    scratch.len() >= data.len(),
+    "sort_f32: scratch is shorter than data"
+  );
+  let keys: &mut [i32] = try_cast_slice_mut(data).unwrap();
+  keys.iter_mut().for_each(|k| *k = total_order_key(*k));
+  sort_i32(keys, try_cast_slice_mut(scratch).unwrap());
+  keys.iter_mut().for_each(|k| *k = total_order_key(*k));
+}
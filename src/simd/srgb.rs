@@ -0,0 +1,475 @@
+//! sRGB transfer functions, for moving colors between the sRGB encoding that
+//! images are stored in and the linear values that blending and lighting math
+//! should be done with.
+//!
+//! * [`srgb_to_linear`] and [`linear_to_srgb`] work on single `f32` values.
+//!   With SSE2 there's also `m128::srgb_to_linear` and
+//!   `m128::linear_to_srgb`, which give the same results for each lane.
+//! * [`srgb_u8_to_linear`] and [`linear_to_srgb_u8`] convert whole slices of
+//!   8-bit channels. They work on as many values as fit in the shorter slice
+//!   and return that count.
+//!
+//! The curves are the piecewise sRGB curves, with the power part done as
+//! `exp2(log2(x) * k)` using polynomials instead of calling `powf`. Compared to
+//! the exact curves, for inputs in `0.0..=1.0`:
+//!
+//! * `srgb_to_linear` has a relative error below `1e-6`.
+//! * `linear_to_srgb` has an absolute error below `3e-7`.
+//! * `srgb_u8_to_linear` uses a lookup table, so each output is the exact
+//!   curve value rounded to the nearest `f32`.
+//! * `linear_to_srgb_u8` is off from the exact curve by at most `0.5001` of a
+//!   step, so it only differs from a correctly rounded result when the exact
+//!   value is within `0.0001` of halfway between two steps.
+
+use core::f32::consts::{LN_2, LOG2_E};
+
+#[cfg(target_feature = "sse2")]
+use core::convert::TryInto;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+/// Below this an sRGB value is on the linear part of the curve.
+const DECODE_KNEE: f32 = 0.04045;
+
+/// Below this a linear value is on the linear part of the curve.
+const ENCODE_KNEE: f32 = 0.003_130_8;
+
+/// `atanh(s) / s` as a series in `s * s`.
+const ATANH_SERIES: [f32; 6] = [1.0, 1.0 / 3.0, 1.0 / 5.0, 1.0 / 7.0, 1.0 / 9.0, 1.0 / 11.0];
+
+/// `e^g` as a series in `g`.
+const EXP_SERIES: [f32; 10] = [
+  1.0,
+  1.0,
+  1.0 / 2.0,
+  1.0 / 6.0,
+  1.0 / 24.0,
+  1.0 / 120.0,
+  1.0 / 720.0,
+  1.0 / 5040.0,
+  1.0 / 40320.0,
+  1.0 / 362_880.0,
+];
+
+/// The exact sRGB to linear curve for each `u8` value.
+static DECODE_TABLE: [f32; 256] = [
+  0.0,
+  0.000303527,
+  0.000607054,
+  0.000910581,
+  0.001214108,
+  0.001517635,
+  0.001821162,
+  0.0021246888,
+  0.002428216,
+  0.0027317428,
+  0.00303527,
+  0.0033465358,
+  0.0036765074,
+  0.004024717,
+  0.004391442,
+  0.0047769533,
+  0.0051815165,
+  0.0056053917,
+  0.006048833,
+  0.0065120906,
+  0.00699541,
+  0.007499032,
+  0.008023193,
+  0.008568126,
+  0.009134059,
+  0.009721218,
+  0.010329823,
+  0.010960094,
+  0.011612245,
+  0.012286488,
+  0.0129830325,
+  0.013702083,
+  0.014443844,
+  0.015208514,
+  0.015996294,
+  0.016807375,
+  0.017641954,
+  0.01850022,
+  0.019382361,
+  0.020288562,
+  0.02121901,
+  0.022173885,
+  0.023153367,
+  0.024157632,
+  0.02518686,
+  0.026241222,
+  0.027320892,
+  0.02842604,
+  0.029556835,
+  0.030713445,
+  0.031896032,
+  0.033104766,
+  0.034339808,
+  0.035601314,
+  0.03688945,
+  0.038204372,
+  0.039546236,
+  0.0409152,
+  0.04231141,
+  0.04373503,
+  0.045186203,
+  0.046665087,
+  0.048171826,
+  0.049706567,
+  0.051269457,
+  0.052860647,
+  0.054480277,
+  0.05612849,
+  0.05780543,
+  0.059511237,
+  0.061246052,
+  0.063010015,
+  0.064803265,
+  0.06662594,
+  0.06847817,
+  0.070360094,
+  0.07227185,
+  0.07421357,
+  0.07618538,
+  0.07818742,
+  0.08021982,
+  0.08228271,
+  0.08437621,
+  0.08650046,
+  0.08865558,
+  0.09084171,
+  0.093058966,
+  0.09530747,
+  0.09758735,
+  0.099898726,
+  0.10224173,
+  0.104616486,
+  0.107023105,
+  0.10946171,
+  0.11193243,
+  0.114435375,
+  0.116970666,
+  0.11953843,
+  0.122138776,
+  0.12477182,
+  0.12743768,
+  0.13013647,
+  0.13286832,
+  0.13563333,
+  0.13843161,
+  0.14126329,
+  0.14412847,
+  0.14702727,
+  0.14995979,
+  0.15292615,
+  0.15592647,
+  0.15896083,
+  0.16202937,
+  0.1651322,
+  0.1682694,
+  0.17144111,
+  0.1746474,
+  0.17788842,
+  0.18116425,
+  0.18447499,
+  0.18782078,
+  0.19120169,
+  0.19461784,
+  0.19806932,
+  0.20155625,
+  0.20507874,
+  0.20863687,
+  0.21223076,
+  0.2158605,
+  0.2195262,
+  0.22322796,
+  0.22696587,
+  0.23074006,
+  0.23455058,
+  0.23839757,
+  0.24228112,
+  0.24620132,
+  0.25015828,
+  0.2541521,
+  0.25818285,
+  0.26225066,
+  0.2663556,
+  0.2704978,
+  0.2746773,
+  0.27889428,
+  0.28314874,
+  0.28744084,
+  0.29177064,
+  0.29613826,
+  0.30054379,
+  0.3049873,
+  0.30946892,
+  0.31398872,
+  0.31854677,
+  0.3231432,
+  0.3277781,
+  0.33245152,
+  0.33716363,
+  0.34191442,
+  0.34670407,
+  0.3515326,
+  0.35640013,
+  0.3613068,
+  0.3662526,
+  0.3712377,
+  0.37626213,
+  0.38132602,
+  0.38642943,
+  0.39157248,
+  0.39675522,
+  0.40197778,
+  0.4072402,
+  0.4125426,
+  0.41788507,
+  0.42326766,
+  0.4286905,
+  0.43415365,
+  0.43965718,
+  0.4452012,
+  0.4507858,
+  0.45641103,
+  0.462077,
+  0.4677838,
+  0.47353148,
+  0.47932017,
+  0.48514995,
+  0.49102086,
+  0.49693298,
+  0.5028865,
+  0.50888133,
+  0.5149177,
+  0.52099556,
+  0.5271151,
+  0.5332764,
+  0.5394795,
+  0.54572445,
+  0.55201143,
+  0.5583404,
+  0.5647115,
+  0.57112485,
+  0.57758045,
+  0.58407843,
+  0.59061885,
+  0.59720176,
+  0.60382736,
+  0.61049557,
+  0.6172066,
+  0.6239604,
+  0.63075715,
+  0.63759685,
+  0.6444797,
+  0.65140563,
+  0.65837485,
+  0.6653873,
+  0.67244315,
+  0.6795425,
+  0.6866853,
+  0.69387174,
+  0.7011019,
+  0.70837575,
+  0.7156935,
+  0.7230551,
+  0.73046076,
+  0.7379104,
+  0.7454042,
+  0.7529422,
+  0.7605245,
+  0.76815116,
+  0.7758222,
+  0.7835378,
+  0.7912979,
+  0.7991027,
+  0.80695224,
+  0.8148466,
+  0.82278574,
+  0.8307699,
+  0.838799,
+  0.8468732,
+  0.8549926,
+  0.8631572,
+  0.8713671,
+  0.8796224,
+  0.8879231,
+  0.8962694,
+  0.9046612,
+  0.91309863,
+  0.92158186,
+  0.9301109,
+  0.9386857,
+  0.9473065,
+  0.9559733,
+  0.9646863,
+  0.9734453,
+  0.9822506,
+  0.9911021,
+  1.0,
+];
+
+/// `log2(x)` for positive, normal `x`.
+#[inline(always)]
+fn log2(x: f32) -> f32 {
+  // With `x = m * 2^e` and `m` in `1.0..2.0` we have
+  // `ln(m) = 2 * atanh((m - 1) / (m + 1))`, and that series converges quickly
+  // since its argument is below `1/3`.
+  let bits = x.to_bits();
+  let e = ((bits >> 23) as i32 - 127) as f32;
+  let m = f32::from_bits((bits & 0x007F_FFFF) | 0x3F80_0000);
+  let s = (m - 1.0) / (m + 1.0);
+  let s2 = s * s;
+  let mut p = ATANH_SERIES[5];
+  for &c in ATANH_SERIES[..5].iter().rev() {
+    p = p * s2 + c;
+  }
+  e + s * (2.0 * LOG2_E) * p
+}
+
+/// `2^y` for `y` well within the normal exponent range.
+#[inline(always)]
+fn exp2(y: f32) -> f32 {
+  let n = y as i32;
+  let g = (y - n as f32) * LN_2;
+  let mut p = EXP_SERIES[9];
+  for &c in EXP_SERIES[..9].iter().rev() {
+    p = p * g + c;
+  }
+  p * f32::from_bits(((n + 127) as u32) << 23)
+}
+
+/// Converts an sRGB encoded value into a linear value.
+///
+/// NaN stays NaN. See the [module docs](self) for the accuracy.
+#[inline]
+pub fn srgb_to_linear(x: f32) -> f32 {
+  if x > DECODE_KNEE {
+    let t = (x + 0.055) / 1.055;
+    t * t * exp2(0.4 * log2(t))
+  } else {
+    x / 12.92
+  }
+}
+
+/// Converts a linear value into an sRGB encoded value.
+///
+/// The input is clamped to `0.0..=1.0` first, so infinities give 0.0 and 1.0,
+/// and NaN stays NaN. See the [module docs](self) for the accuracy.
+#[inline]
+pub fn linear_to_srgb(x: f32) -> f32 {
+  if x > ENCODE_KNEE {
+    // written so that an input of 1.0 gives exactly 1.0
+    1.055 * (exp2(log2(x.min(1.0)) / 2.4) - 1.0) + 1.0
+  } else if x < 0.0 {
+    0.0
+  } else {
+    x * 12.92
+  }
+}
+
+#[cfg(target_feature = "sse2")]
+impl m128 {
+  /// lanewise [`log2`] for positive, normal lanes.
+  #[inline(always)]
+  fn srgb_log2(self) -> m128 {
+    let bits = self.transmute_m128i();
+    let e = bits
+      .shr_logical_u32(23)
+      .sub_i32(m128i::splat_i32(127))
+      .round_i32();
+    let m =
+      ((bits & m128i::splat_i32(0x007F_FFFF)) | m128i::splat_i32(0x3F80_0000)).transmute_m128();
+    let one = m128::splat(1.0);
+    let s = (m - one) / (m + one);
+    let s2 = s * s;
+    let mut p = m128::splat(ATANH_SERIES[5]);
+    for &c in ATANH_SERIES[..5].iter().rev() {
+      p = p * s2 + m128::splat(c);
+    }
+    e + s * m128::splat(2.0 * LOG2_E) * p
+  }
+
+  /// lanewise [`exp2`] for lanes well within the normal exponent range.
+  #[inline(always)]
+  fn srgb_exp2(self) -> m128 {
+    let n = self.truncate_i32();
+    let g = (self - n.round_i32()) * m128::splat(LN_2);
+    let mut p = m128::splat(EXP_SERIES[9]);
+    for &c in EXP_SERIES[..9].iter().rev() {
+      p = p * g + m128::splat(c);
+    }
+    p * n
+      .add_i32(m128i::splat_i32(127))
+      .shl_i32(23)
+      .transmute_m128()
+  }
+
+  /// lanewise [`srgb_to_linear`].
+  #[inline]
+  pub fn srgb_to_linear(self) -> m128 {
+    let knee = m128::splat(DECODE_KNEE);
+    // keep the curve lanes away from zero, they get replaced anyway
+    let t = (self.max(knee) + m128::splat(0.055)) / m128::splat(1.055);
+    let curve = t * t * (m128::splat(0.4) * t.srgb_log2()).srgb_exp2();
+    let on_curve = self.cmp_gt(knee);
+    (curve & on_curve) | on_curve.andnot(self / m128::splat(12.92))
+  }
+
+  /// lanewise [`linear_to_srgb`].
+  #[inline]
+  pub fn linear_to_srgb(self) -> m128 {
+    let (knee, one) = (m128::splat(ENCODE_KNEE), m128::splat(1.0));
+    let x = self.max(knee).min(one);
+    let curve = m128::splat(1.055) * ((x.srgb_log2() / m128::splat(2.4)).srgb_exp2() - one) + one;
+    let on_curve = self.cmp_gt(knee);
+    // `max` gives back its second argument for NaN, so NaN lanes stay NaN
+    let line = m128::zeroed().max(self) * m128::splat(12.92);
+    (curve & on_curve) | on_curve.andnot(line)
+  }
+}
+
+/// Converts 8-bit sRGB channels into linear `f32` values in `0.0..=1.0`.
+pub fn srgb_u8_to_linear(src: &[u8], dst: &mut [f32]) -> usize {
+  for (d, &s) in dst.iter_mut().zip(src.iter()) {
+    *d = DECODE_TABLE[usize::from(s)];
+  }
+  src.len().min(dst.len())
+}
+
+/// Converts linear `f32` values into 8-bit sRGB channels.
+///
+/// Inputs are clamped to `0.0..=1.0` and the result is rounded to nearest. NaN
+/// becomes 0.
+pub fn linear_to_srgb_u8(src: &[f32], dst: &mut [u8]) -> usize {
+  let n = src.len().min(dst.len());
+  #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+  let mut i = 0;
+  #[cfg(target_feature = "sse2")]
+  {
+    let (zero, one) = (m128::zeroed(), m128::splat(1.0));
+    let (max, half) = (m128::splat(255.0), m128::splat(0.5));
+    while i + 16 <= n {
+      // `max` returns its second argument when either one is NaN
+      let quad = |k: usize| {
+        let x = m128::load_from_slice(&src[i + k * 4..]).unwrap();
+        (x.linear_to_srgb().max(zero).min(one) * max + half).truncate_i32()
+      };
+      let low = quad(0).saturating_pack_i32_i16(quad(1));
+      let high = quad(2).saturating_pack_i32_i16(quad(3));
+      low
+        .saturating_pack_i16_u8(high)
+        .storeu((&mut dst[i..i + 16]).try_into().unwrap());
+      i += 16;
+    }
+  }
+  for i in i..n {
+    dst[i] = (linear_to_srgb(src[i]).clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+  }
+  n
+}
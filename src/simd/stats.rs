@@ -0,0 +1,173 @@
+//! Streaming statistics over `f32` values.
+
+#[cfg(target_feature = "sse2")]
+use core::convert::TryInto;
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+/// The count, mean, variance, min, and max of a stream of `f32` values, which
+/// can be fed in one value or one slice at a time.
+///
+/// The mean and variance use Welford's method, which stays accurate when the
+/// values are large compared to how much they vary (where summing the values
+/// and their squares would lose most of the precision).
+///
+/// Values are spread over four separate accumulators, one per `m128` lane, so
+/// that with SSE2 [`extend_from_slice`](RunningStats::extend_from_slice) can
+/// take in 4 values at a time. The lanes are combined (in `f64`) when you ask
+/// for a result.
+///
+/// * With no values the mean and variances are 0, the min is infinity, and the
+///   max is negative infinity.
+/// * A NaN value makes the mean and variances NaN, but the min and max skip
+///   over it, same as [`f32::min`] and [`f32::max`].
+#[derive(Debug, Clone, Copy)]
+pub struct RunningStats {
+  count: u64,
+  mean: [f32; 4],
+  m2: [f32; 4],
+  min: [f32; 4],
+  max: [f32; 4],
+}
+
+impl Default for RunningStats {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl RunningStats {
+  /// Stats with no values in them yet.
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      count: 0,
+      mean: [0.0; 4],
+      m2: [0.0; 4],
+      min: [f32::INFINITY; 4],
+      max: [f32::NEG_INFINITY; 4],
+    }
+  }
+
+  /// How many values are in the given lane.
+  #[inline(always)]
+  fn lane_count(&self, lane: usize) -> u64 {
+    self.count / 4 + u64::from((lane as u64) < self.count % 4)
+  }
+
+  /// Adds one value.
+  #[inline]
+  pub fn push(&mut self, x: f32) {
+    let lane = (self.count % 4) as usize;
+    let n = (self.lane_count(lane) + 1) as f32;
+    let delta = x - self.mean[lane];
+    self.mean[lane] += delta / n;
+    self.m2[lane] += delta * (x - self.mean[lane]);
+    self.min[lane] = self.min[lane].min(x);
+    self.max[lane] = self.max[lane].max(x);
+    self.count += 1;
+  }
+
+  /// Adds every value in the slice.
+  pub fn extend_from_slice(&mut self, values: &[f32]) {
+    #[cfg_attr(not(target_feature = "sse2"), allow(unused_mut))]
+    let mut values = values;
+    #[cfg(target_feature = "sse2")]
+    {
+      let lane = |i: usize| self.lane_count(i) as i32;
+      let mut n = m128i::setr_i32(lane(0), lane(1), lane(2), lane(3));
+      let mut mean = m128::load_unaligned(&self.mean);
+      let mut m2 = m128::load_unaligned(&self.m2);
+      let mut min = m128::load_unaligned(&self.min);
+      let mut max = m128::load_unaligned(&self.max);
+      let one = m128i::splat_i32(1);
+      let mut chunks = values.chunks_exact(4);
+      for chunk in &mut chunks {
+        let x = m128::load_unaligned(chunk.try_into().unwrap());
+        n = n.add_i32(one);
+        let delta = x - mean;
+        mean += delta / n.round_i32();
+        m2 += delta * (x - mean);
+        // `min` and `max` return their second argument when either one is NaN
+        min = x.min(min);
+        max = x.max(max);
+      }
+      mean.store_unaligned(&mut self.mean);
+      m2.store_unaligned(&mut self.m2);
+      min.store_unaligned(&mut self.min);
+      max.store_unaligned(&mut self.max);
+      self.count += (values.len() / 4 * 4) as u64;
+      values = chunks.remainder();
+    }
+    for &x in values {
+      self.push(x);
+    }
+  }
+
+  /// The combined `(mean, m2)` of all the lanes.
+  fn combined(&self) -> (f64, f64) {
+    // This is the pairwise update from Chan et al., which merges two sets of
+    // Welford stats into one.
+    let (mut n, mut mean, mut m2) = (0.0_f64, 0.0_f64, 0.0_f64);
+    for lane in 0..4 {
+      let lane_n = self.lane_count(lane) as f64;
+      if lane_n == 0.0 {
+        continue;
+      }
+      let delta = f64::from(self.mean[lane]) - mean;
+      let total = n + lane_n;
+      mean += delta * lane_n / total;
+      m2 += f64::from(self.m2[lane]) + delta * delta * n * lane_n / total;
+      n = total;
+    }
+    (mean, m2)
+  }
+
+  /// How many values have been added.
+  #[inline]
+  pub fn count(&self) -> u64 {
+    self.count
+  }
+
+  /// The mean of the values.
+  #[inline]
+  pub fn mean(&self) -> f32 {
+    self.combined().0 as f32
+  }
+
+  /// The population variance of the values (divided by `count`).
+  #[inline]
+  pub fn variance(&self) -> f32 {
+    if self.count == 0 {
+      0.0
+    } else {
+      (self.combined().1 / self.count as f64) as f32
+    }
+  }
+
+  /// The sample variance of the values (divided by `count - 1`).
+  #[inline]
+  pub fn sample_variance(&self) -> f32 {
+    if self.count < 2 {
+      0.0
+    } else {
+      (self.combined().1 / (self.count - 1) as f64) as f32
+    }
+  }
+
+  /// The smallest value.
+  #[inline]
+  pub fn min(&self) -> f32 {
+    self.min.iter().fold(f32::INFINITY, |a, &b| a.min(b))
+  }
+
+  /// The largest value.
+  #[inline]
+  pub fn max(&self) -> f32 {
+    self.max.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b))
+  }
+}
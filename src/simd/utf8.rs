@@ -0,0 +1,322 @@
+//! ASCII and UTF-8 validation of byte slices.
+//!
+//! With SSSE3 the UTF-8 check uses the nibble lookup table approach from
+//! Keiser and Lemire, "Validating UTF-8 In Less Than One Instruction Per Byte"
+//! (2021). Each 16 byte block is checked with three `m128i::shuffle_i8` table
+//! lookups. Without SSSE3 there's a plain decoder that skips through all-ASCII
+//! blocks using `m128i::movemask_i8` when SSE2 is available.
+//!
+//! When the fast path finds a problem it re-checks the bytes around it with the
+//! plain decoder, so the error info always matches [`core::str::from_utf8`].
+
+#[cfg(target_feature = "sse2")]
+use super::split_aligned;
+#[cfg(target_feature = "ssse3")]
+use crate::{byte_align_right, cast};
+
+#[cfg(all(target_arch = "x86", target_feature = "sse2"))]
+use crate::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use crate::arch::x86_64::*;
+
+/// An error from [`validate_utf8`] or [`from_utf8`].
+///
+/// This has the same info as [`core::str::Utf8Error`], which we can't make
+/// ourselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utf8Error {
+  valid_up_to: usize,
+  error_len: Option<u8>,
+}
+
+impl Utf8Error {
+  /// The index of the first byte that isn't part of valid UTF-8.
+  pub fn valid_up_to(&self) -> usize {
+    self.valid_up_to
+  }
+
+  /// The length of the invalid byte sequence at
+  /// [`valid_up_to`](Utf8Error::valid_up_to), or `None` if the input ended in
+  /// the middle of a sequence that would otherwise be valid.
+  pub fn error_len(&self) -> Option<usize> {
+    self.error_len.map(usize::from)
+  }
+}
+
+impl core::fmt::Display for Utf8Error {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    match self.error_len {
+      Some(len) => write!(
+        f,
+        "invalid utf-8 sequence of {} bytes from index {}",
+        len, self.valid_up_to
+      ),
+      None => write!(
+        f,
+        "incomplete utf-8 byte sequence from index {}",
+        self.valid_up_to
+      ),
+    }
+  }
+}
+
+/// Checks if every byte is ASCII.
+pub fn is_ascii(bytes: &[u8]) -> bool {
+  #[cfg(target_feature = "sse2")]
+  {
+    let (head, body, tail) = split_aligned::<u8, m128i>(bytes);
+    if !head.is_ascii() || !tail.is_ascii() {
+      return false;
+    }
+    for quad in body.chunks(4) {
+      let any = quad.iter().fold(m128i::zeroed(), |acc, &v| acc | v);
+      if any.movemask_i8() != 0 {
+        return false;
+      }
+    }
+    true
+  }
+  #[cfg(not(target_feature = "sse2"))]
+  {
+    bytes.is_ascii()
+  }
+}
+
+/// Checks that the bytes are UTF-8, with the same rules and error info as
+/// [`core::str::from_utf8`].
+pub fn validate_utf8(bytes: &[u8]) -> Result<(), Utf8Error> {
+  #[cfg(target_feature = "ssse3")]
+  {
+    validate_ssse3(bytes)
+  }
+  #[cfg(not(target_feature = "ssse3"))]
+  {
+    validate_from(bytes, 0)
+  }
+}
+
+/// As [`core::str::from_utf8`], but using [`validate_utf8`].
+pub fn from_utf8(bytes: &[u8]) -> Result<&str, Utf8Error> {
+  validate_utf8(bytes)?;
+  Ok(unsafe { core::str::from_utf8_unchecked(bytes) })
+}
+
+/// Plain decoder, starting from a character boundary at `i`.
+fn validate_from(bytes: &[u8], mut i: usize) -> Result<(), Utf8Error> {
+  let is_cont = |b: u8| b & 0xC0 == 0x80;
+  while i < bytes.len() {
+    let first = bytes[i];
+    if first < 0x80 {
+      #[cfg(target_feature = "sse2")]
+      {
+        if let Some(block) = m128i::load_from_slice(&bytes[i..]) {
+          if block.movemask_i8() == 0 {
+            i += 16;
+            continue;
+          }
+        }
+      }
+      i += 1;
+      continue;
+    }
+    let err = |error_len| {
+      Err(Utf8Error {
+        valid_up_to: i,
+        error_len,
+      })
+    };
+    let width = match first {
+      0xC2..=0xDF => 2,
+      0xE0..=0xEF => 3,
+      0xF0..=0xF4 => 4,
+      _ => return err(Some(1)),
+    };
+    let second = match bytes.get(i + 1) {
+      Some(&b) => b,
+      None => return err(None),
+    };
+    let second_ok = match first {
+      0xE0 => (0xA0..=0xBF).contains(&second),
+      0xED => (0x80..=0x9F).contains(&second),
+      0xF0 => (0x90..=0xBF).contains(&second),
+      0xF4 => (0x80..=0x8F).contains(&second),
+      _ => is_cont(second),
+    };
+    if !second_ok {
+      return err(Some(1));
+    }
+    for n in 2..width {
+      match bytes.get(i + n) {
+        None => return err(None),
+        Some(&b) if !is_cont(b) => return err(Some(n as u8)),
+        Some(_) => (),
+      }
+    }
+    i += width;
+  }
+  Ok(())
+}
+
+#[cfg(target_feature = "ssse3")]
+mod lookup {
+  // Each bit is one kind of error. A lane has an error when a bit is set in the
+  // entry for its previous byte's high nibble, its previous byte's low nibble,
+  // and its own high nibble.
+  const TOO_SHORT: u8 = 1 << 0;
+  const TOO_LONG: u8 = 1 << 1;
+  const OVERLONG_3: u8 = 1 << 2;
+  const TOO_LARGE: u8 = 1 << 3;
+  const SURROGATE: u8 = 1 << 4;
+  const OVERLONG_2: u8 = 1 << 5;
+  const TOO_LARGE_1000: u8 = 1 << 6;
+  const OVERLONG_4: u8 = 1 << 6;
+  const TWO_CONTS: u8 = 1 << 7;
+  const CARRY: u8 = TOO_SHORT | TOO_LONG | TWO_CONTS;
+
+  pub(super) const BYTE_1_HIGH: [u8; 16] = [
+    // 0___: ASCII
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    // 10__: continuation
+    TWO_CONTS,
+    TWO_CONTS,
+    TWO_CONTS,
+    TWO_CONTS,
+    // 1100: two byte lead
+    TOO_SHORT | OVERLONG_2,
+    // 1101: two byte lead
+    TOO_SHORT,
+    // 1110: three byte lead
+    TOO_SHORT | OVERLONG_3 | SURROGATE,
+    // 1111: four byte lead
+    TOO_SHORT | TOO_LARGE | TOO_LARGE_1000 | OVERLONG_4,
+  ];
+
+  pub(super) const BYTE_1_LOW: [u8; 16] = [
+    CARRY | OVERLONG_3 | OVERLONG_2 | OVERLONG_4,
+    CARRY | OVERLONG_2,
+    CARRY,
+    CARRY,
+    CARRY | TOO_LARGE,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000 | SURROGATE,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+  ];
+
+  pub(super) const BYTE_2_HIGH: [u8; 16] = [
+    // 0___: ASCII
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    // 1000
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | OVERLONG_3 | TOO_LARGE_1000 | OVERLONG_4,
+    // 1001
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | OVERLONG_3 | TOO_LARGE,
+    // 101_
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | SURROGATE | TOO_LARGE,
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | SURROGATE | TOO_LARGE,
+    // 11__: lead
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+  ];
+
+  /// Any non-zero lane means the block ends partway into a character.
+  pub(super) const INCOMPLETE_MAX: [u8; 16] = [
+    255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 0xEF, 0xDF, 0xBF,
+  ];
+}
+
+/// Gives a non-zero lane for each error in `input`, given the block before it.
+#[cfg(target_feature = "ssse3")]
+#[inline(always)]
+fn block_errors(input: m128i, prev_input: m128i) -> m128i {
+  let nibble = m128i::splat_i8(0x0F);
+  let prev1 = byte_align_right!(input, prev_input, 15);
+  let byte_1_high =
+    cast::<_, m128i>(lookup::BYTE_1_HIGH).shuffle_i8(prev1.shr_logical_u16(4) & nibble);
+  let byte_1_low = cast::<_, m128i>(lookup::BYTE_1_LOW).shuffle_i8(prev1 & nibble);
+  let byte_2_high =
+    cast::<_, m128i>(lookup::BYTE_2_HIGH).shuffle_i8(input.shr_logical_u16(4) & nibble);
+  let special_cases = byte_1_high & byte_1_low & byte_2_high;
+  // two bytes after a three or four byte lead, and three bytes after a four
+  // byte lead, must be a continuation. The lookups flag any continuation as
+  // TWO_CONTS, so a required one is an error if it _isn't_ flagged and a
+  // surprise one is an error if it _is_.
+  let prev2 = byte_align_right!(input, prev_input, 14);
+  let prev3 = byte_align_right!(input, prev_input, 13);
+  let is_third_byte = prev2.saturating_sub_u8(m128i::splat_i8(0xE0_u8.wrapping_sub(0x80) as i8));
+  let is_fourth_byte = prev3.saturating_sub_u8(m128i::splat_i8(0xF0_u8.wrapping_sub(0x80) as i8));
+  let must_be_cont = (is_third_byte | is_fourth_byte) & m128i::splat_i8(0x80_u8 as i8);
+  must_be_cont ^ special_cases
+}
+
+#[cfg(target_feature = "ssse3")]
+fn validate_ssse3(bytes: &[u8]) -> Result<(), Utf8Error> {
+  let zero = m128i::zeroed();
+  let has_error = |errors: m128i| errors.cmp_eq_i8(zero).movemask_i8() != 0xFFFF;
+  // Everything before `offset` is known good except maybe a character that
+  // runs over into the current block, so the plain decoder only has to go back
+  // to the last character start to find the exact error.
+  let recheck = |offset: usize| {
+    let restart = (offset.saturating_sub(4)..offset)
+      .rev()
+      .find(|&i| bytes[i] & 0xC0 != 0x80)
+      .unwrap_or(offset);
+    validate_from(bytes, restart)
+  };
+  let incomplete_max: m128i = cast(lookup::INCOMPLETE_MAX);
+  let mut prev_input = zero;
+  let mut prev_incomplete = zero;
+  let mut offset = 0;
+  let mut chunks = bytes.chunks_exact(16);
+  for chunk in &mut chunks {
+    let input = m128i::load_from_slice(chunk).unwrap();
+    let ascii_block = input.movemask_i8() == 0;
+    // an ASCII block is only wrong if the block before it was cut short.
+    let errors = if ascii_block {
+      prev_incomplete
+    } else {
+      block_errors(input, prev_input)
+    };
+    if has_error(errors) {
+      return recheck(offset);
+    }
+    prev_incomplete = if ascii_block {
+      zero
+    } else {
+      input.saturating_sub_u8(incomplete_max)
+    };
+    prev_input = input;
+    offset += 16;
+  }
+  // The last partial block gets zero padding, which also catches a character
+  // that's cut off by the end of the input.
+  let rest = chunks.remainder();
+  let mut last = [0_u8; 16];
+  last[..rest.len()].copy_from_slice(rest);
+  if has_error(block_errors(m128i::load_unaligned(&last), prev_input)) {
+    return recheck(offset);
+  }
+  Ok(())
+}
@@ -0,0 +1,148 @@
+#![cfg(target_feature = "aes")]
+
+mod common;
+
+use common::Rng;
+use lokacore::aes::*;
+#[cfg(target_arch = "x86")]
+use lokacore::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use lokacore::arch::x86_64::*;
+use lokacore::*;
+
+fn hex(s: &str) -> [u8; 16] {
+  let mut out = [0; 16];
+  for (i, o) in out.iter_mut().enumerate() {
+    *o = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).unwrap();
+  }
+  out
+}
+
+fn m(s: &str) -> m128i {
+  m128i::load_unaligned(&hex(s))
+}
+
+fn counting_key<const N: usize>() -> [u8; N] {
+  let mut key = [0; N];
+  for (i, k) in key.iter_mut().enumerate() {
+    *k = i as u8;
+  }
+  key
+}
+
+#[test]
+fn rounds_match_fips_197_appendix_b() {
+  // the state at the start of round 1 and round 2, with the round 1 key
+  let start = m("193de3bea0f4e22b9ac68d2ae9f84808");
+  let key = m("a0fafe1788542cb123a339392a6c7605");
+  let next = m("a49c7ff2689f352b6b5bea43026a5049");
+  assert_eq!(start.aes_encrypt_round(key).to_i128(), next.to_i128());
+  // the state at the start of round 10, with the round 10 key
+  let start = m("eb40f21e592e38848ba113e71bc342d2");
+  let key = m("d014f9a8c9ee2589e13f0cc8b6630ca6");
+  let output = m("3925841d02dc09fbdc118597196a0b32");
+  assert_eq!(
+    start.aes_encrypt_last_round(key).to_i128(),
+    output.to_i128()
+  );
+  // undoing the last round gets back to the start of round 10
+  assert_eq!(
+    (output ^ key)
+      .aes_decrypt_last_round(m128i::zeroed())
+      .to_i128(),
+    start.to_i128()
+  );
+}
+
+#[test]
+fn inverse_mix_columns_undoes_mix_columns() {
+  let mut rng = Rng(1);
+  for _ in 0..100 {
+    let x = m128i::setr_i32(
+      rng.next_u32() as i32,
+      rng.next_u32() as i32,
+      rng.next_u32() as i32,
+      rng.next_u32() as i32,
+    );
+    // a full round is the last round plus `MixColumns`
+    let zero = m128i::zeroed();
+    assert_eq!(
+      x.aes_encrypt_round(zero)
+        .aes_inverse_mix_columns()
+        .to_i128(),
+      x.aes_encrypt_last_round(zero).to_i128()
+    );
+  }
+}
+
+#[test]
+fn keygen_assist_matches_fips_197_appendix_a() {
+  // w[3] of the AES-128 example key is 09cf4f3c, and the first expansion step
+  // turns it into 8b84eb01
+  let key = m("2b7e151628aed2a6abf7158809cf4f3c");
+  let assist: [u8; 16] = cast(aes_keygen_assist!(key, 1));
+  assert_eq!(&assist[12..], &[0x8b, 0x84, 0xeb, 0x01]);
+  assert_eq!(&assist[8..12], &[0x01, 0x8a, 0x84, 0xeb]);
+}
+
+#[test]
+fn fips_197_appendix_c() {
+  let plaintext = hex("00112233445566778899aabbccddeeff");
+  let check = |encrypt: &dyn Fn(&mut [u8; 16]), decrypt: &dyn Fn(&mut [u8; 16]), expected: &str| {
+    let mut block = plaintext;
+    encrypt(&mut block);
+    assert_eq!(block, hex(expected));
+    decrypt(&mut block);
+    assert_eq!(block, plaintext);
+  };
+  let aes = Aes128::new(&counting_key());
+  check(
+    &|b| aes.encrypt_block(b),
+    &|b| aes.decrypt_block(b),
+    "69c4e0d86a7b0430d8cdb78070b4c55a",
+  );
+  let aes = Aes192::new(&counting_key());
+  check(
+    &|b| aes.encrypt_block(b),
+    &|b| aes.decrypt_block(b),
+    "dda97ca4864cdfe06eaf70a0ec0d7191",
+  );
+  let aes = Aes256::new(&counting_key());
+  check(
+    &|b| aes.encrypt_block(b),
+    &|b| aes.decrypt_block(b),
+    "8ea2b7ca516745bfeafc49904b496089",
+  );
+}
+
+#[test]
+fn fips_197_appendix_b() {
+  let aes = Aes128::new(&hex("2b7e151628aed2a6abf7158809cf4f3c"));
+  let mut block = hex("3243f6a8885a308d313198a2e0370734");
+  aes.encrypt_block(&mut block);
+  assert_eq!(block, hex("3925841d02dc09fbdc118597196a0b32"));
+}
+
+#[test]
+fn blocks_match_one_at_a_time() {
+  let mut rng = Rng(2);
+  let aes = Aes256::new(&counting_key());
+  for len in 0..11 {
+    let mut blocks: Vec<[u8; 16]> = (0..len)
+      .map(|_| {
+        let mut b = [0; 16];
+        b.iter_mut().for_each(|b| *b = rng.next_u32() as u8);
+        b
+      })
+      .collect();
+    let original = blocks.clone();
+    aes.encrypt_blocks(&mut blocks);
+    for (b, o) in blocks.iter().zip(original.iter()) {
+      let mut one = *o;
+      aes.encrypt_block(&mut one);
+      assert_eq!(*b, one);
+    }
+    aes.decrypt_blocks(&mut blocks);
+    assert_eq!(blocks, original);
+  }
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use common::Rng;
+use lokacore::bits::*;
+
+/// Random values, with a mix of bit densities, plus the usual edge cases.
+fn values(seed: u32) -> Vec<u64> {
+  let mut rng = Rng(seed);
+  let mut out = vec![
+    0,
+    1,
+    2,
+    3,
+    u64::MAX,
+    u64::MAX - 1,
+    1 << 31,
+    1 << 32,
+    1 << 63,
+  ];
+  for _ in 0..300 {
+    out.push(rng.next_u64());
+    out.push(rng.next_u64() & rng.next_u64());
+    out.push(rng.next_u64() | rng.next_u64());
+  }
+  out
+}
+
+/// One bit at a time, straight from the definition.
+fn deposit_reference(a: u64, mask: u64) -> u64 {
+  let mut out = 0;
+  let mut k = 0;
+  for i in 0..64 {
+    if mask >> i & 1 != 0 {
+      out |= (a >> k & 1) << i;
+      k += 1;
+    }
+  }
+  out
+}
+
+fn extract_reference(a: u64, mask: u64) -> u64 {
+  let mut out = 0;
+  let mut k = 0;
+  for i in 0..64 {
+    if mask >> i & 1 != 0 {
+      out |= (a >> i & 1) << k;
+      k += 1;
+    }
+  }
+  out
+}
+
+#[test]
+fn deposit_and_extract() {
+  let a_values = values(1);
+  let masks = values(2);
+  for (&a, &mask) in a_values.iter().zip(masks.iter()) {
+    assert_eq!(deposit_bits_u64(a, mask), deposit_reference(a, mask));
+    assert_eq!(extract_bits_u64(a, mask), extract_reference(a, mask));
+    let (a32, mask32) = (a as u32, mask as u32);
+    assert_eq!(
+      u64::from(deposit_bits_u32(a32, mask32)),
+      deposit_reference(u64::from(a32), u64::from(mask32))
+    );
+    assert_eq!(
+      u64::from(extract_bits_u32(a32, mask32)),
+      extract_reference(u64::from(a32), u64::from(mask32))
+    );
+    // extracting what was deposited gets back the low bits
+    let used = mask.count_ones();
+    let low = if used == 64 { a } else { a & ((1 << used) - 1) };
+    assert_eq!(extract_bits_u64(deposit_bits_u64(a, mask), mask), low);
+  }
+}
+
+#[test]
+fn zero_high_bits_and_bit_field_extract() {
+  for &a in &values(3) {
+    for index in (0..70).chain([255, 256, 257, 300].iter().copied()) {
+      let i = index & 0xFF;
+      let expected_64 = if i >= 64 { a } else { a & ((1 << i) - 1) };
+      assert_eq!(zero_high_bits_u64(a, index), expected_64);
+      let a32 = a as u32;
+      let expected_32 = if i >= 32 { a32 } else { a32 & ((1 << i) - 1) };
+      assert_eq!(zero_high_bits_u32(a32, index), expected_32);
+    }
+    for start in (0..70).step_by(3).chain([255, 256].iter().copied()) {
+      for len in (0..70).step_by(5).chain([255, 256, 260].iter().copied()) {
+        let bit = |width: u32, i: u32| (start & 0xFF) + i < width && i < (len & 0xFF);
+        let expected_64 = (0..64)
+          .filter(|&i| bit(64, i) && a >> ((start & 0xFF) + i) & 1 != 0)
+          .fold(0_u64, |acc, i| acc | 1 << i);
+        assert_eq!(bit_field_extract_u64(a, start, len), expected_64);
+        let a32 = a as u32;
+        let expected_32 = (0..32)
+          .filter(|&i| bit(32, i) && a32 >> ((start & 0xFF) + i) & 1 != 0)
+          .fold(0_u32, |acc, i| acc | 1 << i);
+        assert_eq!(bit_field_extract_u32(a32, start, len), expected_32);
+      }
+    }
+  }
+}
+
+#[test]
+fn lowest_bit_operations() {
+  for &a in &values(4) {
+    let tz = a.trailing_zeros();
+    let lowest = if a == 0 { 0 } else { 1 << tz };
+    assert_eq!(lowest_set_bit_u64(a), lowest);
+    assert_eq!(clear_lowest_set_bit_u64(a), a ^ lowest);
+    let mask = if a == 0 {
+      u64::MAX
+    } else {
+      (lowest - 1) | lowest
+    };
+    assert_eq!(mask_through_lowest_set_bit_u64(a), mask);
+    let a32 = a as u32;
+    assert_eq!(lowest_set_bit_u32(a32), a32 & a32.wrapping_neg());
+    assert_eq!(clear_lowest_set_bit_u32(a32), a32 & a32.wrapping_sub(1));
+    assert_eq!(
+      mask_through_lowest_set_bit_u32(a32),
+      a32 ^ a32.wrapping_sub(1)
+    );
+  }
+}
+
+#[test]
+fn and_not_mul_and_counts() {
+  let a_values = values(5);
+  let b_values = values(6);
+  for (&a, &b) in a_values.iter().zip(b_values.iter()) {
+    assert_eq!(and_not_u64(a, b), !a & b);
+    assert_eq!(and_not_u32(a as u32, b as u32), !(a as u32) & b as u32);
+    let product = u128::from(a) * u128::from(b);
+    assert_eq!(mul_wide_u64(a, b), (product as u64, (product >> 64) as u64));
+    assert_eq!(leading_zeros_u64(a), a.leading_zeros());
+    assert_eq!(trailing_zeros_u64(a), a.trailing_zeros());
+    assert_eq!(count_ones_u64(a), a.count_ones());
+    let a32 = a as u32;
+    assert_eq!(leading_zeros_u32(a32), a32.leading_zeros());
+    assert_eq!(trailing_zeros_u32(a32), a32.trailing_zeros());
+    assert_eq!(count_ones_u32(a32), a32.count_ones());
+  }
+  assert_eq!((leading_zeros_u32(0), trailing_zeros_u32(0)), (32, 32));
+  assert_eq!((leading_zeros_u64(0), trailing_zeros_u64(0)), (64, 64));
+}
+
+#[test]
+fn morton_2d() {
+  // the first few cells of the Z curve
+  let order: Vec<(u16, u16)> = (0..8).map(morton_decode_2d_u32).collect();
+  assert_eq!(
+    order,
+    [
+      (0, 0),
+      (1, 0),
+      (0, 1),
+      (1, 1),
+      (2, 0),
+      (3, 0),
+      (2, 1),
+      (3, 1)
+    ]
+  );
+  assert_eq!(morton_encode_2d_u32(u16::MAX, 0), 0x5555_5555);
+  assert_eq!(morton_encode_2d_u64(0, u32::MAX), 0xAAAA_AAAA_AAAA_AAAA);
+  let mut rng = Rng(7);
+  for _ in 0..1000 {
+    let (x, y) = (rng.next_u32(), rng.next_u32());
+    assert_eq!(morton_decode_2d_u64(morton_encode_2d_u64(x, y)), (x, y));
+    let (x16, y16) = (x as u16, y as u16);
+    assert_eq!(
+      morton_decode_2d_u32(morton_encode_2d_u32(x16, y16)),
+      (x16, y16)
+    );
+    // the 32-bit code is the low half of the 64-bit code
+    assert_eq!(
+      u64::from(morton_encode_2d_u32(x16, y16)),
+      morton_encode_2d_u64(u32::from(x16), u32::from(y16))
+    );
+  }
+}
+
+#[test]
+fn morton_3d() {
+  assert_eq!(morton_encode_3d_u32(1, 0, 0), 0b001);
+  assert_eq!(morton_encode_3d_u32(0, 1, 0), 0b010);
+  assert_eq!(morton_encode_3d_u32(0, 0, 1), 0b100);
+  assert_eq!(morton_encode_3d_u32(0b11, 0b10, 0b01), 0b011_101);
+  assert_eq!(morton_encode_3d_u32(0x3FF, 0x3FF, 0x3FF), 0x3FFF_FFFF);
+  assert_eq!(
+    morton_encode_3d_u64(0x1F_FFFF, 0x1F_FFFF, 0x1F_FFFF),
+    u64::MAX >> 1
+  );
+  // bits past the top are dropped
+  assert_eq!(morton_encode_3d_u32(0x400, 0x400, 0x400), 0);
+  let mut rng = Rng(8);
+  for _ in 0..1000 {
+    let (x, y, z) = (
+      rng.next_u32() & 0x3FF,
+      rng.next_u32() & 0x3FF,
+      rng.next_u32() & 0x3FF,
+    );
+    assert_eq!(
+      morton_decode_3d_u32(morton_encode_3d_u32(x, y, z)),
+      (x, y, z)
+    );
+    let (x, y, z) = (x << 11 | x, y << 11 | y, z << 11 | z);
+    assert_eq!(
+      morton_decode_3d_u64(morton_encode_3d_u64(x, y, z)),
+      (x, y, z)
+    );
+  }
+}
@@ -25,3 +25,275 @@ fn test_branchless_min_and_max() {
     }
   }
 }
+
+mod toolkit {
+  use lokacore::branchless::{self, Branchless};
+
+  /// Calls `f` with every `i16` paired with every `i8` value, in both orders.
+  ///
+  /// That's every pair where at least one side fits in 8 bits, which covers
+  /// both ends and everything around zero. All 2^32 pairs would take minutes
+  /// per test in a debug build.
+  fn each_i16_pair(mut f: impl FnMut(i16, i16)) {
+    for x in i16::MIN..=i16::MAX {
+      for y in i8::MIN..=i8::MAX {
+        f(x, y.into());
+        f(y.into(), x);
+      }
+    }
+  }
+
+  /// Calls `f` with every `u16` paired with every `u8` value, in both orders.
+  fn each_u16_pair(mut f: impl FnMut(u16, u16)) {
+    for x in u16::MIN..=u16::MAX {
+      for y in u8::MIN..=u8::MAX {
+        f(x, y.into());
+        f(y.into(), x);
+      }
+    }
+  }
+
+  #[test]
+  fn is_const() {
+    const _ABS: i8 = branchless::i8::abs(-7);
+    const _CLAMP: u16 = branchless::u16::clamp(900, 0, 255);
+    const _SELECT: i32 = branchless::i32::select(true, 1, 2);
+    const _POW: u64 = branchless::u64::next_power_of_two(1000);
+    const _SAT: u8 = branchless::i128::saturating_to_u8(-5);
+    assert_eq!((_ABS, _CLAMP, _SELECT, _POW, _SAT), (7, 255, 1, 1024, 0));
+  }
+
+  #[test]
+  fn min_max_clamp_select_8() {
+    for x in i8::MIN..=i8::MAX {
+      for y in i8::MIN..=i8::MAX {
+        assert_eq!(branchless::i8::min(x, y), x.min(y));
+        assert_eq!(branchless::i8::max(x, y), x.max(y));
+        assert_eq!(branchless::i8::select(true, x, y), x);
+        assert_eq!(branchless::i8::select(false, x, y), y);
+        let (low, high) = (x.min(y), x.max(y));
+        for &v in &[i8::MIN, -1, 0, 1, 50, i8::MAX] {
+          assert_eq!(branchless::i8::clamp(v, low, high), v.clamp(low, high));
+        }
+        // an empty range gives `low`
+        assert_eq!(branchless::i8::clamp(0, high, low), high);
+      }
+    }
+    for x in u8::MIN..=u8::MAX {
+      for y in u8::MIN..=u8::MAX {
+        assert_eq!(branchless::u8::min(x, y), x.min(y));
+        assert_eq!(branchless::u8::max(x, y), x.max(y));
+        assert_eq!(<u8 as Branchless>::select(x < y, x, y), x.min(y));
+      }
+    }
+  }
+
+  #[test]
+  fn min_max_16() {
+    each_i16_pair(|x, y| {
+      assert_eq!(branchless::i16::min(x, y), x.min(y));
+      assert_eq!(branchless::i16::max(x, y), x.max(y));
+    });
+    each_u16_pair(|x, y| {
+      assert_eq!(branchless::u16::min(x, y), x.min(y));
+      assert_eq!(branchless::u16::max(x, y), x.max(y));
+    });
+  }
+
+  #[test]
+  fn averages() {
+    for x in i8::MIN..=i8::MAX {
+      for y in i8::MIN..=i8::MAX {
+        let sum = i32::from(x) + i32::from(y);
+        assert_eq!(
+          i32::from(branchless::i8::average_floor(x, y)),
+          sum.div_euclid(2)
+        );
+        assert_eq!(
+          i32::from(branchless::i8::average_ceil(x, y)),
+          -(-sum).div_euclid(2)
+        );
+      }
+    }
+    for x in u8::MIN..=u8::MAX {
+      for y in u8::MIN..=u8::MAX {
+        let sum = u32::from(x) + u32::from(y);
+        assert_eq!(u32::from(branchless::u8::average_floor(x, y)), sum / 2);
+        assert_eq!(u32::from(branchless::u8::average_ceil(x, y)), sum.div_ceil(2));
+      }
+    }
+    each_i16_pair(|x, y| {
+      let sum = i32::from(x) + i32::from(y);
+      assert_eq!(i32::from(i16::average_floor(x, y)), sum.div_euclid(2));
+      assert_eq!(i32::from(i16::average_ceil(x, y)), -(-sum).div_euclid(2));
+    });
+    each_u16_pair(|x, y| {
+      let sum = u32::from(x) + u32::from(y);
+      assert_eq!(u32::from(u16::average_floor(x, y)), sum / 2);
+      assert_eq!(u32::from(u16::average_ceil(x, y)), sum.div_ceil(2));
+    });
+  }
+
+  #[test]
+  fn abs_and_sign() {
+    for x in i8::MIN..=i8::MAX {
+      assert_eq!(branchless::i8::abs(x), x.wrapping_abs());
+      assert_eq!(branchless::i8::sign(x), x.signum());
+    }
+    for x in i16::MIN..=i16::MAX {
+      assert_eq!(branchless::i16::abs(x), x.wrapping_abs());
+      assert_eq!(branchless::i16::sign(x), x.signum());
+    }
+    for x in u16::MIN..=u16::MAX {
+      assert_eq!(branchless::u16::abs(x), x);
+      assert_eq!(branchless::u16::sign(x), (x != 0) as u16);
+    }
+  }
+
+  #[test]
+  fn powers_of_two() {
+    for x in u16::MIN..=u16::MAX {
+      assert_eq!(branchless::u16::is_power_of_two(x), x.is_power_of_two());
+      let expected = x.checked_next_power_of_two().unwrap_or(0);
+      assert_eq!(branchless::u16::next_power_of_two(x), expected);
+    }
+    for x in i16::MIN..=i16::MAX {
+      assert_eq!(
+        branchless::i16::is_power_of_two(x),
+        x > 0 && (x as u16).is_power_of_two()
+      );
+      let expected = if x <= 1 {
+        1
+      } else {
+        (x as u16)
+          .checked_next_power_of_two()
+          .filter(|&p| p <= i16::MAX as u16)
+          .map_or(0, |p| p as i16)
+      };
+      assert_eq!(branchless::i16::next_power_of_two(x), expected);
+    }
+    assert_eq!(branchless::u8::next_power_of_two(0), 1);
+    assert_eq!(branchless::u8::next_power_of_two(129), 0);
+    assert_eq!(branchless::i8::next_power_of_two(65), 0);
+    assert_eq!(branchless::u128::next_power_of_two(u128::MAX), 0);
+    assert_eq!(branchless::usize::next_power_of_two(3), 4);
+  }
+
+  #[test]
+  fn alignment() {
+    for shift in 0..16 {
+      let align = 1_u16 << shift;
+      for x in u16::MIN..=u16::MAX {
+        let down = x - x % align;
+        assert_eq!(branchless::u16::align_down(x, align), down);
+        let up = if down == x {
+          x
+        } else {
+          down.wrapping_add(align)
+        };
+        assert_eq!(branchless::u16::align_up(x, align), up);
+      }
+    }
+    for shift in 0..7 {
+      let align = 1_i8 << shift;
+      for x in i8::MIN..=i8::MAX {
+        let down = x.div_euclid(align) * align;
+        assert_eq!(branchless::i8::align_down(x, align), down);
+        let up = if down == x {
+          x
+        } else {
+          down.wrapping_add(align)
+        };
+        assert_eq!(branchless::i8::align_up(x, align), up);
+      }
+    }
+  }
+
+  #[test]
+  fn bit_reverse_and_swap_nibbles() {
+    for x in u16::MIN..=u16::MAX {
+      assert_eq!(branchless::u16::bit_reverse(x), x.reverse_bits());
+      let [a, b] = x.to_le_bytes();
+      let expected = u16::from_le_bytes([a.rotate_left(4), b.rotate_left(4)]);
+      assert_eq!(branchless::u16::swap_nibbles(x), expected);
+      assert_eq!(branchless::i16::swap_nibbles(x as i16), expected as i16);
+      assert_eq!(
+        branchless::i16::bit_reverse(x as i16),
+        x.reverse_bits() as i16
+      );
+    }
+    assert_eq!(branchless::u8::swap_nibbles(0xA5), 0x5A);
+    assert_eq!(
+      branchless::u64::swap_nibbles(0x0123_4567_89AB_CDEF),
+      0x1032_5476_98BA_DCFE
+    );
+  }
+
+  #[test]
+  fn saturating_conversions() {
+    use core::convert::TryFrom;
+    for x in i16::MIN..=i16::MAX {
+      let to_u8 = u8::try_from(x).unwrap_or(if x < 0 { 0 } else { u8::MAX });
+      assert_eq!(branchless::i16::saturating_to_u8(x), to_u8);
+      let to_i8 = i8::try_from(x).unwrap_or(if x < 0 { i8::MIN } else { i8::MAX });
+      assert_eq!(branchless::i16::saturating_to_i8(x), to_i8);
+      let to_u16 = u16::try_from(x).unwrap_or(0);
+      assert_eq!(branchless::i16::saturating_to_u16(x), to_u16);
+      assert_eq!(branchless::i16::saturating_to_i128(x), i128::from(x));
+      assert_eq!(
+        branchless::i16::saturating_to_u64(x),
+        u64::try_from(x).unwrap_or(0)
+      );
+    }
+    for x in u16::MIN..=u16::MAX {
+      assert_eq!(
+        branchless::u16::saturating_to_u8(x),
+        u8::try_from(x).unwrap_or(u8::MAX)
+      );
+      assert_eq!(
+        branchless::u16::saturating_to_i8(x),
+        i8::try_from(x).unwrap_or(i8::MAX)
+      );
+      assert_eq!(
+        branchless::u16::saturating_to_i16(x),
+        i16::try_from(x).unwrap_or(i16::MAX)
+      );
+      assert_eq!(branchless::u16::saturating_to_u32(x), u32::from(x));
+    }
+    // the widest types at their ends
+    assert_eq!(branchless::u128::saturating_to_i128(u128::MAX), i128::MAX);
+    assert_eq!(branchless::i128::saturating_to_u128(i128::MIN), 0);
+    assert_eq!(
+      branchless::i128::saturating_to_u128(i128::MAX),
+      i128::MAX as u128
+    );
+    assert_eq!(branchless::i128::saturating_to_i64(i128::MIN), i64::MIN);
+    assert_eq!(branchless::u64::saturating_to_isize(u64::MAX), isize::MAX);
+  }
+
+  #[test]
+  fn trait_matches_functions() {
+    fn check<T: Branchless + PartialEq + core::fmt::Debug>(a: T, b: T) {
+      assert_eq!(T::min(a, b), T::select(T::max(a, b) == a, b, a));
+      assert_eq!(T::clamp(a, T::min(a, b), T::max(a, b)), a);
+      assert_eq!(T::bit_reverse(T::bit_reverse(a)), a);
+      assert_eq!(T::swap_nibbles(T::swap_nibbles(b)), b);
+      let _ = (
+        T::abs(a),
+        T::sign(b),
+        T::is_power_of_two(a),
+        T::next_power_of_two(b),
+      );
+      let _ = (
+        T::align_up(a, b),
+        T::align_down(a, b),
+        T::average_ceil(a, b),
+      );
+    }
+    check(5_u8, 200);
+    check(-5_i32, 7);
+    check(u128::MAX, 3);
+    check(isize::MIN, 0);
+    assert_eq!(<i64 as Branchless>::average_floor(i64::MIN, i64::MAX), -1);
+  }
+}
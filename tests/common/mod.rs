@@ -0,0 +1,30 @@
+//! Helpers shared by the integration tests.
+//!
+//! Each test file that wants these does `mod common;`, and not every file uses
+//! every helper.
+#![allow(dead_code)]
+
+/// xorshift32, just so the tests can make some noise without any deps.
+pub struct Rng(pub u32);
+impl Rng {
+  pub fn next_u32(&mut self) -> u32 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 17;
+    self.0 ^= self.0 << 5;
+    self.0
+  }
+  pub fn next_u64(&mut self) -> u64 {
+    u64::from(self.next_u32()) << 32 | u64::from(self.next_u32())
+  }
+  pub fn below(&mut self, n: u32) -> usize {
+    (self.next_u32() % n) as usize
+  }
+  /// A value in `0.0..1.0`.
+  pub fn unit_f32(&mut self) -> f32 {
+    (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+  }
+  /// A value in `-1.0..1.0`.
+  pub fn signed_unit_f32(&mut self) -> f32 {
+    (self.next_u32() >> 8) as f32 / (1 << 23) as f32 - 1.0
+  }
+}
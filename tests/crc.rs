@@ -0,0 +1,76 @@
+mod common;
+
+use common::Rng;
+use lokacore::crc::*;
+
+/// One bit at a time, straight from the definition.
+fn crc32_bitwise(data: &[u8]) -> u32 {
+  let mut crc = !0_u32;
+  for &b in data {
+    crc ^= u32::from(b);
+    for _ in 0..8 {
+      crc = if crc & 1 != 0 {
+        (crc >> 1) ^ 0xEDB8_8320
+      } else {
+        crc >> 1
+      };
+    }
+  }
+  !crc
+}
+
+fn crc64_bitwise(data: &[u8]) -> u64 {
+  let mut crc = !0_u64;
+  for &b in data {
+    crc ^= u64::from(b);
+    for _ in 0..8 {
+      crc = if crc & 1 != 0 {
+        (crc >> 1) ^ 0xC96C_5795_D787_0F42
+      } else {
+        crc >> 1
+      };
+    }
+  }
+  !crc
+}
+
+#[test]
+fn check_values() {
+  assert_eq!(crc32(b""), 0);
+  assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+  assert_eq!(
+    crc32(b"The quick brown fox jumps over the lazy dog"),
+    0x414F_A339
+  );
+  assert_eq!(crc64(b""), 0);
+  assert_eq!(crc64(b"123456789"), 0x995D_C9BB_DF19_39FA);
+  // long enough to be folded
+  assert_eq!(crc32(&[0; 4096]), 0xC71C_0011);
+  assert_eq!(crc32(&[0xFF; 256]), crc32_bitwise(&[0xFF; 256]));
+}
+
+#[test]
+fn matches_bitwise() {
+  let mut rng = Rng(1);
+  let data: Vec<u8> = (0..1000).map(|_| rng.next_u32() as u8).collect();
+  for len in 0..300 {
+    assert_eq!(crc32(&data[..len]), crc32_bitwise(&data[..len]), "{}", len);
+    assert_eq!(crc64(&data[..len]), crc64_bitwise(&data[..len]), "{}", len);
+  }
+  for start in 0..16 {
+    let piece = &data[start..];
+    assert_eq!(crc32(piece), crc32_bitwise(piece));
+    assert_eq!(crc64(piece), crc64_bitwise(piece));
+  }
+}
+
+#[test]
+fn update_in_pieces() {
+  let mut rng = Rng(2);
+  let data: Vec<u8> = (0..777).map(|_| rng.next_u32() as u8).collect();
+  for split in [0, 1, 15, 63, 64, 65, 200, 700, 777].iter().copied() {
+    let (a, b) = data.split_at(split);
+    assert_eq!(crc32_update(crc32(a), b), crc32(&data));
+    assert_eq!(crc64_update(crc64(a), b), crc64(&data));
+  }
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use common::Rng;
+use lokacore::ct::*;
+
+#[test]
+fn choice_logic() {
+  for &a in &[false, true] {
+    for &b in &[false, true] {
+      let (ca, cb) = (Choice::from(a), Choice::from(b));
+      assert_eq!(bool::from(ca & cb), a & b);
+      assert_eq!(bool::from(ca | cb), a | b);
+      assert_eq!(bool::from(ca ^ cb), a ^ b);
+      assert_eq!(bool::from(!ca), !a);
+      let mut c = ca;
+      c ^= cb;
+      assert_eq!(bool::from(c), a ^ b);
+    }
+  }
+  assert_eq!(Choice::from_bit(0xFE).unwrap_u8(), 0);
+  assert_eq!(Choice::from_bit(0xFF).unwrap_u8(), 1);
+  assert_eq!(Choice::TRUE.unwrap_u8(), 1);
+  assert_eq!(Choice::FALSE.unwrap_u8(), 0);
+}
+
+#[test]
+fn exhaustive_8_bit() {
+  for a in u8::MIN..=u8::MAX {
+    for b in u8::MIN..=u8::MAX {
+      assert_eq!(bool::from(ct_eq(a, b)), a == b);
+      assert_eq!(bool::from(ct_lt(a, b)), a < b);
+      let (sa, sb) = (a as i8, b as i8);
+      assert_eq!(bool::from(ct_eq(sa, sb)), sa == sb);
+      assert_eq!(bool::from(ct_lt(sa, sb)), sa < sb);
+    }
+  }
+}
+
+#[test]
+fn wide_types_against_std() {
+  let mut rng = Rng(0x1234_5678);
+  let edges_u64 = [0, 1, u64::MAX, u64::MAX - 1, 1 << 63, (1 << 63) - 1];
+  for _ in 0..10_000 {
+    let (a, b) = (u64::from(rng.next_u32()) << 32, u64::from(rng.next_u32()));
+    let pick = rng.next_u32() as usize;
+    let samples = [
+      (a | b, a),
+      (edges_u64[pick % 6], edges_u64[(pick >> 8) % 6]),
+      (a, a),
+    ];
+    for &(x, y) in &samples {
+      assert_eq!(bool::from(ct_eq(x, y)), x == y);
+      assert_eq!(bool::from(ct_lt(x, y)), x < y);
+      let (sx, sy) = (x as i64, y as i64);
+      assert_eq!(bool::from(ct_lt(sx, sy)), sx < sy);
+      let (wx, wy) = (u128::from(x) << 64 | u128::from(y), u128::from(y));
+      assert_eq!(bool::from(ct_lt(wx, wy)), wx < wy);
+      assert_eq!(
+        bool::from(ct_lt(wx as i128, wy as i128)),
+        (wx as i128) < (wy as i128)
+      );
+      assert_eq!(
+        bool::from(ct_lt(x as u32, y as u32)),
+        (x as u32) < (y as u32)
+      );
+      assert_eq!(
+        bool::from(ct_lt(x as i16, y as i16)),
+        (x as i16) < (y as i16)
+      );
+      assert_eq!(
+        bool::from(ct_eq(x as isize, y as isize)),
+        x as isize == y as isize
+      );
+      assert_eq!(
+        bool::from(ct_lt(x as usize, y as usize)),
+        (x as usize) < (y as usize)
+      );
+    }
+  }
+}
+
+#[test]
+fn select_and_swap() {
+  let mut rng = Rng(0xDEAD_BEEF);
+  for _ in 0..1000 {
+    let (a, b) = (rng.next_u32() as i32, rng.next_u32() as i32);
+    assert_eq!(ct_select(Choice::TRUE, a, b), a);
+    assert_eq!(ct_select(Choice::FALSE, a, b), b);
+    let (mut x, mut y) = (a, b);
+    ct_swap(Choice::FALSE, &mut x, &mut y);
+    assert_eq!((x, y), (a, b));
+    ct_swap(Choice::TRUE, &mut x, &mut y);
+    assert_eq!((x, y), (b, a));
+  }
+  assert_eq!(ct_select(Choice::TRUE, u128::MAX, 0), u128::MAX);
+  assert_eq!(ct_select(Choice::FALSE, i8::MIN, i8::MAX), i8::MAX);
+  let (mut x, mut y) = (u64::MAX, 7);
+  ct_swap(ct_lt(y, x), &mut x, &mut y);
+  assert_eq!((x, y), (7, u64::MAX));
+}
+
+#[test]
+fn optimization_barrier_is_identity() {
+  assert_eq!(optimization_barrier(5_u8), 5);
+  assert_eq!(optimization_barrier([1_u64, 2, 3]), [1, 2, 3]);
+}
+
+#[test]
+fn eq_slices() {
+  let mut rng = Rng(0xACE1);
+  for len in 0_usize..100 {
+    let a: Vec<u8> = (0..len).map(|_| rng.next_u32() as u8).collect();
+    let b = a.clone();
+    assert!(bool::from(ct_eq_slices(&a, &b)), "len {}", len);
+    if len > 0 {
+      assert!(!bool::from(ct_eq_slices(&a, &b[..len - 1])));
+      let mut longer = b.clone();
+      longer.push(0);
+      assert!(!bool::from(ct_eq_slices(&a, &longer)));
+    }
+  }
+  assert!(bool::from(ct_eq_slices(&[], &[])));
+}
+
+/// A difference in any position and any bit is found, with the rest of the
+/// data (including the blocks after it) equal.
+///
+/// This only checks the answer. Whether the compare exits early can't be seen
+/// from a test, and a loop that stopped at the first difference would pass
+/// too; that property comes from `ct_eq_slices` having no exit in its loop and
+/// keeping its accumulator behind an `optimization_barrier`.
+#[test]
+fn eq_slices_finds_any_difference() {
+  for len in [1, 15, 16, 17, 31, 32, 33, 64, 65, 100].iter().copied() {
+    let a: Vec<u8> = (0..len).map(|i| i as u8).collect();
+    for pos in 0..len {
+      for bit in 0..8 {
+        let mut b = a.clone();
+        b[pos] ^= 1 << bit;
+        assert!(!bool::from(ct_eq_slices(&a, &b)), "len {} pos {}", len, pos);
+        assert!(!bool::from(ct_eq_slices(&b, &a)), "len {} pos {}", len, pos);
+      }
+    }
+    // differences at both ends must not cancel out
+    if len > 1 {
+      let mut b = a.clone();
+      b[0] ^= 0x80;
+      b[len - 1] ^= 0x80;
+      assert!(!bool::from(ct_eq_slices(&a, &b)));
+    }
+  }
+}
@@ -0,0 +1,27 @@
+#![cfg(target_feature = "f16c")]
+
+#[cfg(target_arch = "x86")]
+use lokacore::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use lokacore::arch::x86_64::*;
+
+use lokacore::half::f16;
+
+#[test]
+fn m128_round_f16() {
+  let f = [1.0, -0.333_333_34, 65520.0, 1e-7];
+  let out: [u16; 8] = lokacore::cast(m128::load_unaligned(&f).round_f16());
+  for (h, x) in out.iter().zip(f.iter()) {
+    assert_eq!(*h, f16::from_f32(*x).0);
+  }
+  assert_eq!(&out[4..], &[0; 4]);
+}
+
+#[test]
+fn m128i_f16_to_f32() {
+  let bits = [0x3C00_u16, 0x8001, 0x7BFF, 0xFC00, 0x7D01, 0, 0, 0];
+  let out = lokacore::cast::<_, m128i>(bits).f16_to_f32().to_array();
+  for (f, h) in out.iter().zip(bits.iter()) {
+    assert_eq!(f.to_bits(), f16(*h).to_f32().to_bits(), "{:04X}", h);
+  }
+}
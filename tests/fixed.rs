@@ -0,0 +1,313 @@
+mod common;
+
+use common::Rng;
+use lokacore::fixed::*;
+
+const MODES: [Rounding; 4] = [
+  Rounding::Nearest,
+  Rounding::Floor,
+  Rounding::Ceil,
+  Rounding::TowardZero,
+];
+
+/// `n / d` rounded, written the long way with `i128` as a reference.
+fn div_ref(n: i128, d: i128, rounding: Rounding) -> i128 {
+  let floor = |n: i128, d: i128| {
+    let q = n / d;
+    if n % d != 0 && ((n < 0) != (d < 0)) {
+      q - 1
+    } else {
+      q
+    }
+  };
+  match rounding {
+    Rounding::Nearest if d < 0 => floor(-2 * n - d, -2 * d),
+    Rounding::Nearest => floor(2 * n + d, 2 * d),
+    Rounding::Floor => floor(n, d),
+    Rounding::Ceil => -floor(-n, d),
+    Rounding::TowardZero => n / d,
+  }
+}
+
+fn saturate(x: i128, min: i128, max: i128) -> i128 {
+  x.max(min).min(max)
+}
+
+/// Every `i16` value paired with a spread of others, including the ends.
+fn q15_pairs() -> impl Iterator<Item = (i16, i16)> {
+  let others: Vec<i16> = (i16::MIN..=i16::MAX)
+    .step_by(331)
+    .chain([-1, 0, 1, 0x4000, -0x4000, i16::MAX].iter().copied())
+    .collect();
+  (i16::MIN..=i16::MAX).flat_map(move |a| others.clone().into_iter().map(move |b| (a, b)))
+}
+
+#[test]
+fn q15_add_sub_neg() {
+  for (a, b) in q15_pairs() {
+    let (qa, qb) = (Q15(a), Q15(b));
+    assert_eq!((qa + qb).0, a.saturating_add(b));
+    assert_eq!((qa - qb).0, a.saturating_sub(b));
+    assert_eq!(qa.wrapping_add(qb).0, a.wrapping_add(b));
+    assert_eq!(qa.wrapping_sub(qb).0, a.wrapping_sub(b));
+  }
+  assert_eq!(-Q15::MIN, Q15::MAX);
+  assert_eq!(-Q15::MAX, Q15(-i16::MAX));
+  let mut x = Q15::HALF;
+  x += Q15::HALF;
+  assert_eq!(x, Q15::MAX);
+  x -= Q15::MAX;
+  assert_eq!(x, Q15::ZERO);
+}
+
+#[test]
+fn q15_mul_div() {
+  for (a, b) in q15_pairs() {
+    let (qa, qb) = (Q15(a), Q15(b));
+    let product = i128::from(a) * i128::from(b);
+    let n = i128::from(a) << 15;
+    for &mode in &MODES {
+      let expected = saturate(div_ref(product, 1 << 15, mode), -32768, 32767);
+      assert_eq!(
+        i128::from(qa.mul_round(qb, mode).0),
+        expected,
+        "{} * {} {:?}",
+        a,
+        b,
+        mode
+      );
+      if b != 0 {
+        let expected = saturate(div_ref(n, i128::from(b), mode), -32768, 32767);
+        assert_eq!(
+          i128::from(qa.div_round(qb, mode).0),
+          expected,
+          "{} / {} {:?}",
+          a,
+          b,
+          mode
+        );
+      }
+    }
+    assert_eq!(qa * qb, qa.mul_round(qb, Rounding::Nearest));
+    if b != 0 {
+      assert_eq!(qa / qb, qa.div_round(qb, Rounding::Nearest));
+    }
+  }
+  assert_eq!(Q15::MIN * Q15::MIN, Q15::MAX);
+  assert_eq!(Q15::MIN * Q15::MAX, Q15(-i16::MAX));
+  assert_eq!(Q15(1) * Q15::HALF, Q15(1));
+  assert_eq!(Q15(-1) * Q15::HALF, Q15(0));
+  assert_eq!(Q15(-1).mul_round(Q15::HALF, Rounding::TowardZero), Q15(0));
+  assert_eq!(Q15(-1).mul_round(Q15::HALF, Rounding::Floor), Q15(-1));
+}
+
+#[test]
+fn divide_by_zero_saturates() {
+  assert_eq!(Q15(5) / Q15::ZERO, Q15::MAX);
+  assert_eq!(Q15(-5) / Q15::ZERO, Q15::MIN);
+  assert_eq!(Q15::ZERO / Q15::ZERO, Q15::ZERO);
+  assert_eq!(Q16_16(1) / Q16_16::ZERO, Q16_16::MAX);
+  assert_eq!(Q16_16(-1) / Q16_16::ZERO, Q16_16::MIN);
+  assert_eq!(Q16_16::ZERO / Q16_16::ZERO, Q16_16::ZERO);
+  assert_eq!(Q15::HALF / Q15::HALF, Q15::MAX);
+}
+
+#[test]
+fn q16_16_against_reference() {
+  let mut rng = Rng(0x0BAD_F00D);
+  let edges = [
+    i32::MIN,
+    i32::MIN + 1,
+    -65536,
+    -1,
+    0,
+    1,
+    32768,
+    65536,
+    i32::MAX,
+  ];
+  for i in 0..50_000 {
+    let a = rng.next_u32() as i32 >> (rng.next_u32() % 32);
+    let b = if i % 10 == 0 {
+      edges[i / 10 % edges.len()]
+    } else {
+      rng.next_u32() as i32 >> (i % 32)
+    };
+    let (qa, qb) = (Q16_16(a), Q16_16(b));
+    assert_eq!((qa + qb).0, a.saturating_add(b));
+    assert_eq!((qa - qb).0, a.saturating_sub(b));
+    let product = i128::from(a) * i128::from(b);
+    for &mode in &MODES {
+      let expected = saturate(
+        div_ref(product, 1 << 16, mode),
+        i32::MIN.into(),
+        i32::MAX.into(),
+      );
+      assert_eq!(
+        i128::from(qa.mul_round(qb, mode).0),
+        expected,
+        "{} * {} {:?}",
+        a,
+        b,
+        mode
+      );
+      if b != 0 {
+        let n = i128::from(a) << 16;
+        let expected = saturate(div_ref(n, b.into(), mode), i32::MIN.into(), i32::MAX.into());
+        assert_eq!(
+          i128::from(qa.div_round(qb, mode).0),
+          expected,
+          "{} / {} {:?}",
+          a,
+          b,
+          mode
+        );
+      }
+      assert_eq!(
+        i128::from(qa.to_int(mode)),
+        div_ref(a.into(), 1 << 16, mode)
+      );
+      let q15 = saturate(div_ref(a.into(), 2, mode), -32768, 32767);
+      assert_eq!(i128::from(qa.saturating_to_q15(mode).0), q15);
+    }
+  }
+  assert_eq!(Q16_16::MAX.to_int(Rounding::Ceil), 32768);
+  assert_eq!(Q16_16::from_int(-3).to_int(Rounding::Floor), -3);
+  assert_eq!(Q16_16::from_int(i16::MIN), Q16_16::MIN);
+  assert_eq!(Q16_16::ONE * Q16_16::ONE, Q16_16::ONE);
+  assert_eq!(Q16_16::from_int(200) * Q16_16::from_int(200), Q16_16::MAX);
+  assert_eq!(Q16_16::from(Q15::MIN), Q16_16::from_int(-1));
+  assert_eq!(Q16_16::from(Q15::HALF), Q16_16::HALF);
+  assert_eq!(Q16_16::ONE.saturating_to_q15(Rounding::Nearest), Q15::MAX);
+}
+
+/// `f` rounded to `frac_bits` after the point, as the bits of a value between
+/// `min` and `max`, the slow way.
+fn from_f32_ref(f: f32, frac_bits: i32, min: f64, max: f64) -> i64 {
+  if f.is_nan() {
+    0
+  } else {
+    (f64::from(f) * 2_f64.powi(frac_bits))
+      .round_ties_even()
+      .max(min)
+      .min(max) as i64
+  }
+}
+
+fn f32_samples() -> impl Iterator<Item = f32> {
+  let specials = [
+    0.0,
+    -0.0,
+    0.5 / 32768.0,
+    1.5 / 32768.0,
+    -0.5 / 32768.0,
+    -2.5 / 32768.0,
+    1.0,
+    -1.0,
+    0.999_99,
+    32767.5,
+    -32768.5,
+    f32::MAX,
+    f32::MIN,
+    f32::INFINITY,
+    f32::NEG_INFINITY,
+    f32::NAN,
+    f32::MIN_POSITIVE,
+  ];
+  (0..=u32::MAX)
+    .step_by(4099)
+    .map(f32::from_bits)
+    .chain(specials.to_vec())
+}
+
+#[test]
+fn f32_conversions() {
+  for f in f32_samples() {
+    let q15 = from_f32_ref(f, 15, -32768.0, 32767.0);
+    assert_eq!(i64::from(Q15::from_f32(f).0), q15, "{}", f);
+    let q16 = from_f32_ref(f, 16, f64::from(i32::MIN), f64::from(i32::MAX));
+    assert_eq!(i64::from(Q16_16::from_f32(f).0), q16, "{}", f);
+  }
+  for bits in i16::MIN..=i16::MAX {
+    let f = Q15(bits).to_f32();
+    assert_eq!(f, f32::from(bits) / 32768.0);
+    assert_eq!(Q15::from_f32(f), Q15(bits));
+    let f = Q16_16(i32::from(bits) << 8).to_f32();
+    assert_eq!(Q16_16::from_f32(f), Q16_16(i32::from(bits) << 8));
+  }
+  assert_eq!(Q16_16::from_f32(-1.25).to_f32(), -1.25);
+}
+
+fn q15x8_samples() -> Vec<q15x8> {
+  let mut rng = Rng(0x5EED);
+  let edges = [i16::MIN, i16::MIN + 1, -1, 0, 1, 0x4000, i16::MAX];
+  let mut out = Vec::new();
+  for i in 0..20_000 {
+    let mut v = q15x8::default();
+    for q in v.0.iter_mut() {
+      let r = rng.next_u32();
+      *q = Q15(if i % 2 == 0 {
+        edges[r as usize % edges.len()]
+      } else {
+        r as i16
+      });
+    }
+    out.push(v);
+  }
+  out
+}
+
+#[test]
+fn q15x8_matches_scalar() {
+  let samples = q15x8_samples();
+  for pair in samples.windows(2) {
+    let (a, b) = (pair[0], pair[1]);
+    let lanes = |f: &dyn Fn(Q15, Q15) -> Q15| {
+      let mut out = q15x8::default();
+      for i in 0..8 {
+        out.0[i] = f(a.0[i], b.0[i]);
+      }
+      out
+    };
+    assert_eq!(a + b, lanes(&|x, y| x + y));
+    assert_eq!(a - b, lanes(&|x, y| x - y));
+    assert_eq!(a * b, lanes(&|x, y| x * y));
+    assert_eq!(a / b, lanes(&|x, y| x / y));
+    assert_eq!(-a, lanes(&|x, _| -x));
+    for &mode in &MODES {
+      assert_eq!(a.mul_round(b, mode), lanes(&|x, y| x.mul_round(y, mode)));
+      assert_eq!(a.div_round(b, mode), lanes(&|x, y| x.div_round(y, mode)));
+    }
+    let mut c = a;
+    c *= b;
+    c += b;
+    c -= a;
+    c /= b;
+    assert_eq!(c, (((a * b) + b) - a) / b);
+  }
+  assert_eq!(
+    q15x8::splat(Q15::MIN) * q15x8::splat(Q15::MIN),
+    q15x8::splat(Q15::MAX)
+  );
+}
+
+#[test]
+fn q15x8_f32_conversions() {
+  let floats: Vec<f32> = f32_samples().collect();
+  for chunk in floats.chunks_exact(8) {
+    let f: [f32; 8] = [
+      chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7],
+    ];
+    let v = q15x8::from_f32s(&f);
+    for (q, &x) in v.0.iter().zip(f.iter()) {
+      assert_eq!(*q, Q15::from_f32(x), "{}", x);
+    }
+  }
+  for v in q15x8_samples() {
+    let f = v.to_f32s();
+    for (q, &x) in v.0.iter().zip(f.iter()) {
+      assert_eq!(q.to_f32(), x);
+    }
+    assert_eq!(q15x8::from_f32s(&f), v);
+  }
+}
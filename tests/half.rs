@@ -0,0 +1,203 @@
+use lokacore::half::*;
+
+/// `f16` bits to `f64`, written out from the format definition.
+fn f16_ref(h: u16) -> f64 {
+  let sign = if h & 0x8000 != 0 { -1.0 } else { 1.0 };
+  let exp = i32::from(h >> 10) & 0x1F;
+  let man = f64::from(h & 0x3FF);
+  match exp {
+    0 => sign * man * 2_f64.powi(-24),
+    0x1F if man == 0.0 => sign * f64::INFINITY,
+    0x1F => f64::NAN,
+    _ => sign * (1.0 + man / 1024.0) * 2_f64.powi(exp - 15),
+  }
+}
+
+/// `f32` values spread over every exponent, including NaN payloads.
+fn f32_samples() -> Vec<f32> {
+  (0..=u32::MAX).step_by(1021).map(f32::from_bits).collect()
+}
+
+#[test]
+fn f16_to_f32_exhaustive() {
+  for h in 0..=u16::MAX {
+    let f = f16(h).to_f32();
+    let expected = f16_ref(h);
+    if expected.is_nan() {
+      assert!(f.is_nan() && f16(h).is_nan());
+      // payload kept, and made quiet
+      assert_eq!(
+        f.to_bits(),
+        u32::from(h & 0x8000) << 16 | 0x7FC0_0000 | u32::from(h & 0x3FF) << 13
+      );
+    } else {
+      assert_eq!(f64::from(f), expected, "{:04X}", h);
+      assert_eq!(f.is_sign_negative(), f16(h).is_sign_negative());
+      assert_eq!(f.is_infinite(), f16(h).is_infinite());
+      assert_eq!(f.is_finite(), f16(h).is_finite());
+    }
+    assert_eq!(f32::from(f16(h)).to_bits(), f.to_bits());
+  }
+}
+
+#[test]
+fn f16_round_trip_exhaustive() {
+  for h in 0..=u16::MAX {
+    let back = f16::from_f32(f16(h).to_f32()).to_bits();
+    if f16(h).is_nan() {
+      assert_eq!(back, h | 0x200, "{:04X}", h);
+    } else {
+      assert_eq!(back, h, "{:04X}", h);
+    }
+  }
+}
+
+/// Between each pair of neighboring `f16` values, the midpoint goes to the
+/// even one, and anything off the midpoint goes to the nearer one.
+#[test]
+fn f16_from_f32_rounding() {
+  for h in 0..0x7C00_u16 {
+    let low = f16(h).to_f32();
+    // past the largest finite value, the next step up would be 65536
+    let high = if h == 0x7BFF {
+      65536.0
+    } else {
+      f16(h + 1).to_f32()
+    };
+    let mid = (low + high) / 2.0;
+    assert_eq!(f64::from(mid), (f64::from(low) + f64::from(high)) / 2.0);
+    let even = if h & 1 == 0 { h } else { h + 1 };
+    let above = f32::from_bits(mid.to_bits() + 1);
+    let below = f32::from_bits(mid.to_bits() - 1);
+    for &sign in &[0, 0x8000] {
+      let neg = |f: f32| f32::from_bits(f.to_bits() | u32::from(sign) << 16);
+      assert_eq!(f16::from_f32(neg(mid)).to_bits(), even | sign, "{:04X}", h);
+      assert_eq!(
+        f16::from_f32(neg(above)).to_bits(),
+        (h + 1) | sign,
+        "{:04X}",
+        h
+      );
+      assert_eq!(f16::from_f32(neg(below)).to_bits(), h | sign, "{:04X}", h);
+    }
+  }
+  assert_eq!(f16::from_f32(f32::MAX), f16::INFINITY);
+  assert_eq!(f16::from_f32(f32::NEG_INFINITY), f16::NEG_INFINITY);
+  assert_eq!(f16::from_f32(f32::from_bits(1)).to_bits(), 0);
+  assert_eq!(f16::from_f32(-f32::from_bits(1)).to_bits(), 0x8000);
+  assert_eq!(f16::from_f32(65504.0), f16::MAX);
+  assert_eq!(f16::from_f32(-65504.0), f16::MIN);
+  assert_eq!(f16::from_f32(2_f32.powi(-14)), f16::MIN_POSITIVE);
+  assert_eq!(
+    f16::from_f32(1.0 + 2_f32.powi(-10)).to_f32() - 1.0,
+    f16::EPSILON.to_f32()
+  );
+}
+
+#[test]
+fn f16_nan_payloads() {
+  // a signaling NaN with the payload only in the high bits keeps them
+  assert_eq!(f16::from_f32(f32::from_bits(0x7FA0_0000)).to_bits(), 0x7F00);
+  // payload bits that don't fit still leave a NaN
+  assert_eq!(f16::from_f32(f32::from_bits(0x7F80_0001)).to_bits(), 0x7E00);
+  assert_eq!(f16::from_f32(f32::from_bits(0xFFFF_FFFF)).to_bits(), 0xFFFF);
+  assert!(f16::NAN.is_nan());
+  assert!(f16::NAN != f16::NAN);
+  assert!(f16::ZERO == f16::NEG_ZERO);
+  assert!(f16::MIN < f16::MAX);
+  assert_eq!(format!("{:?} {}", f16::ONE, f16::MAX), "1.0 65504");
+}
+
+#[test]
+fn f16_slices_match_scalar() {
+  let all: Vec<f16> = (0..=u16::MAX).map(f16).collect();
+  let mut floats = vec![0.0_f32; all.len() + 3];
+  assert_eq!(f16_to_f32(&all, &mut floats), all.len());
+  for (h, f) in all.iter().zip(floats.iter()) {
+    assert_eq!(f.to_bits(), h.to_f32().to_bits(), "{:04X}", h.0);
+  }
+  let samples = f32_samples();
+  // offsets so the SIMD part and the tail both get every kind of value
+  for offset in 0..3 {
+    let src = &samples[offset..];
+    let mut halves = vec![f16::ZERO; src.len()];
+    assert_eq!(f32_to_f16(src, &mut halves[..src.len() - 1]), src.len() - 1);
+    for (f, h) in src.iter().zip(halves.iter()).take(src.len() - 1) {
+      assert_eq!(h.0, f16::from_f32(*f).0, "{:08X}", f.to_bits());
+    }
+  }
+  let mut halves = vec![f16::ZERO; all.len()];
+  f32_to_f16(&floats, &mut halves);
+  for (a, b) in halves.iter().zip(all.iter()) {
+    assert_eq!(a.0, if b.is_nan() { b.0 | 0x200 } else { b.0 });
+  }
+}
+
+#[test]
+fn bf16_exhaustive() {
+  for b in 0..=u16::MAX {
+    let f = bf16(b).to_f32();
+    let bits = u32::from(b) << 16;
+    if f32::from_bits(bits).is_nan() {
+      assert!(bf16(b).is_nan());
+      assert_eq!(f.to_bits(), bits | 0x40_0000);
+      assert_eq!(bf16::from_f32(f).to_bits(), b | 0x40);
+    } else {
+      assert_eq!(f.to_bits(), bits);
+      assert_eq!(bf16::from_f32(f).to_bits(), b);
+      assert_eq!(f.is_infinite(), bf16(b).is_infinite());
+    }
+  }
+}
+
+#[test]
+fn bf16_from_f32_rounding() {
+  for b in 0..0x7F80_u32 {
+    let low = b << 16;
+    let mid = low | 0x8000;
+    let even = if b & 1 == 0 { b } else { b + 1 } as u16;
+    for &sign in &[0, 0x8000_0000_u32] {
+      assert_eq!(
+        bf16::from_f32(f32::from_bits(mid | sign)).to_bits(),
+        even | (sign >> 16) as u16
+      );
+      assert_eq!(
+        bf16::from_f32(f32::from_bits((mid + 1) | sign)).to_bits(),
+        (b + 1) as u16 | (sign >> 16) as u16
+      );
+      assert_eq!(
+        bf16::from_f32(f32::from_bits((mid - 1) | sign)).to_bits(),
+        b as u16 | (sign >> 16) as u16
+      );
+    }
+  }
+  assert_eq!(bf16::from_f32(f32::MAX), bf16::INFINITY);
+  assert_eq!(
+    bf16::from_f32(f32::from_bits(0x7F80_0001)).to_bits(),
+    0x7FC0
+  );
+  assert_eq!(bf16::MIN_POSITIVE.to_f32(), f32::MIN_POSITIVE);
+  assert_eq!(
+    bf16::ONE.to_f32() + bf16::EPSILON.to_f32(),
+    bf16(0x3F81).to_f32()
+  );
+}
+
+#[test]
+fn bf16_slices_match_scalar() {
+  let all: Vec<bf16> = (0..=u16::MAX).map(bf16).collect();
+  let mut floats = vec![0.0_f32; all.len()];
+  assert_eq!(bf16_to_f32(&all[1..], &mut floats), all.len() - 1);
+  for (b, f) in all[1..].iter().zip(floats.iter()) {
+    assert_eq!(f.to_bits(), b.to_f32().to_bits(), "{:04X}", b.0);
+  }
+  let samples = f32_samples();
+  for offset in 0..3 {
+    let src = &samples[offset..];
+    let mut out = vec![bf16::ZERO; src.len() + 5];
+    assert_eq!(f32_to_bf16(src, &mut out), src.len());
+    for (f, b) in src.iter().zip(out.iter()) {
+      assert_eq!(b.0, bf16::from_f32(*f).0, "{:08X}", f.to_bits());
+    }
+  }
+}
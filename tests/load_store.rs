@@ -0,0 +1,284 @@
+#![cfg(target_feature = "sse2")]
+//! Every load and store entry point of the `arch` module.
+//!
+//! These only touch memory through references, so they can run under Miri
+//! with `cargo +nightly miri test --test load_store`. Miri can't run the
+//! non-temporal stores, the masked store, or the cache flush, so those tests
+//! are ignored there.
+
+#[cfg(target_arch = "x86")]
+use lokacore::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use lokacore::arch::x86_64::*;
+use lokacore::*;
+use std::convert::TryInto;
+
+const F32S: [f32; 12] = [
+  1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0,
+];
+const F64S: [f64; 6] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+
+fn bytes() -> Align16<[u8; 48]> {
+  let mut a = Align16([0_u8; 48]);
+  for (i, b) in a.0.iter_mut().enumerate() {
+    *b = i as u8 + 1;
+  }
+  a
+}
+
+fn i128_at(bytes: &[u8]) -> i128 {
+  i128::from_le_bytes(bytes[..16].try_into().unwrap())
+}
+
+#[test]
+fn m128_loads() {
+  let a = Align16([1.0_f32, 2.0, 3.0, 4.0]);
+  assert_eq!(m128::load(&a).to_array(), [1.0, 2.0, 3.0, 4.0]);
+  assert_eq!(m128::load_reverse(&a).to_array(), [4.0, 3.0, 2.0, 1.0]);
+  assert_eq!(m128::load_splat(&5.0).to_array(), [5.0; 4]);
+  assert_eq!(m128::load_low(&5.0).to_array(), [5.0, 0.0, 0.0, 0.0]);
+  for start in 0..=(F32S.len() - 4) {
+    let window: &[f32; 4] = F32S[start..start + 4].try_into().unwrap();
+    assert_eq!(m128::load_unaligned(window).to_array(), *window);
+  }
+}
+
+#[test]
+fn m128_load_from_slice() {
+  for start in 0..=F32S.len() {
+    let out = m128::load_from_slice(&F32S[start..]);
+    if F32S.len() - start >= 4 {
+      let expected: [f32; 4] = F32S[start..start + 4].try_into().unwrap();
+      assert_eq!(out.unwrap().to_array(), expected);
+    } else {
+      assert!(out.is_none());
+    }
+  }
+}
+
+#[test]
+fn m128_stores() {
+  let v = m128::set(4.0, 3.0, 2.0, 1.0);
+  let mut a = Align16([0.0_f32; 4]);
+  v.store(&mut a);
+  assert_eq!(a.0, [1.0, 2.0, 3.0, 4.0]);
+  v.store_reverse(&mut a);
+  assert_eq!(a.0, [4.0, 3.0, 2.0, 1.0]);
+  v.store_splat(&mut a);
+  assert_eq!(a.0, [1.0; 4]);
+  let mut x = 0.0;
+  v.store_low(&mut x);
+  assert_eq!(x, 1.0);
+  assert_eq!(v.to_array(), [1.0, 2.0, 3.0, 4.0]);
+
+  let mut buf = [0.0_f32; 12];
+  for start in 0..=(buf.len() - 4) {
+    let window: &mut [f32; 4] = (&mut buf[start..start + 4]).try_into().unwrap();
+    v.store_unaligned(window);
+    assert_eq!(&buf[start..start + 4], &[1.0, 2.0, 3.0, 4.0]);
+  }
+}
+
+#[test]
+fn m128i_loads() {
+  let a = Align16(0x0102_0304_0506_0708_090A_0B0C_0D0E_0F10_i128);
+  assert_eq!(m128i::load(&a).to_i128(), a.0);
+
+  let low = m128i::set_i64(-1, 0x1234);
+  assert_eq!(m128i::load_low_i64(&low).to_i128(), 0x1234);
+
+  let b = bytes();
+  for start in 0..=(b.0.len() - 16) {
+    let window: &[u8; 16] = b.0[start..start + 16].try_into().unwrap();
+    let expected = i128_at(window);
+    assert_eq!(m128i::load_unaligned(window).to_i128(), expected);
+    #[cfg(target_feature = "sse3")]
+    assert_eq!(m128i::load_quick_unaligned(window).to_i128(), expected);
+
+    let two: &[u8; 2] = b.0[start..start + 2].try_into().unwrap();
+    assert_eq!(
+      m128i::load_unaligned_low_i16(two).to_i128(),
+      i128::from(u16::from_le_bytes(*two))
+    );
+    let four: &[u8; 4] = b.0[start..start + 4].try_into().unwrap();
+    assert_eq!(
+      m128i::load_unaligned_low_i32(four).to_i128(),
+      i128::from(u32::from_le_bytes(*four))
+    );
+    let eight: &[u8; 8] = b.0[start..start + 8].try_into().unwrap();
+    assert_eq!(
+      m128i::load_unaligned_low_i64(eight).to_i128(),
+      i128::from(u64::from_le_bytes(*eight))
+    );
+  }
+}
+
+#[test]
+fn m128i_load_from_slice() {
+  let b = bytes();
+  for start in 0..=b.0.len() {
+    let out = m128i::load_from_slice(&b.0[start..]);
+    if b.0.len() - start >= 16 {
+      assert_eq!(out.unwrap().to_i128(), i128_at(&b.0[start..]));
+    } else {
+      assert!(out.is_none());
+    }
+  }
+
+  let words: [u32; 5] = [1, 2, 3, 4, 5];
+  let out = m128i::load_from_slice(&words[1..]).unwrap();
+  assert_eq!(out.to_i128(), m128i::set_i32(5, 4, 3, 2).to_i128());
+  assert!(m128i::load_from_slice(&words[2..]).is_none());
+  assert!(m128i::load_from_slice::<()>(&[(); 100]).is_none());
+}
+
+#[test]
+fn m128i_stores() {
+  let v = m128i::setr_i8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+  let expected = v.to_i128();
+
+  let mut a = Align16(0_i128);
+  v.store(&mut a);
+  assert_eq!(a.0, expected);
+
+  let mut low = m128i::splat_i8(-1);
+  v.store_low_i64(&mut low);
+  assert_eq!(
+    low.to_i128(),
+    m128i::set_i64(-1, 0x0807_0605_0403_0201).to_i128()
+  );
+
+  let mut buf = [0_u8; 48];
+  for start in 0..=(buf.len() - 16) {
+    buf = [0; 48];
+    let window: &mut [u8; 16] = (&mut buf[start..start + 16]).try_into().unwrap();
+    v.storeu(window);
+    assert_eq!(i128_at(&buf[start..]), expected);
+    assert!(buf[..start]
+      .iter()
+      .chain(&buf[start + 16..])
+      .all(|&b| b == 0));
+
+    buf = [0; 48];
+    let two: &mut [u8; 2] = (&mut buf[start..start + 2]).try_into().unwrap();
+    v.store_unaligned_low_i16(two);
+    assert_eq!(&buf[start..start + 3], &[1, 2, 0]);
+    let four: &mut [u8; 4] = (&mut buf[start..start + 4]).try_into().unwrap();
+    v.store_unaligned_low_i32(four);
+    assert_eq!(&buf[start..start + 5], &[1, 2, 3, 4, 0]);
+    let eight: &mut [u8; 8] = (&mut buf[start..start + 8]).try_into().unwrap();
+    v.store_unaligned_low_i64(eight);
+    assert_eq!(&buf[start..start + 9], &[1, 2, 3, 4, 5, 6, 7, 8, 0]);
+  }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn m128i_masked_store() {
+  let v = m128i::setr_i8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+  let mut masked = [0_i8; 16];
+  v.masked_store(
+    m128i::setr_i8(-1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1),
+    &mut masked,
+  );
+  assert_eq!(masked, [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 16]);
+}
+
+#[test]
+fn m128d_loads() {
+  let a = Align16([1.0_f64, 2.0]);
+  assert_eq!(m128d::load(&a).to_array(), [1.0, 2.0]);
+  assert_eq!(m128d::load_reverse(&a).to_array(), [2.0, 1.0]);
+  assert_eq!(m128d::load_all(&3.0).to_array(), [3.0, 3.0]);
+  assert_eq!(m128d::load_low(&3.0).to_array(), [3.0, 0.0]);
+  #[cfg(target_feature = "sse3")]
+  assert_eq!(m128d::load_splat(&3.0).to_array(), [3.0, 3.0]);
+
+  let v = m128d::set(2.0, 1.0);
+  assert_eq!(v.load_high(&5.0).to_array(), [1.0, 5.0]);
+  assert_eq!(v.load_replace_low(&5.0).to_array(), [5.0, 2.0]);
+
+  for start in 0..=(F64S.len() - 2) {
+    let window: &[f64; 2] = F64S[start..start + 2].try_into().unwrap();
+    assert_eq!(m128d::load_unaligned(window).to_array(), *window);
+  }
+}
+
+#[test]
+fn m128d_load_from_slice() {
+  for start in 0..=F64S.len() {
+    let out = m128d::load_from_slice(&F64S[start..]);
+    if F64S.len() - start >= 2 {
+      assert_eq!(out.unwrap().to_array(), [F64S[start], F64S[start + 1]]);
+    } else {
+      assert!(out.is_none());
+    }
+  }
+}
+
+#[test]
+fn m128d_stores() {
+  let v = m128d::set(2.0, 1.0);
+  let mut a = Align16([0.0_f64; 2]);
+  v.store(&mut a);
+  assert_eq!(a.0, [1.0, 2.0]);
+  v.storer(&mut a);
+  assert_eq!(a.0, [2.0, 1.0]);
+  v.store_lower_all(&mut a);
+  assert_eq!(a.0, [1.0, 1.0]);
+  assert_eq!(v.to_array(), [1.0, 2.0]);
+
+  let mut x = 0.0;
+  v.store_low(&mut x);
+  assert_eq!(x, 1.0);
+  v.store_high(&mut x);
+  assert_eq!(x, 2.0);
+
+  let mut buf = [0.0_f64; 6];
+  for start in 0..=(buf.len() - 2) {
+    let window: &mut [f64; 2] = (&mut buf[start..start + 2]).try_into().unwrap();
+    v.storeu(window);
+    assert_eq!(&buf[start..start + 2], &[1.0, 2.0]);
+  }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn streams() {
+  let v = m128i::setr_i8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+  let mut a = Align16(0_i128);
+  v.stream(&mut a);
+  assert_eq!(a.0, v.to_i128());
+
+  let v = m128d::set(2.0, 1.0);
+  let mut a = Align16([0.0_f64; 2]);
+  v.stream(&mut a);
+  assert_eq!(a.0, [1.0, 2.0]);
+
+  let mut x = 0_i32;
+  stream_i32(&mut x, 7);
+  assert_eq!(x, 7);
+  #[cfg(target_arch = "x86_64")]
+  {
+    let mut y = 0_i64;
+    stream_i64(&mut y, -7);
+    assert_eq!(y, -7);
+  }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn cache_line_flush_keeps_value() {
+  let b = bytes();
+  cache_line_flush(&b);
+  assert_eq!(b.0[47], 48);
+}
+
+#[test]
+fn cache_line_flush_zero_sized() {
+  // an empty `Vec` has a dangling pointer, and so does this reference to it
+  let v: Vec<u8> = Vec::new();
+  let r: &[u8; 0] = v.as_slice().try_into().unwrap();
+  cache_line_flush(r);
+  cache_line_flush(&());
+}
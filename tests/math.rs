@@ -0,0 +1,374 @@
+#![cfg(target_feature = "sse")]
+
+mod common;
+
+use common::Rng;
+use lokacore::math::*;
+use lokacore::*;
+
+impl Rng {
+  fn mat4(&mut self) -> Mat4 {
+    let mut a = [0.0; 16];
+    a.iter_mut().for_each(|f| *f = self.signed_unit_f32() * 4.0);
+    Mat4::from_cols_array(&a)
+  }
+  fn unit_quat(&mut self) -> Quat {
+    Quat::new(
+      self.signed_unit_f32(),
+      self.signed_unit_f32(),
+      self.signed_unit_f32(),
+      self.signed_unit_f32(),
+    )
+    .normalize()
+  }
+  fn vec3a(&mut self) -> Vec3A {
+    Vec3A::new(
+      self.signed_unit_f32(),
+      self.signed_unit_f32(),
+      self.signed_unit_f32(),
+    ) * 10.0
+  }
+}
+
+fn close(a: f32, b: f32, tolerance: f32) -> bool {
+  (a - b).abs() <= tolerance * 1.0_f32.max(a.abs()).max(b.abs())
+}
+
+fn assert_close<const N: usize>(a: [f32; N], b: [f32; N], tolerance: f32) {
+  assert!(
+    a.iter()
+      .zip(b.iter())
+      .all(|(&x, &y)| close(x, y, tolerance)),
+    "{:?} vs {:?}",
+    a,
+    b
+  );
+}
+
+/// `m[col][row]`, in f64 so that it can be the reference
+fn naive(m: &Mat4) -> [[f64; 4]; 4] {
+  let a = m.to_cols_array();
+  let mut out = [[0.0; 4]; 4];
+  for c in 0..4 {
+    for r in 0..4 {
+      out[c][r] = f64::from(a[c * 4 + r]);
+    }
+  }
+  out
+}
+
+fn naive_det(m: [[f64; 4]; 4]) -> f64 {
+  // Leibniz, it's only 24 terms.
+  let mut total = 0.0;
+  for (p, sign) in permutations() {
+    total += sign * (0..4).map(|c| m[c][p[c]]).product::<f64>();
+  }
+  total
+}
+
+fn permutations() -> Vec<([usize; 4], f64)> {
+  let mut out = Vec::new();
+  for a in 0..4 {
+    for b in 0..4 {
+      for c in 0..4 {
+        for d in 0..4 {
+          let p = [a, b, c, d];
+          let mut seen = [false; 4];
+          p.iter().for_each(|&i| seen[i] = true);
+          if seen.iter().all(|&s| s) {
+            let mut inversions = 0;
+            for i in 0..4 {
+              for j in i + 1..4 {
+                if p[i] > p[j] {
+                  inversions += 1;
+                }
+              }
+            }
+            out.push((p, if inversions % 2 == 0 { 1.0 } else { -1.0 }));
+          }
+        }
+      }
+    }
+  }
+  out
+}
+
+#[test]
+fn types_are_pod_and_aligned() {
+  use core::mem::{align_of, size_of};
+  assert_eq!((size_of::<Vec4>(), align_of::<Vec4>()), (16, 16));
+  assert_eq!((size_of::<Vec3A>(), align_of::<Vec3A>()), (16, 16));
+  assert_eq!((size_of::<Quat>(), align_of::<Quat>()), (16, 16));
+  assert_eq!((size_of::<Mat4>(), align_of::<Mat4>()), (64, 16));
+  let v: Vec4 = cast(Align16([1.0_f32, 2.0, 3.0, 4.0]));
+  assert_eq!(v, Vec4::new(1.0, 2.0, 3.0, 4.0));
+  let m: Mat4 = cast([v; 4]);
+  assert_eq!(m.col(3), v);
+}
+
+#[test]
+fn vec_basics() {
+  let a = Vec4::new(1.0, 2.0, 3.0, 4.0);
+  let b = Vec4::from_array([5.0, 6.0, 7.0, 8.0]);
+  assert_eq!((a.x(), a.y(), a.z(), a.w()), (1.0, 2.0, 3.0, 4.0));
+  assert_eq!(a.dot(b), 70.0);
+  assert_eq!(a + b, Vec4::new(6.0, 8.0, 10.0, 12.0));
+  assert_eq!(b - a, Vec4::splat(4.0));
+  assert_eq!(a * 2.0, Vec4::new(2.0, 4.0, 6.0, 8.0));
+  assert_eq!(-a / 2.0, Vec4::new(-0.5, -1.0, -1.5, -2.0));
+  assert_eq!(a.lerp(b, 0.25), Vec4::new(2.0, 3.0, 4.0, 5.0));
+  assert_eq!(a.min(Vec4::splat(2.5)), Vec4::new(1.0, 2.0, 2.5, 2.5));
+  assert_eq!(Vec4::new(0.0, 3.0, 0.0, 4.0).length(), 5.0);
+  assert_close(
+    a.normalize().to_array(),
+    (a / 30.0_f32.sqrt()).to_array(),
+    1e-6,
+  );
+  assert_eq!(format!("{:?}", a), "Vec4(1.0, 2.0, 3.0, 4.0)");
+
+  let x = Vec3A::new(1.0, 0.0, 0.0);
+  let y = Vec3A::new(0.0, 1.0, 0.0);
+  assert_eq!(x.cross(y), Vec3A::new(0.0, 0.0, 1.0));
+  assert_eq!(y.cross(x), Vec3A::new(0.0, 0.0, -1.0));
+  let p = Vec3A::new(1.0, 2.0, 3.0);
+  let q = Vec3A::new(-4.0, 5.0, 0.5);
+  assert_eq!(
+    p.cross(q),
+    Vec3A::new(2.0 * 0.5 - 3.0 * 5.0, 3.0 * -4.0 - 0.5, 5.0 + 8.0)
+  );
+  assert_eq!(p.dot(q), -4.0 + 10.0 + 1.5);
+  // the w lane doesn't count
+  assert_eq!(Vec3A(Vec4::new(1.0, 2.0, 2.0, 100.0).0).length(), 3.0);
+  assert_eq!(p.extend(9.0).truncate(), p);
+  assert_eq!(p.extend(9.0).w(), 9.0);
+}
+
+#[test]
+fn mat4_mul_and_transpose_match_naive() {
+  let mut rng = Rng(1);
+  for _ in 0..200 {
+    let (a, b) = (rng.mat4(), rng.mat4());
+    let (na, nb) = (naive(&a), naive(&b));
+    let product = naive(&(a * b));
+    for c in 0..4 {
+      for r in 0..4 {
+        let expected: f64 = (0..4).map(|k| na[k][r] * nb[c][k]).sum();
+        assert!(
+          (product[c][r] - expected).abs() < 1e-4,
+          "{} {}",
+          product[c][r],
+          expected
+        );
+        assert_eq!(naive(&a.transpose())[c][r], na[r][c]);
+      }
+    }
+    let v = Vec4::new(
+      rng.signed_unit_f32(),
+      rng.signed_unit_f32(),
+      rng.signed_unit_f32(),
+      rng.signed_unit_f32(),
+    );
+    let as_mat = Mat4::from_cols(v, v, v, v);
+    assert_close((a * v).to_array(), (a * as_mat).col(2).to_array(), 1e-6);
+  }
+  let mut m = Mat4::identity();
+  m.cols[0] = Vec4::new(1.0, 2.0, 3.0, 4.0).0;
+  assert_eq!(m * Mat4::identity(), m);
+}
+
+#[test]
+fn mat4_determinant_and_inverse() {
+  let mut rng = Rng(2);
+  for _ in 0..500 {
+    let m = rng.mat4();
+    let expected = naive_det(naive(&m));
+    let det = m.determinant();
+    assert!(
+      (f64::from(det) - expected).abs() < 1e-3 * expected.abs().max(1.0),
+      "{} vs {}",
+      det,
+      expected
+    );
+    if expected.abs() > 0.5 {
+      let inv = m.inverse();
+      assert_close(
+        (m * inv).to_cols_array(),
+        Mat4::identity().to_cols_array(),
+        1e-3,
+      );
+      assert_close(
+        (inv * m).to_cols_array(),
+        Mat4::identity().to_cols_array(),
+        1e-3,
+      );
+    }
+  }
+  assert_eq!(Mat4::identity().inverse(), Mat4::identity());
+  assert_eq!(
+    Mat4::from_scale(Vec3A::new(2.0, 4.0, 8.0)).determinant(),
+    64.0
+  );
+  let t = Mat4::from_translation(Vec3A::new(1.0, 2.0, 3.0));
+  assert_eq!(
+    t.inverse(),
+    Mat4::from_translation(Vec3A::new(-1.0, -2.0, -3.0))
+  );
+  let singular = Mat4::from_cols(
+    Vec4::splat(1.0),
+    Vec4::splat(2.0),
+    Vec4::splat(3.0),
+    Vec4::splat(4.0),
+  );
+  assert_eq!(singular.determinant(), 0.0);
+  assert!(singular
+    .inverse()
+    .to_cols_array()
+    .iter()
+    .any(|f| !f.is_finite()));
+}
+
+#[test]
+fn quat_rotations() {
+  use core::f32::consts::{FRAC_PI_2, PI};
+  let z_quarter = Quat::from_axis_angle(Vec3A::new(0.0, 0.0, 1.0), FRAC_PI_2);
+  let x = Vec3A::new(1.0, 0.0, 0.0);
+  assert_close((z_quarter * x).to_array(), [0.0, 1.0, 0.0], 1e-6);
+  let half = Quat::from_axis_angle(Vec3A::new(0.0, 1.0, 0.0), PI);
+  assert_close((half * x).to_array(), [-1.0, 0.0, 0.0], 1e-6);
+  assert_eq!(Quat::default() * x, x);
+
+  let mut rng = Rng(3);
+  for _ in 0..200 {
+    let (a, b) = (rng.unit_quat(), rng.unit_quat());
+    let v = rng.vec3a();
+    // the product applies `b` and then `a`
+    assert_close((a * b * v).to_array(), (a * (b * v)).to_array(), 1e-5);
+    // the matrix does the same thing as the quaternion
+    let m = Mat4::from_quat(a);
+    assert_close(m.transform_vector3(v).to_array(), (a * v).to_array(), 1e-5);
+    assert_close(
+      (Mat4::from_quat(a) * Mat4::from_quat(b)).to_cols_array(),
+      Mat4::from_quat(a * b).to_cols_array(),
+      1e-5,
+    );
+    assert!((m.determinant() - 1.0).abs() < 1e-5);
+    assert_close((a.inverse() * (a * v)).to_array(), v.to_array(), 1e-5);
+    assert_close(a.conjugate().to_array(), a.inverse().to_array(), 1e-5);
+    // the axis of the rotation doesn't move, and the length doesn't change
+    let axis = v.normalize();
+    let angle = rng.signed_unit_f32() * 10.0;
+    let q = Quat::from_axis_angle(axis, angle);
+    assert_close((q * axis).to_array(), axis.to_array(), 1e-5);
+    assert!(close((q * x).length(), 1.0, 1e-5));
+    assert!(close(q.length(), 1.0, 1e-6));
+  }
+}
+
+#[test]
+fn quat_slerp() {
+  let axis = Vec3A::new(0.0, 0.0, 1.0);
+  let x = Vec3A::new(1.0, 0.0, 0.0);
+  for &total in &[0.001, 0.1, 1.0, 2.0, 3.0, 3.1] {
+    let start = Quat::from_axis_angle(axis, 0.0);
+    let end = Quat::from_axis_angle(axis, total);
+    for i in 0..=10 {
+      let t = i as f32 / 10.0;
+      let q = start.slerp(end, t);
+      let expected = Quat::from_axis_angle(axis, total * t);
+      assert_close(q.to_array(), expected.to_array(), 1e-5);
+      assert!(close(q.length(), 1.0, 1e-5));
+    }
+    // taking the long way around is still the short way
+    let flipped = Quat(-end.0);
+    assert_close(
+      (start.slerp(flipped, 0.5) * x).to_array(),
+      (Quat::from_axis_angle(axis, total / 2.0) * x).to_array(),
+      1e-5,
+    );
+  }
+  let mut rng = Rng(4);
+  for _ in 0..200 {
+    let (a, b) = (rng.unit_quat(), rng.unit_quat());
+    assert_close(a.slerp(b, 0.0).to_array(), a.to_array(), 1e-5);
+    let end = a.slerp(b, 1.0);
+    let sign = if a.dot(b) < 0.0 { -1.0 } else { 1.0 };
+    assert_close(end.to_array(), (Vec4(b.0) * sign).to_array(), 1e-4);
+  }
+}
+
+#[test]
+fn look_at() {
+  let eye = Vec3A::new(3.0, 4.0, 5.0);
+  let center = Vec3A::new(-1.0, 2.0, 0.5);
+  let up = Vec3A::new(0.0, 1.0, 0.0);
+  let distance = (center - eye).length();
+  let rh = Mat4::look_at_rh(eye, center, up);
+  assert_close(rh.transform_point3(eye).to_array(), [0.0; 3], 1e-5);
+  assert_close(
+    rh.transform_point3(center).to_array(),
+    [0.0, 0.0, -distance],
+    1e-5,
+  );
+  let above = rh.transform_point3(eye + up);
+  assert!(above.y() > 0.0 && above.x().abs() < 1e-5);
+  let lh = Mat4::look_at_lh(eye, center, up);
+  assert_close(
+    lh.transform_point3(center).to_array(),
+    [0.0, 0.0, distance],
+    1e-5,
+  );
+  // a rotation and translation only, so lengths stay the same
+  assert!(close(rh.determinant(), 1.0, 1e-5));
+  assert!(close(lh.determinant(), 1.0, 1e-5));
+}
+
+fn project(m: &Mat4, x: f32, y: f32, z: f32) -> [f32; 3] {
+  let v = *m * Vec4::new(x, y, z, 1.0);
+  [v.x() / v.w(), v.y() / v.w(), v.z() / v.w()]
+}
+
+#[test]
+fn perspective() {
+  use core::f32::consts::FRAC_PI_2;
+  let (near, far) = (0.5, 100.0);
+  let rh = Mat4::perspective_rh(FRAC_PI_2, 2.0, near, far);
+  assert_close(project(&rh, 0.0, 0.0, -near), [0.0, 0.0, 0.0], 1e-5);
+  assert_close(project(&rh, 0.0, 0.0, -far), [0.0, 0.0, 1.0], 1e-5);
+  // a 90 degree field of view sees out to 45 degrees, up and down.
+  assert_close(
+    project(&rh, 10.0, 10.0, -10.0),
+    [0.5, 1.0, project(&rh, 0.0, 0.0, -10.0)[2]],
+    1e-5,
+  );
+  let lh = Mat4::perspective_lh(FRAC_PI_2, 2.0, near, far);
+  assert_close(project(&lh, 0.0, 0.0, near), [0.0, 0.0, 0.0], 1e-5);
+  assert_close(project(&lh, 0.0, 0.0, far), [0.0, 0.0, 1.0], 1e-5);
+  let [x, y, _] = project(&lh, -20.0, 10.0, 10.0);
+  assert_close([x, y], [-1.0, 1.0], 1e-5);
+  let gl = Mat4::perspective_rh_gl(FRAC_PI_2, 1.0, near, far);
+  assert_close(project(&gl, 0.0, 0.0, -near), [0.0, 0.0, -1.0], 1e-5);
+  assert_close(project(&gl, 0.0, 0.0, -far), [0.0, 0.0, 1.0], 1e-5);
+  assert_close(
+    project(&gl, -3.0, 3.0, -3.0),
+    [-1.0, 1.0, project(&gl, 0.0, 0.0, -3.0)[2]],
+    1e-5,
+  );
+  // the trig is good enough that other angles work out too
+  let narrow = Mat4::perspective_rh(0.5, 1.0, near, far);
+  let tan = 0.25_f32.tan();
+  assert!(close(project(&narrow, tan, 0.0, -1.0)[0], 1.0, 1e-6));
+}
+
+#[test]
+fn orthographic() {
+  let (l, r, b, t, n, f) = (-2.0, 6.0, -1.0, 3.0, 1.0, 11.0);
+  let rh = Mat4::orthographic_rh(l, r, b, t, n, f);
+  assert_close(project(&rh, l, b, -n), [-1.0, -1.0, 0.0], 1e-6);
+  assert_close(project(&rh, r, t, -f), [1.0, 1.0, 1.0], 1e-6);
+  let lh = Mat4::orthographic_lh(l, r, b, t, n, f);
+  assert_close(project(&lh, l, t, n), [-1.0, 1.0, 0.0], 1e-6);
+  assert_close(project(&lh, r, b, f), [1.0, -1.0, 1.0], 1e-6);
+  let gl = Mat4::orthographic_rh_gl(l, r, b, t, n, f);
+  assert_close(project(&gl, l, b, -n), [-1.0, -1.0, -1.0], 1e-6);
+  assert_close(project(&gl, r, t, -f), [1.0, 1.0, 1.0], 1e-6);
+}
@@ -0,0 +1,124 @@
+#![cfg(target_feature = "pclmulqdq")]
+
+mod common;
+
+use common::Rng;
+#[cfg(target_arch = "x86")]
+use lokacore::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use lokacore::arch::x86_64::*;
+use lokacore::ghash::*;
+use lokacore::*;
+
+fn clmul_scalar(a: u64, b: u64) -> u128 {
+  let mut out = 0;
+  for i in 0..64 {
+    if b >> i & 1 != 0 {
+      out ^= u128::from(a) << i;
+    }
+  }
+  out
+}
+
+/// Algorithm 1 of NIST SP 800-38D, one bit at a time.
+fn gf_mul_scalar(x: u128, y: u128) -> u128 {
+  let mut z = 0;
+  let mut v = y;
+  for i in 0..128 {
+    if x >> (127 - i) & 1 != 0 {
+      z ^= v;
+    }
+    v = if v & 1 != 0 {
+      (v >> 1) ^ (0xE1 << 120)
+    } else {
+      v >> 1
+    };
+  }
+  z
+}
+
+fn ghash_scalar(key: &[u8; 16], blocks: &[[u8; 16]]) -> [u8; 16] {
+  let h = u128::from_be_bytes(*key);
+  let mut acc = 0;
+  for block in blocks {
+    acc = gf_mul_scalar(acc ^ u128::from_be_bytes(*block), h);
+  }
+  acc.to_be_bytes()
+}
+
+fn hex<const N: usize>(s: &str) -> [u8; N] {
+  let mut out = [0; N];
+  for (i, o) in out.iter_mut().enumerate() {
+    *o = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).unwrap();
+  }
+  out
+}
+
+#[test]
+fn clmul_matches_scalar() {
+  let mut rng = Rng(1);
+  for _ in 0..1000 {
+    let (a0, a1, b0, b1) = (
+      rng.next_u64(),
+      rng.next_u64(),
+      rng.next_u64(),
+      rng.next_u64(),
+    );
+    let a = m128i::set_i64(a1 as i64, a0 as i64);
+    let b = m128i::set_i64(b1 as i64, b0 as i64);
+    assert_eq!(clmul!(a, b, 0x00).to_i128() as u128, clmul_scalar(a0, b0));
+    assert_eq!(clmul!(a, b, 0x01).to_i128() as u128, clmul_scalar(a1, b0));
+    assert_eq!(clmul!(a, b, 0x10).to_i128() as u128, clmul_scalar(a0, b1));
+    assert_eq!(clmul!(a, b, 0x11).to_i128() as u128, clmul_scalar(a1, b1));
+  }
+}
+
+#[test]
+fn ghash_gcm_test_case_2() {
+  // key and IV of all zeros, one block of zero plaintext
+  let mut ghash = Ghash::new(&hex("66e94bd4ef8a2c3b884cfa59ca342b2e"));
+  ghash.update(&hex::<16>("0388dace60b6a392f328c2b971b2fe78"));
+  ghash.update_block(&hex("00000000000000000000000000000080"));
+  assert_eq!(ghash.finish(), hex("f38cbb1ad69223dcc3457ae5b6b0f885"));
+}
+
+#[test]
+fn ghash_gcm_test_case_4() {
+  // 20 bytes of additional data and 60 bytes of ciphertext, both padded
+  let mut ghash = Ghash::new(&hex("b83b533708bf535d0aa6e52980d53b78"));
+  ghash.update(&hex::<20>("feedfacedeadbeeffeedfacedeadbeefabaddad2"));
+  ghash.update(&hex::<60>(
+    "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+     21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+  ));
+  ghash.update_block(&hex("00000000000000a000000000000001e0"));
+  assert_eq!(ghash.finish(), hex("698e57f70e6ecc7fd9463b7260a9ae5f"));
+}
+
+#[test]
+fn ghash_matches_scalar() {
+  let mut rng = Rng(2);
+  let mut random_block = || {
+    let mut block = [0; 16];
+    block.iter_mut().for_each(|b| *b = rng.next_u32() as u8);
+    block
+  };
+  for len in 0..20 {
+    let key = random_block();
+    let blocks: Vec<[u8; 16]> = (0..len).map(|_| random_block()).collect();
+    let mut ghash = Ghash::new(&key);
+    blocks.iter().for_each(|b| ghash.update_block(b));
+    assert_eq!(ghash.finish(), ghash_scalar(&key, &blocks));
+  }
+  // the edge cases of the reduction
+  for &(x, y) in &[
+    (1_u128, 1_u128),
+    (u128::MAX, u128::MAX),
+    (1 << 127, 1),
+    (1, 1 << 127),
+  ] {
+    let mut ghash = Ghash::new(&y.to_be_bytes());
+    ghash.update_block(&x.to_be_bytes());
+    assert_eq!(ghash.finish(), gf_mul_scalar(x, y).to_be_bytes());
+  }
+}
@@ -0,0 +1,105 @@
+#![cfg(target_feature = "sha")]
+
+mod common;
+
+use common::Rng;
+#[cfg(target_arch = "x86")]
+use lokacore::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use lokacore::arch::x86_64::*;
+use lokacore::*;
+
+impl Rng {
+  fn next_words(&mut self) -> [u32; 4] {
+    [
+      self.next_u32(),
+      self.next_u32(),
+      self.next_u32(),
+      self.next_u32(),
+    ]
+  }
+}
+
+fn m(words: [u32; 4]) -> m128i {
+  cast(words)
+}
+
+fn words(x: m128i) -> [u32; 4] {
+  cast(x)
+}
+
+#[test]
+fn sha256_message_schedule() {
+  let sigma0 = |x: u32| x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3);
+  let sigma1 = |x: u32| x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10);
+  let mut rng = Rng(1);
+  for _ in 0..100 {
+    // words t-16 through t-9 for the next 4 words t, first word in lane 0
+    let (a, b) = (rng.next_words(), rng.next_words());
+    let expected = [
+      a[0].wrapping_add(sigma0(a[1])),
+      a[1].wrapping_add(sigma0(a[2])),
+      a[2].wrapping_add(sigma0(a[3])),
+      a[3].wrapping_add(sigma0(b[0])),
+    ];
+    assert_eq!(words(m(a).sha256_msg1(m(b))), expected);
+
+    // `msg2` adds sigma1 of words t-2, which are partly the new words
+    let (x, prev) = (rng.next_words(), rng.next_words());
+    let w0 = x[0].wrapping_add(sigma1(prev[2]));
+    let w1 = x[1].wrapping_add(sigma1(prev[3]));
+    let w2 = x[2].wrapping_add(sigma1(w0));
+    let w3 = x[3].wrapping_add(sigma1(w1));
+    assert_eq!(words(m(x).sha256_msg2(m(prev))), [w0, w1, w2, w3]);
+  }
+}
+
+#[test]
+fn sha1_message_schedule() {
+  let mut rng = Rng(2);
+  for _ in 0..100 {
+    // first word in the highest lane: `a` is words 0..4, `b` is words 4..8
+    let (a, b) = (rng.next_words(), rng.next_words());
+    let expected = [a[0] ^ b[2], a[1] ^ b[3], a[2] ^ a[0], a[3] ^ a[1]];
+    assert_eq!(words(m(a).sha1_msg1(m(b))), expected);
+
+    let (x, prev) = (rng.next_words(), rng.next_words());
+    let w0 = (x[3] ^ prev[2]).rotate_left(1);
+    let w1 = (x[2] ^ prev[1]).rotate_left(1);
+    let w2 = (x[1] ^ prev[0]).rotate_left(1);
+    let w3 = (x[0] ^ w0).rotate_left(1);
+    assert_eq!(words(m(x).sha1_msg2(m(prev))), [w3, w2, w1, w0]);
+  }
+}
+
+#[test]
+fn sha1_next_e_and_rounds() {
+  let mut rng = Rng(3);
+  for _ in 0..100 {
+    let (abcd, msg) = (rng.next_words(), rng.next_words());
+    let mut expected = msg;
+    expected[3] = msg[3].wrapping_add(abcd[3].rotate_left(30));
+    assert_eq!(words(m(abcd).sha1_next_e(m(msg))), expected);
+  }
+  // four rounds of the scalar algorithm, with round function 0
+  let [a, b, c, d] = rng.next_words();
+  let (e, w) = (rng.next_u32(), rng.next_words());
+  let (mut s, mut ee) = ([a, b, c, d], e);
+  for &w in &w {
+    let f = (s[1] & s[2]) | (!s[1] & s[3]);
+    let t = s[0]
+      .rotate_left(5)
+      .wrapping_add(f)
+      .wrapping_add(ee)
+      .wrapping_add(0x5A82_7999)
+      .wrapping_add(w);
+    ee = s[3];
+    s = [t, s[0], s[1].rotate_left(30), s[2]];
+  }
+  let abcd_in = m([d, c, b, a]);
+  let e_in = m([w[3], w[2], w[1], w[0].wrapping_add(e)]);
+  assert_eq!(
+    words(sha1_rounds4!(abcd_in, e_in, 0)),
+    [s[3], s[2], s[1], s[0]]
+  );
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::Rng;
+use lokacore::sha::*;
+
+fn hex<const N: usize>(s: &str) -> [u8; N] {
+  let mut out = [0; N];
+  for (i, o) in out.iter_mut().enumerate() {
+    *o = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).unwrap();
+  }
+  out
+}
+
+const TWO_BLOCK: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+#[test]
+fn sha256_nist_vectors() {
+  assert_eq!(
+    Sha256::digest(b"abc"),
+    hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+  );
+  assert_eq!(
+    Sha256::digest(b""),
+    hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+  );
+  assert_eq!(
+    Sha256::digest(TWO_BLOCK),
+    hex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+  );
+  let mut hasher = Sha256::new();
+  for _ in 0..1000 {
+    hasher.update(&[b'a'; 1000]);
+  }
+  assert_eq!(
+    hasher.finalize(),
+    hex("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0")
+  );
+}
+
+#[test]
+fn sha1_nist_vectors() {
+  assert_eq!(
+    Sha1::digest(b"abc"),
+    hex("a9993e364706816aba3e25717850c26c9cd0d89d")
+  );
+  assert_eq!(
+    Sha1::digest(b""),
+    hex("da39a3ee5e6b4b0d3255bfef95601890afd80709")
+  );
+  assert_eq!(
+    Sha1::digest(TWO_BLOCK),
+    hex("84983e441c3bd26ebaae4aa1f95129e5e54670f1")
+  );
+  let mut hasher = Sha1::new();
+  for _ in 0..1000 {
+    hasher.update(&[b'a'; 1000]);
+  }
+  assert_eq!(
+    hasher.finalize(),
+    hex("34aa973cd4c4daa4f61eeb2bdbad27316534016f")
+  );
+}
+
+#[test]
+fn padding_edges() {
+  // 55 bytes fits the length in the same block, 56 needs another block
+  assert_eq!(
+    Sha256::digest(&[b'a'; 55]),
+    hex("9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318")
+  );
+  assert_eq!(
+    Sha256::digest(&[b'a'; 56]),
+    hex("b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a")
+  );
+  assert_eq!(
+    Sha256::digest(&[b'a'; 64]),
+    hex("ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb")
+  );
+}
+
+#[test]
+fn any_split_matches_digest() {
+  let mut rng = Rng(1);
+  let data: Vec<u8> = (0..300).map(|_| rng.next_u32() as u8).collect();
+  let whole_256 = Sha256::digest(&data);
+  let whole_1 = Sha1::digest(&data);
+  for split in 0..data.len() {
+    let (a, b) = data.split_at(split);
+    let mut hasher = Sha256::default();
+    hasher.update(a);
+    hasher.update(b);
+    assert_eq!(hasher.finalize(), whole_256);
+    let mut hasher = Sha1::default();
+    hasher.update(a);
+    hasher.update(b);
+    assert_eq!(hasher.finalize(), whole_1);
+  }
+  // and a byte at a time
+  let mut hasher = Sha256::new();
+  data.chunks(1).for_each(|b| hasher.update(b));
+  assert_eq!(hasher.finalize(), whole_256);
+}
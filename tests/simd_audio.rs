@@ -0,0 +1,179 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::audio::*;
+
+/// The reference `f32` to `i16` conversion, done in `f64`.
+fn to_i16_ref(x: f32) -> i16 {
+  if x.is_nan() {
+    0
+  } else {
+    (f64::from(x) * 32768.0)
+      .round_ties_even()
+      .clamp(-32768.0, 32767.0) as i16
+  }
+}
+
+#[test]
+fn i16_to_f32_exhaustive() {
+  let all: Vec<i16> = (i16::MIN..=i16::MAX).collect();
+  // offsets so every value goes through both the SIMD loop and the tail
+  for offset in 0..8 {
+    let src = &all[offset..];
+    let mut dst = vec![0.0; src.len() + 1];
+    assert_eq!(i16_to_f32(src, &mut dst), src.len());
+    for (&s, &d) in src.iter().zip(dst.iter()) {
+      assert_eq!(f64::from(d), f64::from(s) / 32768.0);
+    }
+  }
+  let mut back = vec![0; all.len()];
+  let mut floats = vec![0.0; all.len()];
+  i16_to_f32(&all, &mut floats);
+  assert_eq!(f32_to_i16(&floats, &mut back), all.len());
+  assert_eq!(back, all);
+}
+
+#[test]
+fn f32_to_i16_against_reference() {
+  let mut samples: Vec<f32> = (0..=u32::MAX).step_by(997).map(f32::from_bits).collect();
+  // every halfway point, and the values right next to them
+  for k in -32770..32770 {
+    let tie = (k as f32 + 0.5) / 32768.0;
+    samples.push(tie);
+    samples.push(f32::from_bits(tie.to_bits() + 1));
+    samples.push(f32::from_bits(tie.to_bits() - 1));
+  }
+  samples.extend_from_slice(&[
+    1.0,
+    -1.0,
+    2.0,
+    -2.0,
+    f32::INFINITY,
+    f32::NEG_INFINITY,
+    -0.0,
+    65536.0 / 32768.0,
+  ]);
+  for offset in 0..8 {
+    let src = &samples[offset..];
+    let mut dst = vec![0; src.len()];
+    assert_eq!(f32_to_i16(src, &mut dst), src.len());
+    for (&s, &d) in src.iter().zip(dst.iter()) {
+      assert_eq!(d, to_i16_ref(s), "{} ({:08X})", s, s.to_bits());
+    }
+  }
+}
+
+#[test]
+fn i24_exhaustive() {
+  let mut bytes = Vec::with_capacity(3 << 24);
+  for x in 0..1_u32 << 24 {
+    bytes.extend_from_slice(&x.to_le_bytes()[..3]);
+  }
+  let mut ints = vec![0; 1 << 24];
+  assert_eq!(i24_to_i32(&bytes, &mut ints), 1 << 24);
+  for (x, &i) in ints.iter().enumerate() {
+    // bit 23 is the sign
+    let expected = if x >= 1 << 23 {
+      x as i32 - (1 << 24)
+    } else {
+      x as i32
+    };
+    assert_eq!(i, expected);
+  }
+  let mut floats = vec![0.0; 1 << 24];
+  assert_eq!(i24_to_f32(&bytes, &mut floats), 1 << 24);
+  for (&f, &i) in floats.iter().zip(ints.iter()) {
+    assert_eq!(f64::from(f), f64::from(i) / 8_388_608.0);
+  }
+  assert_eq!(floats[0x80_0000], -1.0);
+  assert_eq!(floats[0x7F_FFFF], 8_388_607.0 / 8_388_608.0);
+}
+
+#[test]
+fn i24_lengths() {
+  let mut rng = Rng(24);
+  let bytes: Vec<u8> = (0..200).map(|_| rng.next_u32() as u8).collect();
+  for len in 0..bytes.len() {
+    let src = &bytes[..len];
+    let mut ints = vec![0; 70];
+    let n = i24_to_i32(src, &mut ints);
+    assert_eq!(n, len / 3);
+    for (i, &x) in ints[..n].iter().enumerate() {
+      let b = &src[i * 3..];
+      assert_eq!(x, i32::from_le_bytes([b[0], b[1], b[2], 0]) << 8 >> 8);
+    }
+    // a short destination limits the count
+    let mut floats = vec![0.0; 5];
+    assert_eq!(i24_to_f32(src, &mut floats), (len / 3).min(5));
+    for (&f, &i) in floats.iter().zip(ints.iter()).take(len / 3) {
+      assert_eq!(f, i as f32 / 8_388_608.0);
+    }
+  }
+}
+
+#[test]
+fn gain_against_reference() {
+  let mut rng = Rng(0xA0D1_0000);
+  let mut samples: Vec<f32> = (0..1000).map(|_| rng.signed_unit_f32()).collect();
+  samples.extend_from_slice(&[f32::NAN, f32::INFINITY, f32::NEG_INFINITY, -0.0, 1.0, -1.0]);
+  for &gain in &[0.0, 0.5, 1.0, 1.5, -3.0, 1e30, f32::INFINITY, f32::NAN] {
+    for offset in 0..4 {
+      let mut out = samples[offset..].to_vec();
+      apply_gain(&mut out, gain);
+      for (&s, &d) in samples[offset..].iter().zip(out.iter()) {
+        let x = s * gain;
+        let expected = if x.is_nan() { 0.0 } else { x.clamp(-1.0, 1.0) };
+        assert_eq!(d.to_bits(), expected.to_bits(), "{} * {}", s, gain);
+      }
+    }
+  }
+}
+
+#[test]
+fn stereo_round_trip() {
+  let mut rng = Rng(2);
+  for frames in 0..40 {
+    let left: Vec<f32> = (0..frames).map(|_| rng.signed_unit_f32()).collect();
+    let right: Vec<f32> = (0..frames + 3).map(|_| rng.signed_unit_f32()).collect();
+    let mut stereo = vec![0.0; frames * 2 + 1];
+    assert_eq!(interleave_stereo(&left, &right, &mut stereo), frames);
+    for i in 0..frames {
+      assert_eq!(stereo[i * 2], left[i]);
+      assert_eq!(stereo[i * 2 + 1], right[i]);
+    }
+    let (mut l, mut r) = (vec![0.0; frames], vec![0.0; frames + 10]);
+    assert_eq!(deinterleave_stereo(&stereo, &mut l, &mut r), frames);
+    assert_eq!(l, left);
+    assert_eq!(&r[..frames], &right[..frames]);
+  }
+}
+
+#[test]
+fn multichannel_round_trip() {
+  let mut rng = Rng(6);
+  for count in 0..7 {
+    for frames in 0..20 {
+      let channels: Vec<Vec<f32>> = (0..count)
+        .map(|c| (0..frames + c).map(|_| rng.signed_unit_f32()).collect())
+        .collect();
+      let refs: Vec<&[f32]> = channels.iter().map(|c| c.as_slice()).collect();
+      let mut frames_out = vec![0.0; count * frames + 2];
+      let n = interleave(&refs, &mut frames_out);
+      assert_eq!(n, if count == 0 { 0 } else { frames });
+      for f in 0..n {
+        for c in 0..count {
+          assert_eq!(frames_out[f * count + c], channels[c][f]);
+        }
+      }
+      let mut split: Vec<Vec<f32>> = vec![vec![0.0; frames]; count];
+      let mut split_refs: Vec<&mut [f32]> = split.iter_mut().map(|c| c.as_mut_slice()).collect();
+      assert_eq!(
+        deinterleave(&frames_out[..count * frames], &mut split_refs),
+        n
+      );
+      for c in 0..count {
+        assert_eq!(&split[c][..], &channels[c][..frames]);
+      }
+    }
+  }
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::bytes::*;
+
+impl Rng {
+  /// Bytes from a small alphabet so that matches are common, and sometimes
+  /// from the high half so that the signed compares get exercised too.
+  fn byte(&mut self) -> u8 {
+    match self.below(4) {
+      0 => 0xF0 + self.below(4) as u8,
+      _ => b'a' + self.below(4) as u8,
+    }
+  }
+}
+
+fn haystacks(rng: &mut Rng, mut check: impl FnMut(&mut Rng, &[u8])) {
+  for _ in 0..400 {
+    let len = rng.below(200);
+    let data: Vec<u8> = (0..len + 16).map(|_| rng.byte()).collect();
+    // every starting offset, so every head length gets a turn.
+    for offset in 0..16 {
+      check(rng, &data[offset..offset + len]);
+    }
+  }
+}
+
+fn naive_find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+  if needle.is_empty() {
+    return Some(0);
+  }
+  haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn naive_find_all(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+  let mut out = Vec::new();
+  let mut p = 0;
+  while let Some(i) = haystack.get(p..).and_then(|rest| naive_find(rest, needle)) {
+    out.push(p + i);
+    p += i + needle.len().max(1);
+  }
+  out
+}
+
+#[test]
+fn memchr_family_matches_naive() {
+  let mut rng = Rng(1);
+  haystacks(&mut rng, |rng, h| {
+    let (a, b, c) = (rng.byte(), rng.byte(), rng.byte());
+    assert_eq!(memchr(a, h), h.iter().position(|&x| x == a));
+    assert_eq!(memrchr(a, h), h.iter().rposition(|&x| x == a));
+    assert_eq!(memchr2(a, b, h), h.iter().position(|&x| x == a || x == b));
+    assert_eq!(
+      memchr3(a, b, c, h),
+      h.iter().position(|&x| x == a || x == b || x == c)
+    );
+    assert_eq!(count_byte(a, h), h.iter().filter(|&&x| x == a).count());
+  });
+}
+
+#[test]
+fn memchr_missing_byte() {
+  let h = vec![7_u8; 1000];
+  assert_eq!(memchr(8, &h), None);
+  assert_eq!(memrchr(8, &h), None);
+  assert_eq!(memchr3(1, 2, 3, &h), None);
+  assert_eq!(count_byte(8, &h), 0);
+  assert_eq!(memchr(8, &[]), None);
+  assert_eq!(memrchr(8, &[]), None);
+}
+
+#[test]
+fn count_byte_large() {
+  // more than 255 blocks of all matches, so the lane counters must be flushed
+  let h = vec![0xFF_u8; 255 * 16 * 3 + 7];
+  assert_eq!(count_byte(0xFF, &h), h.len());
+}
+
+#[test]
+fn memchr_iters_match_naive() {
+  let mut rng = Rng(2);
+  haystacks(&mut rng, |rng, h| {
+    let (a, b, c) = (rng.byte(), rng.byte(), rng.byte());
+    let positions = |f: &dyn Fn(u8) -> bool| -> Vec<usize> {
+      h.iter()
+        .enumerate()
+        .filter(|(_, &x)| f(x))
+        .map(|(i, _)| i)
+        .collect()
+    };
+    assert_eq!(
+      memchr_iter(a, h).collect::<Vec<_>>(),
+      positions(&|x| x == a)
+    );
+    assert_eq!(
+      memchr2_iter(a, b, h).collect::<Vec<_>>(),
+      positions(&|x| x == a || x == b)
+    );
+    assert_eq!(
+      memchr3_iter(a, b, c, h).collect::<Vec<_>>(),
+      positions(&|x| x == a || x == b || x == c)
+    );
+  });
+}
+
+#[test]
+fn find_matches_naive() {
+  let mut rng = Rng(3);
+  haystacks(&mut rng, |rng, h| {
+    for _ in 0..4 {
+      let needle: Vec<u8> = if !h.is_empty() && rng.below(2) == 0 {
+        // a needle that's really in there
+        let start = rng.below(h.len() as u32);
+        let len = rng.below((h.len() - start) as u32 + 1).min(20);
+        h[start..start + len].to_vec()
+      } else {
+        (0..rng.below(6)).map(|_| rng.byte()).collect()
+      };
+      assert_eq!(
+        find(h, &needle),
+        naive_find(h, &needle),
+        "{:?} in {:?}",
+        needle,
+        h
+      );
+      assert_eq!(
+        find_iter(h, &needle).collect::<Vec<_>>(),
+        naive_find_all(h, &needle)
+      );
+    }
+  });
+}
+
+#[test]
+fn find_edge_cases() {
+  assert_eq!(find(b"", b""), Some(0));
+  assert_eq!(find(b"abc", b""), Some(0));
+  assert_eq!(find(b"ab", b"abc"), None);
+  assert_eq!(find(b"abc", b"abc"), Some(0));
+  let mut h = vec![b'a'; 100];
+  h.extend_from_slice(b"ab");
+  assert_eq!(find(&h, b"aab"), Some(99));
+  assert_eq!(find_iter(b"aaaa", b"aa").collect::<Vec<_>>(), vec![0, 2]);
+  assert_eq!(find_iter(b"ab", b"").collect::<Vec<_>>(), vec![0, 1, 2]);
+}
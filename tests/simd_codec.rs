@@ -0,0 +1,258 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::codec::*;
+
+impl Rng {
+  fn bytes(&mut self, len: usize) -> Vec<u8> {
+    (0..len).map(|_| self.next_u32() as u8).collect()
+  }
+}
+
+fn naive_hex(src: &[u8]) -> String {
+  src.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn naive_base64(src: &[u8], alphabet: &[u8; 64], padded: bool) -> String {
+  let mut out = String::new();
+  for group in src.chunks(3) {
+    let mut n = 0_u32;
+    for (k, &b) in group.iter().enumerate() {
+      n |= u32::from(b) << (16 - 8 * k);
+    }
+    for k in 0..4 {
+      if k <= group.len() {
+        out.push(alphabet[(n >> (18 - 6 * k)) as usize & 0x3F] as char);
+      } else if padded {
+        out.push('=');
+      }
+    }
+  }
+  out
+}
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[test]
+fn hex_round_trip() {
+  let mut rng = Rng(1);
+  for len in 0..200 {
+    let src = rng.bytes(len);
+    let mut enc = vec![0; hex_encoded_len(len)];
+    let s = hex_encode(&src, &mut enc).unwrap();
+    assert_eq!(s, naive_hex(&src));
+    let mut upper = vec![0; hex_encoded_len(len)];
+    assert_eq!(
+      hex_encode_upper(&src, &mut upper).unwrap(),
+      naive_hex(&src).to_uppercase()
+    );
+    let mut dec = vec![0; len];
+    assert_eq!(hex_decode(&enc, &mut dec), Ok(len));
+    assert_eq!(dec, src);
+    assert_eq!(hex_decode(&upper, &mut dec), Ok(len));
+    assert_eq!(dec, src);
+  }
+}
+
+#[test]
+fn hex_decode_errors() {
+  let mut out = [0; 64];
+  assert_eq!(
+    hex_decode(b"abc", &mut out),
+    Err(CodecError::InvalidLength { len: 3 })
+  );
+  assert_eq!(
+    hex_decode(b"abcd", &mut out[..1]),
+    Err(CodecError::OutputTooSmall { needed: 2 })
+  );
+  // a bad byte at every position, both in the SIMD part and the tail.
+  let good = "0123456789abcdefABCDEF".repeat(4);
+  for index in 0..good.len() {
+    for &byte in &[b'g', b'G', b'/', b':', b'@', b'`', b' ', 0x80, 0xFF] {
+      let mut bad = good.clone().into_bytes();
+      bad[index] = byte;
+      assert_eq!(
+        hex_decode(&bad, &mut out),
+        Err(CodecError::InvalidByte { index, byte })
+      );
+    }
+  }
+}
+
+#[test]
+fn hex_encode_too_small() {
+  let mut out = [0; 5];
+  assert_eq!(
+    hex_encode(b"abc", &mut out),
+    Err(CodecError::OutputTooSmall { needed: 6 })
+  );
+  let mut out = [0; 7];
+  assert_eq!(hex_encode(b"abc", &mut out), Ok("616263"));
+}
+
+#[test]
+fn base64_rfc4648_vectors() {
+  let vectors = [
+    ("", ""),
+    ("f", "Zg=="),
+    ("fo", "Zm8="),
+    ("foo", "Zm9v"),
+    ("foob", "Zm9vYg=="),
+    ("fooba", "Zm9vYmE="),
+    ("foobar", "Zm9vYmFy"),
+  ];
+  let mut buf = [0; 64];
+  for &(plain, encoded) in &vectors {
+    assert_eq!(
+      Base64::Standard.encode(plain.as_bytes(), &mut buf),
+      Ok(encoded)
+    );
+    let mut out = [0; 64];
+    let n = Base64::Standard
+      .decode(encoded.as_bytes(), &mut out)
+      .unwrap();
+    assert_eq!(&out[..n], plain.as_bytes());
+    let unpadded = encoded.trim_end_matches('=');
+    assert_eq!(
+      Base64::Standard.encode_unpadded(plain.as_bytes(), &mut buf),
+      Ok(unpadded)
+    );
+    let n = Base64::Standard
+      .decode(unpadded.as_bytes(), &mut out)
+      .unwrap();
+    assert_eq!(&out[..n], plain.as_bytes());
+  }
+}
+
+#[test]
+fn base64_round_trip() {
+  let mut rng = Rng(2);
+  for len in 0..300 {
+    let src = rng.bytes(len);
+    for &(b64, alphabet) in &[(Base64::Standard, STANDARD), (Base64::UrlSafe, URL_SAFE)] {
+      for &padded in &[true, false] {
+        let mut enc = vec![0; Base64::encoded_len(len, padded)];
+        let s = if padded {
+          b64.encode(&src, &mut enc)
+        } else {
+          b64.encode_unpadded(&src, &mut enc)
+        };
+        assert_eq!(s.unwrap(), naive_base64(&src, alphabet, padded));
+        let mut dec = vec![0; Base64::decoded_len_max(enc.len())];
+        assert_eq!(b64.decode(&enc, &mut dec), Ok(len));
+        assert_eq!(&dec[..len], &src[..]);
+        // exactly enough room is fine, one less isn't
+        assert_eq!(b64.decode(&enc, &mut dec[..len]), Ok(len));
+        if len > 0 {
+          assert_eq!(
+            b64.decode(&enc, &mut dec[..len - 1]),
+            Err(CodecError::OutputTooSmall { needed: len })
+          );
+        }
+      }
+    }
+  }
+}
+
+#[test]
+fn base64_bad_bytes() {
+  let mut rng = Rng(3);
+  let mut out = [0; 128];
+  let src = rng.bytes(60);
+  let mut enc = [0; 80];
+  Base64::Standard.encode(&src, &mut enc).unwrap();
+  for index in 0..enc.len() {
+    for &byte in &[
+      b'-', b'_', b'=', b'.', b' ', b'@', b'[', b'`', b'{', 0x80, 0xFF,
+    ] {
+      let mut bad = enc;
+      bad[index] = byte;
+      let result = Base64::Standard.decode(&bad, &mut out);
+      if byte == b'=' && index >= enc.len() - 2 {
+        // that's just padding, but then the bits before it don't fit.
+        assert!(result.is_err());
+      } else {
+        assert_eq!(result, Err(CodecError::InvalidByte { index, byte }));
+      }
+    }
+  }
+  // and the other way around for the URL alphabet
+  let mut url = [0; 80];
+  Base64::UrlSafe.encode(&src, &mut url).unwrap();
+  for index in 0..url.len() {
+    for &byte in b"+/" {
+      let mut bad = url;
+      bad[index] = byte;
+      assert_eq!(
+        Base64::UrlSafe.decode(&bad, &mut out),
+        Err(CodecError::InvalidByte { index, byte })
+      );
+    }
+  }
+}
+
+#[test]
+fn base64_bad_lengths_and_padding() {
+  let mut out = [0; 16];
+  let b = Base64::Standard;
+  assert_eq!(
+    b.decode(b"Z", &mut out),
+    Err(CodecError::InvalidLength { len: 1 })
+  );
+  assert_eq!(
+    b.decode(b"Zm9vY", &mut out),
+    Err(CodecError::InvalidLength { len: 5 })
+  );
+  assert_eq!(
+    b.decode(b"Zg=", &mut out),
+    Err(CodecError::InvalidLength { len: 3 })
+  );
+  assert_eq!(
+    b.decode(b"Zm9=v", &mut out),
+    Err(CodecError::InvalidLength { len: 5 })
+  );
+  assert_eq!(
+    b.decode(b"Zg===", &mut out),
+    Err(CodecError::InvalidLength { len: 5 })
+  );
+  assert_eq!(
+    b.decode(b"Z===", &mut out),
+    Err(CodecError::InvalidByte {
+      index: 1,
+      byte: b'='
+    })
+  );
+  assert_eq!(
+    b.decode(b"Zm=v", &mut out),
+    Err(CodecError::InvalidByte {
+      index: 2,
+      byte: b'='
+    })
+  );
+  // leftover bits in the last character must be zero
+  assert_eq!(
+    b.decode(b"Zh==", &mut out),
+    Err(CodecError::InvalidByte {
+      index: 1,
+      byte: b'h'
+    })
+  );
+  assert_eq!(
+    b.decode(b"Zm9", &mut out),
+    Err(CodecError::InvalidByte {
+      index: 2,
+      byte: b'9'
+    })
+  );
+  assert_eq!(b.decode(b"Zm8", &mut out), Ok(2));
+}
+
+#[test]
+fn error_display() {
+  let e = CodecError::InvalidByte {
+    index: 3,
+    byte: b'!',
+  };
+  assert_eq!(e.to_string(), "invalid byte 0x21 at index 3");
+}
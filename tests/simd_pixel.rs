@@ -0,0 +1,257 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::pixel::*;
+
+impl Rng {
+  fn next_pixel(&mut self) -> [u8; 4] {
+    self.next_u32().to_le_bytes()
+  }
+}
+
+/// `x / 255` rounded to nearest, done the slow way.
+fn div255_ref(x: u32) -> u8 {
+  (f64::from(x) / 255.0).round() as u8
+}
+
+#[test]
+fn swap_red_blue_matches_scalar() {
+  let mut rng = Rng(1);
+  for len in 0..40 {
+    let src: Vec<[u8; 4]> = (0..len).map(|_| rng.next_pixel()).collect();
+    let mut pixels = src.clone();
+    swap_red_blue(&mut pixels);
+    for (p, s) in pixels.iter().zip(src.iter()) {
+      assert_eq!(*p, [s[2], s[1], s[0], s[3]]);
+    }
+    swap_red_blue(&mut pixels);
+    assert_eq!(pixels, src);
+  }
+}
+
+#[test]
+fn rgb565_every_value() {
+  let src: Vec<u16> = (0..=u16::MAX).collect();
+  let mut rgba = vec![[0; 4]; src.len()];
+  assert_eq!(rgb565_to_rgba8(&src, &mut rgba), src.len());
+  for (&v, p) in src.iter().zip(rgba.iter()) {
+    let (r, g, b) = (v >> 11, (v >> 5) & 0x3F, v & 0x1F);
+    let expected = [
+      (r * 255 / 31) as u8,
+      (g * 255 / 63) as u8,
+      (b * 255 / 31) as u8,
+      255,
+    ];
+    // bit replication is within one of the truncated scale
+    for c in 0..3 {
+      assert!(p[c].wrapping_sub(expected[c]) <= 1, "{:04X}: {:?}", v, p);
+    }
+    assert_eq!(p[3], 255);
+  }
+  assert_eq!(rgba[0], [0, 0, 0, 255]);
+  assert_eq!(rgba[0xFFFF], [255, 255, 255, 255]);
+  // expanding then reducing gives back the same value
+  let mut back = vec![0; src.len()];
+  assert_eq!(rgba8_to_rgb565(&rgba, &mut back), src.len());
+  assert_eq!(back, src);
+}
+
+#[test]
+fn rgba8_to_rgb565_matches_scalar() {
+  let mut rng = Rng(2);
+  for len in 0..40 {
+    let src: Vec<[u8; 4]> = (0..len).map(|_| rng.next_pixel()).collect();
+    let mut dst = vec![0; len + 3];
+    assert_eq!(rgba8_to_rgb565(&src, &mut dst), len);
+    for (p, &v) in src.iter().zip(dst.iter()) {
+      let r = u16::from(div255_ref(u32::from(p[0]) * 31));
+      let g = u16::from(div255_ref(u32::from(p[1]) * 63));
+      let b = u16::from(div255_ref(u32::from(p[2]) * 31));
+      assert_eq!(v, (r << 11) | (g << 5) | b, "{:?}", p);
+    }
+    assert_eq!(&dst[len..], &[0, 0, 0]);
+  }
+}
+
+#[test]
+fn rgba4444_every_value() {
+  let src: Vec<u16> = (0..=u16::MAX).collect();
+  let mut rgba = vec![[0; 4]; src.len()];
+  assert_eq!(rgba4444_to_rgba8(&src, &mut rgba), src.len());
+  for (&v, p) in src.iter().zip(rgba.iter()) {
+    let expected = [v >> 12, (v >> 8) & 0xF, (v >> 4) & 0xF, v & 0xF];
+    for c in 0..4 {
+      assert_eq!(u16::from(p[c]), expected[c] * 17, "{:04X}: {:?}", v, p);
+    }
+  }
+  let mut back = vec![0; src.len()];
+  assert_eq!(rgba8_to_rgba4444(&rgba, &mut back), src.len());
+  assert_eq!(back, src);
+}
+
+#[test]
+fn rgba8_to_rgba4444_matches_scalar() {
+  let mut rng = Rng(3);
+  for len in 0..40 {
+    let src: Vec<[u8; 4]> = (0..len).map(|_| rng.next_pixel()).collect();
+    let mut dst = vec![0; len];
+    assert_eq!(rgba8_to_rgba4444(&src, &mut dst[..len / 2]), len / 2);
+    assert_eq!(rgba8_to_rgba4444(&src, &mut dst), len);
+    for (p, &v) in src.iter().zip(dst.iter()) {
+      let n = |c: u8| u16::from(div255_ref(u32::from(c) * 15));
+      assert_eq!(
+        v,
+        (n(p[0]) << 12) | (n(p[1]) << 8) | (n(p[2]) << 4) | n(p[3])
+      );
+    }
+  }
+}
+
+/// One pixel for every `(c, a)` pair, with the other colors varied too.
+fn every_color_alpha() -> Vec<[u8; 4]> {
+  (0..=u16::MAX)
+    .map(|x| {
+      let [c, a] = x.to_le_bytes();
+      [c, 255 - c, c / 3, a]
+    })
+    .collect()
+}
+
+#[test]
+fn premultiply_every_pair() {
+  let src = every_color_alpha();
+  let mut pixels = src.clone();
+  premultiply_alpha(&mut pixels);
+  for (p, s) in pixels.iter().zip(src.iter()) {
+    let a = u32::from(s[3]);
+    let expected = [
+      div255_ref(u32::from(s[0]) * a),
+      div255_ref(u32::from(s[1]) * a),
+      div255_ref(u32::from(s[2]) * a),
+      s[3],
+    ];
+    assert_eq!(*p, expected, "{:?}", s);
+  }
+}
+
+#[test]
+fn unpremultiply_every_pair() {
+  let src = every_color_alpha();
+  let mut pixels = src.clone();
+  unpremultiply_alpha(&mut pixels);
+  for (p, s) in pixels.iter().zip(src.iter()) {
+    let a = f64::from(s[3]);
+    let un = |c: u8| {
+      if s[3] == 0 {
+        0
+      } else {
+        (f64::from(c) * 255.0 / a + 0.5).floor().min(255.0) as u8
+      }
+    };
+    assert_eq!(*p, [un(s[0]), un(s[1]), un(s[2]), s[3]], "{:?}", s);
+  }
+}
+
+#[test]
+fn premultiply_round_trip_when_opaque() {
+  let mut rng = Rng(4);
+  for len in 0..40 {
+    let src: Vec<[u8; 4]> = (0..len)
+      .map(|_| {
+        let [r, g, b, _] = rng.next_pixel();
+        [r, g, b, 255]
+      })
+      .collect();
+    let mut pixels = src.clone();
+    premultiply_alpha(&mut pixels);
+    assert_eq!(pixels, src);
+    unpremultiply_alpha(&mut pixels);
+    assert_eq!(pixels, src);
+  }
+}
+
+#[test]
+fn blend_over_matches_scalar() {
+  let mut rng = Rng(5);
+  for len in 0..300 {
+    let mut src: Vec<[u8; 4]> = (0..len).map(|_| rng.next_pixel()).collect();
+    let mut dst: Vec<[u8; 4]> = (0..len).map(|_| rng.next_pixel()).collect();
+    premultiply_alpha(&mut src);
+    premultiply_alpha(&mut dst);
+    let before = dst.clone();
+    assert_eq!(blend_over(&src, &mut dst), len);
+    for ((d, s), b) in dst.iter().zip(src.iter()).zip(before.iter()) {
+      let inv_a = 255 - u32::from(s[3]);
+      for c in 0..4 {
+        let expected = u32::from(s[c]) + u32::from(div255_ref(u32::from(b[c]) * inv_a));
+        assert!(expected <= 255);
+        assert_eq!(u32::from(d[c]), expected, "{:?} over {:?}", s, b);
+      }
+    }
+  }
+}
+
+#[test]
+fn blend_over_extremes() {
+  let mut dst = vec![[10, 20, 30, 40]; 9];
+  // fully transparent source leaves the destination alone
+  blend_over(&[[0; 4]; 9], &mut dst);
+  assert_eq!(dst, vec![[10, 20, 30, 40]; 9]);
+  // fully opaque source replaces it
+  blend_over(&[[1, 2, 3, 255]; 9], &mut dst);
+  assert_eq!(dst, vec![[1, 2, 3, 255]; 9]);
+  // and only as many pixels as the shorter slice are touched
+  assert_eq!(blend_over(&[[0, 0, 0, 255]; 5], &mut dst), 5);
+  assert_eq!(&dst[4..6], &[[0, 0, 0, 255], [1, 2, 3, 255]]);
+}
+
+#[test]
+fn u8_to_unorm_every_value() {
+  let src: Vec<u8> = (0..=255).chain(0..=255).collect();
+  for len in [0, 1, 15, 16, 17, 100, src.len()].iter().copied() {
+    let mut dst = vec![-1.0; len];
+    assert_eq!(u8_to_unorm_f32(&src[..len], &mut dst), len);
+    for (&x, &f) in src.iter().zip(dst.iter()) {
+      assert_eq!(f, f32::from(x) / 255.0);
+    }
+  }
+  let mut ends = [0.0; 2];
+  u8_to_unorm_f32(&[0, 255], &mut ends);
+  assert_eq!(ends, [0.0, 1.0]);
+}
+
+#[test]
+fn unorm_to_u8_round_trip() {
+  let src: Vec<u8> = (0..=255).collect();
+  let mut unorm = vec![0.0; 256];
+  u8_to_unorm_f32(&src, &mut unorm);
+  let mut back = vec![0; 256];
+  assert_eq!(unorm_f32_to_u8(&unorm, &mut back), 256);
+  assert_eq!(back, src);
+}
+
+#[test]
+fn unorm_to_u8_clamps_and_rounds() {
+  let mut src = vec![
+    f32::NAN,
+    f32::INFINITY,
+    f32::NEG_INFINITY,
+    -0.0,
+    -1.0,
+    2.0,
+    0.5 / 255.0,
+    0.49 / 255.0,
+    254.5 / 255.0,
+    1.0,
+  ];
+  let expected = [0, 255, 0, 0, 0, 255, 1, 0, 255, 255];
+  let mut rng = Rng(6);
+  src.extend((0..100).map(|_| (rng.next_u32() as f32 / u32::MAX as f32) * 1.5 - 0.25));
+  let mut dst = vec![0; src.len()];
+  assert_eq!(unorm_f32_to_u8(&src, &mut dst), src.len());
+  assert_eq!(&dst[..expected.len()], &expected);
+  for (&f, &x) in src.iter().zip(dst.iter()).skip(expected.len()) {
+    let reference = (f64::from(f) * 255.0).round().clamp(0.0, 255.0);
+    assert_eq!(f64::from(x), reference, "{}", f);
+  }
+}
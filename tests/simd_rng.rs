@@ -0,0 +1,180 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::rng::*;
+
+#[test]
+fn xoshiro128plusplus_reference_outputs() {
+  // from the reference C code, seeded with `{1, 2, 3, 4}`
+  let expected = [
+    641, 1573767, 3222811527, 3517856514, 836907274, 4247214768, 3867114732, 1355841295, 495546011,
+    621204420,
+  ];
+  let mut rng = Xoshiro128PlusPlus::from_state([1, 2, 3, 4]);
+  for &e in expected.iter() {
+    assert_eq!(rng.next_u32(), e);
+  }
+}
+
+#[test]
+fn pcg32_reference_outputs() {
+  // from the `pcg32-demo` program, with seed 42 and stream 54
+  let expected = [
+    0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e,
+  ];
+  let mut rng = Pcg32::new(42, 54);
+  for &e in expected.iter() {
+    assert_eq!(rng.next_u32(), e);
+  }
+}
+
+#[test]
+fn xoshiro_new_never_all_zero() {
+  for seed in 0..1000 {
+    assert_ne!(Xoshiro128PlusPlus::new(seed).state(), [0; 4]);
+  }
+}
+
+#[test]
+fn f32_in_unit_range() {
+  let mut rng = Pcg32::new(7, 0);
+  for _ in 0..100_000 {
+    let f = rng.next_f32();
+    assert!((0.0..1.0).contains(&f), "{}", f);
+  }
+}
+
+#[test]
+fn below_stays_in_range() {
+  let mut rng = Xoshiro128PlusPlus::new(1);
+  for &bound in [1, 2, 3, 6, 10, 1000, 0x8000_0001, u32::MAX].iter() {
+    for _ in 0..10_000 {
+      assert!(rng.next_below(bound) < bound);
+    }
+  }
+  for _ in 0..100 {
+    assert_eq!(rng.next_below(0), 0);
+  }
+}
+
+#[test]
+fn below_has_no_bias() {
+  // with a bound just over `2^31` almost half of the raw values would be
+  // rejected, and plain `x % bound` would put about 2/3 of the results below
+  // `2^31 - 1`
+  let bound = 0x8000_0001;
+  let mut rng = Pcg32::new(3, 3);
+  let n = 100_000;
+  let low = (0..n).filter(|_| rng.next_below(bound) < bound / 2).count();
+  assert!((n * 48 / 100..n * 52 / 100).contains(&low), "{}", low);
+}
+
+#[test]
+fn xoshiro_x4_matches_scalar_lanes() {
+  let mut noise = Rng(0x1234_5678);
+  let mut x4 = Xoshiro128PlusPlusX4::new(99);
+  let mut lanes = [
+    Xoshiro128PlusPlus::new(99),
+    Xoshiro128PlusPlus::new(100),
+    Xoshiro128PlusPlus::new(101),
+    Xoshiro128PlusPlus::new(102),
+  ];
+  assert_eq!(x4.to_lanes(), lanes);
+  for round in 0..20_000 {
+    match round % 3 {
+      0 => {
+        let v = x4.next_u32x4();
+        for (lane, &x) in lanes.iter_mut().zip(&v) {
+          assert_eq!(lane.next_u32(), x);
+        }
+      }
+      1 => {
+        let v = x4.next_f32x4();
+        for (lane, &x) in lanes.iter_mut().zip(&v) {
+          assert_eq!(lane.next_f32().to_bits(), x.to_bits());
+        }
+      }
+      _ => {
+        // mostly big bounds, so that rejections actually happen
+        let bound = noise.next_u32() | (noise.next_u32() & 0x8000_0000);
+        let v = x4.next_below_x4(bound);
+        for (lane, &x) in lanes.iter_mut().zip(&v) {
+          assert_eq!(lane.next_below(bound), x);
+        }
+      }
+    }
+  }
+  assert_eq!(x4.to_lanes(), lanes);
+}
+
+#[test]
+fn pcg32_x4_matches_scalar_lanes() {
+  let mut noise = Rng(0x9ABC_DEF0);
+  let mut x4 = Pcg32X4::new(u64::MAX);
+  let mut lanes = [
+    Pcg32::new(u64::MAX, 0),
+    Pcg32::new(u64::MAX, 1),
+    Pcg32::new(u64::MAX, 2),
+    Pcg32::new(u64::MAX, 3),
+  ];
+  assert_eq!(x4.to_lanes(), lanes);
+  for round in 0..20_000 {
+    match round % 3 {
+      0 => {
+        let v = x4.next_u32x4();
+        for (lane, &x) in lanes.iter_mut().zip(&v) {
+          assert_eq!(lane.next_u32(), x);
+        }
+      }
+      1 => {
+        let v = x4.next_f32x4();
+        for (lane, &x) in lanes.iter_mut().zip(&v) {
+          assert_eq!(lane.next_f32().to_bits(), x.to_bits());
+        }
+      }
+      _ => {
+        let bound = noise.next_u32() | (noise.next_u32() & 0x8000_0000);
+        let v = x4.next_below_x4(bound);
+        for (lane, &x) in lanes.iter_mut().zip(&v) {
+          assert_eq!(lane.next_below(bound), x);
+        }
+      }
+    }
+  }
+  assert_eq!(x4.to_lanes(), lanes);
+}
+
+#[test]
+fn lanes_round_trip() {
+  let lanes = [
+    Pcg32::new(1, 10),
+    Pcg32::new(2, 20),
+    Pcg32::new(3, 30),
+    Pcg32::new(4, 40),
+  ];
+  assert_eq!(Pcg32X4::from_lanes(lanes).to_lanes(), lanes);
+  assert_eq!(Pcg32X4::from(lanes).to_lanes(), lanes);
+  let lanes = [
+    Xoshiro128PlusPlus::from_state([1, 2, 3, 4]),
+    Xoshiro128PlusPlus::from_state([5, 6, 7, 8]),
+    Xoshiro128PlusPlus::from_state([9, 10, 11, 12]),
+    Xoshiro128PlusPlus::from_state([13, 14, 15, 16]),
+  ];
+  assert_eq!(Xoshiro128PlusPlusX4::from_lanes(lanes).to_lanes(), lanes);
+  assert_eq!(Xoshiro128PlusPlusX4::from(lanes).to_lanes(), lanes);
+}
+
+#[cfg(target_feature = "sse2")]
+#[test]
+fn m128_outputs_match_arrays() {
+  let mut a = Pcg32X4::new(5);
+  let mut b = a;
+  for _ in 0..1000 {
+    let f: [f32; 4] = lokacore::cast(a.next_m128());
+    assert_eq!(f, b.next_f32x4());
+    let x: [u32; 4] = lokacore::cast(a.next_m128i());
+    assert_eq!(x, b.next_u32x4());
+    let d: [u32; 4] = lokacore::cast(a.next_below_m128i(6));
+    assert_eq!(d, b.next_below_x4(6));
+  }
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::scan::*;
+
+#[test]
+fn doc_example() {
+  let mut inclusive = [3, 1, 4, 1, 5];
+  assert_eq!(prefix_sum_i32(&mut inclusive), 14);
+  assert_eq!(inclusive, [3, 4, 8, 9, 14]);
+  let mut exclusive = [3, 1, 4, 1, 5];
+  assert_eq!(exclusive_prefix_sum_i32(&mut exclusive), 14);
+  assert_eq!(exclusive, [0, 3, 4, 8, 9]);
+  assert_eq!(prefix_sum_i32(&mut []), 0);
+  assert_eq!(exclusive_prefix_sum_f32(&mut []), 0.0);
+}
+
+#[test]
+fn i32_matches_scalar() {
+  let mut rng = Rng(1);
+  for len in 0..70 {
+    let data: Vec<i32> = (0..len).map(|_| rng.next_u32() as i32).collect();
+    let mut total = 0_i32;
+    let mut inclusive_ref = Vec::new();
+    let mut exclusive_ref = Vec::new();
+    for &x in &data {
+      exclusive_ref.push(total);
+      total = total.wrapping_add(x);
+      inclusive_ref.push(total);
+    }
+    let mut inclusive = data.clone();
+    assert_eq!(prefix_sum_i32(&mut inclusive), total);
+    assert_eq!(inclusive, inclusive_ref);
+    let mut exclusive = data.clone();
+    assert_eq!(exclusive_prefix_sum_i32(&mut exclusive), total);
+    assert_eq!(exclusive, exclusive_ref);
+  }
+}
+
+#[test]
+fn i32_wraps() {
+  let mut data = [i32::MAX, 1, 1, i32::MIN, -1, 5, i32::MAX, i32::MAX, 2];
+  assert_eq!(prefix_sum_i32(&mut data), 5);
+  assert_eq!(
+    data,
+    [
+      i32::MAX,
+      i32::MIN,
+      i32::MIN + 1,
+      1,
+      0,
+      5,
+      i32::MIN + 4,
+      3,
+      5
+    ]
+  );
+}
+
+#[test]
+fn f32_matches_scalar() {
+  let mut rng = Rng(2);
+  for len in 0..70 {
+    // small integers add up exactly in any order
+    let data: Vec<f32> = (0..len)
+      .map(|_| (rng.next_u32() % 201) as f32 - 100.0)
+      .collect();
+    let mut total = 0.0;
+    let mut inclusive_ref = Vec::new();
+    let mut exclusive_ref = Vec::new();
+    for &x in &data {
+      exclusive_ref.push(total);
+      total += x;
+      inclusive_ref.push(total);
+    }
+    let mut inclusive = data.clone();
+    assert_eq!(prefix_sum_f32(&mut inclusive), total);
+    assert_eq!(inclusive, inclusive_ref);
+    let mut exclusive = data.clone();
+    assert_eq!(exclusive_prefix_sum_f32(&mut exclusive), total);
+    assert_eq!(exclusive, exclusive_ref);
+  }
+}
+
+#[test]
+fn f32_rounding_stays_close() {
+  let mut rng = Rng(3);
+  let data: Vec<f32> = (0..1000)
+    .map(|_| rng.next_u32() as f32 / u32::MAX as f32)
+    .collect();
+  let mut sums = data.clone();
+  prefix_sum_f32(&mut sums);
+  let mut exact = 0.0_f64;
+  for (&x, &s) in data.iter().zip(sums.iter()) {
+    exact += f64::from(x);
+    assert!(
+      (f64::from(s) - exact).abs() < exact * 1e-5,
+      "{} vs {}",
+      s,
+      exact
+    );
+  }
+}
+
+#[test]
+fn u8_to_u32_matches_scalar() {
+  let mut rng = Rng(4);
+  for len in 0..100 {
+    let src: Vec<u8> = (0..len).map(|_| rng.next_u32() as u8).collect();
+    let mut inclusive_ref = Vec::new();
+    let mut exclusive_ref = Vec::new();
+    let mut total = 0_u32;
+    for &x in &src {
+      exclusive_ref.push(total);
+      total += u32::from(x);
+      inclusive_ref.push(total);
+    }
+    let mut dst = vec![0; len + 2];
+    assert_eq!(prefix_sum_u8_to_u32(&src, &mut dst), len);
+    assert_eq!(&dst[..len], &inclusive_ref[..]);
+    assert_eq!(&dst[len..], &[0, 0]);
+    assert_eq!(exclusive_prefix_sum_u8_to_u32(&src, &mut dst), len);
+    assert_eq!(&dst[..len], &exclusive_ref[..]);
+    assert_eq!(prefix_sum_u8_to_u32(&src, &mut dst[..len / 2]), len / 2);
+    assert_eq!(&dst[..len / 2], &inclusive_ref[..len / 2]);
+  }
+}
+
+#[test]
+fn u8_to_u32_all_max() {
+  // the largest possible sum in each block, and many blocks of them
+  let src = vec![255_u8; 4096 + 7];
+  let mut dst = vec![0; src.len()];
+  prefix_sum_u8_to_u32(&src, &mut dst);
+  for (i, &d) in dst.iter().enumerate() {
+    assert_eq!(d, 255 * (i as u32 + 1));
+  }
+  exclusive_prefix_sum_u8_to_u32(&src, &mut dst);
+  for (i, &d) in dst.iter().enumerate() {
+    assert_eq!(d, 255 * i as u32);
+  }
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::slice::*;
+
+impl Rng {
+  fn next_f32(&mut self) -> f32 {
+    (self.next_u32() >> 8) as f32 / (1 << 24) as f32 * 200.0 - 100.0
+  }
+}
+
+/// Lengths up to a few SIMD chunks, each tried at every starting offset so
+/// that the unaligned head sizes all get covered.
+fn for_each_case<T>(
+  rng: &mut Rng,
+  mut make: impl FnMut(&mut Rng) -> T,
+  mut check: impl FnMut(&[T]),
+) {
+  for _ in 0..300 {
+    let len = rng.below(100);
+    let data: Vec<T> = (0..len + 16).map(|_| make(rng)).collect();
+    for offset in 0..16 {
+      check(&data[offset..offset + len]);
+    }
+  }
+}
+
+fn close_f32(actual: f32, expected: f32, magnitude: f32) -> bool {
+  (actual - expected).abs() <= 1e-5 * magnitude.max(1.0)
+}
+
+fn min_ref(s: &[f32]) -> Option<f32> {
+  s.iter()
+    .copied()
+    .fold(None, |acc, x| Some(acc.map_or(x, |a: f32| a.min(x))))
+}
+
+fn max_ref(s: &[f32]) -> Option<f32> {
+  s.iter()
+    .copied()
+    .fold(None, |acc, x| Some(acc.map_or(x, |a: f32| a.max(x))))
+}
+
+fn arg_ref(s: &[f32], better: fn(f32, f32) -> bool) -> Option<usize> {
+  let mut best: Option<(f32, usize)> = None;
+  for (i, &x) in s.iter().enumerate() {
+    if x.is_nan() {
+      continue;
+    }
+    match best {
+      Some((b, _)) if !better(x, b) => (),
+      _ => best = Some((x, i)),
+    }
+  }
+  best.map(|(_, i)| i)
+}
+
+fn same_f32(a: Option<f32>, b: Option<f32>) -> bool {
+  match (a, b) {
+    (Some(a), Some(b)) => a == b || (a.is_nan() && b.is_nan()),
+    (None, None) => true,
+    _ => false,
+  }
+}
+
+#[test]
+fn sum_f32_matches_scalar() {
+  let mut rng = Rng(1);
+  for_each_case(&mut rng, Rng::next_f32, |s| {
+    let expected: f32 = s.iter().sum();
+    let magnitude: f32 = s.iter().map(|x| x.abs()).sum();
+    let actual = sum_f32(s);
+    assert!(
+      close_f32(actual, expected, magnitude),
+      "{} vs {} for {:?}",
+      actual,
+      expected,
+      s
+    );
+    match mean_f32(s) {
+      None => assert!(s.is_empty()),
+      Some(m) => assert!(close_f32(m * s.len() as f32, expected, magnitude)),
+    }
+  });
+}
+
+#[test]
+fn sum_f64_matches_scalar() {
+  let mut rng = Rng(2);
+  for_each_case(
+    &mut rng,
+    |r| f64::from(r.next_f32()) * 1.5,
+    |s| {
+      let expected: f64 = s.iter().sum();
+      let magnitude: f64 = s.iter().map(|x| x.abs()).sum();
+      let tolerance = 1e-12 * magnitude.max(1.0);
+      assert!((sum_f64(s) - expected).abs() <= tolerance);
+      match mean_f64(s) {
+        None => assert!(s.is_empty()),
+        Some(m) => assert!((m * s.len() as f64 - expected).abs() <= tolerance),
+      }
+    },
+  );
+}
+
+#[test]
+fn dot_f32_matches_scalar() {
+  let mut rng = Rng(3);
+  for _ in 0..500 {
+    let a_len = rng.below(70);
+    let b_len = rng.below(70);
+    let a: Vec<f32> = (0..a_len).map(|_| rng.next_f32()).collect();
+    let b: Vec<f32> = (0..b_len).map(|_| rng.next_f32()).collect();
+    let a_start = rng.below(4).min(a_len);
+    let b_start = rng.below(4).min(b_len);
+    let (a, b) = (&a[a_start..], &b[b_start..]);
+    let expected: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let magnitude: f32 = a.iter().zip(b).map(|(x, y)| (x * y).abs()).sum();
+    assert!(close_f32(dot_f32(a, b), expected, magnitude));
+  }
+}
+
+#[test]
+fn min_max_match_scalar() {
+  let mut rng = Rng(4);
+  let sometimes_nan = |r: &mut Rng| {
+    if r.below(8) == 0 {
+      f32::NAN
+    } else {
+      r.next_f32()
+    }
+  };
+  for_each_case(&mut rng, sometimes_nan, |s| {
+    assert!(same_f32(min_f32(s), min_ref(s)), "{:?}", s);
+    assert!(same_f32(max_f32(s), max_ref(s)), "{:?}", s);
+    assert_eq!(argmin_f32(s), arg_ref(s, |x, b| x < b), "{:?}", s);
+    assert_eq!(argmax_f32(s), arg_ref(s, |x, b| x > b), "{:?}", s);
+  });
+}
+
+#[test]
+fn min_max_edge_cases() {
+  assert_eq!(min_f32(&[]), None);
+  assert_eq!(argmax_f32(&[]), None);
+  let nans = [f32::NAN; 21];
+  assert!(min_f32(&nans).unwrap().is_nan());
+  assert!(max_f32(&nans).unwrap().is_nan());
+  assert_eq!(argmin_f32(&nans), None);
+
+  let infs = [f32::INFINITY; 21];
+  assert_eq!(min_f32(&infs), Some(f32::INFINITY));
+  assert_eq!(argmin_f32(&infs), Some(0));
+  assert_eq!(argmax_f32(&infs), Some(0));
+
+  // ties go to the lowest index, even across SIMD lanes
+  let mut ties = [1.0_f32; 40];
+  ties[13] = 0.0;
+  ties[29] = 0.0;
+  ties[6] = 5.0;
+  ties[35] = 5.0;
+  assert_eq!(argmin_f32(&ties), Some(13));
+  assert_eq!(argmax_f32(&ties), Some(6));
+}
+
+#[test]
+fn sum_u8_matches_scalar() {
+  let mut rng = Rng(5);
+  for_each_case(
+    &mut rng,
+    |r| r.next_u32() as u8,
+    |s| {
+      assert_eq!(sum_u8(s), s.iter().map(|&x| u64::from(x)).sum::<u64>());
+    },
+  );
+  let big = vec![255_u8; 100_003];
+  assert_eq!(sum_u8(&big), 255 * 100_003);
+}
+
+#[test]
+fn sum_i16_matches_scalar() {
+  let mut rng = Rng(6);
+  for_each_case(
+    &mut rng,
+    |r| r.next_u32() as i16,
+    |s| {
+      assert_eq!(sum_i16(s), s.iter().map(|&x| i64::from(x)).sum::<i64>());
+    },
+  );
+  // enough to overflow an `i32` lane if the sum wasn't widened along the way
+  let big = vec![i16::MIN; 1 << 20];
+  assert_eq!(sum_i16(&big), i64::from(i16::MIN) << 20);
+  let big = vec![i16::MAX; 1 << 20];
+  assert_eq!(sum_i16(&big), i64::from(i16::MAX) << 20);
+}
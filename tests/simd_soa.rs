@@ -0,0 +1,99 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::soa::*;
+
+impl Rng {
+  fn next_f32(&mut self) -> f32 {
+    self.next_u32() as f32 / 1000.0
+  }
+}
+
+#[test]
+fn f32_round_trips() {
+  let mut rng = Rng(1);
+  for len in 0..40 {
+    let src2: Vec<[f32; 2]> = (0..len).map(|_| [rng.next_f32(), rng.next_f32()]).collect();
+    let (mut xs, mut ys) = (vec![0.0; len], vec![0.0; len]);
+    assert_eq!(deinterleave2_f32(&src2, &mut xs, &mut ys), len);
+    assert_eq!(xs, src2.iter().map(|r| r[0]).collect::<Vec<_>>());
+    assert_eq!(ys, src2.iter().map(|r| r[1]).collect::<Vec<_>>());
+    let mut back = vec![[0.0; 2]; len];
+    assert_eq!(interleave2_f32(&xs, &ys, &mut back), len);
+    assert_eq!(back, src2);
+
+    let src3: Vec<[f32; 3]> = (0..len)
+      .map(|_| [rng.next_f32(), rng.next_f32(), rng.next_f32()])
+      .collect();
+    let mut zs = vec![0.0; len];
+    assert_eq!(deinterleave3_f32(&src3, &mut xs, &mut ys, &mut zs), len);
+    for (i, r) in src3.iter().enumerate() {
+      assert_eq!([xs[i], ys[i], zs[i]], *r);
+    }
+    let mut back = vec![[0.0; 3]; len];
+    assert_eq!(interleave3_f32(&xs, &ys, &zs, &mut back), len);
+    assert_eq!(back, src3);
+
+    let src4: Vec<[f32; 4]> = (0..len)
+      .map(|_| {
+        [
+          rng.next_f32(),
+          rng.next_f32(),
+          rng.next_f32(),
+          rng.next_f32(),
+        ]
+      })
+      .collect();
+    let mut ws = vec![0.0; len];
+    assert_eq!(
+      deinterleave4_f32(&src4, &mut xs, &mut ys, &mut zs, &mut ws),
+      len
+    );
+    for (i, r) in src4.iter().enumerate() {
+      assert_eq!([xs[i], ys[i], zs[i], ws[i]], *r);
+    }
+    let mut back = vec![[0.0; 4]; len];
+    assert_eq!(interleave4_f32(&xs, &ys, &zs, &ws, &mut back), len);
+    assert_eq!(back, src4);
+  }
+}
+
+#[test]
+fn rgba_round_trips() {
+  let mut rng = Rng(2);
+  for len in 0..100 {
+    let src: Vec<[u8; 4]> = (0..len).map(|_| rng.next_u32().to_le_bytes()).collect();
+    let (mut r, mut g, mut b, mut a) = (vec![0; len], vec![0; len], vec![0; len], vec![0; len]);
+    assert_eq!(deinterleave4_u8(&src, &mut r, &mut g, &mut b, &mut a), len);
+    for (i, p) in src.iter().enumerate() {
+      assert_eq!([r[i], g[i], b[i], a[i]], *p);
+    }
+    let mut back = vec![[0_u8; 4]; len];
+    assert_eq!(interleave4_u8(&r, &g, &b, &a, &mut back), len);
+    assert_eq!(back, src);
+  }
+}
+
+#[test]
+fn uneven_lengths_use_the_shortest() {
+  let src: Vec<[f32; 3]> = (0..11).map(|i| [i as f32, -(i as f32), 0.5]).collect();
+  let (mut xs, mut ys, mut zs) = (vec![9.0; 13], vec![9.0; 9], vec![9.0; 20]);
+  assert_eq!(deinterleave3_f32(&src, &mut xs, &mut ys, &mut zs), 9);
+  assert_eq!(&xs[..9], &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+  // the rest is left alone
+  assert_eq!(&xs[9..], &[9.0; 4]);
+  assert_eq!(&zs[9..], &[9.0; 11]);
+  let mut dst = vec![[7.0; 3]; 5];
+  assert_eq!(interleave3_f32(&xs, &ys, &zs, &mut dst), 5);
+  assert_eq!(dst[4], [4.0, -4.0, 0.5]);
+
+  let pixels = vec![[1, 2, 3, 4]; 40];
+  let (mut r, mut g, mut b, mut a) = (vec![0; 40], vec![0; 40], vec![0; 17], vec![0; 40]);
+  assert_eq!(
+    deinterleave4_u8(&pixels, &mut r, &mut g, &mut b, &mut a),
+    17
+  );
+  assert_eq!(r[16], 1);
+  assert_eq!(r[17], 0);
+  assert_eq!(interleave4_u8(&r, &g, &b, &a, &mut []), 0);
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::sort::*;
+
+/// Every 0/1 input (which is enough to prove a sorting network correct), and
+/// then some noise.
+fn inputs<const N: usize>(rng: &mut Rng) -> impl Iterator<Item = [i32; N]> + '_ {
+  let zero_one = (0..1_u32 << N).map(|bits| {
+    let mut a = [0; N];
+    a.iter_mut()
+      .enumerate()
+      .for_each(|(i, x)| *x = (bits >> i) as i32 & 1);
+    a
+  });
+  let noise = (0..1000).map(move |_| {
+    let mut a = [0; N];
+    a.iter_mut().for_each(|x| *x = rng.next_u32() as i32);
+    a
+  });
+  zero_one.chain(noise)
+}
+
+#[test]
+fn networks_i32() {
+  let mut rng = Rng(1);
+  for a in inputs::<4>(&mut rng) {
+    let (mut got, mut expected) = (a, a);
+    sort4_i32(&mut got);
+    expected.sort_unstable_by(i32::cmp);
+    assert_eq!(got, expected, "{:?}", a);
+  }
+  for a in inputs::<8>(&mut rng) {
+    let (mut got, mut expected) = (a, a);
+    sort8_i32(&mut got);
+    expected.sort_unstable_by(i32::cmp);
+    assert_eq!(got, expected, "{:?}", a);
+  }
+  for a in inputs::<16>(&mut rng) {
+    let (mut got, mut expected) = (a, a);
+    sort16_i32(&mut got);
+    expected.sort_unstable_by(i32::cmp);
+    assert_eq!(got, expected, "{:?}", a);
+  }
+  let mut extremes = [i32::MAX, i32::MIN, -1, 0, 1, i32::MIN, i32::MAX, 0];
+  sort8_i32(&mut extremes);
+  assert_eq!(
+    extremes,
+    [i32::MIN, i32::MIN, -1, 0, 0, 1, i32::MAX, i32::MAX]
+  );
+}
+
+const SPECIAL: [f32; 12] = [
+  f32::NAN,
+  -f32::NAN,
+  f32::INFINITY,
+  f32::NEG_INFINITY,
+  0.0,
+  -0.0,
+  1.0,
+  -1.0,
+  f32::MIN_POSITIVE,
+  -f32::MIN_POSITIVE,
+  f32::MAX,
+  f32::MIN,
+];
+
+fn bits<const N: usize>(a: [f32; N]) -> [u32; N] {
+  let mut out = [0; N];
+  out
+    .iter_mut()
+    .zip(a.iter())
+    .for_each(|(o, x)| *o = x.to_bits());
+  out
+}
+
+fn random_f32<const N: usize>(rng: &mut Rng) -> [f32; N] {
+  let mut a = [0.0; N];
+  a.iter_mut().for_each(|x| {
+    let r = rng.next_u32();
+    *x = if r % 4 == 0 {
+      SPECIAL[(r >> 8) as usize % SPECIAL.len()]
+    } else {
+      f32::from_bits(r)
+    };
+  });
+  a
+}
+
+#[test]
+fn networks_f32() {
+  let mut rng = Rng(2);
+  for _ in 0..2000 {
+    let a: [f32; 4] = random_f32(&mut rng);
+    let (mut got, mut expected) = (a, a);
+    sort4_f32(&mut got);
+    expected.sort_unstable_by(f32::total_cmp);
+    assert_eq!(bits(got), bits(expected), "{:?}", a);
+
+    let a: [f32; 8] = random_f32(&mut rng);
+    let (mut got, mut expected) = (a, a);
+    sort8_f32(&mut got);
+    expected.sort_unstable_by(f32::total_cmp);
+    assert_eq!(bits(got), bits(expected), "{:?}", a);
+
+    let a: [f32; 16] = random_f32(&mut rng);
+    let (mut got, mut expected) = (a, a);
+    sort16_f32(&mut got);
+    expected.sort_unstable_by(f32::total_cmp);
+    assert_eq!(bits(got), bits(expected), "{:?}", a);
+  }
+}
+
+#[test]
+fn nan_and_zero_order() {
+  let mut a = [
+    0.0,
+    f32::NAN,
+    -0.0,
+    1.0,
+    -f32::NAN,
+    f32::NEG_INFINITY,
+    -1.0,
+    0.0,
+  ];
+  sort8_f32(&mut a);
+  assert!(a[0].is_nan() && a[0].is_sign_negative());
+  assert_eq!(a[1], f32::NEG_INFINITY);
+  assert_eq!(a[2], -1.0);
+  assert_eq!(a[3].to_bits(), (-0.0_f32).to_bits());
+  assert_eq!(a[4].to_bits(), 0.0_f32.to_bits());
+  assert_eq!(a[5].to_bits(), 0.0_f32.to_bits());
+  assert_eq!(a[6], 1.0);
+  assert!(a[7].is_nan() && a[7].is_sign_positive());
+}
+
+#[test]
+fn sort_i32_matches_core() {
+  let mut rng = Rng(3);
+  let lengths = (0..100_usize).chain([255, 256, 257, 1000, 4099].iter().copied());
+  for len in lengths {
+    // a small range of values has plenty of duplicates
+    let modulus = if len % 2 == 0 { 7 } else { u32::MAX };
+    let data: Vec<i32> = (0..len)
+      .map(|_| (rng.next_u32() % modulus) as i32)
+      .collect();
+    let mut got = data.clone();
+    let mut scratch = vec![0; len + 3];
+    sort_i32(&mut got, &mut scratch);
+    let mut expected = data;
+    expected.sort_unstable_by(i32::cmp);
+    assert_eq!(got, expected, "len {}", len);
+  }
+}
+
+#[test]
+fn sort_f32_matches_core() {
+  let mut rng = Rng(4);
+  for len in (0..70).chain([300, 1025].iter().copied()) {
+    let data: Vec<f32> = (0..len)
+      .map(|_| {
+        let r = rng.next_u32();
+        if r % 3 == 0 {
+          SPECIAL[(r >> 8) as usize % SPECIAL.len()]
+        } else {
+          f32::from_bits(r)
+        }
+      })
+      .collect();
+    let mut got = data.clone();
+    let mut scratch = vec![0.0; len];
+    sort_f32(&mut got, &mut scratch);
+    let mut expected = data;
+    expected.sort_unstable_by(f32::total_cmp);
+    let to_bits = |v: &[f32]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+    assert_eq!(to_bits(&got), to_bits(&expected), "len {}", len);
+  }
+}
+
+#[test]
+#[should_panic]
+fn sort_i32_short_scratch() {
+  sort_i32(&mut [3, 2, 1], &mut [0; 2]);
+}
@@ -0,0 +1,156 @@
+use lokacore::simd::srgb::*;
+
+fn srgb_to_linear_exact(x: f64) -> f64 {
+  if x <= 0.04045 {
+    x / 12.92
+  } else {
+    ((x + 0.055) / 1.055).powf(2.4)
+  }
+}
+
+fn linear_to_srgb_exact(x: f64) -> f64 {
+  if x <= 0.003_130_8 {
+    x * 12.92
+  } else {
+    1.055 * x.powf(1.0 / 2.4) - 0.055
+  }
+}
+
+/// Every 251st `f32` in `0.0..=1.0`, and then 1.0 itself.
+fn unit_interval() -> impl Iterator<Item = f32> {
+  (0..=1.0_f32.to_bits())
+    .step_by(251)
+    .map(f32::from_bits)
+    .chain(Some(1.0))
+}
+
+#[test]
+fn srgb_to_linear_error_bound() {
+  for x in unit_interval() {
+    let exact = srgb_to_linear_exact(f64::from(x));
+    let got = f64::from(srgb_to_linear(x));
+    if exact < 1e-30 {
+      // the result is subnormal or zero, so only the absolute error matters
+      assert!((got - exact).abs() < 1e-37, "{:e}", x);
+    } else {
+      assert!(
+        ((got - exact) / exact).abs() < 1e-6,
+        "{:e}: {} vs {}",
+        x,
+        got,
+        exact
+      );
+    }
+  }
+  assert_eq!(srgb_to_linear(0.0), 0.0);
+  assert!(srgb_to_linear(f32::NAN).is_nan());
+}
+
+#[test]
+fn linear_to_srgb_error_bound() {
+  for x in unit_interval() {
+    let exact = linear_to_srgb_exact(f64::from(x));
+    let got = f64::from(linear_to_srgb(x));
+    assert!((got - exact).abs() < 3e-7, "{:e}: {} vs {}", x, got, exact);
+  }
+  assert_eq!(linear_to_srgb(0.0), 0.0);
+  assert_eq!(linear_to_srgb(1.0), 1.0);
+  assert!(linear_to_srgb(f32::NAN).is_nan());
+}
+
+#[test]
+fn linear_to_srgb_clamps() {
+  assert_eq!(linear_to_srgb(f32::INFINITY), 1.0);
+  assert_eq!(linear_to_srgb(2.0), 1.0);
+  assert_eq!(linear_to_srgb(f32::NEG_INFINITY), 0.0);
+  assert_eq!(linear_to_srgb(-1.0), 0.0);
+  assert!(linear_to_srgb(f32::NAN).is_nan());
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[test]
+fn m128_matches_scalar() {
+  use lokacore::arch::x86_64::m128;
+  let xs: Vec<f32> = unit_interval()
+    .step_by(7)
+    .chain([f32::NAN, -0.5, 1.5, 0.04045, 0.003_130_8].iter().copied())
+    .chain([f32::INFINITY, f32::NEG_INFINITY].iter().copied())
+    .collect();
+  for chunk in xs.chunks(4) {
+    let mut lanes = [0.25; 4];
+    lanes[..chunk.len()].copy_from_slice(chunk);
+    let v = m128::load_unaligned(&lanes);
+    let decoded = v.srgb_to_linear().to_array();
+    let encoded = v.linear_to_srgb().to_array();
+    for (i, &x) in lanes.iter().enumerate() {
+      assert_eq!(decoded[i].to_bits(), srgb_to_linear(x).to_bits(), "{:e}", x);
+      assert_eq!(encoded[i].to_bits(), linear_to_srgb(x).to_bits(), "{:e}", x);
+    }
+  }
+}
+
+#[test]
+fn u8_to_linear_is_exact() {
+  let src: Vec<u8> = (0..=255).collect();
+  let mut dst = vec![0.0; 256];
+  assert_eq!(srgb_u8_to_linear(&src, &mut dst), 256);
+  for (&s, &d) in src.iter().zip(dst.iter()) {
+    assert_eq!(
+      d,
+      srgb_to_linear_exact(f64::from(s) / 255.0) as f32,
+      "{}",
+      s
+    );
+  }
+  assert_eq!(dst[0], 0.0);
+  assert_eq!(dst[255], 1.0);
+  assert_eq!(srgb_u8_to_linear(&src[..10], &mut dst), 10);
+  assert_eq!(srgb_u8_to_linear(&src, &mut dst[..3]), 3);
+}
+
+#[test]
+fn linear_to_u8_error_bound() {
+  let src: Vec<f32> = unit_interval().collect();
+  for &len in [0, 1, 15, 16, 17, 40].iter() {
+    let mut dst = vec![0; len];
+    assert_eq!(linear_to_srgb_u8(&src[..len], &mut dst), len);
+  }
+  let mut dst = vec![0; src.len()];
+  assert_eq!(linear_to_srgb_u8(&src, &mut dst), src.len());
+  for (&x, &d) in src.iter().zip(dst.iter()) {
+    let exact = linear_to_srgb_exact(f64::from(x)) * 255.0;
+    assert!(
+      (f64::from(d) - exact).abs() <= 0.5001,
+      "{:e}: {} vs {}",
+      x,
+      d,
+      exact
+    );
+  }
+}
+
+#[test]
+fn linear_to_u8_clamps() {
+  let src = [
+    f32::NAN,
+    -1.0,
+    f32::NEG_INFINITY,
+    2.0,
+    f32::INFINITY,
+    0.0,
+    1.0,
+  ];
+  let mut dst = [1; 7];
+  linear_to_srgb_u8(&src, &mut dst);
+  assert_eq!(dst, [0, 0, 0, 255, 255, 0, 255]);
+}
+
+#[test]
+fn u8_round_trip() {
+  let src: Vec<u8> = (0..=255).chain(0..=255).collect();
+  let mut linear = vec![0.0; src.len()];
+  srgb_u8_to_linear(&src, &mut linear);
+  let mut back = vec![0; src.len()];
+  assert_eq!(linear_to_srgb_u8(&linear, &mut back), src.len());
+  assert_eq!(back, src);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::stats::*;
+
+/// Two pass `(mean, population variance, sample variance)` in `f64`.
+fn reference(values: &[f32]) -> (f64, f64, f64) {
+  let n = values.len() as f64;
+  let mean = values.iter().map(|&x| f64::from(x)).sum::<f64>() / n;
+  let m2: f64 = values.iter().map(|&x| (f64::from(x) - mean).powi(2)).sum();
+  (mean, m2 / n, m2 / (n - 1.0))
+}
+
+fn assert_close(got: f32, expected: f64, tolerance: f64) {
+  let error = (f64::from(got) - expected).abs();
+  assert!(
+    error <= tolerance * expected.abs().max(1.0),
+    "{} vs {}",
+    got,
+    expected
+  );
+}
+
+fn check(stats: &RunningStats, values: &[f32], tolerance: f64) {
+  let (mean, variance, sample_variance) = reference(values);
+  assert_eq!(stats.count(), values.len() as u64);
+  assert_close(stats.mean(), mean, tolerance);
+  assert_close(stats.variance(), variance, tolerance);
+  assert_close(stats.sample_variance(), sample_variance, tolerance);
+  let min = values.iter().fold(f32::INFINITY, |a, &b| a.min(b));
+  let max = values.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+  assert_eq!(stats.min(), min);
+  assert_eq!(stats.max(), max);
+}
+
+#[test]
+fn empty() {
+  let stats = RunningStats::new();
+  assert_eq!(stats.count(), 0);
+  assert_eq!(stats.mean(), 0.0);
+  assert_eq!(stats.variance(), 0.0);
+  assert_eq!(stats.sample_variance(), 0.0);
+  assert_eq!(stats.min(), f32::INFINITY);
+  assert_eq!(stats.max(), f32::NEG_INFINITY);
+  let mut one = RunningStats::default();
+  one.push(3.5);
+  assert_eq!(
+    (one.mean(), one.variance(), one.sample_variance()),
+    (3.5, 0.0, 0.0)
+  );
+  assert_eq!((one.min(), one.max()), (3.5, 3.5));
+}
+
+#[test]
+fn push_and_slices_match_reference() {
+  let mut rng = Rng(1);
+  for len in 2..60 {
+    let values: Vec<f32> = (0..len).map(|_| rng.signed_unit_f32() * 100.0).collect();
+    let mut pushed = RunningStats::new();
+    values.iter().for_each(|&x| pushed.push(x));
+    check(&pushed, &values, 1e-5);
+
+    let mut whole = RunningStats::new();
+    whole.extend_from_slice(&values);
+    check(&whole, &values, 1e-5);
+
+    // odd sized pieces leave the lanes with different counts in between
+    let mut pieces = RunningStats::new();
+    for piece in values.chunks(1 + len % 5) {
+      pieces.extend_from_slice(piece);
+    }
+    check(&pieces, &values, 1e-5);
+  }
+}
+
+#[test]
+fn large_offset_stays_accurate() {
+  // adding up `x` and `x * x` in `f32` would lose the variance entirely here
+  let mut rng = Rng(2);
+  let values: Vec<f32> = (0..10_000).map(|_| 10_000.0 + rng.unit_f32()).collect();
+  let mut stats = RunningStats::new();
+  stats.extend_from_slice(&values);
+  let (mean, variance, _) = reference(&values);
+  assert_close(stats.mean(), mean, 1e-6);
+  assert!((f64::from(stats.variance()) - variance).abs() < variance * 0.01);
+}
+
+#[test]
+fn nan_handling() {
+  let mut stats = RunningStats::new();
+  stats.extend_from_slice(&[1.0, f32::NAN, -2.0, 3.0, 4.0, f32::NAN]);
+  assert!(stats.mean().is_nan());
+  assert!(stats.variance().is_nan());
+  assert_eq!(stats.min(), -2.0);
+  assert_eq!(stats.max(), 4.0);
+  assert_eq!(stats.count(), 6);
+}
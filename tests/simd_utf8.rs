@@ -0,0 +1,160 @@
+mod common;
+
+use common::Rng;
+use lokacore::simd::utf8::*;
+
+/// Bytes from each edge of the ranges that matter to UTF-8.
+const INTERESTING: &[u8] = &[
+  0x00, 0x41, 0x7F, 0x80, 0x8F, 0x90, 0x9F, 0xA0, 0xBF, 0xC0, 0xC1, 0xC2, 0xDF, 0xE0, 0xE1, 0xEC,
+  0xED, 0xEE, 0xEF, 0xF0, 0xF1, 0xF3, 0xF4, 0xF5, 0xFF,
+];
+
+fn check(bytes: &[u8]) {
+  let expected = core::str::from_utf8(bytes);
+  let got = from_utf8(bytes);
+  match (expected, got) {
+    (Ok(e), Ok(g)) => assert_eq!(e, g),
+    (Err(e), Err(g)) => {
+      assert_eq!(e.valid_up_to(), g.valid_up_to(), "{:02X?}", bytes);
+      assert_eq!(e.error_len(), g.error_len(), "{:02X?}", bytes);
+      assert_eq!(e.to_string(), g.to_string());
+    }
+    (e, g) => panic!("{:02X?}: expected {:?}, got {:?}", bytes, e, g),
+  }
+}
+
+#[test]
+fn is_ascii_matches_core() {
+  let mut rng = Rng(1);
+  for _ in 0..300 {
+    let len = rng.below(300);
+    let mut data = vec![b'a'; len + 16];
+    if len > 0 && rng.below(2) == 0 {
+      data[rng.below(len as u32)] = 0x80 | rng.below(128) as u8;
+    }
+    for offset in 0..16 {
+      let s = &data[offset..offset + len];
+      assert_eq!(is_ascii(s), s.is_ascii());
+    }
+  }
+}
+
+#[test]
+fn short_sequences_exhaustive() {
+  // every 1 to 3 byte sequence of interesting bytes, placed so that it sits
+  // across each block boundary and also right at the end of the input.
+  let n = INTERESTING.len();
+  let mut seqs: Vec<Vec<u8>> = Vec::new();
+  for &a in INTERESTING {
+    seqs.push(vec![a]);
+    for &b in INTERESTING {
+      seqs.push(vec![a, b]);
+      for &c in INTERESTING {
+        seqs.push(vec![a, b, c]);
+      }
+    }
+  }
+  assert_eq!(seqs.len(), n + n * n + n * n * n);
+  for seq in &seqs {
+    for at in 10..20 {
+      let mut buf = vec![b'x'; 40];
+      buf[at..at + seq.len()].copy_from_slice(seq);
+      check(&buf);
+      check(&buf[..at + seq.len()]);
+    }
+  }
+}
+
+#[test]
+fn four_byte_sequences_exhaustive() {
+  for &a in INTERESTING {
+    for &b in INTERESTING {
+      for &c in INTERESTING {
+        for &d in INTERESTING {
+          let seq = [a, b, c, d];
+          for &at in &[0, 13, 14, 15, 28] {
+            let mut buf = vec![b'x'; 48];
+            buf[at..at + 4].copy_from_slice(&seq);
+            check(&buf);
+            check(&buf[..at + 4]);
+          }
+        }
+      }
+    }
+  }
+}
+
+#[test]
+fn every_char_width_at_every_offset() {
+  for ch in &[
+    "a",
+    "\u{7FF}",
+    "\u{800}",
+    "\u{FFFF}",
+    "\u{10000}",
+    "\u{10FFFF}",
+  ] {
+    for at in 0..40 {
+      let mut s = "x".repeat(at);
+      s.push_str(ch);
+      s.push_str("yz");
+      check(s.as_bytes());
+      // and every way of cutting it short
+      for cut in at..s.len() {
+        check(&s.as_bytes()[..cut]);
+      }
+    }
+  }
+}
+
+#[test]
+fn fuzz_against_core() {
+  let mut rng = Rng(0xBEEF);
+  let chars = [
+    'a',
+    '~',
+    '\u{80}',
+    'é',
+    '\u{7FF}',
+    '\u{800}',
+    '€',
+    '\u{D7FF}',
+    '\u{E000}',
+    '\u{FFFD}',
+    '\u{10000}',
+    '😀',
+    '\u{10FFFF}',
+  ];
+  for _ in 0..3000 {
+    let count = rng.below(80);
+    let mut s = String::new();
+    for _ in 0..count {
+      s.push(chars[rng.below(chars.len() as u32)]);
+    }
+    let mut bytes = s.into_bytes();
+    check(&bytes);
+    for _ in 0..rng.below(3) {
+      if bytes.is_empty() {
+        break;
+      }
+      let i = rng.below(bytes.len() as u32);
+      bytes[i] = match rng.below(3) {
+        0 => rng.next_u32() as u8,
+        1 => INTERESTING[rng.below(INTERESTING.len() as u32)],
+        _ => bytes[i] ^ 0x40,
+      };
+    }
+    check(&bytes);
+    let cut = rng.below(bytes.len() as u32 + 1);
+    check(&bytes[..cut]);
+  }
+}
+
+#[test]
+fn utf8_error_display() {
+  let e = validate_utf8(b"ab\xFFc").unwrap_err();
+  assert_eq!((e.valid_up_to(), e.error_len()), (2, Some(1)));
+  let e = validate_utf8(b"ab\xE2\x82").unwrap_err();
+  assert_eq!((e.valid_up_to(), e.error_len()), (2, None));
+  assert_eq!(e.to_string(), "incomplete utf-8 byte sequence from index 2");
+}
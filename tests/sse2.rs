@@ -4,7 +4,7 @@
 use lokacore::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
 use lokacore::arch::x86_64::*;
-//use lokacore::*;
+use lokacore::*;
 
 #[test]
 fn m128i_debug() {
@@ -19,3 +19,609 @@ fn m128d_debug() {
   let m = m128d::set(5.0, 6.5);
   assert_eq!(&std::format!("{:?}", m), "m128d(5, 6.5)");
 }
+
+/// Interesting `f32` inputs for the rounding functions.
+const F32_ROUNDING_INPUTS: &[f32] = &[
+  0.0,
+  -0.0,
+  0.3,
+  -0.3,
+  0.5,
+  -0.5,
+  0.7,
+  -0.7,
+  1.0,
+  -1.0,
+  1.5,
+  -1.5,
+  2.5,
+  -2.5,
+  3.5,
+  -3.5,
+  8_388_607.5,
+  -8_388_607.5,
+  8_388_608.0,
+  -8_388_609.0,
+  3.0e9,
+  -3.0e9,
+  1.0e30,
+  -1.0e30,
+  f32::MIN_POSITIVE,
+  -f32::MIN_POSITIVE,
+  f32::INFINITY,
+  f32::NEG_INFINITY,
+  f32::NAN,
+];
+
+/// Interesting `f64` inputs for the rounding functions.
+const F64_ROUNDING_INPUTS: &[f64] = &[
+  0.0,
+  -0.0,
+  0.3,
+  -0.3,
+  0.5,
+  -0.5,
+  0.7,
+  -0.7,
+  1.0,
+  -1.0,
+  1.5,
+  -1.5,
+  2.5,
+  -2.5,
+  3.5,
+  -3.5,
+  8_388_607.5,
+  -8_388_607.5,
+  3.0e9 + 0.5,
+  -3.0e9 - 0.5,
+  4_503_599_627_370_495.5,
+  -4_503_599_627_370_495.5,
+  4_503_599_627_370_496.0,
+  -4_503_599_627_370_497.0,
+  1.0e300,
+  -1.0e300,
+  f64::MIN_POSITIVE,
+  -f64::MIN_POSITIVE,
+  f64::INFINITY,
+  f64::NEG_INFINITY,
+  f64::NAN,
+];
+
+fn f32_same(a: f32, b: f32) -> bool {
+  (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+}
+
+fn f64_same(a: f64, b: f64) -> bool {
+  (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+}
+
+fn check_m128_unary(name: &str, op: fn(m128) -> m128, reference: fn(f32) -> f32, inputs: &[f32]) {
+  for &x in inputs {
+    let out = op(m128::set(0.0, x, -1.0, x)).to_array();
+    let expected = reference(x);
+    assert!(
+      f32_same(out[0], expected),
+      "{}({}) gave {}, expected {}",
+      name,
+      x,
+      out[0],
+      expected
+    );
+    assert!(
+      f32_same(out[2], expected),
+      "{}({}) gave {}, expected {}",
+      name,
+      x,
+      out[2],
+      expected
+    );
+  }
+}
+
+fn check_m128d_unary(
+  name: &str,
+  op: fn(m128d) -> m128d,
+  reference: fn(f64) -> f64,
+  inputs: &[f64],
+) {
+  for &x in inputs {
+    for &other in &[x, -1.0, 12345.5] {
+      let out = op(m128d::set(other, x)).to_array();
+      let expected = reference(x);
+      assert!(
+        f64_same(out[0], expected),
+        "{}({}) gave {}, expected {}",
+        name,
+        x,
+        out[0],
+        expected
+      );
+      let expected = reference(other);
+      assert!(
+        f64_same(out[1], expected),
+        "{}({}) gave {}, expected {}",
+        name,
+        other,
+        out[1],
+        expected
+      );
+    }
+  }
+}
+
+/// Every 65521st `f32` bit pattern, which covers all exponents and both signs.
+fn f32_sweep() -> impl Iterator<Item = f32> {
+  (0..=u32::MAX).step_by(65521).map(f32::from_bits)
+}
+
+/// A spread of `f64` bit patterns covering all exponents and both signs.
+fn f64_sweep() -> impl Iterator<Item = f64> {
+  (0..=u32::MAX)
+    .step_by(65521)
+    .map(|u| f64::from_bits(u64::from(u) << 32 | u64::from(u)))
+}
+
+#[test]
+fn m128_rounding_matches_std() {
+  let sweep: Vec<f32> = F32_ROUNDING_INPUTS
+    .iter()
+    .cloned()
+    .chain(f32_sweep())
+    .collect();
+  check_m128_unary("trunc", m128::trunc, f32::trunc, &sweep);
+  check_m128_unary("floor", m128::floor, f32::floor, &sweep);
+  check_m128_unary("ceil", m128::ceil, f32::ceil, &sweep);
+  check_m128_unary("round", m128::round, f32::round, &sweep);
+  check_m128_unary("round_even", m128::round_even, f32::round_ties_even, &sweep);
+  check_m128_unary("fract", m128::fract, f32::fract, &sweep);
+  check_m128_unary("abs", m128::abs, f32::abs, &sweep);
+  check_m128_unary("signum", m128::signum, f32::signum, &sweep);
+}
+
+#[test]
+fn m128d_rounding_matches_std() {
+  let sweep: Vec<f64> = F64_ROUNDING_INPUTS
+    .iter()
+    .cloned()
+    .chain(f64_sweep())
+    .collect();
+  check_m128d_unary("trunc", m128d::trunc, f64::trunc, &sweep);
+  check_m128d_unary("floor", m128d::floor, f64::floor, &sweep);
+  check_m128d_unary("ceil", m128d::ceil, f64::ceil, &sweep);
+  check_m128d_unary("round", m128d::round, f64::round, &sweep);
+  check_m128d_unary(
+    "round_even",
+    m128d::round_even,
+    f64::round_ties_even,
+    &sweep,
+  );
+  check_m128d_unary("fract", m128d::fract, f64::fract, &sweep);
+  check_m128d_unary("abs", m128d::abs, f64::abs, &sweep);
+  check_m128d_unary("signum", m128d::signum, f64::signum, &sweep);
+}
+
+#[test]
+fn m128_copysign() {
+  let a = m128::set(1.0, -2.0, 3.0, -0.0);
+  let b = m128::set(-0.0, 1.0, -5.0, 0.0);
+  let out = a.copysign(b).to_array();
+  assert_eq!(out, [0.0, -3.0, 2.0, -1.0]);
+  assert!(out[0].is_sign_positive());
+}
+
+#[test]
+fn m128d_copysign() {
+  let a = m128d::set(1.0, -2.0);
+  let b = m128d::set(-0.0, 1.0);
+  assert_eq!(a.copysign(b).to_array(), [2.0, -1.0]);
+}
+
+type F32Case = (fn(m128) -> m128, fn(f32) -> f32);
+type F64Case = (fn(m128d) -> m128d, fn(f64) -> f64);
+
+#[test]
+fn rounding_ignores_rounding_mode() {
+  // the `std` reference functions can themselves be affected by the rounding
+  // mode, so the expected values are computed up front.
+  let f32_ops: [F32Case; 4] = [
+    (m128::floor, f32::floor),
+    (m128::ceil, f32::ceil),
+    (m128::round, f32::round),
+    (m128::round_even, f32::round_ties_even),
+  ];
+  let f64_ops: [F64Case; 4] = [
+    (m128d::floor, f64::floor),
+    (m128d::ceil, f64::ceil),
+    (m128d::round, f64::round),
+    (m128d::round_even, f64::round_ties_even),
+  ];
+  for &mode in &[
+    RoundingMode::Down,
+    RoundingMode::Up,
+    RoundingMode::TowardZero,
+  ] {
+    for &(op, reference) in &f32_ops {
+      for &x in F32_ROUNDING_INPUTS {
+        let expected = reference(x);
+        set_rounding_mode(mode);
+        let out = op(m128::splat(x)).to_array()[0];
+        set_rounding_mode(RoundingMode::Nearest);
+        assert!(
+          f32_same(out, expected),
+          "{:?}: {} gave {}, expected {}",
+          mode,
+          x,
+          out,
+          expected
+        );
+      }
+    }
+    for &(op, reference) in &f64_ops {
+      for &x in F64_ROUNDING_INPUTS {
+        let expected = reference(x);
+        set_rounding_mode(mode);
+        let out = op(m128d::splat(x)).to_array()[0];
+        set_rounding_mode(RoundingMode::Nearest);
+        assert!(
+          f64_same(out, expected),
+          "{:?}: {} gave {}, expected {}",
+          mode,
+          x,
+          out,
+          expected
+        );
+      }
+    }
+  }
+}
+
+#[test]
+fn m128i_shift_immediate_i16() {
+  let a = m128i::set_i16(-32768, -2, -1, 0, 1, 2, 0x4000, 0x7FFF);
+  let expected = m128i::set_i16(0, -8, -4, 0, 4, 8, 0, -4);
+  assert_eq!(shl_i16!(a, 2).to_i128(), expected.to_i128());
+  let expected = m128i::set_i16(-8192, -1, -1, 0, 0, 0, 0x1000, 0x1FFF);
+  assert_eq!(shr_arith_i16!(a, 2).to_i128(), expected.to_i128());
+  let expected = m128i::set_i16(0x2000, 0x3FFF, 0x3FFF, 0, 0, 0, 0x1000, 0x1FFF);
+  assert_eq!(shr_logical_u16!(a, 2).to_i128(), expected.to_i128());
+  assert_eq!(shl_i16!(a, 0).to_i128(), a.to_i128());
+  let expected = m128i::set_i16(-1, -1, -1, 0, 0, 0, 0, 0);
+  assert_eq!(shr_arith_i16!(a, 15).to_i128(), expected.to_i128());
+}
+
+#[test]
+fn m128i_shift_immediate_i32() {
+  let a = m128i::set_i32(i32::MIN, -1, 1, 0x4000_0001);
+  let expected = m128i::set_i32(0, -2, 2, -0x7FFF_FFFE);
+  assert_eq!(shl_i32!(a, 1).to_i128(), expected.to_i128());
+  let expected = m128i::set_i32(-0x4000_0000, -1, 0, 0x2000_0000);
+  assert_eq!(shr_arith_i32!(a, 1).to_i128(), expected.to_i128());
+  let expected = m128i::set_i32(0x4000_0000, 0x7FFF_FFFF, 0, 0x2000_0000);
+  assert_eq!(shr_logical_u32!(a, 1).to_i128(), expected.to_i128());
+  let expected = m128i::set_i32(1, 1, 0, 0);
+  assert_eq!(shr_logical_u32!(a, 31).to_i128(), expected.to_i128());
+}
+
+#[test]
+fn m128i_shift_immediate_i64() {
+  let a = m128i::set_i64(-1, 0x0123_4567_89AB_CDEF);
+  let expected = m128i::set_i64(-16, 0x1234_5678_9ABC_DEF0);
+  assert_eq!(shl_i64!(a, 4).to_i128(), expected.to_i128());
+  let expected = m128i::set_i64(0x0FFF_FFFF_FFFF_FFFF, 0x0012_3456_789A_BCDE);
+  assert_eq!(shr_logical_u64!(a, 4).to_i128(), expected.to_i128());
+  let expected = m128i::set_i64(1, 0);
+  assert_eq!(shr_logical_u64!(a, 63).to_i128(), expected.to_i128());
+}
+
+#[test]
+fn m128i_byte_shift() {
+  let a = m128i::set_i64(0x0F0E_0D0C_0B0A_0908, 0x0706_0504_0302_0100);
+  let bits = a.to_i128() as u128;
+  assert_eq!(byte_shift_left!(a, 0).to_i128() as u128, bits);
+  assert_eq!(byte_shift_left!(a, 3).to_i128() as u128, bits << 24);
+  assert_eq!(byte_shift_left!(a, 15).to_i128() as u128, bits << 120);
+  assert_eq!(byte_shift_right!(a, 3).to_i128() as u128, bits >> 24);
+  assert_eq!(byte_shift_right!(a, 15).to_i128() as u128, bits >> 120);
+}
+
+#[test]
+fn m128i_shuffle_i32() {
+  let a = m128i::set_i32(9, 8, 7, 6);
+  let expected = m128i::splat_i32(6);
+  assert_eq!(shuffle_i32!(a, 0, 0, 0, 0).to_i128(), expected.to_i128());
+  let expected = m128i::set_i32(6, 7, 8, 9);
+  assert_eq!(shuffle_i32!(a, 0, 1, 2, 3).to_i128(), expected.to_i128());
+  let expected = m128i::set_i32(8, 9, 6, 7);
+  assert_eq!(shuffle_i32!(a, 2, 3, 0, 1).to_i128(), expected.to_i128());
+}
+
+#[test]
+fn m128i_shuffle_i16() {
+  let a = m128i::set_i16(17, 16, 15, 14, 13, 12, 11, 10);
+  let expected = m128i::set_i16(14, 15, 16, 17, 13, 12, 11, 10);
+  assert_eq!(
+    shuffle_high_i16!(a, 0, 1, 2, 3).to_i128(),
+    expected.to_i128()
+  );
+  let expected = m128i::set_i16(17, 16, 15, 14, 10, 11, 12, 13);
+  assert_eq!(
+    shuffle_low_i16!(a, 0, 1, 2, 3).to_i128(),
+    expected.to_i128()
+  );
+  let expected = m128i::set_i16(17, 17, 17, 17, 11, 11, 11, 11);
+  let out = shuffle_low_i16!(shuffle_high_i16!(a, 3, 3, 3, 3), 1, 1, 1, 1);
+  assert_eq!(out.to_i128(), expected.to_i128());
+}
+
+#[test]
+fn m128d_shuffle() {
+  let a = m128d::set(2.0, 1.0);
+  let b = m128d::set(4.0, 3.0);
+  assert_eq!(shuffle128d!(a, b, 0, 0).to_array(), [1.0, 3.0]);
+  assert_eq!(shuffle128d!(a, b, 1, 0).to_array(), [1.0, 4.0]);
+  assert_eq!(shuffle128d!(a, b, 0, 1).to_array(), [2.0, 3.0]);
+  assert_eq!(shuffle128d!(a, a, 1, 1).to_array(), [2.0, 2.0]);
+}
+
+#[test]
+fn immediate_macros_infer_argument_types() {
+  // the argument types come from the macro, so `cast` doesn't need a turbofish
+  let v: [i32; 4] = cast(shl_i32!(cast([1_i32, 2, 3, 4]), 1));
+  assert_eq!(v, [2, 4, 6, 8]);
+  let v: [i32; 4] = cast(shuffle_i32!(cast([1_i32, 2, 3, 4]), 0, 1, 2, 3));
+  assert_eq!(v, [4, 3, 2, 1]);
+  let v: [i16; 8] = cast(shuffle_low_i16!(cast([0_i16, 1, 2, 3, 4, 5, 6, 7]), 0, 1, 2, 3));
+  assert_eq!(v, [3, 2, 1, 0, 4, 5, 6, 7]);
+  let v: [f64; 2] = cast(shuffle128d!(cast([1.0_f64, 2.0]), cast([3.0_f64, 4.0]), 0, 1));
+  assert_eq!(v, [2.0, 3.0]);
+}
+
+#[test]
+fn m128i_shift_count_i16() {
+  let a = m128i::set_i16(-32768, -2, -1, 0, 1, 2, 0x4000, 0x7FFF);
+  for count in 0..20 {
+    let lanes: [i16; 8] = cast(a.to_i128());
+    let shl: [i16; 8] = cast(a.shl_i16(count).to_i128());
+    let sra: [i16; 8] = cast(a.shr_arith_i16(count).to_i128());
+    let srl: [u16; 8] = cast(a.shr_logical_u16(count).to_i128());
+    for i in 0..8 {
+      let x = lanes[i];
+      assert_eq!(
+        shl[i],
+        x.checked_shl(count).unwrap_or(0),
+        "{} << {}",
+        x,
+        count
+      );
+      assert_eq!(sra[i], x >> count.min(15), "{} >> {}", x, count);
+      assert_eq!(
+        srl[i],
+        (x as u16).checked_shr(count).unwrap_or(0),
+        "{} >> {}",
+        x,
+        count
+      );
+    }
+  }
+  let max = m128i::splat_i16(-1);
+  assert_eq!(max.shl_i16(u32::MAX).to_i128(), 0);
+  assert_eq!(max.shr_arith_i16(u32::MAX).to_i128(), -1);
+}
+
+#[test]
+fn m128i_shift_count_i32() {
+  let a = m128i::set_i32(i32::MIN, -1, 1, 0x4000_0001);
+  for count in 0..40 {
+    let lanes: [i32; 4] = cast(a.to_i128());
+    let shl: [i32; 4] = cast(a.shl_i32(count).to_i128());
+    let sra: [i32; 4] = cast(a.shr_arith_i32(count).to_i128());
+    let srl: [u32; 4] = cast(a.shr_logical_u32(count).to_i128());
+    for i in 0..4 {
+      let x = lanes[i];
+      assert_eq!(
+        shl[i],
+        x.checked_shl(count).unwrap_or(0),
+        "{} << {}",
+        x,
+        count
+      );
+      assert_eq!(sra[i], x >> count.min(31), "{} >> {}", x, count);
+      assert_eq!(
+        srl[i],
+        (x as u32).checked_shr(count).unwrap_or(0),
+        "{} >> {}",
+        x,
+        count
+      );
+    }
+  }
+}
+
+#[test]
+fn m128i_shift_count_i64() {
+  let a = m128i::set_i64(i64::MIN + 5, 0x0123_4567_89AB_CDEF);
+  for count in 0..70 {
+    let lanes: [i64; 2] = cast(a.to_i128());
+    let shl: [i64; 2] = cast(a.shl_i64(count).to_i128());
+    let sra: [i64; 2] = cast(a.shr_arith_i64(count).to_i128());
+    let srl: [u64; 2] = cast(a.shr_logical_u64(count).to_i128());
+    for i in 0..2 {
+      let x = lanes[i];
+      assert_eq!(
+        shl[i],
+        x.checked_shl(count).unwrap_or(0),
+        "{} << {}",
+        x,
+        count
+      );
+      assert_eq!(sra[i], x >> count.min(63), "{} >> {}", x, count);
+      assert_eq!(
+        srl[i],
+        (x as u64).checked_shr(count).unwrap_or(0),
+        "{} >> {}",
+        x,
+        count
+      );
+    }
+  }
+}
+
+#[test]
+fn lane_wrapper_shift_operators() {
+  let a = i16x8(m128i::splat_i16(-4));
+  assert_eq!((a >> 1).0.to_i128(), m128i::splat_i16(-2).to_i128());
+  assert_eq!((a << 2).0.to_i128(), m128i::splat_i16(-16).to_i128());
+  let b = u16x8(m128i::splat_i16(-4));
+  assert_eq!((b >> 1).0.to_i128(), m128i::splat_i16(0x7FFE).to_i128());
+  assert_eq!((b >> 16).0.to_i128(), 0);
+
+  let mut c = i32x4(m128i::set_i32(-8, 8, -1, 1));
+  c >>= 2;
+  assert_eq!(c.0.to_i128(), m128i::set_i32(-2, 2, -1, 0).to_i128());
+  c <<= 40;
+  assert_eq!(c.0.to_i128(), 0);
+  let d = u32x4(m128i::splat_i32(-1));
+  assert_eq!((d >> 31).0.to_i128(), m128i::splat_i32(1).to_i128());
+
+  let e = i64x2(m128i::set_i64(-256, 256));
+  assert_eq!((e >> 4).0.to_i128(), m128i::set_i64(-16, 16).to_i128());
+  assert_eq!((e >> 100).0.to_i128(), m128i::set_i64(-1, 0).to_i128());
+  let f = u64x2(m128i::set_i64(-256, 256));
+  assert_eq!((f >> 60).0.to_i128(), m128i::set_i64(15, 0).to_i128());
+  assert_eq!((f << 1).0.to_i128(), m128i::set_i64(-512, 512).to_i128());
+}
+
+#[test]
+fn lane_wrapper_debug() {
+  let a = i16x8(m128i::set_i16(7, 6, 5, 4, 3, 2, 1, -1));
+  assert_eq!(&format!("{:?}", a), "i16x8(7, 6, 5, 4, 3, 2, 1, -1)");
+  let b = u32x4(m128i::set_i32(3, 2, 1, -1));
+  assert_eq!(&format!("{:?}", b), "u32x4(3, 2, 1, 4294967295)");
+}
+
+#[test]
+fn m128i_saturating_pack_i16() {
+  let a = m128i::set_i16(300, -300, 127, -128, 128, -129, 1, -1);
+  let b = m128i::set_i16(i16::MAX, i16::MIN, 255, 256, 0, 2, -2, 5);
+  let signed: [i8; 16] = cast(a.saturating_pack_i16_i8(b));
+  assert_eq!(
+    signed,
+    [-1, 1, -128, 127, -128, 127, -128, 127, 5, -2, 2, 0, 127, 127, -128, 127]
+  );
+  let unsigned: [u8; 16] = cast(a.saturating_pack_i16_u8(b));
+  assert_eq!(
+    unsigned,
+    [0, 1, 0, 128, 0, 127, 0, 255, 5, 0, 2, 0, 255, 255, 0, 255]
+  );
+}
+
+#[test]
+fn m128i_mul_low_i32() {
+  let inputs = [0, 1, -1, 7, -9, 0x1234_5678, i32::MAX, i32::MIN, 65537];
+  for &x in &inputs {
+    for &y in &inputs {
+      let a = m128i::set_i32(x, y, x, y.wrapping_neg());
+      let b = m128i::set_i32(y, x, x.wrapping_neg(), y);
+      let out: [i32; 4] = cast(a.mul_low_i32(b));
+      assert_eq!(
+        out,
+        [
+          y.wrapping_neg().wrapping_mul(y),
+          x.wrapping_mul(x.wrapping_neg()),
+          y.wrapping_mul(x),
+          x.wrapping_mul(y)
+        ]
+      );
+    }
+  }
+}
+
+#[test]
+fn m128i_setr_i32() {
+  let a = m128i::setr_i32(1, 2, 3, 4);
+  assert_eq!(a.to_i128(), m128i::set_i32(4, 3, 2, 1).to_i128());
+}
+
+#[test]
+fn m128i_unaligned_low_loads_and_stores() {
+  let bytes: [u8; 9] = [0xFF, 1, 2, 3, 4, 5, 6, 7, 8];
+  let mut two = [0; 2];
+  two.copy_from_slice(&bytes[1..3]);
+  let mut four = [0; 4];
+  four.copy_from_slice(&bytes[1..5]);
+  let mut eight = [0; 8];
+  eight.copy_from_slice(&bytes[1..9]);
+
+  let a = m128i::load_unaligned_low_i16(&two);
+  assert_eq!(a.to_i128(), i128::from(u16::from_le_bytes(two)));
+  let b = m128i::load_unaligned_low_i32(&four);
+  assert_eq!(b.to_i128(), i128::from(u32::from_le_bytes(four)));
+  let c = m128i::load_unaligned_low_i64(&eight);
+  assert_eq!(c.to_i128(), i128::from(u64::from_le_bytes(eight)));
+
+  let v = m128i::setr_i8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+  let mut two = [0; 2];
+  v.store_unaligned_low_i16(&mut two);
+  assert_eq!(two, [1, 2]);
+  let mut four = [0; 4];
+  v.store_unaligned_low_i32(&mut four);
+  assert_eq!(four, [1, 2, 3, 4]);
+  let mut eight = [0; 8];
+  v.store_unaligned_low_i64(&mut eight);
+  assert_eq!(eight, [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn m128i_stream() {
+  let v = m128i::set_i32(4, 3, 2, 1);
+  let mut out = Align16(0_i128);
+  v.stream(&mut out);
+  assert_eq!(out.0, v.to_i128());
+}
+
+#[test]
+fn m128i_masked_store() {
+  let v = m128i::splat_i8(9);
+  let mask = m128i::setr_i8(-1, 0, -1, 0, 1, -128, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1);
+  let mut out = [0_i8; 16];
+  v.masked_store(mask, &mut out);
+  assert_eq!(out, [9, 0, 9, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9]);
+}
+
+#[test]
+fn m128d_low_lane_arithmetic() {
+  let a = m128d::set(10.0, 6.0);
+  let b = m128d::set(20.0, 4.0);
+  assert_eq!(a.div_low(b).to_array(), [1.5, 10.0]);
+  assert_eq!(a.mul_low(b).to_array(), [24.0, 10.0]);
+  assert_eq!(a.sub_low(b).to_array(), [2.0, 10.0]);
+}
+
+#[test]
+fn m128d_stream() {
+  let v = m128d::set(2.0, 1.0);
+  let mut out = Align16([0.0; 2]);
+  v.stream(&mut out);
+  assert_eq!(out.0, [1.0, 2.0]);
+}
+
+#[test]
+fn stream_scalars() {
+  let mut x = 0_i32;
+  stream_i32(&mut x, -7);
+  assert_eq!(x, -7);
+  #[cfg(target_arch = "x86_64")]
+  {
+    let mut y = 0_i64;
+    stream_i64(&mut y, i64::MIN);
+    assert_eq!(y, i64::MIN);
+  }
+}
+
+#[test]
+fn pause_returns() {
+  for _ in 0..4 {
+    pause();
+  }
+}
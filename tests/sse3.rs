@@ -61,8 +61,9 @@ fn m128_horizontal_sub() {
 
 #[test]
 fn m128i_load_quick_unaligned() {
-  let out = m128i::load_quick_unaligned(&12345);
-  let expected = m128i::load_unaligned(&12345);
+  let bytes = 12345_i128.to_le_bytes();
+  let out = m128i::load_quick_unaligned(&bytes);
+  let expected = m128i::load_unaligned(&bytes);
   assert_eq!(out.to_i128(), expected.to_i128());
 }
 
@@ -0,0 +1,135 @@
+#![cfg(target_feature = "ssse3")]
+
+#[cfg(target_arch = "x86")]
+use lokacore::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use lokacore::arch::x86_64::*;
+use lokacore::*;
+
+#[test]
+fn m128i_abs() {
+  let a = m128i::setr_i8(0, 1, -1, 127, -127, -128, 5, -5, 0, 0, 0, 0, 0, 0, 0, 0);
+  let out: [i8; 16] = cast(a.abs_i8());
+  assert_eq!(out, [0, 1, 1, 127, 127, -128, 5, 5, 0, 0, 0, 0, 0, 0, 0, 0]);
+  let a = m128i::set_i16(i16::MIN, -300, 300, 0, -1, 1, 7, -7);
+  let expected = m128i::set_i16(i16::MIN, 300, 300, 0, 1, 1, 7, 7);
+  assert_eq!(a.abs_i16().to_i128(), expected.to_i128());
+  let a = m128i::set_i32(i32::MIN, -70000, 0, 5);
+  let expected = m128i::set_i32(i32::MIN, 70000, 0, 5);
+  assert_eq!(a.abs_i32().to_i128(), expected.to_i128());
+}
+
+#[test]
+fn m128i_horizontal_i16() {
+  let a = m128i::set_i16(i16::MAX, 1, 4, 3, -2, 5, 1, 2);
+  let b = m128i::set_i16(i16::MIN, -1, 0, 0, 10, 20, 7, 8);
+  assert_eq!(
+    a.horizontal_add_i16(b).to_i128(),
+    m128i::set_i16(i16::MAX, 0, 30, 15, i16::MIN, 7, 3, 3).to_i128()
+  );
+  assert_eq!(
+    a.horizontal_saturating_add_i16(b).to_i128(),
+    m128i::set_i16(i16::MIN, 0, 30, 15, i16::MAX, 7, 3, 3).to_i128()
+  );
+  let b = m128i::set_i16(1, i16::MIN, 0, 0, 10, 20, 7, 8);
+  assert_eq!(
+    a.horizontal_sub_i16(b).to_i128(),
+    m128i::set_i16(i16::MAX, 0, 10, 1, i16::MIN + 2, -1, 7, 1).to_i128()
+  );
+  assert_eq!(
+    a.horizontal_saturating_sub_i16(b).to_i128(),
+    m128i::set_i16(i16::MIN, 0, 10, 1, i16::MIN + 2, -1, 7, 1).to_i128()
+  );
+}
+
+#[test]
+fn m128i_horizontal_i32() {
+  let a = m128i::set_i32(4, 3, 2, 1);
+  let b = m128i::set_i32(40, 30, 20, 10);
+  assert_eq!(
+    a.horizontal_add_i32(b).to_i128(),
+    m128i::set_i32(70, 30, 7, 3).to_i128()
+  );
+  assert_eq!(
+    a.horizontal_sub_i32(b).to_i128(),
+    m128i::set_i32(-10, -10, -1, -1).to_i128()
+  );
+}
+
+#[test]
+fn m128i_mul_hadd_saturating_u8_i8() {
+  let a = m128i::setr_i8(-1, -1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+  let b = m128i::setr_i8(127, 127, -4, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+  // 255*127 + 255*127 saturates, 2*-4 + 3*5 = 7
+  let expected = m128i::set_i16(0, 0, 0, 0, 0, 0, 7, i16::MAX);
+  assert_eq!(a.mul_hadd_saturating_u8_i8(b).to_i128(), expected.to_i128());
+}
+
+#[test]
+fn m128i_mul_high_round_i16() {
+  let inputs = [0, 1, -1, 16384, -16384, i16::MAX, i16::MIN, 12345, -999];
+  for &x in &inputs {
+    for &y in &inputs {
+      let out: [i16; 8] = cast(m128i::splat_i16(x).mul_high_round_i16(m128i::splat_i16(y)));
+      let expected = (((i32::from(x) * i32::from(y)) >> 14) + 1) >> 1;
+      assert_eq!(out[0], expected as i16, "{} * {}", x, y);
+    }
+  }
+}
+
+#[test]
+fn m128i_shuffle_i8() {
+  let a = m128i::setr_i8(
+    10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+  );
+  let i = m128i::setr_i8(15, 0, -1, 1, 17, -128, 2, 2, 0, 0, 0, 0, 0, 0, 0, 3);
+  let out: [i8; 16] = cast(a.shuffle_i8(i));
+  assert_eq!(
+    out,
+    [25, 10, 0, 11, 11, 0, 12, 12, 10, 10, 10, 10, 10, 10, 10, 13]
+  );
+}
+
+#[test]
+fn m128i_sign() {
+  let a = m128i::splat_i8(5);
+  let b = m128i::setr_i8(-1, 0, 1, -128, 127, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+  let out: [i8; 16] = cast(a.sign_i8(b));
+  assert_eq!(out, [-5, 0, 5, -5, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+  let a = m128i::set_i16(1, 2, 3, 4, 5, 6, 7, 8);
+  let b = m128i::set_i16(-1, 0, 1, -1, 0, 1, -1, 0);
+  let expected = m128i::set_i16(-1, 0, 3, -4, 0, 6, -7, 0);
+  assert_eq!(a.sign_i16(b).to_i128(), expected.to_i128());
+  let a = m128i::set_i32(1, 2, 3, 4);
+  let b = m128i::set_i32(-9, 0, 9, i32::MIN);
+  let expected = m128i::set_i32(-1, 0, 3, -4);
+  assert_eq!(a.sign_i32(b).to_i128(), expected.to_i128());
+}
+
+#[test]
+fn byte_align_right_all_amounts() {
+  let a = m128i::setr_i8(
+    16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+  );
+  let b = m128i::setr_i8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+  let joined = (u128::from_le_bytes(cast(a)), u128::from_le_bytes(cast(b)));
+  macro_rules! check {
+    ($($imm:expr),*) => {$({
+      let out: [u8; 16] = cast(byte_align_right!(a, b, $imm));
+      let mut expected = [0_u8; 16];
+      for (i, e) in expected.iter_mut().enumerate() {
+        let n = i + $imm;
+        *e = if n < 16 {
+          joined.1.to_le_bytes()[n]
+        } else if n < 32 {
+          joined.0.to_le_bytes()[n - 16]
+        } else {
+          0
+        };
+      }
+      assert_eq!(out, expected, "imm {}", $imm);
+    })*};
+  }
+  check!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+  check!(16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31);
+}